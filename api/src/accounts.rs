@@ -6,31 +6,43 @@ use crate::{
     accept_type::AcceptType,
     context::{api_spawn_blocking, Context},
     failpoint::fail_point_poem,
-    page::determine_limit,
+    page::{determine_limit, Page},
     response::{
-        account_not_found, resource_not_found, struct_field_not_found, BadRequestError,
-        BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404, InternalError,
+        account_not_found, build_not_found, resource_not_found, struct_field_not_found,
+        version_pruned, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, InternalError,
     },
     ApiTags,
 };
-use anyhow::Context as AnyhowContext;
+use anyhow::{ensure, Context as AnyhowContext};
 use aptos_api_types::{
-    AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo, MoveModuleBytecode,
-    MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
+    AccountBalance, AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo,
+    MoveModuleBytecode, MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
 };
 use aptos_types::{
-    account_config::{AccountResource, ObjectGroupResource},
+    account_address::{create_derived_object_address, AccountAddress},
+    account_config::{
+        AccountResource, CoinStoreResourceUntyped, ConcurrentFungibleBalanceResource,
+        FungibleStoreResource, ObjectGroupResource,
+    },
     event::{EventHandle, EventKey},
     state_store::state_key::StateKey,
+    AptosCoinType, CoinType,
 };
 use move_core_types::{
-    identifier::Identifier, language_storage::StructTag, move_resource::MoveStructType,
+    ident_str,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
 };
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use std::{collections::BTreeMap, convert::TryInto, sync::Arc};
+use std::{collections::BTreeMap, convert::TryInto, str::FromStr, sync::Arc};
+
+/// Max number of versions of a balance read by a single balance history request
+const MAX_BALANCE_HISTORY_READS: usize = 1000;
 
 /// API for accounts, their associated resources, and modules
 pub struct AccountsApi {
@@ -175,6 +187,110 @@ impl AccountsApi {
         })
         .await
     }
+
+    /// Get account balance
+    ///
+    /// Retrieves the balance of a coin or fungible asset held by an account at a specific ledger
+    /// version. If the ledger version is not specified in the request, the latest ledger version
+    /// is used.
+    ///
+    /// The asset type is either a coin type e.g. `0x1::aptos_coin::AptosCoin`, or the address of
+    /// the metadata of a fungible asset e.g. `0xa`. Fungible assets are read from the primary
+    /// fungible store of the account. For APT, the balance in the primary fungible store is
+    /// included along with the coin balance.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/balance/:asset_type",
+        method = "get",
+        operation_id = "get_account_balance",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_balance(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Coin type or fungible asset metadata address e.g. `0x1::aptos_coin::AptosCoin`
+        asset_type: Path<String>,
+        /// Ledger version to get the balance at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<AccountBalance> {
+        fail_point_poem("endpoint_get_account_balance")?;
+        self.context
+            .check_api_output_enabled("Get account balance", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let account = Account::new(context, address.0, ledger_version.0, None, None)?;
+            account.balance(&accept_type, &asset_type.0)
+        })
+        .await
+    }
+
+    /// Get account balance history
+    ///
+    /// Retrieves the history of the balance of a coin or fungible asset held by an account,
+    /// reconstructed from the versioned state in storage. Each entry is the balance along with
+    /// the version it changed at, most recent first.
+    ///
+    /// The history starts with the balance at `end_version`, and goes back until the balance
+    /// held at `start_version`, or `limit` entries. The asset type is interpreted as in
+    /// `get_account_balance`.
+    ///
+    /// A single call reads a bounded number of versions of the balance, so a page can hold
+    /// fewer than `limit` entries. If the history goes on, the X-Aptos-Cursor header of the
+    /// response holds the version to pass as `end_version` to get the next page. When a page
+    /// ends while the balance doesn't change, the next page starts with the same balance.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested end version has been pruned, the server responds with a 410, and the
+    /// history is cut at the oldest version that hasn't been pruned.
+    #[oai(
+        path = "/accounts/:address/balance/:asset_type/history",
+        method = "get",
+        operation_id = "get_account_balance_history",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_balance_history(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Coin type or fungible asset metadata address e.g. `0x1::aptos_coin::AptosCoin`
+        asset_type: Path<String>,
+        /// Ledger version to stop going back at
+        ///
+        /// If not provided, it will be the oldest version that hasn't been pruned
+        start_version: Query<Option<U64>>,
+        /// Ledger version to start the history at
+        ///
+        /// If not provided, it will be the latest version. To get the next page, pass the
+        /// cursor returned in the X-Aptos-Cursor header of the response.
+        end_version: Query<Option<U64>>,
+        /// Max number of balance changes to retrieve
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<AccountBalance>> {
+        fail_point_poem("endpoint_get_account_balance_history")?;
+        self.context
+            .check_api_output_enabled("Get account balance history", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let account = Account::new(context, address.0, end_version.0, None, limit.0)?;
+            account.balance_history(
+                &accept_type,
+                &asset_type.0,
+                start_version.0.map(|inner| inner.0),
+            )
+        })
+        .await
+    }
 }
 
 /// A struct representing Account related lookups for resources and modules
@@ -251,6 +367,175 @@ impl Account {
         }
     }
 
+    /// Retrieves the balance of an asset at the lookup ledger version
+    ///
+    /// * JSON: Return a JSON encoded version of [`AccountBalance`]
+    /// * BCS: Return a BCS encoded version of [`AccountBalance`]
+    pub fn balance(
+        self,
+        accept_type: &AcceptType,
+        asset_type: &str,
+    ) -> BasicResultWith404<AccountBalance> {
+        let sources = self.balance_sources(asset_type)?;
+        let balance = self
+            .balance_at(&sources, self.ledger_version)?
+            .ok_or_else(|| {
+                build_not_found(
+                    "Balance",
+                    format!(
+                        "Address({}), Asset type({}) and Ledger version({})",
+                        self.address, asset_type, self.ledger_version
+                    ),
+                    AptosErrorCode::ResourceNotFound,
+                    &self.latest_ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                balance,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                balance,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
+
+    /// Retrieves the balance changes of an asset, going back from the lookup ledger version
+    ///
+    /// Versioned state reads return the version a state value was last written at, so the
+    /// history is walked backwards one change at a time.  Writes which don't change the
+    /// balance are merged into the previous entry.
+    ///
+    /// * JSON: Return a JSON encoded version of a list of [`AccountBalance`]
+    /// * BCS: Return a BCS encoded version of a list of [`AccountBalance`]
+    pub fn balance_history(
+        self,
+        accept_type: &AcceptType,
+        asset_type: &str,
+        start_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<AccountBalance>> {
+        let sources = self.balance_sources(asset_type)?;
+        let limit = Page::new(None, self.limit, self.context.max_transactions_page_size())
+            .limit::<BasicErrorWith404>(&self.latest_ledger_info)? as usize;
+        let start_version = std::cmp::max(
+            start_version.unwrap_or(0),
+            self.latest_ledger_info.oldest_ledger_version.0,
+        );
+
+        let mut history: Vec<AccountBalance> = vec![];
+        let mut version = self.ledger_version;
+        let mut next_version = None;
+        let mut num_reads = 0;
+        loop {
+            let balance = match self.balance_at(&sources, version) {
+                Ok(Some(balance)) => balance,
+                Ok(None) => break,
+                // The history is cut where it was pruned while being read
+                Err(BasicErrorWith404::Gone(..)) if !history.is_empty() => break,
+                Err(err) => return Err(err),
+            };
+            num_reads += 1;
+            if let Some(last) = history
+                .last_mut()
+                .filter(|last| last.balance == balance.balance)
+            {
+                last.version = balance.version;
+            } else if history.len() == limit {
+                next_version = Some(version);
+                break;
+            } else {
+                history.push(balance.clone());
+            }
+            if balance.version.0 <= start_version {
+                break;
+            }
+            version = balance.version.0 - 1;
+            if num_reads == MAX_BALANCE_HISTORY_READS {
+                next_version = Some(version);
+                break;
+            }
+        }
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                history,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                history,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+        .map(|v| v.with_version_cursor(next_version))
+    }
+
+    /// Parses the asset type into the stores holding its balance for the account
+    fn balance_sources(&self, asset_type: &str) -> Result<Vec<BalanceSource>, BasicErrorWith404> {
+        BalanceSource::for_asset(self.address.into(), asset_type)
+            .context(format!("Invalid asset type '{}'", asset_type))
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &self.latest_ledger_info,
+                )
+            })
+    }
+
+    /// Sums up the balance in all stores at `version`, along with the latest version any of them
+    /// changed at.  Returns `None` if none of the stores exist, and a 410 if `version` is pruned.
+    fn balance_at(
+        &self,
+        sources: &[BalanceSource],
+        version: u64,
+    ) -> Result<Option<AccountBalance>, BasicErrorWith404> {
+        let mut result: Option<(u64, u64)> = None;
+        for source in sources {
+            let state_value = self
+                .context
+                .db
+                .get_state_value_with_version_by_version(source.state_key(), version)
+                .map_err(anyhow::Error::from)
+                .and_then(|value| {
+                    value
+                        .map(|(changed_at, value)| Ok((changed_at, source.balance(value.bytes())?)))
+                        .transpose()
+                })
+                .context(format!(
+                    "Failed to read balance of {} at version {}",
+                    self.address, version
+                ))
+                .map_err(|err| {
+                    // The state values are pruned along with the rest of the ledger
+                    match self.context.db.get_first_txn_version() {
+                        Ok(Some(first_version)) if version < first_version => {
+                            version_pruned(version, &self.latest_ledger_info)
+                        },
+                        _ => BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &self.latest_ledger_info,
+                        ),
+                    }
+                })?;
+            if let Some((changed_at, balance)) = state_value {
+                let (last_changed_at, total) = result.unwrap_or_default();
+                result = Some((std::cmp::max(last_changed_at, changed_at), total + balance));
+            }
+        }
+        Ok(result.map(|(version, balance)| AccountBalance {
+            version: version.into(),
+            balance: balance.into(),
+        }))
+    }
+
     pub fn get_account_resource(&self) -> Result<Vec<u8>, BasicErrorWith404> {
         let state_key =
             StateKey::resource_typed::<AccountResource>(self.address.inner()).map_err(|e| {
@@ -559,3 +844,82 @@ impl Account {
             })
     }
 }
+
+/// A store holding (part of) the balance of an asset for an account
+enum BalanceSource {
+    /// `0x1::coin::CoinStore<CoinType>`
+    CoinStore(StateKey),
+    /// The `0x1::object::ObjectGroup` of the primary fungible store
+    PrimaryFungibleStore {
+        state_key: StateKey,
+        metadata: AccountAddress,
+    },
+}
+
+impl BalanceSource {
+    /// Returns the stores holding the balance of `asset_type`, which is either a coin type or
+    /// the address of the metadata of a fungible asset
+    fn for_asset(address: AccountAddress, asset_type: &str) -> anyhow::Result<Vec<Self>> {
+        if !asset_type.contains("::") {
+            let metadata: Address = asset_type.parse()?;
+            return Ok(vec![Self::primary_fungible_store(address, metadata.into())]);
+        }
+
+        let coin_type = TypeTag::Struct(Box::new(StructTag::from_str(asset_type)?));
+        let coin_store = StructTag {
+            address: AccountAddress::ONE,
+            module: ident_str!("coin").to_owned(),
+            name: ident_str!("CoinStore").to_owned(),
+            type_args: vec![coin_type.clone()],
+        };
+        let mut sources = vec![Self::CoinStore(StateKey::resource(&address, &coin_store)?)];
+        // APT is being migrated to the fungible asset at 0xa
+        if coin_type == AptosCoinType::type_tag() {
+            sources.push(Self::primary_fungible_store(address, AccountAddress::TEN));
+        }
+        Ok(sources)
+    }
+
+    fn primary_fungible_store(address: AccountAddress, metadata: AccountAddress) -> Self {
+        Self::PrimaryFungibleStore {
+            state_key: StateKey::resource_group(
+                &create_derived_object_address(address, metadata),
+                &ObjectGroupResource::struct_tag(),
+            ),
+            metadata,
+        }
+    }
+
+    fn state_key(&self) -> &StateKey {
+        match self {
+            Self::CoinStore(state_key) => state_key,
+            Self::PrimaryFungibleStore { state_key, .. } => state_key,
+        }
+    }
+
+    fn balance(&self, bytes: &[u8]) -> anyhow::Result<u64> {
+        match self {
+            Self::CoinStore(_) => Ok(bcs::from_bytes::<CoinStoreResourceUntyped>(bytes)?.coin()),
+            Self::PrimaryFungibleStore { metadata, .. } => {
+                let group: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(bytes)?;
+                let mut balance = 0;
+                if let Some(bytes) = group.get(&FungibleStoreResource::struct_tag()) {
+                    let store: FungibleStoreResource = bcs::from_bytes(bytes)?;
+                    ensure!(
+                        store.metadata() == *metadata,
+                        "Fungible store holds {} instead of {}",
+                        store.metadata(),
+                        metadata
+                    );
+                    balance += store.balance();
+                }
+                // Concurrent stores keep the balance in an aggregator instead
+                if let Some(bytes) = group.get(&ConcurrentFungibleBalanceResource::struct_tag()) {
+                    balance +=
+                        bcs::from_bytes::<ConcurrentFungibleBalanceResource>(bytes)?.balance();
+                }
+                Ok(balance)
+            },
+        }
+    }
+}
@@ -10,11 +10,12 @@ use crate::{
         block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
         ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
     },
+    state_overlay::StateOverrideLimits,
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{
     AptosErrorCode, AsConverter, BcsBlock, GasEstimation, LedgerInfo, ResourceGroup,
-    TransactionOnChainData,
+    StateValueWithProof, TransactionOnChainData, TransactionWithProofBcs,
 };
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
@@ -49,7 +50,7 @@ use futures::{channel::oneshot, SinkExt};
 use mini_moka::sync::Cache;
 use move_core_types::{
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     move_resource::MoveResource,
 };
 use serde::Serialize;
@@ -78,6 +79,7 @@ pub struct Context {
     simulate_txn_stats: Arc<FunctionStats>,
     pub indexer_reader: Option<Arc<dyn IndexerReader>>,
     pub wait_for_hash_active_connections: Arc<AtomicUsize>,
+    pub subscription_active_connections: Arc<AtomicUsize>,
 }
 
 impl std::fmt::Debug for Context {
@@ -131,6 +133,7 @@ impl Context {
             simulate_txn_stats,
             indexer_reader,
             wait_for_hash_active_connections: Arc::new(AtomicUsize::new(0)),
+            subscription_active_connections: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -337,6 +340,73 @@ impl Context {
         Ok(self.db.get_latest_ledger_info()?)
     }
 
+    /// The version of the latest state snapshot at or before `version`, if any
+    ///
+    /// State values can only be proven at state snapshots, since the Jellyfish Merkle tree isn't
+    /// persisted for every version.
+    pub fn get_state_snapshot_version(&self, version: Version) -> Result<Option<Version>> {
+        Ok(self
+            .db
+            .get_state_snapshot_before(version + 1)?
+            .map(|(snapshot_version, _)| snapshot_version))
+    }
+
+    /// Reads a state value along with the proofs authenticating it against the latest
+    /// `LedgerInfoWithSignatures`
+    ///
+    /// `version` must be a state snapshot, see [`Self::get_state_snapshot_version`].
+    pub fn get_state_value_with_proof(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<StateValueWithProof> {
+        let ledger_info_with_signatures = self.get_latest_ledger_info_with_signatures()?;
+        let ledger_version = ledger_info_with_signatures.ledger_info().version();
+        ensure!(
+            version <= ledger_version,
+            "Version {} is newer than the signed ledger version {}",
+            version,
+            ledger_version
+        );
+        let (state_value, sparse_merkle_proof) = self
+            .db
+            .get_state_value_with_proof_by_version_ext(state_key, version, 0)?;
+        let transaction_info_with_proof = self
+            .db
+            .get_transaction_by_version(version, ledger_version, false)?
+            .proof;
+
+        Ok(StateValueWithProof {
+            state_key: state_key.clone(),
+            state_value,
+            version,
+            sparse_merkle_proof,
+            transaction_info_with_proof,
+            ledger_info_with_signatures,
+        })
+    }
+
+    /// Reads a committed transaction and its events along with the proof authenticating them
+    /// against the latest `LedgerInfoWithSignatures`
+    pub fn get_transaction_with_proof(&self, version: Version) -> Result<TransactionWithProofBcs> {
+        let ledger_info_with_signatures = self.get_latest_ledger_info_with_signatures()?;
+        let ledger_version = ledger_info_with_signatures.ledger_info().version();
+        ensure!(
+            version <= ledger_version,
+            "Version {} is newer than the signed ledger version {}",
+            version,
+            ledger_version
+        );
+        let transaction_with_proof =
+            self.db
+                .get_transaction_by_version(version, ledger_version, true)?;
+
+        Ok(TransactionWithProofBcs {
+            transaction_with_proof,
+            ledger_info_with_signatures,
+        })
+    }
+
     pub fn get_state_value(&self, state_key: &StateKey, version: u64) -> Result<Option<Vec<u8>>> {
         Ok(self
             .db
@@ -944,6 +1014,34 @@ impl Context {
         }
     }
 
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        end_version: Version,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        Ok(self
+            .indexer_reader
+            .as_ref()
+            .ok_or(anyhow!("Internal indexer reader doesn't exist"))?
+            .get_events_by_type(
+                type_tag,
+                start_version,
+                end_version,
+                limit as u64,
+                ledger_version,
+            )?)
+    }
+
+    pub fn get_events_by_type_min_version(&self) -> Result<Version> {
+        self.indexer_reader
+            .as_ref()
+            .ok_or(anyhow!("Internal indexer reader doesn't exist"))?
+            .get_events_by_type_min_version()
+    }
+
     fn next_bucket(&self, gas_unit_price: u64) -> u64 {
         match self
             .node_config
@@ -1241,6 +1339,20 @@ impl Context {
         Ok(gas_schedule.vm.txn.min_price_per_gas_unit.into())
     }
 
+    /// Limits of the state overrides of a simulation or a view function: they can't write more
+    /// state items than a transaction, nor more bytes than a request can hold
+    pub fn state_override_limits<E: InternalError>(
+        &self,
+        ledger_info: &LedgerInfo,
+    ) -> Result<StateOverrideLimits, E> {
+        let (_, gas_schedule) = self.get_gas_schedule(ledger_info)?;
+        Ok(StateOverrideLimits {
+            max_num_overrides: u64::from(gas_schedule.vm.txn.max_write_ops_per_transaction)
+                as usize,
+            max_total_bytes: self.content_length_limit(),
+        })
+    }
+
     pub fn get_gas_schedule<E: InternalError>(
         &self,
         ledger_info: &LedgerInfo,
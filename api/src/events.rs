@@ -9,8 +9,8 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        version_pruned, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, InternalError,
     },
     ApiTags,
 };
@@ -19,7 +19,8 @@ use aptos_api_types::{
     verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
@@ -150,6 +151,77 @@ impl EventsApi {
         })
         .await
     }
+
+    /// Get events by type
+    ///
+    /// Retrieve events of the given type emitted by any account, within a range of
+    /// ledger versions. Unlike the other event APIs, this includes module events,
+    /// which are not attached to an event handle.
+    ///
+    /// This API is only available on nodes running the internal indexer with the
+    /// event index enabled. Events are returned in ascending version order. A page
+    /// never splits the events emitted by a single transaction, so it can contain
+    /// slightly more than `limit` events. To retrieve the next page, pass the version
+    /// after the last returned event as `start_version`.
+    ///
+    /// The index only covers transactions committed after the node started building
+    /// it, so on a node upgraded from an older release, older versions are reported as
+    /// pruned (410). Indexing them requires rebuilding the internal indexer DB.
+    #[oai(
+        path = "/events",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Type of the events to retrieve e.g. `0x1::coin::CoinDeposit`
+        #[oai(name = "type")]
+        event_type: Query<MoveStructTag>,
+        /// Ledger version to start retrieving events from, inclusive
+        ///
+        /// If unspecified, defaults to the oldest version available in the index
+        start_version: Query<Option<U64>>,
+        /// Ledger version to stop retrieving events at, inclusive
+        ///
+        /// If unspecified, defaults to the latest ledger version
+        end_version: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        event_type
+            .0
+            .verify(0)
+            .context("'type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let type_tag = StructTag::try_from(event_type.0)
+            .map(|tag| TypeTag::Struct(Box::new(tag)))
+            .context("'type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        let page = Page::new(None, limit.0, self.context.max_events_page_size());
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.list_by_type(
+                accept_type,
+                page,
+                type_tag,
+                start_version.0.map(|v| v.0),
+                end_version.0.map(|v| v.0),
+            )
+        })
+        .await
+    }
 }
 
 impl EventsApi {
@@ -179,6 +251,79 @@ impl EventsApi {
                 )
             })?;
 
+        self.render(latest_ledger_info, accept_type, events)
+    }
+
+    /// List events of a given type within a range of versions
+    fn list_by_type(
+        &self,
+        accept_type: AcceptType,
+        page: Page,
+        type_tag: TypeTag,
+        start_version: Option<u64>,
+        end_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let ledger_version = latest_ledger_info.version();
+        // Events older than the start of the events by type index can't be looked up, whether
+        // they're pruned or were committed before the node started building the index.
+        let oldest_version = self
+            .context
+            .get_events_by_type_min_version()
+            .context("Failed to get the oldest version of the events by type index")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?
+            .max(latest_ledger_info.oldest_ledger_version.0);
+
+        let start_version = start_version.unwrap_or(oldest_version);
+        if start_version < oldest_version {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        }
+        let end_version = end_version.map_or(ledger_version, |v| v.min(ledger_version));
+        if start_version > end_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "start_version {} is greater than end_version {}",
+                    start_version, end_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &latest_ledger_info,
+            ));
+        }
+
+        let events = self
+            .context
+            .get_events_by_type(
+                &type_tag,
+                start_version,
+                end_version,
+                page.limit(&latest_ledger_info)?,
+                ledger_version,
+            )
+            .context(format!("Failed to find events by type {}", type_tag))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+
+        self.render(latest_ledger_info, accept_type, events)
+    }
+
+    /// Converts events into the outgoing type
+    fn render(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let events = self
@@ -5,11 +5,15 @@
 use crate::{
     accept_type::AcceptType,
     context::{api_spawn_blocking, Context},
-    response::{BasicResponse, BasicResponseStatus, BasicResult},
+    response::{
+        api_forbidden, version_not_found, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResult, BasicResultWith404, InternalError,
+    },
     ApiTags,
 };
-use aptos_api_types::{IndexResponse, IndexResponseBcs};
-use poem_openapi::OpenApi;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AptosErrorCode, IndexResponse, IndexResponseBcs, MoveValue, U64};
+use poem_openapi::{param::Query, OpenApi};
 use std::sync::Arc;
 
 /// API for the index, to retrieve the ledger information
@@ -55,4 +59,60 @@ impl IndexApi {
         })
         .await
     }
+
+    /// Get state proof
+    ///
+    /// Get the latest ledger info with signatures, along with the epoch change proof from the
+    /// epoch of `known_version` to the latest epoch. Light clients use it to ratchet their
+    /// trusted state forward.
+    ///
+    /// Only BCS is supported as an AcceptType.
+    #[oai(
+        path = "/state_proof",
+        method = "get",
+        operation_id = "get_state_proof",
+        tag = "ApiTags::General"
+    )]
+    async fn get_state_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Latest ledger version already trusted by the client
+        ///
+        /// If not provided, it will be 0
+        known_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveValue> {
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get state proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get state proof", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let known_version = known_version.0.map_or(0, |inner| inner.0);
+        if known_version > ledger_info.version() {
+            return Err(version_not_found(known_version, &ledger_info));
+        }
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let state_proof = context
+                .db
+                .get_state_proof(known_version)
+                .context(format!(
+                    "Failed to get state proof from version {}",
+                    known_version
+                ))
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            BasicResponse::try_from_bcs((state_proof, &ledger_info, BasicResponseStatus::Ok))
+        })
+        .await
+    }
 }
@@ -18,11 +18,14 @@ mod index;
 mod log;
 pub mod metrics;
 mod page;
+mod rate_limit;
 mod response;
 mod runtime;
 mod set_failpoints;
 pub mod spec;
 mod state;
+mod state_overlay;
+mod subscriptions;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
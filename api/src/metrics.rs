@@ -105,3 +105,38 @@ pub static WAIT_TRANSACTION_POLL_TIME: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static SUBSCRIPTION_GAUGE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_api_subscriptions",
+        "Number of active transaction and event subscriptions"
+    )
+    .unwrap()
+});
+
+pub static SUBSCRIPTION_ITEMS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_subscription_items",
+        "Number of items pushed to subscribers grouped by subscription type",
+        &["subscription_type"]
+    )
+    .unwrap()
+});
+
+pub static RATE_LIMITED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_rate_limited_requests",
+        "API requests rejected by the rate limiter, grouped by client type (ip or api_key) and endpoint class",
+        &["client_type", "endpoint_class"]
+    )
+    .unwrap()
+});
+
+pub static RATE_LIMIT_BUCKETS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_api_rate_limit_buckets",
+        "Number of API requests allowed or throttled per client between two refills of its bucket",
+        &["bucket", "result"]
+    )
+    .unwrap()
+});
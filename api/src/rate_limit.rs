@@ -0,0 +1,312 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::{RATE_LIMITED_REQUESTS, RATE_LIMIT_BUCKETS};
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_config::config::{ApiRateLimitConfig, EndpointClassRateLimits, TokenBucketConfig};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::{Bucket, SharedBucket, TokenBucketRateLimiter};
+use poem::{
+    http::{header, HeaderName, Method, StatusCode},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use poem_openapi::payload::Json;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const CLIENT_TYPE_IP: &str = "ip";
+const CLIENT_TYPE_API_KEY: &str = "api_key";
+
+/// Classes of endpoints, limited independently of each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EndpointClass {
+    Read,
+    Submit,
+    Simulate,
+}
+
+impl EndpointClass {
+    fn of(request: &Request) -> Self {
+        if request.method() != Method::POST {
+            return EndpointClass::Read;
+        }
+        let path = request.uri().path().trim_end_matches('/');
+        if path.starts_with("/v1/transactions/simulate") {
+            EndpointClass::Simulate
+        } else if path == "/v1/transactions" || path == "/v1/transactions/batch" {
+            EndpointClass::Submit
+        } else {
+            EndpointClass::Read
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Read => "read",
+            EndpointClass::Submit => "submit",
+            EndpointClass::Simulate => "simulate",
+        }
+    }
+}
+
+/// The buckets of the clients of an API key, shared by all of them
+struct ApiKeyBuckets {
+    read: Option<SharedBucket>,
+    submit: Option<SharedBucket>,
+    simulate: Option<SharedBucket>,
+}
+
+impl ApiKeyBuckets {
+    fn new(name: &str, limits: &EndpointClassRateLimits) -> Self {
+        let bucket = |label: &'static str, config: &Option<TokenBucketConfig>| {
+            config.as_ref().map(|config| {
+                // The name is used as the bucket key, so that the key itself is never logged
+                Arc::new(Mutex::new(Bucket::new(
+                    label.to_string(),
+                    String::new(),
+                    name.to_string(),
+                    config.bucket_size,
+                    config.bucket_size,
+                    config.fill_rate,
+                    Some(RATE_LIMIT_BUCKETS.clone()),
+                )))
+            })
+        };
+        Self {
+            read: bucket("api_key_read", &limits.read),
+            submit: bucket("api_key_submit", &limits.submit),
+            simulate: bucket("api_key_simulate", &limits.simulate),
+        }
+    }
+
+    fn get(&self, class: EndpointClass) -> Option<&SharedBucket> {
+        match class {
+            EndpointClass::Read => self.read.as_ref(),
+            EndpointClass::Submit => self.submit.as_ref(),
+            EndpointClass::Simulate => self.simulate.as_ref(),
+        }
+    }
+}
+
+/// The buckets of every IP address, for each class of endpoint
+struct IpRateLimiters {
+    read: Option<TokenBucketRateLimiter<Option<IpAddr>>>,
+    submit: Option<TokenBucketRateLimiter<Option<IpAddr>>>,
+    simulate: Option<TokenBucketRateLimiter<Option<IpAddr>>>,
+}
+
+impl IpRateLimiters {
+    fn new(limits: &EndpointClassRateLimits) -> Self {
+        let rate_limiter = |label: &'static str, config: &Option<TokenBucketConfig>| {
+            config.as_ref().map(|config| {
+                TokenBucketRateLimiter::new(
+                    label,
+                    String::new(),
+                    100,
+                    config.bucket_size,
+                    config.fill_rate,
+                    Some(RATE_LIMIT_BUCKETS.clone()),
+                )
+            })
+        };
+        Self {
+            read: rate_limiter("ip_read", &limits.read),
+            submit: rate_limiter("ip_submit", &limits.submit),
+            simulate: rate_limiter("ip_simulate", &limits.simulate),
+        }
+    }
+
+    fn get(&self, class: EndpointClass) -> Option<&TokenBucketRateLimiter<Option<IpAddr>>> {
+        match class {
+            EndpointClass::Read => self.read.as_ref(),
+            EndpointClass::Submit => self.submit.as_ref(),
+            EndpointClass::Simulate => self.simulate.as_ref(),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &TokenBucketRateLimiter<Option<IpAddr>>> {
+        [&self.read, &self.submit, &self.simulate]
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// Rate limiters of the API, shared by all the connections
+pub struct RateLimiters {
+    enabled: bool,
+    client_ip_header: Option<HeaderName>,
+    client_ip_header_trusted_hops: usize,
+    api_key_header: Option<HeaderName>,
+    per_ip: IpRateLimiters,
+    api_keys: HashMap<String, ApiKeyBuckets>,
+}
+
+impl RateLimiters {
+    pub fn new(config: &ApiRateLimitConfig) -> anyhow::Result<Self> {
+        let parse_header = |header: &Option<String>| {
+            header
+                .as_deref()
+                .map(HeaderName::try_from)
+                .transpose()
+                .map_err(|err| anyhow::anyhow!("Invalid rate limit header: {}", err))
+        };
+        Ok(Self {
+            enabled: config.enabled,
+            client_ip_header: parse_header(&config.client_ip_header)?,
+            client_ip_header_trusted_hops: config.client_ip_header_trusted_hops,
+            api_key_header: parse_header(&config.api_key_header)?,
+            per_ip: IpRateLimiters::new(&config.per_ip),
+            api_keys: config
+                .api_keys
+                .iter()
+                .map(|api_key| {
+                    (
+                        api_key.key.clone(),
+                        ApiKeyBuckets::new(&api_key.name, &api_key.limits),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Drops the buckets of the IP addresses that haven't sent requests for a while, so they
+    /// don't pile up
+    pub fn garbage_collect(&self) -> usize {
+        self.per_ip
+            .iter()
+            .map(TokenBucketRateLimiter::garbage_collect_full_buckets)
+            .sum()
+    }
+
+    /// Takes a token for the request, or returns when to retry if there are none left
+    fn acquire(&self, request: &Request) -> Result<(), (&'static str, EndpointClass, Instant)> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let class = EndpointClass::of(request);
+
+        // Unknown keys fall back to the IP limits, so they can't be used to bypass them
+        let api_key_buckets = self
+            .api_key_header
+            .as_ref()
+            .and_then(|header| request.headers().get(header))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|key| self.api_keys.get(key));
+        let (client_type, bucket) = match api_key_buckets {
+            Some(buckets) => (CLIENT_TYPE_API_KEY, buckets.get(class).cloned()),
+            None => (
+                CLIENT_TYPE_IP,
+                self.per_ip
+                    .get(class)
+                    .map(|rate_limiter| rate_limiter.bucket(self.client_ip(request))),
+            ),
+        };
+
+        match bucket {
+            Some(bucket) => bucket.lock().acquire_all_tokens(1).map_err(|retry_at| {
+                // The bucket can always hold a single token, so there's always a time
+                let retry_at = retry_at.unwrap_or_else(|| Instant::now() + Duration::from_secs(1));
+                (client_type, class, retry_at)
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The IP address from the configured header, or the address of the peer
+    ///
+    /// Proxies append to the header, so only the entries on the right, added by the trusted
+    /// proxies, can't be forged by the client.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        self.client_ip_header
+            .as_ref()
+            .and_then(|header| {
+                request
+                    .headers()
+                    .get_all(header)
+                    .iter()
+                    .map(|value| value.to_str().ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .and_then(|values| {
+                let entries: Vec<_> = values.iter().flat_map(|value| value.split(',')).collect();
+                self.client_ip_header_trusted_hops
+                    .checked_sub(1)
+                    .and_then(|hops| entries.iter().rev().nth(hops))
+                    .and_then(|ip| ip.trim().parse().ok())
+            })
+            .or_else(|| {
+                request
+                    .remote_addr()
+                    .as_socket_addr()
+                    .map(|address| address.ip())
+            })
+    }
+}
+
+/// This middleware rejects requests with a 429 once the client ran out of tokens for the
+/// class of the endpoint, see [`ApiRateLimitConfig`].
+pub struct RateLimit {
+    rate_limiters: Arc<RateLimiters>,
+}
+
+impl RateLimit {
+    pub fn new(rate_limiters: Arc<RateLimiters>) -> Self {
+        Self { rate_limiters }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RateLimit {
+    type Output = RateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            rate_limiters: self.rate_limiters.clone(),
+        }
+    }
+}
+
+/// Endpoint for RateLimit middleware.
+pub struct RateLimitEndpoint<E> {
+    inner: E,
+    rate_limiters: Arc<RateLimiters>,
+}
+
+impl<E: Endpoint> Endpoint for RateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if let Err((client_type, class, retry_at)) = self.rate_limiters.acquire(&req) {
+            RATE_LIMITED_REQUESTS
+                .with_label_values(&[client_type, class.as_str()])
+                .inc();
+            return Ok(rate_limit_exceeded_response(class, retry_at));
+        }
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+fn rate_limit_exceeded_response(class: EndpointClass, retry_at: Instant) -> Response {
+    let retry_after_secs = retry_at
+        .saturating_duration_since(Instant::now())
+        .as_secs_f64()
+        .ceil()
+        .max(1.0) as u64;
+    let error = AptosError::new_with_error_code(
+        format!(
+            "Rate limit exceeded for {} requests, retry after {} seconds",
+            class.as_str(),
+            retry_after_secs
+        ),
+        AptosErrorCode::RateLimitExceeded,
+    );
+    Json(error)
+        .with_status(StatusCode::TOO_MANY_REQUESTS)
+        .with_header(header::RETRY_AFTER, retry_after_secs)
+        .into_response()
+}
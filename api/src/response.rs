@@ -486,6 +486,19 @@ macro_rules! generate_success_response {
                 self
             }
 
+            /// Sets the cursor of endpoints paginating by ledger version, to the version to
+            /// continue from on the next call.
+            pub fn with_version_cursor(mut self, next_version: Option<u64>) -> Self {
+                match self {
+                    $(
+                    [<$enum_name>]::$name(_, _, _, _, _, _, _, _, _, ref mut cursor) => {
+                        *cursor = next_version.map(|version| version.to_string());
+                    }
+                    )*
+                }
+                self
+            }
+
             pub fn with_gas_used(mut self, new_gas_used: Option<u64>) -> Self {
                 match self {
                     $(
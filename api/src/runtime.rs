@@ -12,9 +12,11 @@ use crate::{
     events::EventsApi,
     index::IndexApi,
     log::middleware_log,
+    rate_limit::{RateLimit, RateLimiters},
     set_failpoints,
     spec::{spec_endpoint_json, spec_endpoint_yaml},
     state::StateApi,
+    subscriptions::SubscriptionsApi,
     transactions::TransactionsApi,
     view_function::ViewFunctionApi,
 };
@@ -115,6 +117,7 @@ pub fn get_api_service(
         EventsApi,
         IndexApi,
         StateApi,
+        SubscriptionsApi,
         TransactionsApi,
         ViewFunctionApi,
     ),
@@ -140,6 +143,9 @@ pub fn get_api_service(
         StateApi {
             context: context.clone(),
         },
+        SubscriptionsApi {
+            context: context.clone(),
+        },
         TransactionsApi {
             context: context.clone(),
         },
@@ -172,6 +178,19 @@ pub fn attach_poem_to_runtime(
 
     let size_limit = context.content_length_limit();
 
+    let rate_limiters = Arc::new(RateLimiters::new(&config.api.rate_limit)?);
+    if config.api.rate_limit.enabled {
+        let rate_limiters = rate_limiters.clone();
+        let period_sec = config.api.rate_limit.garbage_collection_interval_secs;
+        runtime_handle.spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(period_sec));
+            loop {
+                interval.tick().await;
+                rate_limiters.garbage_collect();
+            }
+        });
+    }
+
     let api_service = get_api_service(context.clone());
 
     let spec_json = spec_endpoint_json(&api_service);
@@ -240,6 +259,8 @@ pub fn attach_poem_to_runtime(
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
                     ),
             )
+            // Keep this inside of CORS, so browsers can read the rate limited responses
+            .with(RateLimit::new(rate_limiters))
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
             // NOTE: Make sure to keep this after all the `with` middleware.
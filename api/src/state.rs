@@ -14,7 +14,7 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
+    verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
     RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
 };
@@ -23,8 +23,10 @@ use move_core_types::language_storage::StructTag;
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
+    types::ToJSON,
     OpenApi,
 };
+use serde::Serialize;
 use std::{convert::TryInto, sync::Arc};
 
 /// API for retrieving individual state
@@ -40,6 +42,14 @@ impl StateApi {
     /// Retrieves an individual resource from a given account and at a specific ledger version. If the
     /// ledger version is not specified in the request, the latest ledger version is used.
     ///
+    /// If `with_proof` is set, the BCS encoded `StateValueWithProof` of the state value holding
+    /// the resource is returned instead, which can be verified against the included
+    /// `LedgerInfoWithSignatures`. For resources in a resource group, the whole group is
+    /// returned. A missing resource is returned with a proof of its absence instead of a 404.
+    /// State values can only be proven at state snapshots: without a ledger version, the value is
+    /// proven at the latest snapshot, whose version is in the `StateValueWithProof`, and a
+    /// requested ledger version without a snapshot is rejected with a 400.
+    ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
     #[oai(
@@ -59,6 +69,10 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
+        /// If true, return the state value along with a proof of it
+        ///
+        /// Only BCS is supported as an AcceptType when a proof is requested.
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<MoveResource> {
         resource_type
             .0
@@ -70,15 +84,31 @@ impl StateApi {
         fail_point_poem("endpoint_get_account_resource")?;
         self.context
             .check_api_output_enabled("Get account resource", &accept_type)?;
+        let with_proof = with_proof.0.unwrap_or(false);
+        if with_proof && AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get account resource with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
 
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.resource(
-                &accept_type,
-                address.0,
-                resource_type.0,
-                ledger_version.0.map(|inner| inner.0),
-            )
+            if with_proof {
+                api.resource_with_proof(
+                    &accept_type,
+                    address.0,
+                    resource_type.0,
+                    ledger_version.0.map(|inner| inner.0),
+                )
+            } else {
+                api.resource(
+                    &accept_type,
+                    address.0,
+                    resource_type.0,
+                    ledger_version.0.map(|inner| inner.0),
+                )
+            }
         })
         .await
     }
@@ -133,6 +163,11 @@ impl StateApi {
     /// fields could themselves be composed of other structs. This makes it
     /// impractical to express using query params, meaning GET isn't an option.
     ///
+    /// If `with_proof` is set, the BCS encoded `StateValueWithProof` of the table item is
+    /// returned instead, which can be verified against the included `LedgerInfoWithSignatures`.
+    /// A missing item is returned with a proof of its absence instead of a 404. As for resources,
+    /// values are only proven at state snapshots.
+    ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
     #[oai(
@@ -152,6 +187,10 @@ impl StateApi {
         ///
         /// If not provided, it will be the latest version
         ledger_version: Query<Option<U64>>,
+        /// If true, return the state value along with a proof of it
+        ///
+        /// Only BCS is supported as an AcceptType when a proof is requested.
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<MoveValue> {
         table_item_request
             .0
@@ -163,6 +202,13 @@ impl StateApi {
         fail_point_poem("endpoint_get_table_item")?;
         self.context
             .check_api_output_enabled("Get table item", &accept_type)?;
+        let with_proof = with_proof.0.unwrap_or(false);
+        if with_proof && AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get table item with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
         let api = self.clone();
         api_spawn_blocking(move || {
             api.table_item(
@@ -170,6 +216,7 @@ impl StateApi {
                 table_handle.0,
                 table_item_request.0,
                 ledger_version.0,
+                with_proof,
             )
         })
         .await
@@ -326,6 +373,49 @@ impl StateApi {
         }
     }
 
+    /// Read the state value holding a resource at the ledger version, along with its proof
+    ///
+    /// BCS: `StateValueWithProof`
+    fn resource_with_proof(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTag,
+        requested_version: Option<u64>,
+    ) -> BasicResultWith404<MoveResource> {
+        let tag: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let (ledger_info, ledger_version, state_view) =
+            self.context.state_view(requested_version)?;
+        let state_key = state_view
+            .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
+            .find_resource_state_key(address, &tag)
+            .context(format!(
+                "Failed to build state key for {} at {}",
+                tag, address
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+
+        self.state_value_with_proof(
+            accept_type,
+            &state_key,
+            requested_version,
+            ledger_version,
+            &ledger_info,
+        )
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
@@ -383,6 +473,7 @@ impl StateApi {
         table_handle: Address,
         table_item_request: TableItemRequest,
         ledger_version: Option<U64>,
+        with_proof: bool,
     ) -> BasicResultWith404<MoveValue> {
         // Parse the key and value types for the table
         let key_type = table_item_request
@@ -402,9 +493,9 @@ impl StateApi {
             })?;
 
         // Retrieve local state
-        let (ledger_info, ledger_version, state_view) = self
-            .context
-            .state_view(ledger_version.map(|inner| inner.0))?;
+        let requested_version = ledger_version.map(|inner| inner.0);
+        let (ledger_info, ledger_version, state_view) =
+            self.context.state_view(requested_version)?;
 
         let converter =
             state_view.as_converter(self.context.db.clone(), self.context.indexer_reader.clone());
@@ -429,6 +520,15 @@ impl StateApi {
 
         // Retrieve value from the state key
         let state_key = StateKey::table_item(&TableHandle(table_handle.into()), &raw_key);
+        if with_proof {
+            return self.state_value_with_proof(
+                accept_type,
+                &state_key,
+                requested_version,
+                ledger_version,
+                &ledger_info,
+            );
+        }
         let bytes = state_view
             .get_state_value_bytes(&state_key)
             .context(format!(
@@ -590,4 +690,80 @@ impl StateApi {
             },
         }
     }
+
+    /// Retrieve a state value along with the proof authenticating it
+    ///
+    /// The value is proven at the requested version, which must be a state snapshot, or else at
+    /// the latest state snapshot at or before the ledger version.
+    ///
+    /// JSON: Not supported
+    /// BCS: `StateValueWithProof`
+    fn state_value_with_proof<T: ToJSON + Send + Sync + Serialize>(
+        &self,
+        accept_type: &AcceptType,
+        state_key: &StateKey,
+        requested_version: Option<u64>,
+        ledger_version: u64,
+        ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<T> {
+        let snapshot_version = self
+            .context
+            .get_state_snapshot_version(ledger_version)
+            .context(format!(
+                "Failed to find the state snapshot at or before version {}",
+                ledger_version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+        let version = match (requested_version, snapshot_version) {
+            (None, Some(snapshot_version)) => snapshot_version,
+            (Some(version), Some(snapshot_version)) if version == snapshot_version => version,
+            (_, snapshot_version) => {
+                let latest = snapshot_version.map_or_else(String::new, |snapshot_version| {
+                    format!(", the latest one before is at version {}", snapshot_version)
+                });
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    format!(
+                        "State values can only be proven at state snapshots, and there is none \
+                        at version {}{}",
+                        ledger_version, latest
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                ));
+            },
+        };
+
+        let state_value_with_proof = self
+            .context
+            .get_state_value_with_proof(state_key, version)
+            .context(format!(
+                "Failed to read state value with proof. key: {:?}",
+                state_key
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => Err(api_forbidden(
+                "Get state value with proof",
+                "Only BCS is supported as an AcceptType.",
+            )),
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                state_value_with_proof,
+                ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        }
+    }
 }
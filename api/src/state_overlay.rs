@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::ensure;
+use aptos_api_types::{AsConverter, StateOverride, StateOverrideBcs};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    indexer::indexer_db_reader::IndexerReader,
+    state_store::{
+        errors::StateviewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, StateViewId, TStateView,
+    },
+    write_set::{TransactionWrite, WriteSet},
+};
+use std::{collections::HashMap, sync::Arc};
+
+type Result<T, E = StateviewError> = std::result::Result<T, E>;
+
+/// Bounds of the state overrides of a request, see `Context::state_override_limits`
+pub struct StateOverrideLimits {
+    /// Max number of state overrides
+    pub max_num_overrides: usize,
+    /// Max total size of the overridden state items, in bytes
+    pub max_total_bytes: u64,
+}
+
+impl StateOverrideLimits {
+    fn check_num_overrides(&self, num_overrides: usize) -> anyhow::Result<()> {
+        ensure!(
+            num_overrides <= self.max_num_overrides,
+            "At most {} state overrides are allowed, got {}",
+            self.max_num_overrides,
+            num_overrides
+        );
+        Ok(())
+    }
+
+    fn check_total_bytes(&self, total_bytes: u64) -> anyhow::Result<()> {
+        ensure!(
+            total_bytes <= self.max_total_bytes,
+            "State overrides must be at most {} bytes in total, got {}",
+            self.max_total_bytes,
+            total_bytes
+        );
+        Ok(())
+    }
+}
+
+/// A scratch state on top of a read-only state view
+///
+/// Writes are only kept in memory, and shadow the values of the base state view. This is used to
+/// simulate transactions on top of the changes of previously simulated ones, or on top of state
+/// overrides given by the user, without touching the DB.
+pub struct OverlayStateView<S> {
+    base: S,
+    /// `None` means the value was deleted
+    overlay: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl<S: TStateView<Key = StateKey>> OverlayStateView<S> {
+    pub fn new(base: S) -> Self {
+        Self {
+            base,
+            overlay: HashMap::new(),
+        }
+    }
+
+    /// Applies the changes of a transaction, so that following reads observe them
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set {
+            self.overlay
+                .insert(state_key.clone(), write_op.as_state_value());
+        }
+    }
+
+    /// Replaces state items, e.g. to execute as if an account held some resource
+    ///
+    /// Fails if the overrides exceed the limits, in which case the state view must not be used.
+    pub fn apply_state_overrides(
+        &mut self,
+        state_overrides: Vec<StateOverrideBcs>,
+        limits: &StateOverrideLimits,
+    ) -> anyhow::Result<()> {
+        limits.check_num_overrides(state_overrides.len())?;
+        for state_override in state_overrides {
+            self.insert_state_override(state_override);
+        }
+        limits.check_total_bytes(self.overlay_bytes())
+    }
+
+    /// Same as [`OverlayStateView::apply_state_overrides`], for overrides given in JSON
+    ///
+    /// The overrides are applied in order, so overrides of members of the same resource group
+    /// add up.
+    pub fn apply_json_state_overrides(
+        &mut self,
+        db: Arc<dyn DbReader>,
+        indexer_reader: Option<Arc<dyn IndexerReader>>,
+        state_overrides: Vec<StateOverride>,
+        limits: &StateOverrideLimits,
+    ) -> anyhow::Result<()> {
+        // Checked before converting, as converting an override may read its resource group
+        limits.check_num_overrides(state_overrides.len())?;
+        for state_override in state_overrides {
+            let state_override = self
+                .as_converter(db.clone(), indexer_reader.clone())
+                .try_into_state_override(&*self, state_override)?;
+            self.insert_state_override(state_override);
+        }
+        limits.check_total_bytes(self.overlay_bytes())
+    }
+
+    fn insert_state_override(&mut self, state_override: StateOverrideBcs) {
+        let StateOverrideBcs { state_key, value } = state_override;
+        self.overlay.insert(
+            state_key,
+            value.map(|value| StateValue::new_legacy(value.into())),
+        );
+    }
+
+    /// Total size of the state items written to the overlay
+    fn overlay_bytes(&self) -> u64 {
+        self.overlay
+            .iter()
+            .map(|(state_key, state_value)| {
+                state_key.size() as u64 + state_value.as_ref().map_or(0, |v| v.size() as u64)
+            })
+            .sum()
+    }
+
+    /// Whether any module differs from the base state view
+    ///
+    /// The VM must not share its code cache with other VMs when executing on such a state, see
+    /// [`aptos_vm::AptosVM::new_with_isolated_code_cache`].
+    pub fn overrides_code(&self) -> bool {
+        self.overlay.keys().any(StateKey::is_aptos_code)
+    }
+}
+
+impl<S: TStateView<Key = StateKey>> TStateView for OverlayStateView<S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        match self.overlay.get(state_key) {
+            Some(state_value) => Ok(state_value.clone()),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        // The usage isn't tracked for the overlay, it's only informational for the VM
+        self.base.get_usage()
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::{api_spawn_blocking, Context},
+    failpoint::fail_point_poem,
+    metrics::{SUBSCRIPTION_GAUGE, SUBSCRIPTION_ITEMS},
+    response::{
+        api_disabled, version_pruned, BadRequestError, BasicError, BasicErrorWith404,
+        InternalError, ServiceUnavailableError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, EntryFunctionId, LedgerInfo, MoveStructTag, Transaction,
+    TransactionOnChainData, VerifyInput, VerifyInputWithRecursion, VersionedEvent, U64,
+};
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::EventWithVersion,
+    transaction::{TransactionPayload, Version},
+};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use poem_openapi::{param::Query, payload::EventStream, types::ToJSON, OpenApi};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+/// How often an idle subscription sends a keep alive comment, so proxies
+/// don't close the connection while the chain is quiet.
+const SUBSCRIPTION_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+type SubscriptionResult<T> = poem::Result<EventStream<BoxStream<'static, T>>, BasicErrorWith404>;
+
+/// API for subscribing to committed transactions and events as server-sent events
+#[derive(Clone)]
+pub struct SubscriptionsApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl SubscriptionsApi {
+    /// Subscribe to transactions
+    ///
+    /// Streams committed transactions as server-sent events, starting at the given
+    /// ledger version. Each event carries a single JSON encoded transaction, and
+    /// transactions are always delivered in ledger version order.
+    ///
+    /// The optional filters are combined, so a transaction is only sent if it matches
+    /// all of them. If the start version has been pruned, a 410 will be returned.
+    #[oai(
+        path = "/subscribe/transactions",
+        method = "get",
+        operation_id = "subscribe_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn subscribe_transactions(
+        &self,
+        /// Ledger version to start streaming transactions from
+        ///
+        /// If not provided, only transactions committed after the subscription is
+        /// opened are streamed
+        start_version: Query<Option<U64>>,
+        /// Only stream user transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Only stream user transactions calling this entry function
+        /// e.g. `0x1::aptos_account::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream transactions that emitted an event of this type
+        /// e.g. `0x1::coin::CoinDeposit`
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> SubscriptionResult<Transaction> {
+        fail_point_poem("endpoint_subscribe_transactions")?;
+        let filter = SubscriptionFilter::new(sender.0, entry_function.0, event_type.0)?;
+        self.subscribe(
+            "transactions",
+            start_version.0.map(|v| v.0),
+            filter,
+            render_transactions,
+        )
+        .await
+    }
+
+    /// Subscribe to events
+    ///
+    /// Streams events emitted by committed transactions as server-sent events,
+    /// starting at the given ledger version. Each event carries a single JSON
+    /// encoded event, including module events that are not attached to an event handle.
+    ///
+    /// The optional filters are combined, so an event is only sent if it matches
+    /// all of them. If the start version has been pruned, a 410 will be returned.
+    #[oai(
+        path = "/subscribe/events",
+        method = "get",
+        operation_id = "subscribe_events",
+        tag = "ApiTags::Events"
+    )]
+    async fn subscribe_events(
+        &self,
+        /// Ledger version to start streaming events from
+        ///
+        /// If not provided, only events committed after the subscription is
+        /// opened are streamed
+        start_version: Query<Option<U64>>,
+        /// Only stream events emitted by user transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Only stream events emitted by user transactions calling this entry function
+        /// e.g. `0x1::aptos_account::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream events of this type e.g. `0x1::coin::CoinDeposit`
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> SubscriptionResult<VersionedEvent> {
+        fail_point_poem("endpoint_subscribe_events")?;
+        let filter = SubscriptionFilter::new(sender.0, entry_function.0, event_type.0)?;
+        self.subscribe(
+            "events",
+            start_version.0.map(|v| v.0),
+            filter,
+            render_events,
+        )
+        .await
+    }
+}
+
+impl SubscriptionsApi {
+    /// Validates the subscription request and builds the stream of rendered items
+    async fn subscribe<T: ToJSON + Send + 'static>(
+        &self,
+        subscription_type: &'static str,
+        start_version: Option<Version>,
+        filter: SubscriptionFilter,
+        render: RenderFn<T>,
+    ) -> SubscriptionResult<T> {
+        let api_config = &self.context.node_config.api;
+        if !api_config.subscription_enabled {
+            return Err(api_disabled("Subscriptions"));
+        }
+
+        let context = self.context.clone();
+        let ledger_info = api_spawn_blocking(move || context.get_latest_ledger_info()).await?;
+        let start_version = match start_version {
+            Some(version) if version < ledger_info.oldest_ledger_version.0 => {
+                return Err(version_pruned(version, &ledger_info));
+            },
+            Some(version) => version,
+            None => ledger_info.version() + 1,
+        };
+
+        // Refuse new subscriptions if the active connections are too high
+        let guard = SubscriptionGuard::new(self.context.clone());
+        if guard.active_connections > api_config.subscription_max_active_connections {
+            return Err(BasicErrorWith404::service_unavailable_with_code(
+                "Too many active subscriptions, try again later",
+                AptosErrorCode::ServiceUnavailable,
+                &ledger_info,
+            ));
+        }
+
+        let state = SubscriptionState {
+            context: self.context.clone(),
+            filter: Arc::new(filter),
+            next_version: start_version,
+            poll_interval: Duration::from_millis(api_config.subscription_poll_interval_ms),
+            _guard: guard,
+        };
+        let items = stream::unfold(state, move |state| next_batch(state, render))
+            .flat_map(stream::iter)
+            .inspect(move |_| {
+                SUBSCRIPTION_ITEMS
+                    .with_label_values(&[subscription_type])
+                    .inc()
+            })
+            .boxed();
+
+        Ok(EventStream::new(items).keep_alive(SUBSCRIPTION_KEEP_ALIVE))
+    }
+}
+
+/// Converts the matching transactions of a batch into the items sent to subscribers
+type RenderFn<T> = fn(
+    &Context,
+    &LedgerInfo,
+    &SubscriptionFilter,
+    Vec<TransactionOnChainData>,
+) -> Result<Vec<T>, BasicError>;
+
+fn render_transactions(
+    context: &Context,
+    ledger_info: &LedgerInfo,
+    _filter: &SubscriptionFilter,
+    txns: Vec<TransactionOnChainData>,
+) -> Result<Vec<Transaction>, BasicError> {
+    context.render_transactions_non_sequential(ledger_info, txns)
+}
+
+fn render_events(
+    context: &Context,
+    ledger_info: &LedgerInfo,
+    filter: &SubscriptionFilter,
+    txns: Vec<TransactionOnChainData>,
+) -> Result<Vec<VersionedEvent>, BasicError> {
+    let events: Vec<_> = txns
+        .iter()
+        .flat_map(|txn| {
+            txn.events
+                .iter()
+                .filter(|event| filter.matches_event_type(event.type_tag()))
+                .map(|event| EventWithVersion::new(txn.version, event.clone()))
+        })
+        .collect();
+    if events.is_empty() {
+        return Ok(vec![]);
+    }
+
+    context
+        .latest_state_view_poem(ledger_info)?
+        .as_converter(context.db.clone(), context.indexer_reader.clone())
+        .try_into_versioned_events(&events)
+        .context("Failed to convert events from storage into response")
+        .map_err(|err| {
+            BasicError::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+        })
+}
+
+/// Filters applied to committed transactions before they are sent to a subscriber
+#[derive(Debug, Default)]
+pub struct SubscriptionFilter {
+    sender: Option<AccountAddress>,
+    entry_function: Option<(ModuleId, Identifier)>,
+    event_type: Option<TypeTag>,
+}
+
+impl SubscriptionFilter {
+    fn new(
+        sender: Option<Address>,
+        entry_function: Option<EntryFunctionId>,
+        event_type: Option<MoveStructTag>,
+    ) -> Result<Self, BasicErrorWith404> {
+        let entry_function = entry_function
+            .map(|function| {
+                function
+                    .verify()
+                    .map(|_| (function.module.into(), function.name.into()))
+            })
+            .transpose()
+            .context("'entry_function' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let event_type = event_type
+            .map(|tag| {
+                tag.verify(0)?;
+                StructTag::try_from(tag).map(|tag| TypeTag::Struct(Box::new(tag)))
+            })
+            .transpose()
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        Ok(Self {
+            sender: sender.map(Into::into),
+            entry_function,
+            event_type,
+        })
+    }
+
+    /// Returns true if the transaction matches the sender and entry function filters,
+    /// and emitted at least one event matching the event type filter.
+    fn matches_transaction(&self, txn: &TransactionOnChainData) -> bool {
+        if self.sender.is_some() || self.entry_function.is_some() {
+            let Some(user_txn) = txn.transaction.try_as_signed_user_txn() else {
+                return false;
+            };
+            if let Some(sender) = self.sender {
+                if user_txn.sender() != sender {
+                    return false;
+                }
+            }
+            if let Some((module, function)) = &self.entry_function {
+                match user_txn.payload() {
+                    TransactionPayload::EntryFunction(entry_function) => {
+                        if entry_function.module() != module
+                            || entry_function.function() != function.as_ident_str()
+                        {
+                            return false;
+                        }
+                    },
+                    _ => return false,
+                }
+            }
+        }
+
+        self.event_type.is_none()
+            || txn
+                .events
+                .iter()
+                .any(|event| self.matches_event_type(event.type_tag()))
+    }
+
+    fn matches_event_type(&self, type_tag: &TypeTag) -> bool {
+        self.event_type
+            .as_ref()
+            .map_or(true, |event_type| event_type == type_tag)
+    }
+}
+
+/// Keeps track of the number of active subscriptions, releasing the slot
+/// once the stream is dropped by the server.
+struct SubscriptionGuard {
+    context: Arc<Context>,
+    active_connections: usize,
+}
+
+impl SubscriptionGuard {
+    fn new(context: Arc<Context>) -> Self {
+        let active_connections = context
+            .subscription_active_connections
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        SUBSCRIPTION_GAUGE.inc();
+        Self {
+            context,
+            active_connections,
+        }
+    }
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        SUBSCRIPTION_GAUGE.dec();
+        self.context
+            .subscription_active_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct SubscriptionState {
+    context: Arc<Context>,
+    filter: Arc<SubscriptionFilter>,
+    next_version: Version,
+    poll_interval: Duration,
+    _guard: SubscriptionGuard,
+}
+
+/// Waits until the next batch of matching items is available. Returns `None`, ending
+/// the stream, if storage can't serve the subscription any longer, e.g. because the
+/// subscriber fell behind the pruning window.
+async fn next_batch<T: Send + 'static>(
+    mut state: SubscriptionState,
+    render: RenderFn<T>,
+) -> Option<(Vec<T>, SubscriptionState)> {
+    loop {
+        let context = state.context.clone();
+        let filter = state.filter.clone();
+        let next_version = state.next_version;
+        let result =
+            api_spawn_blocking(move || read_batch(&context, &filter, next_version, render)).await;
+
+        match result {
+            Ok((next_version, items)) => {
+                let caught_up = next_version == state.next_version;
+                state.next_version = next_version;
+                if !items.is_empty() {
+                    return Some((items, state));
+                }
+                if caught_up {
+                    tokio::time::sleep(state.poll_interval).await;
+                }
+            },
+            Err(err) => {
+                warn!(
+                    "Closing subscription at version {}: {}",
+                    state.next_version, err
+                );
+                return None;
+            },
+        }
+    }
+}
+
+/// Reads up to a page of transactions starting at `next_version`, returning the
+/// version to continue from and the rendered items that matched the filter.
+fn read_batch<T>(
+    context: &Context,
+    filter: &SubscriptionFilter,
+    next_version: Version,
+    render: RenderFn<T>,
+) -> Result<(Version, Vec<T>), BasicError> {
+    let ledger_info = context.get_latest_ledger_info::<BasicError>()?;
+    let ledger_version = ledger_info.version();
+    if next_version > ledger_version {
+        return Ok((next_version, vec![]));
+    }
+
+    let limit = std::cmp::min(
+        context.max_transactions_page_size() as u64,
+        ledger_version - next_version + 1,
+    ) as u16;
+    let txns = context
+        .get_transactions(next_version, limit, ledger_version)
+        .context("Failed to read raw transactions from storage")
+        .map_err(|err| {
+            BasicError::internal_with_code(err, AptosErrorCode::InternalError, &ledger_info)
+        })?;
+
+    let next_version = next_version + txns.len() as u64;
+    let txns: Vec<_> = txns
+        .into_iter()
+        .filter(|txn| filter.matches_transaction(txn))
+        .collect();
+    if txns.is_empty() {
+        return Ok((next_version, vec![]));
+    }
+    Ok((next_version, render(context, &ledger_info, filter, txns)?))
+}
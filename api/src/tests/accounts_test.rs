@@ -7,7 +7,7 @@ use crate::tests::new_test_context_with_db_sharding_and_internal_indexer;
 use aptos_api_test_context::{current_function_name, find_value, TestContext};
 use aptos_api_types::{MoveModuleBytecode, MoveResource, MoveStructTag, StateKeyWrapper};
 use aptos_cached_packages::aptos_stdlib;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::str::FromStr;
 
 /* TODO: reactivate once cause of failure for `"8"` vs `8` in the JSON output is known.
@@ -406,6 +406,80 @@ async fn test_get_account_items_limit_params() {
     assert_eq!(resp.status(), 400);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_balance_history() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    let path = account_balance(&account.address().to_hex_literal(), APT);
+
+    let resp = context.get(&path).await;
+    let created_at = resp["version"].as_str().unwrap().to_string();
+    assert_eq!(
+        resp["balance"],
+        context.get_apt_balance(account.address()).await.to_string()
+    );
+
+    let txn = context.mint_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context.get(&format!("{}/history", path)).await;
+    let history = resp.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[0]["balance"],
+        context.get_apt_balance(account.address()).await.to_string()
+    );
+    assert_eq!(history[1]["version"], created_at.as_str());
+
+    // The balance before the latest change is still available at older versions
+    let resp = context
+        .get(&format!("{}?ledger_version={}", path, created_at))
+        .await;
+    assert_eq!(resp, history[1]);
+
+    // A page ending before the history does returns a cursor to the next page
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}/history?limit=1", path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let page: Vec<Value> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(page, history[0..1].to_vec());
+
+    let req = warp::test::request().method("GET").path(&format!(
+        "/v1{}/history?limit=1&end_version={}",
+        path, cursor
+    ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Aptos-Cursor"));
+    let page: Vec<Value> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(page, history[1..2].to_vec());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_balance_invalid_asset_type() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get(&account_balance("0x1", "0x1::coin"))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+
+    let resp = context
+        .expect_status_code(404)
+        .get(&account_balance("0x12345", APT))
+        .await;
+    assert_eq!(resp["error_code"], "resource_not_found");
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
@@ -429,3 +503,9 @@ fn account_modules_with_ledger_version(address: &str, ledger_version: i128) -> S
         ledger_version
     )
 }
+
+const APT: &str = "0x1::aptos_coin::AptosCoin";
+
+fn account_balance(address: &str, asset_type: &str) -> String {
+    format!("/accounts/{}/balance/{}", address, asset_type)
+}
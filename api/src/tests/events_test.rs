@@ -230,3 +230,36 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let context = new_test_context_with_db_sharding_and_internal_indexer(current_function_name!());
+    let resp = context
+        .get("/accounts/0x1/events/0x1::reconfiguration::Configuration/events")
+        .await;
+
+    // The same events can be found by their type, without knowing the event handle
+    let resp_by_type = context
+        .get("/events?type=0x1::reconfiguration::NewEpochEvent")
+        .await;
+    assert_eq!(resp, resp_by_type);
+
+    let resp_out_of_range = context
+        .get("/events?type=0x1::reconfiguration::NewEpochEvent&start_version=1")
+        .await;
+    assert!(resp_out_of_range
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|event| event["version"] != "0"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_invalid_type_range() {
+    let context = new_test_context_with_db_sharding_and_internal_indexer(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .get("/events?type=0x1::reconfiguration::NewEpochEvent&start_version=1&end_version=0")
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
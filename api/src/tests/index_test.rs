@@ -4,6 +4,8 @@
 
 use super::new_test_context;
 use aptos_api_test_context::current_function_name;
+use aptos_api_types::mime_types::BCS;
+use aptos_types::state_proof::StateProof;
 use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -13,6 +15,26 @@ async fn test_get_index() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_state_proof() {
+    let context = new_test_context(current_function_name!());
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&context.prepend_path("/state_proof?known_version=0"));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let state_proof: StateProof = bcs::from_bytes(resp.body()).unwrap();
+    assert!(!state_proof.epoch_changes().more);
+    assert!(state_proof.latest_ledger_info().version() > 0);
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/state_proof?known_version=0")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 // TODO: Un-ignore this pending https://github.com/poem-web/poem/issues/343.
 #[ignore]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -11,11 +11,13 @@ mod invalid_post_request_test;
 mod modules;
 mod multisig_transactions_test;
 mod objects;
+mod rate_limit_test;
 mod resource_groups;
 mod secp256k1_ecdsa;
 mod simulation_test;
 mod state_test;
 mod string_resource_test;
+mod subscriptions_test;
 mod transaction_vector_test;
 mod transactions_test;
 mod view_function;
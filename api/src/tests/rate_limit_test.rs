@@ -0,0 +1,95 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context_with_config;
+use aptos_api_test_context::current_function_name;
+use aptos_config::config::{
+    ApiKeyRateLimitConfig, ApiRateLimitConfig, EndpointClassRateLimits, NodeConfig,
+    TokenBucketConfig,
+};
+use serde_json::json;
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_per_ip_and_api_key() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.rate_limit = ApiRateLimitConfig {
+        enabled: true,
+        api_key_header: Some(API_KEY_HEADER.into()),
+        per_ip: EndpointClassRateLimits {
+            read: Some(TokenBucketConfig::new(2, 1)),
+            submit: None,
+            simulate: Some(TokenBucketConfig::new(100, 100)),
+        },
+        api_keys: vec![ApiKeyRateLimitConfig {
+            name: "test".into(),
+            key: "secret".into(),
+            limits: EndpointClassRateLimits {
+                read: Some(TokenBucketConfig::new(100, 100)),
+                ..Default::default()
+            },
+        }],
+        ..Default::default()
+    };
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // The read bucket of the IP address runs out after 2 requests
+    context.get("/").await;
+    context.get("/").await;
+    let resp = context.expect_status_code(429).get("/").await;
+    assert_eq!(resp["error_code"], "rate_limit_exceeded");
+
+    let req = warp::test::request()
+        .method("GET")
+        .path(&context.prepend_path("/"));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 429);
+    assert!(resp.headers().contains_key("retry-after"));
+
+    // Other classes of endpoints have their own buckets
+    context
+        .expect_status_code(400)
+        .post("/transactions/simulate", json!({}))
+        .await;
+
+    // Clients with a known API key use the bucket of the key, others the one of their IP address
+    for (api_key, status_code) in [("secret", 200), ("unknown", 429)] {
+        let req = warp::test::request()
+            .method("GET")
+            .header(API_KEY_HEADER, api_key)
+            .path(&context.prepend_path("/"));
+        context.expect_status_code(status_code).execute(req).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_client_ip_header() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.rate_limit = ApiRateLimitConfig {
+        enabled: true,
+        client_ip_header: Some("x-forwarded-for".into()),
+        client_ip_header_trusted_hops: 1,
+        per_ip: EndpointClassRateLimits {
+            read: Some(TokenBucketConfig::new(1, 1)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // The client is identified by the entry added by the trusted proxy, the rightmost one, so
+    // it can't get a new bucket by making up the entries on the left
+    for (forwarded_for, status_code) in [
+        ("1.1.1.1, 2.2.2.2", 200),
+        ("3.3.3.3, 2.2.2.2", 429),
+        ("2.2.2.2", 429),
+        ("2.2.2.2, 4.4.4.4", 200),
+    ] {
+        let req = warp::test::request()
+            .method("GET")
+            .header("x-forwarded-for", forwarded_for)
+            .path(&context.prepend_path("/"));
+        context.expect_status_code(status_code).execute(req).await;
+    }
+}
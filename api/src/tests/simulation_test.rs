@@ -3,9 +3,13 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, pretty, TestContext};
+use aptos_api_types::{SignedTransactionWithStateOverrides, StateOverrideBcs};
 use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_gas_schedule::{AptosGasParameters, InitialGasSchedule};
 use aptos_types::{
     account_address::AccountAddress,
+    account_config::AccountResource,
+    state_store::{state_key::StateKey, TStateView},
     transaction::{
         authenticator::{AccountAuthenticator, TransactionAuthenticator},
         EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
@@ -426,3 +430,279 @@ async fn test_bcs_execute_fee_payer_transaction_no_authenticator_fail() {
         .unwrap()
         .contains("INVALID_SIGNATURE"));
 }
+
+fn without_authenticator(txn: SignedTransaction) -> SignedTransaction {
+    SignedTransaction::new_signed_transaction(
+        txn.into_raw_transaction(),
+        TransactionAuthenticator::SingleSender {
+            sender: AccountAuthenticator::NoAccountAuthenticator,
+        },
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_bundle() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+
+    // Alice only exists once the first transaction is applied
+    let fund_alice = without_authenticator(context.mint_user_account(alice).await);
+    let transfer_to_bob = without_authenticator(context.account_transfer_to(
+        alice,
+        bob.address(),
+        SMALL_TRANSFER_AMOUNT,
+    ));
+
+    let body = bcs::to_bytes(&vec![transfer_to_bob.clone()]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+    assert!(
+        !resp["transactions"][0]["success"].as_bool().unwrap(),
+        "{}",
+        pretty(&resp)
+    );
+
+    let body = bcs::to_bytes(&vec![fund_alice, transfer_to_bob]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+    let transactions = resp["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2, "{}", pretty(&resp));
+    let mut gas_used = 0;
+    for txn in transactions {
+        assert!(txn["success"].as_bool().unwrap(), "{}", pretty(&resp));
+        gas_used += txn["gas_used"].as_str().unwrap().parse::<u64>().unwrap();
+    }
+    assert_eq!(
+        resp["cumulative_gas_used"].as_str().unwrap(),
+        gas_used.to_string()
+    );
+
+    // Nothing was committed
+    context
+        .expect_status_code(404)
+        .get(&format!("/accounts/{}", alice.address()))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_bundle_does_not_leak_published_modules() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+
+    let named_addresses = vec![("addr".to_string(), account.address())];
+    let path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("src/tests/move/pack_counter");
+    let payload = TestContext::build_package(path, named_addresses);
+    let publish = without_authenticator(
+        account.sign_with_transaction_builder(context.transaction_factory().payload(payload)),
+    );
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(account.address(), ident_str!("counter").to_owned()),
+        ident_str!("increment_counter").to_owned(),
+        vec![],
+        vec![],
+    ));
+    let call = without_authenticator(
+        account.sign_with_transaction_builder(context.transaction_factory().payload(payload)),
+    );
+
+    // The module published by the first transaction can be called by the second one
+    let body = bcs::to_bytes(&vec![publish, call.clone()]).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+    let transactions = resp["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2, "{}", pretty(&resp));
+    for txn in transactions {
+        assert!(txn["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    }
+
+    // But it was never published, so it must not be found by later views or simulations, even
+    // through the code cache shared by the VMs
+    let function = format!("{}::counter::add_and_get_counter_value", account.address());
+    context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": function,
+                "arguments": [],
+                "type_arguments": [],
+            }),
+        )
+        .await;
+
+    let call = SignedTransaction::new_signed_transaction(
+        RawTransaction::new(
+            call.sender(),
+            call.sequence_number() - 1,
+            call.payload().clone(),
+            call.max_gas_amount(),
+            call.gas_unit_price(),
+            call.expiration_timestamp_secs(),
+            call.chain_id(),
+        ),
+        call.authenticator(),
+    );
+    let body = bcs::to_bytes(&call).unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body)
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_bundle_with_valid_signature() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let fund_alice = context.mint_user_account(alice).await;
+
+    let body = bcs::to_bytes(&vec![fund_alice]).unwrap();
+    context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions/simulate_bundle", body)
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_with_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Simulate as if alice had already sent 42 transactions
+    let state_key = StateKey::resource_typed::<AccountResource>(&alice.address()).unwrap();
+    let account: AccountResource = bcs::from_bytes(
+        &context
+            .latest_state_view()
+            .get_state_value_bytes(&state_key)
+            .unwrap()
+            .unwrap(),
+    )
+    .unwrap();
+    let account = AccountResource::new(
+        42,
+        account.authentication_key().to_vec(),
+        account.coin_register_events().clone(),
+        account.key_rotation_events().clone(),
+    );
+    alice.set_sequence_number(42);
+    let txn = without_authenticator(context.account_transfer_to(
+        alice,
+        bob.address(),
+        SMALL_TRANSFER_AMOUNT,
+    ));
+
+    let body = bcs::to_bytes(&SignedTransactionWithStateOverrides {
+        transaction: txn,
+        state_overrides: vec![StateOverrideBcs {
+            state_key,
+            value: Some(bcs::to_bytes(&account).unwrap()),
+        }],
+    })
+    .unwrap();
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate/with_state_overrides", body)
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    assert_eq!(resp[0]["sequence_number"], "42");
+
+    // Nothing was committed
+    assert_eq!(context.get_sequence_number(alice.address()).await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_with_too_many_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = without_authenticator(context.account_transfer_to(
+        alice,
+        bob.address(),
+        SMALL_TRANSFER_AMOUNT,
+    ));
+    // More state items than a transaction can write
+    let max_num_overrides = u64::from(
+        AptosGasParameters::initial()
+            .vm
+            .txn
+            .max_write_ops_per_transaction,
+    );
+    let state_overrides = (0..=max_num_overrides)
+        .map(|i| StateOverrideBcs {
+            state_key: StateKey::raw(&i.to_be_bytes()),
+            value: None,
+        })
+        .collect();
+
+    let body = bcs::to_bytes(&SignedTransactionWithStateOverrides {
+        transaction: txn,
+        state_overrides,
+    })
+    .unwrap();
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions/simulate/with_state_overrides", body)
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input", "{}", pretty(&resp));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bcs_simulate_with_gas_profile() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = without_authenticator(context.account_transfer_to(
+        alice,
+        bob.address(),
+        SMALL_TRANSFER_AMOUNT,
+    ));
+    let body = bcs::to_bytes(&txn).unwrap();
+
+    // The profile is opt-in
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate", body.clone())
+        .await;
+    assert!(resp[0].get("gas_profile").is_none(), "{}", pretty(&resp));
+
+    let resp = context
+        .expect_status_code(200)
+        .post_bcs_txn("/transactions/simulate?profile_gas=true", body)
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    let gas_profile = &resp[0]["gas_profile"];
+    let gas_used: u64 = resp[0]["gas_used"].as_str().unwrap().parse().unwrap();
+    let execution_gas: u64 = gas_profile["execution_gas_units"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let io_gas: u64 = gas_profile["io_gas_units"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(execution_gas > 0, "{}", pretty(&resp));
+    assert!(execution_gas + io_gas <= gas_used, "{}", pretty(&resp));
+    assert!(gas_profile["execution_and_io"]["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|function| function["name"] == "0x1::aptos_account::transfer"));
+}
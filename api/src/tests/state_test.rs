@@ -3,6 +3,7 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{mime_types::BCS, StateValueWithProof};
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
 use move_core_types::account_address::AccountAddress;
@@ -35,6 +36,80 @@ async fn test_get_account_resource_by_invalid_address() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let proof = get_resource_with_proof(&context, "0x1", "0x1::account::Account").await;
+    proof
+        .verify(proof.ledger_info_with_signatures.ledger_info())
+        .unwrap();
+    assert!(proof.bytes().is_some());
+
+    // Tampering with the value makes the proof invalid
+    let mut tampered = proof.clone();
+    tampered.state_value = None;
+    assert!(tampered
+        .verify(proof.ledger_info_with_signatures.ledger_info())
+        .is_err());
+
+    // A missing resource comes with a proof of its absence
+    let proof = get_resource_with_proof(&context, "0x12345", "0x1::account::Account").await;
+    proof
+        .verify(proof.ledger_info_with_signatures.ledger_info())
+        .unwrap();
+    assert!(proof.bytes().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_at_version() {
+    let mut context = new_test_context(current_function_name!());
+    context.create_account().await;
+
+    // Without a version, the value is proven at the latest state snapshot
+    let proof = get_resource_with_proof(&context, "0x1", "0x1::account::Account").await;
+    let ledger_version = context.get_latest_ledger_info().version();
+    assert!(proof.version <= ledger_version);
+
+    // A state snapshot can be requested
+    let resource = format!(
+        "{}&with_proof=true",
+        get_account_resource_with_version("0x1", "0x1::account::Account", proof.version)
+    );
+    let resp = context.reply(bcs_request(&context, &resource)).await;
+    assert_eq!(resp.status(), 200);
+    let proof_at_version: StateValueWithProof = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(proof_at_version.version, proof.version);
+    proof_at_version
+        .verify(proof_at_version.ledger_info_with_signatures.ledger_info())
+        .unwrap();
+
+    // A version without a state snapshot is rejected, as the value can't be proven there
+    let version = if proof.version > 0 {
+        proof.version - 1
+    } else {
+        ledger_version
+    };
+    let resource = format!(
+        "{}&with_proof=true",
+        get_account_resource_with_version("0x1", "0x1::account::Account", version)
+    );
+    let resp = context.reply(bcs_request(&context, &resource)).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_json() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(403)
+        .get(&format!(
+            "{}?with_proof=true",
+            get_account_resource("0x1", "0x1::account::Account")
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource_by_invalid_struct_tag() {
     let mut context = new_test_context(current_function_name!());
@@ -308,6 +383,27 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+async fn get_resource_with_proof(
+    context: &TestContext,
+    address: &str,
+    struct_tag: &str,
+) -> StateValueWithProof {
+    let resource = format!(
+        "{}?with_proof=true",
+        get_account_resource(address, struct_tag)
+    );
+    let resp = context.reply(bcs_request(context, &resource)).await;
+    assert_eq!(resp.status(), 200);
+    bcs::from_bytes(resp.body()).unwrap()
+}
+
+fn bcs_request(context: &TestContext, path: &str) -> warp::test::RequestBuilder {
+    warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&context.prepend_path(path))
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
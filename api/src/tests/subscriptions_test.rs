@@ -0,0 +1,96 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config};
+use aptos_api_test_context::{current_function_name, ApiSpecificConfig};
+use aptos_config::config::NodeConfig;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_transactions_invalid_entry_function() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/subscribe/transactions?entry_function=0x1::coin")
+        .await;
+    assert_eq!(resp["error_code"], "web_framework_error");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_events_invalid_event_type() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/subscribe/events?event_type=0x1::coin")
+        .await;
+    assert_eq!(resp["error_code"], "web_framework_error");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_transactions_delivers_committed_transaction() {
+    let mut context = new_test_context(current_function_name!());
+    let root_account = context.root_account().await;
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+
+    // Connect to the server directly, as the test proxy waits for the whole body.
+    let ApiSpecificConfig::V1(address) = context.api_specific_config;
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let mut resp = reqwest::get(format!(
+        "http://{}/v1/subscribe/transactions?start_version={}&sender={}",
+        address,
+        start_version,
+        root_account.address()
+    ))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    context.commit_block(&[txn.clone()]).await;
+
+    let hash = txn.committed_hash().to_hex_literal();
+    let mut body = String::new();
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while !body.contains(&hash) {
+            let chunk = resp.chunk().await.unwrap().expect("stream ended early");
+            body.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    })
+    .await
+    .expect("committed transaction not delivered");
+    assert!(body.starts_with("data: "), "unexpected body: {}", body);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscriptions_disabled() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.subscription_enabled = false;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/subscribe/transactions")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/subscribe/events")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subscribe_with_too_many_active_subscriptions() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.subscription_max_active_connections = 0;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = context
+        .expect_status_code(503)
+        .get("/subscribe/transactions?start_version=0")
+        .await;
+    assert_eq!(resp["error_code"], "service_unavailable");
+}
@@ -7,6 +7,7 @@ use crate::tests::{
     new_test_context_with_config, new_test_context_with_db_sharding_and_internal_indexer,
 };
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_types::{mime_types::BCS, TransactionWithProofBcs};
 use aptos_config::config::{GasEstimationStaticOverride, NodeConfig};
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519Signature},
@@ -538,6 +539,34 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_by_version_with_proof() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", BCS)
+        .path(&context.prepend_path("/transactions/by_version/2?with_proof=true"));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let txn_with_proof: TransactionWithProofBcs = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(txn_with_proof.transaction_with_proof.version, 2);
+    txn_with_proof
+        .verify(txn_with_proof.ledger_info_with_signatures.ledger_info())
+        .unwrap();
+    assert_eq!(
+        txn_with_proof
+            .transaction_with_proof
+            .transaction
+            .try_as_signed_user_txn()
+            .unwrap(),
+        &txn
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
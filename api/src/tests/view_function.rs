@@ -3,9 +3,14 @@
 
 use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{mime_types::BCS_VIEW_FUNCTION, HexEncodedBytes};
 use aptos_cached_packages::aptos_stdlib;
 use aptos_config::config::{NodeConfig, ViewFilter, ViewFunctionId};
-use aptos_types::account_address::AccountAddress;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    event::{EventHandle, EventKey},
+};
 use serde_json::{json, Value};
 use std::{path::PathBuf, str::FromStr};
 
@@ -252,3 +257,156 @@ async fn test_view_aggregator() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let invalid_request = json!({
+        "function":"0x1::coin::does_not_exist",
+        "arguments": Vec::<String>::new(),
+        "type_arguments": Vec::<String>::new(),
+    });
+    let resp = context
+        .post(
+            "/view/batch",
+            json!(vec![
+                build_coin_balance_request(&owner.address()),
+                invalid_request,
+                build_coin_decimals_request(),
+            ]),
+        )
+        .await;
+
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[0]["error"].is_null());
+    assert!(results[1]["values"].is_null());
+    assert_eq!(results[1]["error"]["error_code"], "invalid_input");
+    assert_eq!(results[2]["values"], json!([8]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_allowlist() {
+    let mut node_config = NodeConfig::default();
+
+    // Allowlist only the balance function.
+    node_config.api.view_filter = ViewFilter::Allowlist(vec![ViewFunctionId {
+        address: AccountAddress::from_str("0x1").unwrap(),
+        module: "coin".to_string(),
+        function_name: "balance".to_string(),
+    }]);
+
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    // A non-allowed function only fails its own entry in the batch.
+    let resp = context
+        .post(
+            "/view/batch",
+            json!(vec![
+                build_coin_balance_request(&owner.address()),
+                build_coin_decimals_request(),
+            ]),
+        )
+        .await;
+
+    let results = resp.as_array().unwrap();
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[1]["values"].is_null());
+    assert_eq!(results[1]["error"]["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_too_large() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_view_function_batch_size = 1;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view/batch",
+            json!(vec![
+                build_coin_decimals_request(),
+                build_coin_decimals_request()
+            ]),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+
+    // The size of a BCS batch is checked before its requests are decoded, so a
+    // length prefix alone is enough.
+    let resp = context
+        .expect_status_code(400)
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path(&context.prepend_path("/view/batch"))
+                .header("Content-Type", BCS_VIEW_FUNCTION)
+                .body(vec![2u8]),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .contains("larger than the max batch size of 1"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_with_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let address = context.gen_account().address();
+    let account = AccountResource::new(
+        42,
+        address.to_vec(),
+        EventHandle::new(EventKey::new(0, address), 0),
+        EventHandle::new(EventKey::new(1, address), 0),
+    );
+    let request = json!({
+        "function": "0x1::account::get_sequence_number",
+        "arguments": vec![address.to_string()],
+        "type_arguments": Vec::<String>::new(),
+    });
+
+    // The account doesn't exist on chain
+    context
+        .expect_status_code(400)
+        .post("/view", request.clone())
+        .await;
+
+    let resp = context
+        .post(
+            "/view/with_state_overrides",
+            json!({
+                "request": request,
+                "state_overrides": [{
+                    "type": "resource",
+                    "address": address.to_string(),
+                    "resource_type": "0x1::account::Account",
+                    "data": HexEncodedBytes::from(bcs::to_bytes(&account).unwrap()),
+                }],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!(["42"]));
+
+    // Nothing was committed
+    context
+        .expect_status_code(404)
+        .get(&format!("/accounts/{}", address))
+        .await;
+}
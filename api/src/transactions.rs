@@ -17,24 +17,31 @@ use crate::{
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         ForbiddenError, InsufficientStorageError, InternalError,
     },
+    state_overlay::OverlayStateView,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    AsConverter, EncodeSubmissionRequest, ExecutionAndIoGasProfile, GasEstimation,
+    GasEstimationBcs, GasProfile, GasProfileEntry, HashValue, HexEncodedBytes, LedgerInfo,
+    MoveType, PendingTransaction, SignedTransactionWithStateOverrides, SimulateBundleResult,
+    SimulateBundleResultBcs, SimulateTransactionRequestWithStateOverrides, StorageFeeEntry,
+    StorageFeeProfile, SubmitTransactionRequest, Transaction, TransactionData,
+    TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_types::{
     account_address::AccountAddress,
+    fee_statement::FeeStatement,
     mempool_status::MempoolStatusCode,
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload,
+        RawTransactionWithData, SignedTransaction, TransactionOutput, TransactionPayload,
     },
     vm_status::StatusCode,
     AptosCoinType, CoinType,
@@ -116,6 +123,27 @@ pub enum SubmitTransactionsBatchPost {
     Bcs(Bcs),
 }
 
+// We need a custom type here because we use different types for each of the
+// content types possible for the POST data.
+#[derive(ApiRequest, Debug)]
+pub enum SimulateTransactionWithStateOverridesPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<SimulateTransactionRequestWithStateOverrides>),
+
+    /// A BCS encoded `SignedTransactionWithStateOverrides`
+    #[oai(content_type = "application/x.aptos.signed_transaction+bcs")]
+    Bcs(Bcs),
+}
+
+impl VerifyInput for SimulateTransactionWithStateOverridesPost {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            SimulateTransactionWithStateOverridesPost::Json(inner) => inner.0.verify(),
+            SimulateTransactionWithStateOverridesPost::Bcs(_) => Ok(()),
+        }
+    }
+}
+
 impl VerifyInput for SubmitTransactionsBatchPost {
     fn verify(&self) -> anyhow::Result<()> {
         match self {
@@ -282,6 +310,9 @@ impl TransactionsApi {
     ///
     /// Retrieves a transaction by a given version. If the version has been
     /// pruned, a 410 will be returned.
+    ///
+    /// If `with_proof` is set, the BCS encoded `TransactionWithProofBcs` is returned
+    /// instead, which can be verified against the included `LedgerInfoWithSignatures`.
     #[oai(
         path = "/transactions/by_version/:txn_version",
         method = "get",
@@ -293,13 +324,28 @@ impl TransactionsApi {
         accept_type: AcceptType,
         /// Version of transaction to retrieve
         txn_version: Path<U64>,
+        /// If true, return the transaction along with a proof of it
+        ///
+        /// Only BCS is supported as an AcceptType when a proof is requested.
+        with_proof: Query<Option<bool>>,
     ) -> BasicResultWith404<Transaction> {
         fail_point_poem("endpoint_transaction_by_version")?;
         self.context
             .check_api_output_enabled("Get transactions by version", &accept_type)?;
+        let with_proof = with_proof.0.unwrap_or(false);
+        if with_proof && AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get transaction by version with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
         let api = self.clone();
         api_spawn_blocking(move || {
-            api.get_transaction_by_version_inner(&accept_type, txn_version.0)
+            if with_proof {
+                api.get_transaction_by_version_with_proof(txn_version.0)
+            } else {
+                api.get_transaction_by_version_inner(&accept_type, txn_version.0)
+            }
         })
         .await
     }
@@ -489,6 +535,11 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        /// If set to true, the transaction will be simulated with the gas profiler, and
+        /// the breakdown of the gas used will be returned in `gas_profile`
+        ///
+        /// Only JSON is supported as an AcceptType.
+        profile_gas: Query<Option<bool>>,
         data: SubmitTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
@@ -512,20 +563,7 @@ impl TransactionsApi {
             let ledger_info = context.get_latest_ledger_info()?;
             let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
 
-            // Confirm the simulation filter allows the transaction. We use HashValue::zero()
-            // here for the block ID because we don't allow filtering by block ID for the
-            // simulation filters. See the ConfigSanitizer for ApiConfig.
-            if !context.node_config.api.simulation_filter.allows(
-                aptos_crypto::HashValue::zero(),
-                ledger_info.timestamp(),
-                &signed_transaction,
-            ) {
-                return Err(SubmitTransactionError::forbidden_with_code(
-                    "Transaction not allowed by simulation filter",
-                    AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                ));
-            }
+            api.check_simulation_filter(&ledger_info, &signed_transaction)?;
 
             let estimated_gas_unit_price = match (
                 estimate_gas_unit_price.0.unwrap_or_default(),
@@ -617,7 +655,143 @@ impl TransactionsApi {
                 );
             }
 
-            api.simulate(&accept_type, ledger_info, signed_transaction)
+            let state_view = OverlayStateView::new(context.latest_state_view_poem(&ledger_info)?);
+            api.simulate(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                state_view,
+                profile_gas.0.unwrap_or_default(),
+            )
+        })
+        .await
+    }
+
+    /// Simulate transaction with state overrides
+    ///
+    /// Same as the simulate endpoint, but the transaction is executed on top of the given state
+    /// overrides, e.g. to preview a transaction as if an account held some resource, or to call
+    /// a module which isn't deployed yet. Overrides with no data delete the state item. Nothing
+    /// is committed. There can't be more overrides than state items a transaction can write, and
+    /// they can't be larger than the request size limit in total.
+    ///
+    /// Gas estimation isn't supported here, the gas parameters of the transaction are used as is.
+    ///
+    /// To use this endpoint with BCS, you must submit a SignedTransactionWithStateOverrides
+    /// encoded as BCS. See SignedTransactionWithStateOverrides in api/types/src/transaction.rs.
+    #[oai(
+        path = "/transactions/simulate/with_state_overrides",
+        method = "post",
+        operation_id = "simulate_transaction_with_state_overrides",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_state_overrides(
+        &self,
+        accept_type: AcceptType,
+        data: SimulateTransactionWithStateOverridesPost,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_state_overrides")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction with state overrides"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction with state overrides", &accept_type)?;
+
+        let api = self.clone();
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = context.get_latest_ledger_info()?;
+            let mut state_view =
+                OverlayStateView::new(context.latest_state_view_poem(&ledger_info)?);
+            let signed_transaction = api.get_signed_transaction_with_state_overrides(
+                &ledger_info,
+                &mut state_view,
+                data,
+            )?;
+
+            api.check_simulation_filter(&ledger_info, &signed_transaction)?;
+
+            api.simulate(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                state_view,
+                false,
+            )
+        })
+        .await
+    }
+
+    /// Simulate a bundle of transactions
+    ///
+    /// Simulates the transactions in order, each one on top of the changes of the previous
+    /// ones, e.g. to preview creating an account, funding it, and then calling a function from
+    /// it. Nothing is committed, and the outputs are the same as for simulating a single
+    /// transaction, along with the gas used by the whole bundle.
+    ///
+    /// As for a single simulation, every transaction must have an invalid signature, e.g. a
+    /// zero-padded one.
+    ///
+    /// To use this endpoint with BCS, you must submit a `Vec<SignedTransaction>`
+    /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    #[oai(
+        path = "/transactions/simulate_bundle",
+        method = "post",
+        operation_id = "simulate_transaction_bundle",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_bundle(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionsBatchPost,
+    ) -> SimulateTransactionResult<SimulateBundleResult> {
+        data.verify()
+            .context("Simulated transactions invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_bundle")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction bundle"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction bundle", &accept_type)?;
+
+        let api = self.clone();
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = context.get_latest_ledger_info()?;
+            let signed_transactions = api.get_signed_transactions_batch(&ledger_info, data)?;
+            if signed_transactions.is_empty()
+                || context.max_submit_transaction_batch_size() < signed_transactions.len()
+            {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    format!(
+                        "Simulated bundle must have between 1 and {} transactions, got {}",
+                        context.max_submit_transaction_batch_size(),
+                        signed_transactions.len(),
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+
+            for signed_transaction in &signed_transactions {
+                api.check_simulation_filter(&ledger_info, signed_transaction)?;
+            }
+
+            api.simulate_bundle(&accept_type, ledger_info, signed_transactions)
         })
         .await
     }
@@ -881,6 +1055,42 @@ impl TransactionsApi {
         }
     }
 
+    /// Retrieves a committed transaction along with its proof
+    ///
+    /// BCS: `TransactionWithProofBcs`
+    fn get_transaction_by_version_with_proof(
+        &self,
+        version: U64,
+    ) -> BasicResultWith404<Transaction> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if version.0 > ledger_info.version() {
+            return Err(transaction_not_found_by_version(version.0, &ledger_info));
+        }
+        if version.0 < ledger_info.oldest_version() {
+            return Err(version_pruned(version.0, &ledger_info));
+        }
+        let transaction_with_proof = self
+            .context
+            .get_transaction_with_proof(version.0)
+            .context(format!(
+                "Failed to get transaction with proof by version {}",
+                version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_bcs((
+            transaction_with_proof,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
     /// Converts a transaction into the outgoing type
     fn get_transaction_inner(
         &self,
@@ -1011,6 +1221,102 @@ impl TransactionsApi {
         }
     }
 
+    /// Confirms the simulation filter allows the transaction
+    fn check_simulation_filter(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: &SignedTransaction,
+    ) -> Result<(), SubmitTransactionError> {
+        // We use HashValue::zero() here for the block ID because we don't allow filtering by
+        // block ID for the simulation filters. See the ConfigSanitizer for ApiConfig.
+        if !self.context.node_config.api.simulation_filter.allows(
+            aptos_crypto::HashValue::zero(),
+            ledger_info.timestamp(),
+            txn,
+        ) {
+            return Err(SubmitTransactionError::forbidden_with_code(
+                "Transaction not allowed by simulation filter",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a transaction, and applies the state overrides to `state_view`
+    ///
+    /// JSON transactions are converted on top of the overrides, so they can call overridden
+    /// modules.
+    fn get_signed_transaction_with_state_overrides(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &mut OverlayStateView<DbStateView>,
+        data: SimulateTransactionWithStateOverridesPost,
+    ) -> Result<SignedTransaction, SubmitTransactionError> {
+        match data {
+            SimulateTransactionWithStateOverridesPost::Bcs(data) => {
+                let SignedTransactionWithStateOverrides {
+                    transaction,
+                    state_overrides,
+                } = bcs::from_bytes_with_limit(&data.0, MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                    .context("Failed to deserialize input into SignedTransactionWithStateOverrides")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            ledger_info,
+                        )
+                    })?;
+                Self::validate_signed_transaction_payload(ledger_info, &transaction)?;
+                let limits = self.context.state_override_limits(ledger_info)?;
+                state_view
+                    .apply_state_overrides(state_overrides, &limits)
+                    .context("Failed to apply state overrides")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            ledger_info,
+                        )
+                    })?;
+                Ok(transaction)
+            },
+            SimulateTransactionWithStateOverridesPost::Json(data) => {
+                let SimulateTransactionRequestWithStateOverrides {
+                    transaction,
+                    state_overrides,
+                } = data.0;
+                let limits = self.context.state_override_limits(ledger_info)?;
+                state_view
+                    .apply_json_state_overrides(
+                        self.context.db.clone(),
+                        self.context.indexer_reader.clone(),
+                        state_overrides,
+                        &limits,
+                    )
+                    .context("Failed to apply state overrides")
+                    .and_then(|_| {
+                        state_view
+                            .as_converter(
+                                self.context.db.clone(),
+                                self.context.indexer_reader.clone(),
+                            )
+                            .try_into_signed_transaction_poem(transaction, self.context.chain_id())
+                            .context(
+                                "Failed to create SignedTransaction from SubmitTransactionRequest",
+                            )
+                    })
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            ledger_info,
+                        )
+                    })
+            },
+        }
+    }
+
     /// Parses a single signed transaction
     fn get_signed_transaction(
         &self,
@@ -1029,61 +1335,7 @@ impl TransactionsApi {
                                 ledger_info,
                             )
                         })?;
-                // Verify the signed transaction
-                match signed_transaction.payload() {
-                    TransactionPayload::EntryFunction(entry_function) => {
-                        TransactionsApi::validate_entry_function_payload_format(
-                            ledger_info,
-                            entry_function,
-                        )?;
-                    },
-                    TransactionPayload::Script(script) => {
-                        if script.code().is_empty() {
-                            return Err(SubmitTransactionError::bad_request_with_code(
-                                "Script payload bytecode must not be empty",
-                                AptosErrorCode::InvalidInput,
-                                ledger_info,
-                            ));
-                        }
-
-                        for arg in script.ty_args() {
-                            let arg = MoveType::from(arg);
-                            arg.verify(0)
-                                .context("Transaction script function type arg invalid")
-                                .map_err(|err| {
-                                    SubmitTransactionError::bad_request_with_code(
-                                        err,
-                                        AptosErrorCode::InvalidInput,
-                                        ledger_info,
-                                    )
-                                })?;
-                        }
-                    },
-                    TransactionPayload::Multisig(multisig) => {
-                        if let Some(payload) = &multisig.transaction_payload {
-                            match payload {
-                                MultisigTransactionPayload::EntryFunction(entry_function) => {
-                                    TransactionsApi::validate_entry_function_payload_format(
-                                        ledger_info,
-                                        entry_function,
-                                    )?;
-                                },
-                            }
-                        }
-                    },
-
-                    // Deprecated. To avoid panics when malicios users submit this
-                    // payload, return an error.
-                    TransactionPayload::ModuleBundle(_) => {
-                        return Err(SubmitTransactionError::bad_request_with_code(
-                            "Module bundle payload has been removed",
-                            AptosErrorCode::InvalidInput,
-                            ledger_info,
-                        ))
-                    },
-                }
-                // TODO: Verify script args?
-
+                Self::validate_signed_transaction_payload(ledger_info, &signed_transaction)?;
                 Ok(signed_transaction)
             },
             SubmitTransactionPost::Json(data) => self
@@ -1102,6 +1354,68 @@ impl TransactionsApi {
         }
     }
 
+    /// Verifies the payload of a transaction submitted as BCS
+    fn validate_signed_transaction_payload(
+        ledger_info: &LedgerInfo,
+        signed_transaction: &SignedTransaction,
+    ) -> Result<(), SubmitTransactionError> {
+        match signed_transaction.payload() {
+            TransactionPayload::EntryFunction(entry_function) => {
+                TransactionsApi::validate_entry_function_payload_format(
+                    ledger_info,
+                    entry_function,
+                )?;
+            },
+            TransactionPayload::Script(script) => {
+                if script.code().is_empty() {
+                    return Err(SubmitTransactionError::bad_request_with_code(
+                        "Script payload bytecode must not be empty",
+                        AptosErrorCode::InvalidInput,
+                        ledger_info,
+                    ));
+                }
+
+                for arg in script.ty_args() {
+                    let arg = MoveType::from(arg);
+                    arg.verify(0)
+                        .context("Transaction script function type arg invalid")
+                        .map_err(|err| {
+                            SubmitTransactionError::bad_request_with_code(
+                                err,
+                                AptosErrorCode::InvalidInput,
+                                ledger_info,
+                            )
+                        })?;
+                }
+            },
+            TransactionPayload::Multisig(multisig) => {
+                if let Some(payload) = &multisig.transaction_payload {
+                    match payload {
+                        MultisigTransactionPayload::EntryFunction(entry_function) => {
+                            TransactionsApi::validate_entry_function_payload_format(
+                                ledger_info,
+                                entry_function,
+                            )?;
+                        },
+                    }
+                }
+            },
+
+            // Deprecated. To avoid panics when malicios users submit this
+            // payload, return an error.
+            TransactionPayload::ModuleBundle(_) => {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "Module bundle payload has been removed",
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                ))
+            },
+        }
+        // TODO: Verify script args?
+
+        Ok(())
+    }
+
     // Validates that the module, function, and args in EntryFunction payload are correctly
     // formatted.
     fn validate_entry_function_payload_format(
@@ -1352,6 +1666,8 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
+        state_view: OverlayStateView<DbStateView>,
+        profile_gas: bool,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         // The caller must ensure that the signature is not valid, as otherwise
         // a malicious actor could execute the transaction without their knowledge
@@ -1362,12 +1678,161 @@ impl TransactionsApi {
                 &ledger_info,
             ));
         }
+        if profile_gas {
+            if accept_type == &AcceptType::Bcs {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "Gas profiling is only supported with JSON as an AcceptType",
+                    AptosErrorCode::BcsNotSupported,
+                    &ledger_info,
+                ));
+            }
+            if !matches!(
+                txn.payload(),
+                TransactionPayload::EntryFunction(_) | TransactionPayload::Script(_)
+            ) {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "Gas profiling is only supported for entry function and script payloads",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+        }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
-        let version = ledger_info.version();
+        let (vm_status, simulated_txn, gas_profile) =
+            self.simulate_on_state_view(&state_view, ledger_info.version(), txn, profile_gas);
+        let gas_used = simulated_txn.info.gas_used();
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let mut user_transactions = self.render_simulated_transactions(
+                    &ledger_info,
+                    vec![vm_status],
+                    vec![simulated_txn],
+                )?;
+                user_transactions[0].gas_profile = gas_profile;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(gas_used)))
+    }
+
+    /// Simulate transactions in the VM, each one on top of the changes of the previous ones
+    ///
+    /// Transactions which fail are still part of the result, but they only change the state
+    /// by charging gas, so later transactions depending on them will likely fail too.
+    pub fn simulate_bundle(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txns: Vec<SignedTransaction>,
+    ) -> SimulateTransactionResult<SimulateBundleResult> {
+        for (index, txn) in txns.iter().enumerate() {
+            // Same as for a single simulation, no transaction can be executable as is
+            if txn.verify_signature().is_ok() {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    format!(
+                        "Simulated transaction at index {} must not have a valid signature",
+                        index
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+        }
+
+        let mut state_view =
+            OverlayStateView::new(self.context.latest_state_view_poem(&ledger_info)?);
+        let mut vm_statuses = Vec::with_capacity(txns.len());
+        let mut simulated_txns = Vec::with_capacity(txns.len());
+        let mut cumulative_gas_used = 0u64;
+        for txn in txns {
+            // Once a transaction publishes modules, the following ones are executed by VMs
+            // with their own code cache, so that simulated code never reaches the shared one
+            let (vm_status, simulated_txn, _) =
+                self.simulate_on_state_view(&state_view, ledger_info.version(), txn, false);
+            state_view.apply_write_set(&simulated_txn.changes);
+            cumulative_gas_used = cumulative_gas_used.saturating_add(simulated_txn.info.gas_used());
+            vm_statuses.push(vm_status);
+            simulated_txns.push(simulated_txn);
+        }
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let transactions =
+                    self.render_simulated_transactions(&ledger_info, vm_statuses, simulated_txns)?;
+                BasicResponse::try_from_json((
+                    SimulateBundleResult {
+                        transactions,
+                        cumulative_gas_used: cumulative_gas_used.into(),
+                    },
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                SimulateBundleResultBcs {
+                    transactions: simulated_txns,
+                    cumulative_gas_used,
+                },
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        };
+
+        result.map(|r| r.with_gas_used(Some(cumulative_gas_used)))
+    }
+
+    /// Executes a transaction on the given state view, without committing anything
+    ///
+    /// The caller must have checked that the signature is not valid, and that the payload
+    /// can be profiled if `profile_gas` is set. The gas profile is only returned if the
+    /// transaction wasn't discarded.
+    fn simulate_on_state_view(
+        &self,
+        state_view: &OverlayStateView<DbStateView>,
+        version: u64,
+        txn: SignedTransaction,
+        profile_gas: bool,
+    ) -> (VMStatus, TransactionOnChainData, Option<GasProfile>) {
+        // Code which isn't on chain must not end up in the code cache shared with other VMs
+        let vm = if state_view.overrides_code() {
+            AptosSimulationVM::new_with_isolated_code_cache(state_view)
+        } else {
+            AptosSimulationVM::new(state_view)
+        };
+        let (vm_status, output, gas_profile) = if profile_gas {
+            let (vm_status, output, gas_profiler) = vm
+                .simulate_signed_transaction_with_modified_gas_meter(
+                    &txn,
+                    state_view,
+                    |gas_meter| match txn.payload() {
+                        TransactionPayload::EntryFunction(entry_function) => {
+                            GasProfiler::new_function(
+                                gas_meter,
+                                entry_function.module().clone(),
+                                entry_function.function().to_owned(),
+                                entry_function.ty_args().to_vec(),
+                            )
+                        },
+                        _ => GasProfiler::new_script(gas_meter),
+                    },
+                );
+            let gas_profile = gas_profiler
+                .map(|gas_profiler| render_gas_profile(&gas_profiler.finish(), &output));
+            (vm_status, output, gas_profile)
+        } else {
+            let (vm_status, output) = vm.simulate_signed_transaction(&txn, state_view);
+            (vm_status, output, None)
+        };
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
         let exe_status = ExecutionStatus::conmbine_vm_status_for_simulation(
@@ -1423,55 +1888,50 @@ impl TransactionsApi {
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
         };
+        (vm_status, simulated_txn, gas_profile)
+    }
 
-        let result = match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(mut user_txn) => {
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    user_txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(user_txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
+    /// Renders simulated transactions as JSON, adding the VM error messages to their status
+    fn render_simulated_transactions(
+        &self,
+        ledger_info: &LedgerInfo,
+        vm_statuses: Vec<VMStatus>,
+        simulated_txns: Vec<TransactionOnChainData>,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let transactions = self
+            .context
+            .render_transactions_non_sequential(ledger_info, simulated_txns)?;
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the Vec<Transaction> into Vec<UserTransaction>.
+        let mut user_transactions = Vec::new();
+        for (transaction, vm_status) in transactions.into_iter().zip(vm_statuses) {
+            match transaction {
+                Transaction::UserTransaction(mut user_txn) => {
+                    match &vm_status {
+                        VMStatus::Error {
+                            message: Some(msg), ..
+                        }
+                        | VMStatus::ExecutionFailure {
+                            message: Some(msg), ..
+                        } => {
+                            user_txn.info.vm_status +=
+                                format!("\nExecution failed with message: {}", msg).as_str();
                         },
+                        _ => (),
                     }
-                }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
-        };
-
-        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+                    user_transactions.push(user_txn);
+                },
+                _ => {
+                    return Err(SubmitTransactionError::internal_with_code(
+                        "Simulation transaction resulted in a non-UserTransaction",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    ))
+                },
+            }
+        }
+        Ok(user_transactions)
     }
 
     /// Encode message as BCS
@@ -1553,6 +2013,69 @@ fn override_gas_parameters(
     SignedTransaction::new_signed_transaction(raw_txn, signed_txn.authenticator())
 }
 
+/// Converts the log of the gas profiler into its API representation
+///
+/// The totals by category come from the fee statement of the transaction, which is what was
+/// actually charged.
+fn render_gas_profile(gas_log: &TransactionGasLog, output: &TransactionOutput) -> GasProfile {
+    let fee_statement = output
+        .try_extract_fee_statement()
+        .ok()
+        .flatten()
+        .unwrap_or_else(FeeStatement::zero);
+
+    let exec_io = gas_log.exec_io.aggregate_gas_events();
+    let storage = gas_log.storage.aggregate_storage_fees();
+    GasProfile {
+        gas_scaling_factor: u64::from(exec_io.gas_scaling_factor).into(),
+        execution_gas_units: fee_statement.execution_gas_used().into(),
+        io_gas_units: fee_statement.io_gas_used().into(),
+        storage_fee_octas: fee_statement.storage_fee_used().into(),
+        storage_fee_refund_octas: fee_statement.storage_fee_refund().into(),
+        execution_and_io: ExecutionAndIoGasProfile {
+            total: u64::from(exec_io.total).into(),
+            intrinsic: u64::from(gas_log.exec_io.intrinsic_cost).into(),
+            keyless: u64::from(gas_log.exec_io.keyless_cost).into(),
+            dependencies: render_gas_profile_entries(exec_io.dependencies),
+            functions: render_gas_profile_entries(exec_io.functions),
+            instructions: render_gas_profile_entries(exec_io.ops),
+            storage_reads: render_gas_profile_entries(exec_io.storage_reads),
+            storage_writes: render_gas_profile_entries(exec_io.storage_writes),
+            event_writes: render_gas_profile_entries(exec_io.event_writes),
+            transaction_write: u64::from(exec_io.transaction_write).into(),
+        },
+        storage: StorageFeeProfile {
+            total: u64::from(storage.total).into(),
+            total_refund: u64::from(storage.total_refund).into(),
+            transaction: u64::from(storage.txn_storage).into(),
+            state_writes: storage
+                .write_set_storage
+                .into_iter()
+                .map(|(name, fee, refund)| StorageFeeEntry {
+                    name,
+                    fee: u64::from(fee).into(),
+                    refund: u64::from(refund).into(),
+                })
+                .collect(),
+            events: render_gas_profile_entries(storage.events),
+            event_discount: u64::from(storage.event_discount).into(),
+        },
+    }
+}
+
+fn render_gas_profile_entries<C: Into<u64>>(
+    entries: Vec<(String, usize, C)>,
+) -> Vec<GasProfileEntry> {
+    entries
+        .into_iter()
+        .map(|(name, hits, cost)| GasProfileEntry {
+            name,
+            hits: (hits as u64).into(),
+            cost: U64(cost.into()),
+        })
+        .collect()
+}
+
 enum GetByVersionResponse {
     VersionTooNew,
     VersionTooOld,
@@ -10,14 +10,17 @@ use crate::{
         BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
         ForbiddenError, InternalError,
     },
+    state_overlay::OverlayStateView,
     ApiTags, Context,
 };
 use anyhow::Context as anyhowContext;
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosError, AptosErrorCode, AsConverter, LedgerInfo, MoveConverter, MoveValue, ViewFunction,
+    ViewFunctionBatchResult, ViewFunctionBatchResultBcs, ViewFunctionWithStateOverrides,
+    ViewRequest, ViewRequestWithStateOverrides, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
-use aptos_bcs_utils::serialize_uleb128;
+use aptos_bcs_utils::{deserialize_uleb128, serialize_uleb128};
+use aptos_types::state_store::StateView;
 use aptos_vm::AptosVM;
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
@@ -39,6 +42,26 @@ pub enum ViewFunctionRequest {
     Bcs(Bcs),
 }
 
+// The BCS variant expects a BCS encoded `ViewFunctionWithStateOverrides`.
+#[derive(ApiRequest, Debug)]
+pub enum ViewFunctionWithStateOverridesRequest {
+    #[oai(content_type = "application/json")]
+    Json(Json<ViewRequestWithStateOverrides>),
+
+    #[oai(content_type = "application/x.aptos.view_function+bcs")]
+    Bcs(Bcs),
+}
+
+// The BCS variant expects a BCS encoded `Vec<ViewFunction>`.
+#[derive(ApiRequest, Debug)]
+pub enum ViewFunctionBatchRequest {
+    #[oai(content_type = "application/json")]
+    Json(Json<Vec<ViewRequest>>),
+
+    #[oai(content_type = "application/x.aptos.view_function+bcs")]
+    Bcs(Bcs),
+}
+
 #[OpenApi]
 impl ViewFunctionApi {
     /// Execute view function of a module
@@ -71,6 +94,102 @@ impl ViewFunctionApi {
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
             .await
     }
+
+    /// Execute view function with state overrides
+    ///
+    /// Execute the Move function as with `/view`, but as if some state items had different
+    /// values, e.g. as if an account held a resource, or as if a module had new bytecode.
+    /// The overrides are only applied for this execution, nothing is written to storage. There
+    /// can't be more overrides than state items a transaction can write, and they can't be larger
+    /// than the request size limit in total.
+    ///
+    /// To use this endpoint with BCS, you must submit a `ViewFunctionWithStateOverrides`
+    /// encoded as BCS, where the overrides are given on raw state keys.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/with_state_overrides",
+        method = "post",
+        operation_id = "view_with_state_overrides",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_with_state_overrides(
+        &self,
+        accept_type: AcceptType,
+        /// View function request along with the state overrides
+        request: ViewFunctionWithStateOverridesRequest,
+        /// Ledger version of the state to override
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        fail_point_poem("endpoint_view_function_with_state_overrides")?;
+        self.context
+            .check_api_output_enabled("View function with state overrides", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            view_request_with_state_overrides(context, accept_type, request, ledger_version)
+        })
+        .await
+    }
+
+    /// Execute a batch of view functions
+    ///
+    /// Execute several Move view functions against the same ledger version and return
+    /// the result of each of them, in the same order as the requests. A failing view
+    /// function doesn't fail the whole batch, instead its error is returned in place
+    /// of its return values.
+    ///
+    /// All view functions are executed at the given ledger version, or at the ledger
+    /// version returned in the response headers if none is given. The total gas used
+    /// by the batch is returned in the `X-Aptos-Gas-Used` header.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        request: ViewFunctionBatchRequest,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ViewFunctionBatchResult>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            view_batch_request(context, accept_type, request, ledger_version)
+        })
+        .await
+    }
+}
+
+/// Returns an error message if the view function is not allowed by the filter.
+fn check_view_filter(context: &Context, view_function: &ViewFunction) -> Result<(), String> {
+    if context.node_config.api.view_filter.allows(
+        view_function.module.address(),
+        view_function.module.name().as_str(),
+        view_function.function.as_str(),
+    ) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Function {}::{} is not allowed",
+            view_function.module, view_function.function
+        ))
+    }
 }
 
 fn view_request(
@@ -117,23 +236,127 @@ fn view_request(
         },
     };
 
-    // Reject the request if it's not allowed by the filter.
-    if !context.node_config.api.view_filter.allows(
-        view_function.module.address(),
-        view_function.module.name().as_str(),
-        view_function.function.as_str(),
-    ) {
-        return Err(BasicErrorWith404::forbidden_with_code_no_info(
-            format!(
-                "Function {}::{} is not allowed",
-                view_function.module, view_function.function
-            ),
-            AptosErrorCode::InvalidInput,
-        ));
-    }
+    execute_view_function(
+        &context,
+        &accept_type,
+        &ledger_info,
+        &state_view,
+        view_function,
+        false,
+    )
+}
+
+fn view_request_with_state_overrides(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: ViewFunctionWithStateOverridesRequest,
+    ledger_version: Query<Option<U64>>,
+) -> BasicResultWith404<Vec<MoveValue>> {
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let mut state_view = OverlayStateView::new(
+        context
+            .state_view_at_version(requested_version)
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?,
+    );
+
+    // The overrides are applied first, so the request can refer to overridden modules
+    let limits = context.state_override_limits(&ledger_info)?;
+    let view_function: ViewFunction = match request {
+        ViewFunctionWithStateOverridesRequest::Json(data) => {
+            let ViewRequestWithStateOverrides {
+                request,
+                state_overrides,
+            } = data.0;
+            state_view
+                .apply_json_state_overrides(
+                    context.db.clone(),
+                    context.indexer_reader.clone(),
+                    state_overrides,
+                    &limits,
+                )
+                .context("Failed to apply state overrides")
+                .and_then(|_| {
+                    state_view
+                        .as_converter(context.db.clone(), context.indexer_reader.clone())
+                        .convert_view_function(request)
+                })
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?
+        },
+        ViewFunctionWithStateOverridesRequest::Bcs(data) => {
+            let ViewFunctionWithStateOverrides {
+                view_function,
+                state_overrides,
+            } = bcs::from_bytes_with_limit(data.0.as_slice(), MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                .context("Failed to deserialize input into ViewFunctionWithStateOverrides")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?;
+            state_view
+                .apply_state_overrides(state_overrides, &limits)
+                .context("Failed to apply state overrides")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?;
+            view_function
+        },
+    };
 
-    let output = AptosVM::execute_view_function(
+    let overrides_code = state_view.overrides_code();
+    execute_view_function(
+        &context,
+        &accept_type,
+        &ledger_info,
         &state_view,
+        view_function,
+        overrides_code,
+    )
+}
+
+/// Executes a view function, and renders its return values
+///
+/// If the state view contains code which isn't on chain, the VM must not share its code cache.
+fn execute_view_function(
+    context: &Context,
+    accept_type: &AcceptType,
+    ledger_info: &LedgerInfo,
+    state_view: &impl StateView,
+    view_function: ViewFunction,
+    use_isolated_code_cache: bool,
+) -> BasicResultWith404<Vec<MoveValue>> {
+    // Reject the request if it's not allowed by the filter.
+    check_view_filter(context, &view_function).map_err(|err| {
+        BasicErrorWith404::forbidden_with_code_no_info(err, AptosErrorCode::InvalidInput)
+    })?;
+
+    let vm = if use_isolated_code_cache {
+        AptosVM::new_with_isolated_code_cache(state_view)
+    } else {
+        AptosVM::new(state_view)
+    };
+    let output = vm.execute_view_function_with_vm(
+        state_view,
         view_function.module.clone(),
         view_function.function.clone(),
         view_function.ty_args.clone(),
@@ -155,7 +378,7 @@ fn view_request(
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
             })?;
 
@@ -163,7 +386,7 @@ fn view_request(
             let values = values.into_iter().concat();
             let ret = [length, values].concat();
 
-            BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
+            BasicResponse::try_from_encoded((ret, ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
             let return_types = state_view
@@ -178,7 +401,7 @@ fn view_request(
                     BasicErrorWith404::bad_request_with_code(
                         err,
                         AptosErrorCode::InternalError,
-                        &ledger_info,
+                        ledger_info,
                     )
                 })?;
 
@@ -195,11 +418,11 @@ fn view_request(
                     BasicErrorWith404::bad_request_with_code(
                         err,
                         AptosErrorCode::InternalError,
-                        &ledger_info,
+                        ledger_info,
                     )
                 })?;
 
-            BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
+            BasicResponse::try_from_json((move_vals, ledger_info, BasicResponseStatus::Ok))
         },
     };
     context.view_function_stats().increment(
@@ -208,3 +431,179 @@ fn view_request(
     );
     result.map(|r| r.with_gas_used(Some(output.gas_used)))
 }
+
+fn view_batch_request(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: ViewFunctionBatchRequest,
+    ledger_version: Query<Option<U64>>,
+) -> BasicResultWith404<Vec<ViewFunctionBatchResult>> {
+    // Retrieve the current state of the chain
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let state_view = context
+        .state_view_at_version(requested_version)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+    let converter = state_view.as_converter(context.db.clone(), context.indexer_reader.clone());
+
+    // Check the size of the batch before converting or even decoding its requests
+    let max_batch_size = context.node_config.api.max_view_function_batch_size;
+    let batch_size = match &request {
+        ViewFunctionBatchRequest::Json(data) => Some(data.0.len() as u64),
+        ViewFunctionBatchRequest::Bcs(data) => deserialize_uleb128(data.0.as_slice())
+            .ok()
+            .map(|(len, _)| len),
+    };
+    if let Some(batch_size) = batch_size.filter(|size| *size > max_batch_size as u64) {
+        return Err(BasicErrorWith404::bad_request_with_code(
+            format!(
+                "Batch of {} view functions is larger than the max batch size of {}",
+                batch_size, max_batch_size
+            ),
+            AptosErrorCode::InvalidInput,
+            &ledger_info,
+        ));
+    }
+
+    // Requests that fail to convert are reported in place, only a malformed
+    // BCS batch fails as a whole.
+    let view_functions: Vec<Result<ViewFunction, AptosError>> = match request {
+        ViewFunctionBatchRequest::Json(data) => data
+            .0
+            .into_iter()
+            .map(|request| {
+                converter.convert_view_function(request).map_err(|err| {
+                    AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput)
+                })
+            })
+            .collect(),
+        ViewFunctionBatchRequest::Bcs(data) => bcs::from_bytes_with_limit::<Vec<ViewFunction>>(
+            data.0.as_slice(),
+            MAX_RECURSIVE_TYPES_ALLOWED as usize,
+        )
+        .with_context(|| {
+            format!(
+                "Failed to deserialize input into a batch of at most {} view functions",
+                max_batch_size
+            )
+        })
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?
+        .into_iter()
+        .map(Ok)
+        .collect(),
+    };
+
+    let mut total_gas_used = 0;
+    let outputs: Vec<(Result<(ViewFunction, Vec<Vec<u8>>), AptosError>, u64)> = view_functions
+        .into_iter()
+        .map(|view_function| {
+            let view_function = match view_function {
+                Ok(view_function) => view_function,
+                Err(err) => return (Err(err), 0),
+            };
+            if let Err(err) = check_view_filter(&context, &view_function) {
+                return (
+                    Err(AptosError::new_with_error_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )),
+                    0,
+                );
+            }
+
+            let output = AptosVM::execute_view_function(
+                &state_view,
+                view_function.module.clone(),
+                view_function.function.clone(),
+                view_function.ty_args.clone(),
+                view_function.args.clone(),
+                context.node_config.api.max_gas_view_function,
+            );
+            context.view_function_stats().increment(
+                FunctionStats::function_to_key(&view_function.module, &view_function.function),
+                output.gas_used,
+            );
+            total_gas_used += output.gas_used;
+
+            let values = output
+                .values
+                .map(|values| (view_function, values))
+                .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput));
+            (values, output.gas_used)
+        })
+        .collect();
+
+    let result = match accept_type {
+        AcceptType::Bcs => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|(output, gas_used)| {
+                    let (values, error) = match output {
+                        Ok((_, values)) => (Some(values), None),
+                        Err(err) => (None, Some(err)),
+                    };
+                    ViewFunctionBatchResultBcs {
+                        values,
+                        error,
+                        gas_used,
+                    }
+                })
+                .collect();
+            BasicResponse::try_from_bcs((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+        AcceptType::Json => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|(output, gas_used)| {
+                    let values = output.and_then(|(view_function, values)| {
+                        convert_return_values(&converter, &view_function, values).map_err(|err| {
+                            AptosError::new_with_error_code(err, AptosErrorCode::InternalError)
+                        })
+                    });
+                    let (values, error) = match values {
+                        Ok(values) => (Some(values), None),
+                        Err(err) => (None, Some(err)),
+                    };
+                    ViewFunctionBatchResult {
+                        values,
+                        error,
+                        gas_used: gas_used.into(),
+                    }
+                })
+                .collect();
+            BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+    };
+    result.map(|r| r.with_gas_used(Some(total_gas_used)))
+}
+
+/// Converts the BCS encoded return values of a view function into JSON friendly Move values
+fn convert_return_values<S: StateView>(
+    converter: &MoveConverter<S>,
+    view_function: &ViewFunction,
+    values: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<MoveValue>> {
+    let return_types = converter
+        .function_return_types(view_function)?
+        .into_iter()
+        .map(TypeTag::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    values
+        .into_iter()
+        .zip(return_types)
+        .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+        .collect()
+}
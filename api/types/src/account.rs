@@ -25,3 +25,13 @@ impl From<AccountResource> for AccountData {
         }
     }
 }
+
+/// Balance of an asset held by an account
+///
+/// `version` is the ledger version at which the balance was last changed, at or before the
+/// requested ledger version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AccountBalance {
+    pub version: U64,
+    pub balance: U64,
+}
@@ -10,11 +10,11 @@ use crate::{
     },
     view::{ViewFunction, ViewRequest},
     Address, Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event,
-    HexEncodedBytes, MoveFunction, MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveType,
-    MoveValue, PendingTransaction, ResourceGroup, ScriptPayload, ScriptWriteSet,
-    SubmitTransactionRequest, Transaction, TransactionInfo, TransactionOnChainData,
-    TransactionPayload, UserTransactionRequest, VersionedEvent, WriteSet, WriteSetChange,
-    WriteSetPayload,
+    HexEncodedBytes, ModuleOverride, MoveFunction, MoveModuleBytecode, MoveResource,
+    MoveScriptBytecode, MoveType, MoveValue, PendingTransaction, ResourceGroup, ResourceOverride,
+    ScriptPayload, ScriptWriteSet, StateOverride, StateOverrideBcs, SubmitTransactionRequest,
+    TableItemOverride, Transaction, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    UserTransactionRequest, VersionedEvent, WriteSet, WriteSetChange, WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -110,6 +110,75 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         false
     }
 
+    /// Returns the state key holding the resource, which is the resource group's key when the
+    /// resource is a member of a resource group
+    pub fn find_resource_state_key(&self, address: Address, tag: &StructTag) -> Result<StateKey> {
+        Ok(match self.inner.view_resource_group_member(tag) {
+            Some(group_tag) => StateKey::resource_group(&address.into(), &group_tag),
+            None => StateKey::resource(&address.into(), tag)?,
+        })
+    }
+
+    /// Converts a state override to the state item it replaces
+    ///
+    /// Overriding a member of a resource group replaces the whole group, so the other members
+    /// are read from `state_view`.
+    pub fn try_into_state_override(
+        &self,
+        state_view: &impl StateView,
+        state_override: StateOverride,
+    ) -> Result<StateOverrideBcs> {
+        Ok(match state_override {
+            StateOverride::Resource(ResourceOverride {
+                address,
+                resource_type,
+                data,
+            }) => {
+                let tag: StructTag = resource_type.try_into()?;
+                match self.inner.view_resource_group_member(&tag) {
+                    Some(group_tag) => {
+                        let state_key = StateKey::resource_group(&address.into(), &group_tag);
+                        let mut group: BTreeMap<StructTag, Bytes> =
+                            match state_view.get_state_value_bytes(&state_key)? {
+                                Some(group_bytes) => bcs::from_bytes(&group_bytes)?,
+                                None => BTreeMap::new(),
+                            };
+                        match data {
+                            Some(data) => group.insert(tag, data.0.into()),
+                            None => group.remove(&tag),
+                        };
+                        let value = if group.is_empty() {
+                            None
+                        } else {
+                            Some(bcs::to_bytes(&group)?)
+                        };
+                        StateOverrideBcs { state_key, value }
+                    },
+                    None => StateOverrideBcs {
+                        state_key: StateKey::resource(&address.into(), &tag)?,
+                        value: data.map(|data| data.0),
+                    },
+                }
+            },
+            StateOverride::Module(ModuleOverride { bytecode }) => {
+                let module = MoveModuleBytecode::new(bytecode.0.clone())
+                    .try_parse_abi()?
+                    .abi
+                    .ok_or_else(|| format_err!("Failed to deserialize module bytecode"))?;
+                StateOverrideBcs {
+                    state_key: StateKey::module(&module.address.into(), &module.name),
+                    value: Some(bytecode.0),
+                }
+            },
+            StateOverride::TableItem(TableItemOverride { handle, key, value }) => {
+                StateOverrideBcs {
+                    state_key: StateKey::table_item(&TableHandle(handle.into()), &key.0),
+                    value: value.map(|value| value.0),
+                }
+            },
+        })
+    }
+
     pub fn find_resource(
         &self,
         state_view: &impl StateView,
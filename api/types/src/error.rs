@@ -101,6 +101,10 @@ pub enum AptosErrorCode {
     HealthCheckFailed = 500,
     /// The mempool is full, no new transactions can be submitted.
     MempoolIsFull = 501,
+    /// The client sent too many requests, and must wait before sending more.
+    RateLimitExceeded = 502,
+    /// The server is at capacity, e.g. it holds too many open streams, try again later.
+    ServiceUnavailable = 503,
 
     /// Internal server error
     InternalError = 600,
@@ -0,0 +1,86 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::U64;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// Breakdown of where the gas of a simulated transaction went, as measured by the gas profiler
+///
+/// Execution and IO costs are in internal gas units, divide them by `gas_scaling_factor` to get
+/// gas units. Storage fees are in octas.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct GasProfile {
+    /// Number of internal gas units per gas unit
+    pub gas_scaling_factor: U64,
+    /// Gas units charged for executing the transaction
+    pub execution_gas_units: U64,
+    /// Gas units charged for reading and writing state
+    pub io_gas_units: U64,
+    /// Storage fee charged for the new state, in octas
+    pub storage_fee_octas: U64,
+    /// Storage fee refunded for the freed state, in octas
+    pub storage_fee_refund_octas: U64,
+    pub execution_and_io: ExecutionAndIoGasProfile,
+    pub storage: StorageFeeProfile,
+}
+
+/// Execution and IO costs, in internal gas units
+///
+/// Every list is sorted by cost, from high to low.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ExecutionAndIoGasProfile {
+    pub total: U64,
+    /// Fixed cost of the transaction, depending on its size
+    pub intrinsic: U64,
+    /// Cost of verifying keyless signatures
+    pub keyless: U64,
+    /// Cost of loading each module the transaction depends on
+    pub dependencies: Vec<GasProfileEntry>,
+    /// Cost of each function, including the functions it calls
+    pub functions: Vec<GasProfileEntry>,
+    /// Cost of each instruction and native function
+    pub instructions: Vec<GasProfileEntry>,
+    /// Cost of each type of resource read
+    pub storage_reads: Vec<GasProfileEntry>,
+    /// Cost of each state item written
+    pub storage_writes: Vec<GasProfileEntry>,
+    /// Cost of each type of event emitted
+    pub event_writes: Vec<GasProfileEntry>,
+    /// Cost of writing the transaction itself
+    pub transaction_write: U64,
+}
+
+/// Storage fees, in octas
+///
+/// Every list is sorted by fee, from high to low.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StorageFeeProfile {
+    pub total: U64,
+    pub total_refund: U64,
+    /// Fee for storing the transaction itself
+    pub transaction: U64,
+    /// Fee and refund for each state item written
+    pub state_writes: Vec<StorageFeeEntry>,
+    /// Fee for each type of event emitted
+    pub events: Vec<GasProfileEntry>,
+    /// Discount applied to the total fee for events
+    pub event_discount: U64,
+}
+
+/// Aggregated cost of an item of a gas profile
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct GasProfileEntry {
+    pub name: String,
+    /// Number of times the item was charged for
+    pub hits: U64,
+    pub cost: U64,
+}
+
+/// Storage fee and refund of a state item
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StorageFeeEntry {
+    pub name: String,
+    pub fee: U64,
+    pub refund: U64,
+}
@@ -11,6 +11,7 @@ mod bytecode;
 mod convert;
 mod derives;
 mod error;
+mod gas_profile;
 mod hash;
 mod headers;
 mod index;
@@ -23,12 +24,15 @@ pub mod transaction;
 mod view;
 mod wrappers;
 
-pub use account::AccountData;
+pub use account::{AccountBalance, AccountData};
 pub use address::Address;
 pub use block::{BcsBlock, Block};
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_utf8_string, AsConverter, MoveConverter};
 pub use error::{AptosError, AptosErrorCode};
+pub use gas_profile::{
+    ExecutionAndIoGasProfile, GasProfile, GasProfileEntry, StorageFeeEntry, StorageFeeProfile,
+};
 pub use hash::HashValue;
 pub use headers::*;
 pub use index::{IndexResponse, IndexResponseBcs};
@@ -41,7 +45,10 @@ pub use move_types::{
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
 use serde::{Deserialize, Deserializer};
-pub use state::RawStateValueRequest;
+pub use state::{
+    ModuleOverride, RawStateValueRequest, ResourceOverride, StateOverride, StateOverrideBcs,
+    StateValueWithProof, TableItemOverride,
+};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
@@ -50,14 +57,19 @@ pub use transaction::{
     FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
     MultiAgentSignature, MultiEd25519Signature, MultiKeySignature, MultisigPayload,
     MultisigTransactionPayload, NoAccountSignature, PendingTransaction, PublicKey, ScriptPayload,
-    ScriptWriteSet, Signature, SingleKeySignature, SubmitTransactionRequest, Transaction,
-    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSignature, TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
+    ScriptWriteSet, Signature, SignedTransactionWithStateOverrides, SimulateBundleResult,
+    SimulateBundleResultBcs, SimulateTransactionRequestWithStateOverrides, SingleKeySignature,
+    SubmitTransactionRequest, Transaction, TransactionData, TransactionId, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionWithProofBcs, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
     UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
     WriteSetPayload, WriteTableItem,
 };
-pub use view::{ViewFunction, ViewRequest};
+pub use view::{
+    ViewFunction, ViewFunctionBatchResult, ViewFunctionBatchResultBcs,
+    ViewFunctionWithStateOverrides, ViewRequest, ViewRequestWithStateOverrides,
+};
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
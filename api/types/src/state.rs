@@ -1,8 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HexEncodedBytes;
-use poem_openapi::Object;
+use crate::{Address, HexEncodedBytes, MoveStructTag};
+use anyhow::{ensure, format_err};
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{SparseMerkleProofExt, TransactionInfoWithProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use poem_openapi::{Object, Union};
 use serde::{Deserialize, Serialize};
 
 /// Table Item request for the GetTableItemRaw API
@@ -10,3 +18,114 @@ use serde::{Deserialize, Serialize};
 pub struct RawStateValueRequest {
     pub key: HexEncodedBytes,
 }
+
+/// A state value along with everything needed to authenticate it against a ledger info
+///
+/// The value is proven against the state snapshot at `version`, since values can only be proven at
+/// state snapshots. That is the requested ledger version, which the node rejects if it has no
+/// snapshot, or else the latest snapshot of the node, so it can be older than the ledger version
+/// of the response. The snapshot is in turn proven to be part of the ledger committed by
+/// `ledger_info_with_signatures`.
+///
+/// Only available in BCS
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateValueWithProof {
+    pub state_key: StateKey,
+    /// `None` proves that the key doesn't exist at `version`
+    pub state_value: Option<StateValue>,
+    /// Version of the state snapshot the value is proven at
+    pub version: Version,
+    pub sparse_merkle_proof: SparseMerkleProofExt,
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+    pub ledger_info_with_signatures: LedgerInfoWithSignatures,
+}
+
+impl StateValueWithProof {
+    /// Verifies the state value against the given ledger info
+    ///
+    /// The ledger info must already be trusted, this doesn't check any signatures.
+    pub fn verify(&self, ledger_info: &LedgerInfo) -> anyhow::Result<()> {
+        ensure!(
+            self.version <= ledger_info.version(),
+            "State version {} is newer than the ledger info version {}",
+            self.version,
+            ledger_info.version(),
+        );
+        self.transaction_info_with_proof
+            .verify(ledger_info, self.version)?;
+        let state_root_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .state_checkpoint_hash()
+            .ok_or_else(|| {
+                format_err!(
+                    "Transaction at version {} is not a state checkpoint",
+                    self.version
+                )
+            })?;
+        self.sparse_merkle_proof.verify(
+            state_root_hash,
+            CryptoHash::hash(&self.state_key),
+            self.state_value.as_ref(),
+        )
+    }
+
+    /// Returns the bytes of the value, if the key exists
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.state_value
+            .as_ref()
+            .map(|value| value.bytes().as_ref())
+    }
+}
+
+/// A state item to use instead of the one in storage
+///
+/// Used to execute view functions or simulate transactions as if the state was different, e.g.
+/// as if an account had a resource it doesn't have.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum StateOverride {
+    Resource(ResourceOverride),
+    Module(ModuleOverride),
+    TableItem(TableItemOverride),
+}
+
+/// Overrides a resource of an account
+///
+/// Resources in a resource group are overridden within the group, the other members are kept.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    pub resource_type: MoveStructTag,
+    /// BCS encoded value of the resource, or null if the resource doesn't exist
+    pub data: Option<HexEncodedBytes>,
+}
+
+/// Overrides the bytecode of a module
+///
+/// The address and name of the module are the ones in the bytecode.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub bytecode: HexEncodedBytes,
+}
+
+/// Overrides an item of a table
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TableItemOverride {
+    pub handle: Address,
+    /// BCS encoded key of the item
+    pub key: HexEncodedBytes,
+    /// BCS encoded value of the item, or null if the item doesn't exist
+    pub value: Option<HexEncodedBytes>,
+}
+
+/// BCS representation of a state override, on the raw state key
+///
+/// Unlike [`ResourceOverride`], overriding a resource group replaces the whole group.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateOverrideBcs {
+    pub state_key: StateKey,
+    /// `None` if the state item doesn't exist
+    pub value: Option<Vec<u8>>,
+}
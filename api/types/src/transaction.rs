@@ -3,9 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, AptosError, EntryFunctionId, EventGuid, HashValue, HexEncodedBytes,
+    Address, AptosError, EntryFunctionId, EventGuid, GasProfile, HashValue, HexEncodedBytes,
     MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
-    MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
+    MoveValue, StateOverride, StateOverrideBcs, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use anyhow::{bail, Context as AnyhowContext};
 use aptos_crypto::{
@@ -101,6 +101,26 @@ pub struct TransactionOnChainData {
     pub changes: aptos_types::write_set::WriteSet,
 }
 
+/// A committed transaction along with the proof that it's part of the ledger
+///
+/// The transaction and its events are proven against `ledger_info_with_signatures`.
+///
+/// Only available in BCS
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionWithProofBcs {
+    pub transaction_with_proof: TransactionWithProof,
+    pub ledger_info_with_signatures: aptos_types::ledger_info::LedgerInfoWithSignatures,
+}
+
+impl TransactionWithProofBcs {
+    /// Verifies the transaction and its events against the given ledger info
+    ///
+    /// The ledger info must already be trusted, this doesn't check any signatures.
+    pub fn verify(&self, ledger_info: &aptos_types::ledger_info::LedgerInfo) -> anyhow::Result<()> {
+        self.transaction_with_proof.verify(ledger_info)
+    }
+}
+
 impl From<(TransactionWithProof, aptos_crypto::HashValue)> for TransactionOnChainData {
     fn from((txn, accumulator_root_hash): (TransactionWithProof, aptos_crypto::HashValue)) -> Self {
         Self {
@@ -297,6 +317,7 @@ impl
             request: (txn, payload).into(),
             events,
             timestamp: timestamp.into(),
+            gas_profile: None,
         })
     }
 }
@@ -413,6 +434,10 @@ pub struct UserTransaction {
     /// Events generated by the transaction
     pub events: Vec<Event>,
     pub timestamp: U64,
+    /// Breakdown of the gas used, only present for simulations with gas profiling enabled
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<GasProfile>,
 }
 
 /// A state checkpoint transaction
@@ -460,6 +485,27 @@ impl VerifyInput for SubmitTransactionRequest {
     }
 }
 
+/// A transaction to simulate on top of overridden state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionRequestWithStateOverrides {
+    pub transaction: SubmitTransactionRequest,
+    /// State items to use instead of the ones in storage, applied in order
+    pub state_overrides: Vec<StateOverride>,
+}
+
+impl VerifyInput for SimulateTransactionRequestWithStateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()
+    }
+}
+
+/// BCS representation of [`SimulateTransactionRequestWithStateOverrides`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransactionWithStateOverrides {
+    pub transaction: SignedTransaction,
+    pub state_overrides: Vec<StateOverrideBcs>,
+}
+
 /// Batch transaction submission result
 ///
 /// Tells which transactions failed
@@ -469,6 +515,24 @@ pub struct TransactionsBatchSubmissionResult {
     pub transaction_failures: Vec<TransactionsBatchSingleSubmissionFailure>,
 }
 
+/// Result of simulating a bundle of transactions
+///
+/// The transactions are simulated in order, each one on top of the changes of the previous
+/// ones, so they're in the same order as in the request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateBundleResult {
+    pub transactions: Vec<UserTransaction>,
+    /// Total gas used by all the transactions of the bundle
+    pub cumulative_gas_used: U64,
+}
+
+/// BCS representation of [`SimulateBundleResult`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulateBundleResultBcs {
+    pub transactions: Vec<TransactionOnChainData>,
+    pub cumulative_gas_used: u64,
+}
+
 /// Information telling which batch submission transactions failed
 #[derive(Debug, Serialize, Deserialize, Object)]
 pub struct TransactionsBatchSingleSubmissionFailure {
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{
+    AptosError, EntryFunctionId, MoveType, MoveValue, StateOverride, StateOverrideBcs, U64,
+};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -20,6 +22,21 @@ pub struct ViewRequest {
     pub arguments: Vec<serde_json::Value>,
 }
 
+/// View request executed on top of overridden state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ViewRequestWithStateOverrides {
+    pub request: ViewRequest,
+    /// State items to use instead of the ones in storage, applied in order
+    pub state_overrides: Vec<StateOverride>,
+}
+
+/// BCS representation of [`ViewRequestWithStateOverrides`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ViewFunctionWithStateOverrides {
+    pub view_function: ViewFunction,
+    pub state_overrides: Vec<StateOverrideBcs>,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ViewFunction {
     pub module: ModuleId,
@@ -28,3 +45,25 @@ pub struct ViewFunction {
     #[serde(with = "vec_bytes")]
     pub args: Vec<Vec<u8>>,
 }
+
+/// Result of a single view function call within a batch view request
+///
+/// Exactly one of `values` and `error` is set.
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct ViewFunctionBatchResult {
+    /// Return values of the view function, if it executed successfully
+    pub values: Option<Vec<MoveValue>>,
+    /// The reason the view function failed, if it did
+    pub error: Option<AptosError>,
+    /// Gas used by the view function
+    pub gas_used: U64,
+}
+
+/// BCS representation of [`ViewFunctionBatchResult`], where each return value
+/// is itself BCS encoded
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewFunctionBatchResultBcs {
+    pub values: Option<Vec<Vec<u8>>>,
+    pub error: Option<AptosError>,
+    pub gas_used: u64,
+}
@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    log::{ExecutionAndIOCosts, ExecutionGasEvent},
+    log::{CallFrame, ExecutionAndIOCosts, ExecutionGasEvent, StorageFees},
     render::{Render, TableKey},
 };
-use aptos_gas_algebra::{GasQuantity, GasScalingFactor, InternalGas};
+use aptos_gas_algebra::{Fee, GasQuantity, GasScalingFactor, InternalGas};
 use std::collections::{btree_map, BTreeMap};
 
 /// Represents an aggregation of execution gas events, including the count and total gas costs for each type of event.
@@ -21,6 +21,11 @@ pub struct AggregatedExecutionGasEvents {
     pub total: InternalGas,
 
     // TODO: Make this more strongly typed?
+    pub dependencies: Vec<(String, usize, InternalGas)>,
+    /// Cost of each function, including the cost of the functions it calls.
+    ///
+    /// The cost of recursive calls is counted once per frame.
+    pub functions: Vec<(String, usize, InternalGas)>,
     pub ops: Vec<(String, usize, InternalGas)>,
     pub transaction_write: InternalGas,
     pub event_writes: Vec<(String, usize, InternalGas)>,
@@ -28,6 +33,20 @@ pub struct AggregatedExecutionGasEvents {
     pub storage_writes: Vec<(String, usize, InternalGas)>,
 }
 
+/// Represents an aggregation of storage fees, with the state writes and events sorted by fee,
+/// from high to low.
+#[derive(Debug)]
+pub struct AggregatedStorageFees {
+    pub total: Fee,
+    pub total_refund: Fee,
+
+    pub txn_storage: Fee,
+    /// The fee and refund for each state write.
+    pub write_set_storage: Vec<(String, Fee, Fee)>,
+    pub events: Vec<(String, usize, Fee)>,
+    pub event_discount: Fee,
+}
+
 fn insert_or_add<K, U>(
     map: &mut BTreeMap<K, (usize, GasQuantity<U>)>,
     key: K,
@@ -64,11 +83,46 @@ where
     v
 }
 
+/// Adds up the cost of each call frame in the tree, returning the cost of the root frame.
+fn aggregate_function_costs(
+    frame: &CallFrame,
+    functions: &mut BTreeMap<String, (usize, InternalGas)>,
+) -> InternalGas {
+    use ExecutionGasEvent::*;
+
+    let mut total = InternalGas::zero();
+    for event in &frame.events {
+        match event {
+            Loc(..) => (),
+            Call(child) => total += aggregate_function_costs(child, functions),
+            Bytecode { cost, .. }
+            | CallNative { cost, .. }
+            | LoadResource { cost, .. }
+            | CreateTy { cost } => total += *cost,
+        }
+    }
+    insert_or_add(functions, format!("{}", frame.name), total);
+    total
+}
+
 impl ExecutionAndIOCosts {
     /// Counts the number of hits and aggregates the gas costs for each type of event.
     pub fn aggregate_gas_events(&self) -> AggregatedExecutionGasEvents {
         use ExecutionGasEvent::*;
 
+        let mut dependencies = BTreeMap::new();
+        for dep in &self.dependencies {
+            let name = format!(
+                "{}{}",
+                Render(&dep.id),
+                if dep.is_new { " (new)" } else { "" }
+            );
+            insert_or_add(&mut dependencies, name, dep.cost);
+        }
+
+        let mut functions = BTreeMap::new();
+        aggregate_function_costs(&self.call_graph, &mut functions);
+
         let mut ops = BTreeMap::new();
         let mut storage_reads = BTreeMap::new();
         let mut storage_writes = BTreeMap::new();
@@ -130,6 +184,8 @@ impl ExecutionAndIOCosts {
             gas_scaling_factor: self.gas_scaling_factor,
             total: self.total,
 
+            dependencies: into_sorted_vec(dependencies),
+            functions: into_sorted_vec(functions),
             ops: into_sorted_vec(ops),
             transaction_write: self.transaction_transient.unwrap_or_else(|| 0.into()),
             event_writes: into_sorted_vec(event_writes),
@@ -138,3 +194,31 @@ impl ExecutionAndIOCosts {
         }
     }
 }
+
+impl StorageFees {
+    /// Aggregates the storage fees for each event type, and sorts the state writes by fee.
+    pub fn aggregate_storage_fees(&self) -> AggregatedStorageFees {
+        let mut write_set_storage = self
+            .write_set_storage
+            .iter()
+            .map(|write| (format!("{}", Render(&write.key)), write.cost, write.refund))
+            .collect::<Vec<_>>();
+        write_set_storage
+            .sort_by(|(_key1, cost1, _refund1), (_key2, cost2, _refund2)| cost2.cmp(cost1));
+
+        let mut events = BTreeMap::new();
+        for event in &self.events {
+            insert_or_add(&mut events, format!("{}", event.ty), event.cost);
+        }
+
+        AggregatedStorageFees {
+            total: self.total,
+            total_refund: self.total_refund,
+
+            txn_storage: self.txn_storage,
+            write_set_storage,
+            events: into_sorted_vec(events),
+            event_discount: self.event_discount,
+        }
+    }
+}
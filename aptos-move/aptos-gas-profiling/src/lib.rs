@@ -10,5 +10,6 @@ mod profiler;
 mod render;
 mod report;
 
+pub use aggregate::{AggregatedExecutionGasEvents, AggregatedStorageFees};
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
@@ -228,6 +228,15 @@ impl AptosVM {
         Self::new_with_environment(env, state_view, true)
     }
 
+    /// Creates a new VM instance which doesn't share its code cache with other instances.
+    ///
+    /// This must be used if the state view contains modules which aren't on chain, e.g. when
+    /// simulating with overridden state, as they would otherwise be cached for every other VM.
+    pub fn new_with_isolated_code_cache(state_view: &impl StateView) -> Self {
+        let env = Arc::new(Environment::new(state_view));
+        Self::new_impl(env, state_view, false, false)
+    }
+
     /// Creates a new VM instance based on the runtime environment, and used by block
     /// executor to create multiple tasks sharing the same execution configurations.
     // TODO: Passing `state_view` is not needed once we move keyless and gas-related
@@ -236,6 +245,15 @@ impl AptosVM {
         env: Arc<Environment>,
         state_view: &impl StateView,
         inject_create_signer_for_gov_sim: bool,
+    ) -> Self {
+        Self::new_impl(env, state_view, inject_create_signer_for_gov_sim, true)
+    }
+
+    fn new_impl(
+        env: Arc<Environment>,
+        state_view: &impl StateView,
+        inject_create_signer_for_gov_sim: bool,
+        use_warm_vm_cache: bool,
     ) -> Self {
         let _timer = TIMER.timer_with(&["AptosVM::new"]);
 
@@ -243,14 +261,23 @@ impl AptosVM {
             get_gas_parameters(env.features(), state_view);
 
         let resolver = state_view.as_move_resolver();
-        let move_vm = MoveVmExt::new_with_extended_options(
-            gas_feature_version,
-            gas_params.as_ref(),
-            env,
-            None,
-            inject_create_signer_for_gov_sim,
-            &resolver,
-        );
+        let move_vm = if use_warm_vm_cache {
+            MoveVmExt::new_with_extended_options(
+                gas_feature_version,
+                gas_params.as_ref(),
+                env,
+                None,
+                inject_create_signer_for_gov_sim,
+                &resolver,
+            )
+        } else {
+            MoveVmExt::new_with_isolated_code_cache(
+                gas_feature_version,
+                gas_params.as_ref(),
+                env,
+                &resolver,
+            )
+        };
 
         // We use an `Option` to handle the VK not being set on-chain, or an incorrect VK being set
         // via governance (although, currently, we do check for that in `keyless_account.move`).
@@ -2248,18 +2275,37 @@ impl AptosVM {
         arguments: Vec<Vec<u8>>,
         max_gas_amount: u64,
     ) -> ViewFunctionOutput {
-        let vm = AptosVM::new(state_view);
+        AptosVM::new(state_view).execute_view_function_with_vm(
+            state_view,
+            module_id,
+            func_name,
+            type_args,
+            arguments,
+            max_gas_amount,
+        )
+    }
 
+    /// Same as [`AptosVM::execute_view_function`], but on an existing VM instance, e.g. one
+    /// created with [`AptosVM::new_with_isolated_code_cache`].
+    pub fn execute_view_function_with_vm(
+        &self,
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        max_gas_amount: u64,
+    ) -> ViewFunctionOutput {
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
-        let vm_gas_params = match get_or_vm_startup_failure(&vm.gas_params, &log_context) {
+        let vm_gas_params = match get_or_vm_startup_failure(&self.gas_params, &log_context) {
             Ok(gas_params) => gas_params.vm.clone(),
             Err(err) => {
                 return ViewFunctionOutput::new(Err(anyhow::Error::msg(format!("{}", err))), 0)
             },
         };
         let storage_gas_params =
-            match get_or_vm_startup_failure(&vm.storage_gas_params, &log_context) {
+            match get_or_vm_startup_failure(&self.storage_gas_params, &log_context) {
                 Ok(gas_params) => gas_params.clone(),
                 Err(err) => {
                     return ViewFunctionOutput::new(Err(anyhow::Error::msg(format!("{}", err))), 0)
@@ -2267,7 +2313,7 @@ impl AptosVM {
             };
 
         let mut gas_meter = make_prod_gas_meter(
-            vm.gas_feature_version,
+            self.gas_feature_version,
             vm_gas_params,
             storage_gas_params,
             /* is_approved_gov_script */ false,
@@ -2275,10 +2321,10 @@ impl AptosVM {
         );
 
         let resolver = state_view.as_move_resolver();
-        let mut session = vm.new_session(&resolver, SessionId::Void, None);
+        let mut session = self.new_session(&resolver, SessionId::Void, None);
         let execution_result = Self::execute_view_function_in_vm(
             &mut session,
-            &vm,
+            self,
             module_id,
             func_name,
             type_args,
@@ -2737,6 +2783,13 @@ impl AptosSimulationVM {
         Self(vm)
     }
 
+    /// See [`AptosVM::new_with_isolated_code_cache`].
+    pub fn new_with_isolated_code_cache(state_view: &impl StateView) -> Self {
+        let mut vm = AptosVM::new_with_isolated_code_cache(state_view);
+        vm.is_simulation = true;
+        Self(vm)
+    }
+
     /// Simulates a signed transaction (i.e., executes it without performing
     /// signature verification) on a newly created VM instance.
     /// *Precondition:* the transaction must **not** have a valid signature.
@@ -2749,17 +2802,74 @@ impl AptosSimulationVM {
             "Simulated transaction should not have a valid signature"
         );
 
-        let vm = Self::new(state_view);
+        Self::new(state_view).simulate_signed_transaction(transaction, state_view)
+    }
+
+    /// Simulates a signed transaction on this VM instance.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
         let resolver = state_view.as_move_resolver();
         let (vm_status, vm_output) =
-            vm.0.execute_user_transaction(&resolver, transaction, &log_context);
+            self.0
+                .execute_user_transaction(&resolver, transaction, &log_context);
         let txn_output = vm_output
             .try_materialize_into_transaction_output(&resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
         (vm_status, txn_output)
     }
+
+    /// Same as [`AptosSimulationVM::simulate_signed_transaction`], but with a gas meter built
+    /// on top of the production one, e.g. to profile gas.
+    ///
+    /// The gas meter is not returned if the transaction is discarded before execution.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_signed_transaction_with_modified_gas_meter<G, F>(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        modify_gas_meter: F,
+    ) -> (VMStatus, TransactionOutput, Option<G>)
+    where
+        F: FnOnce(ProdGasMeter) -> G,
+        G: AptosGasMeter,
+    {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+        let resolver = state_view.as_move_resolver();
+        let (vm_status, vm_output, gas_meter) =
+            match self.0.execute_user_transaction_with_modified_gas_meter(
+                &resolver,
+                transaction,
+                &log_context,
+                modify_gas_meter,
+            ) {
+                Ok((vm_status, vm_output, gas_meter)) => (vm_status, vm_output, Some(gas_meter)),
+                Err(vm_status) => {
+                    let vm_output = discarded_output(vm_status.status_code());
+                    (vm_status, vm_output, None)
+                },
+            };
+        let txn_output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .expect("Materializing aggregator V1 deltas should never fail");
+        (vm_status, txn_output, gas_meter)
+    }
 }
 
 fn create_account_if_does_not_exist(
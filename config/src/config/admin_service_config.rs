@@ -20,6 +20,9 @@ pub struct AdminServiceConfig {
     pub port: u16,
     // If empty, will allow all requests without authentication. (Not allowed on mainnet.)
     pub authentication_configs: Vec<AuthenticationConfig>,
+    // Whether to serve the endpoints controlling a localnet, e.g. moving its time forward. These
+    // are only served to local clients, and only allowed on local testing chains.
+    pub enable_localnet_controls: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -44,6 +47,7 @@ impl Default for AdminServiceConfig {
             address: "0.0.0.0".to_string(),
             port: 9102,
             authentication_configs: vec![],
+            enable_localnet_controls: false,
         }
     }
 }
@@ -75,6 +79,17 @@ impl ConfigSanitizer for AdminServiceConfig {
             }
         }
 
+        if node_config.admin_service.enable_localnet_controls {
+            if let Some(chain_id) = chain_id {
+                if chain_id != ChainId::test() {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        "Localnet controls can only be enabled on local testing chains.".into(),
+                    ));
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -108,6 +123,28 @@ impl ConfigOptimizer for AdminServiceConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sanitize_localnet_controls() {
+        // Create a node config with the localnet controls enabled
+        let node_config = NodeConfig {
+            admin_service: AdminServiceConfig {
+                enable_localnet_controls: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config is only valid on local testing chains
+        AdminServiceConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::test()))
+            .unwrap();
+        for chain_id in [ChainId::mainnet(), ChainId::testnet(), ChainId::new(2)] {
+            let error =
+                AdminServiceConfig::sanitize(&node_config, NodeType::Validator, Some(chain_id))
+                    .unwrap_err();
+            assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        }
+    }
+
     #[test]
     fn test_optimize_admin_service_disabled() {
         // Create a node config with the admin service disabled
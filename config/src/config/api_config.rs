@@ -5,8 +5,9 @@
 use super::transaction_filter_type::{Filter, Matcher};
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, gas_estimation_config::GasEstimationConfig,
-        node_config_loader::NodeType, Error, NodeConfig, MAX_RECEIVING_BLOCK_TXNS,
+        api_rate_limit_config::ApiRateLimitConfig, config_sanitizer::ConfigSanitizer,
+        gas_estimation_config::GasEstimationConfig, node_config_loader::NodeType, Error,
+        NodeConfig, MAX_RECEIVING_BLOCK_TXNS,
     },
     utils,
 };
@@ -65,6 +66,8 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be executed with the batch view API
+    pub max_view_function_batch_size: usize,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -89,6 +92,15 @@ pub struct ApiConfig {
     pub wait_by_hash_poll_interval_ms: u64,
     /// The number of active wait_by_hash requests that can be active at any given time.
     pub wait_by_hash_max_active_connections: usize,
+    /// Enables the server-sent event subscription APIs
+    #[serde(default = "default_enabled")]
+    pub subscription_enabled: bool,
+    /// The interval at which subscriptions will poll the storage for newly committed transactions.
+    pub subscription_poll_interval_ms: u64,
+    /// The number of subscriptions that can be active at any given time.
+    pub subscription_max_active_connections: usize,
+    /// Configs for rate limiting requests per client
+    pub rate_limit: ApiRateLimitConfig,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -99,6 +111,7 @@ pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
+pub const DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE: usize = 50;
 
 fn default_enabled() -> bool {
     true
@@ -131,6 +144,7 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_batch_size: DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
@@ -141,6 +155,10 @@ impl Default for ApiConfig {
             wait_by_hash_timeout_ms: 1_000,
             wait_by_hash_poll_interval_ms: 20,
             wait_by_hash_max_active_connections: 100,
+            subscription_enabled: default_enabled(),
+            subscription_poll_interval_ms: 100,
+            subscription_max_active_connections: 100,
+            rate_limit: ApiRateLimitConfig::default(),
         }
     }
 }
@@ -203,6 +221,9 @@ impl ConfigSanitizer for ApiConfig {
         // Sanitize the gas estimation config
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
 
+        // Sanitize the rate limit config
+        ApiRateLimitConfig::sanitize(node_config, node_type, chain_id)?;
+
         Ok(())
     }
 }
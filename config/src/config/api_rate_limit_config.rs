@@ -0,0 +1,290 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Token bucket rate limiting of the REST API
+///
+/// Requests are limited per client, and per class of endpoint. A client is identified by its API
+/// key if it sends one of the configured keys, or by its IP address otherwise.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    /// A gate for rate limiting. If false, all requests are let through.
+    pub enabled: bool,
+    /// Header holding the IP address of the client, e.g. `X-Forwarded-For` when the node is
+    /// behind a proxy. If not set, the address of the peer is used.
+    pub client_ip_header: Option<String>,
+    /// Number of trusted proxies in front of the node appending to `client_ip_header`.
+    ///
+    /// Each proxy appends the address it received the request from, and anything on the left
+    /// of the entries added by the trusted proxies is set by the client. So the address used is
+    /// this many entries from the right of the header, or the address of the peer if the header
+    /// holds fewer entries.
+    pub client_ip_header_trusted_hops: usize,
+    /// Header holding the API key of the client
+    pub api_key_header: Option<String>,
+    /// Limits applied to each IP address
+    pub per_ip: EndpointClassRateLimits,
+    /// Limits applied to the clients of each API key, instead of the per IP limits.
+    ///
+    /// Requests with an unknown key are limited by IP address.
+    pub api_keys: Vec<ApiKeyRateLimitConfig>,
+    /// Interval at which the buckets of idle clients are dropped
+    pub garbage_collection_interval_secs: u64,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> ApiRateLimitConfig {
+        ApiRateLimitConfig {
+            enabled: false,
+            client_ip_header: None,
+            client_ip_header_trusted_hops: 1,
+            api_key_header: None,
+            per_ip: EndpointClassRateLimits {
+                read: Some(TokenBucketConfig::new(200, 100)),
+                submit: Some(TokenBucketConfig::new(20, 10)),
+                simulate: Some(TokenBucketConfig::new(20, 10)),
+            },
+            api_keys: vec![],
+            garbage_collection_interval_secs: 60,
+        }
+    }
+}
+
+/// Limits for each class of endpoint. A class without limits isn't rate limited.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointClassRateLimits {
+    /// Every request that isn't a submission or a simulation
+    pub read: Option<TokenBucketConfig>,
+    /// Transaction submission, single or batch
+    pub submit: Option<TokenBucketConfig>,
+    /// Transaction simulation
+    pub simulate: Option<TokenBucketConfig>,
+}
+
+impl EndpointClassRateLimits {
+    fn iter(&self) -> impl Iterator<Item = &TokenBucketConfig> {
+        [&self.read, &self.submit, &self.simulate]
+            .into_iter()
+            .flatten()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenBucketConfig {
+    /// Maximum number of requests that can be made in a burst
+    pub bucket_size: usize,
+    /// Number of requests added back to the bucket every second
+    pub fill_rate: usize,
+}
+
+impl TokenBucketConfig {
+    pub fn new(bucket_size: usize, fill_rate: usize) -> Self {
+        Self {
+            bucket_size,
+            fill_rate,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyRateLimitConfig {
+    /// Name of the key, used in logs instead of the key itself
+    pub name: String,
+    pub key: String,
+    pub limits: EndpointClassRateLimits,
+}
+
+impl ConfigSanitizer for ApiRateLimitConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let rate_limit_config = &node_config.api.rate_limit;
+
+        // Validate the buckets even if rate limiting is disabled, the rate limiters are built
+        // anyway and panic on invalid ones
+        let api_key_limits = rate_limit_config
+            .api_keys
+            .iter()
+            .flat_map(|api_key| api_key.limits.iter());
+        for bucket in rate_limit_config.per_ip.iter().chain(api_key_limits) {
+            if bucket.fill_rate == 0 || bucket.bucket_size < bucket.fill_rate {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!(
+                        "Rate limit fill rate {} must be > 0 and <= bucket size {}",
+                        bucket.fill_rate, bucket.bucket_size
+                    ),
+                ));
+            }
+        }
+
+        if !rate_limit_config.enabled {
+            return Ok(());
+        }
+
+        if rate_limit_config.client_ip_header.is_some()
+            && rate_limit_config.client_ip_header_trusted_hops == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "client_ip_header_trusted_hops must be > 0 to use client_ip_header!".into(),
+            ));
+        }
+
+        if !rate_limit_config.api_keys.is_empty() {
+            if rate_limit_config.api_key_header.is_none() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "api_key_header must be set to rate limit API keys!".into(),
+                ));
+            }
+
+            let mut keys = HashSet::new();
+            for api_key in &rate_limit_config.api_keys {
+                if api_key.key.is_empty() || !keys.insert(&api_key.key) {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!("API key {} is empty or duplicated!", api_key.name),
+                    ));
+                }
+            }
+        }
+
+        if rate_limit_config.garbage_collection_interval_secs == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "garbage_collection_interval_secs must be > 0!".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+
+    fn node_config_with_rate_limit(rate_limit: ApiRateLimitConfig) -> NodeConfig {
+        NodeConfig {
+            api: ApiConfig {
+                rate_limit,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sanitize_valid_rate_limit_config() {
+        let node_config = node_config_with_rate_limit(ApiRateLimitConfig {
+            enabled: true,
+            api_key_header: Some("x-api-key".into()),
+            api_keys: vec![ApiKeyRateLimitConfig {
+                name: "indexer".into(),
+                key: "secret".into(),
+                limits: EndpointClassRateLimits {
+                    read: Some(TokenBucketConfig::new(1000, 500)),
+                    ..Default::default()
+                },
+            }],
+            ..Default::default()
+        });
+
+        ApiRateLimitConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_invalid_bucket() {
+        let node_config = node_config_with_rate_limit(ApiRateLimitConfig {
+            enabled: true,
+            per_ip: EndpointClassRateLimits {
+                submit: Some(TokenBucketConfig::new(5, 10)),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let error = ApiRateLimitConfig::sanitize(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::mainnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_invalid_bucket_when_disabled() {
+        let node_config = node_config_with_rate_limit(ApiRateLimitConfig {
+            enabled: false,
+            per_ip: EndpointClassRateLimits {
+                read: Some(TokenBucketConfig::new(10, 0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let error = ApiRateLimitConfig::sanitize(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::mainnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_client_ip_header_without_trusted_hops() {
+        let node_config = node_config_with_rate_limit(ApiRateLimitConfig {
+            enabled: true,
+            client_ip_header: Some("x-forwarded-for".into()),
+            client_ip_header_trusted_hops: 0,
+            ..Default::default()
+        });
+
+        let error = ApiRateLimitConfig::sanitize(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::mainnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_api_keys_without_header() {
+        let node_config = node_config_with_rate_limit(ApiRateLimitConfig {
+            enabled: true,
+            api_keys: vec![ApiKeyRateLimitConfig {
+                name: "indexer".into(),
+                key: "secret".into(),
+                limits: EndpointClassRateLimits::default(),
+            }],
+            ..Default::default()
+        });
+
+        let error = ApiRateLimitConfig::sanitize(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::mainnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+}
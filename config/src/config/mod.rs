@@ -5,6 +5,7 @@
 // All modules should be declared below
 mod admin_service_config;
 mod api_config;
+mod api_rate_limit_config;
 mod base_config;
 mod config_optimizer;
 mod config_sanitizer;
@@ -43,6 +44,7 @@ mod utils;
 // All public usage statements should be declared below
 pub use admin_service_config::*;
 pub use api_config::*;
+pub use api_rate_limit_config::*;
 pub use base_config::*;
 pub use consensus_config::*;
 pub use consensus_observer_config::*;
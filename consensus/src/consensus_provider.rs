@@ -22,7 +22,7 @@ use crate::{
     state_computer::ExecutionProxy,
     transaction_filter::TransactionFilter,
     txn_notifier::MempoolNotifier,
-    util::time_service::ClockTimeService,
+    util::{localnet_clock::LocalnetClock, time_service::ClockTimeService},
 };
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_channels::aptos_channel::Receiver;
@@ -54,7 +54,12 @@ pub fn start_consensus(
     reconfig_events: ReconfigNotificationListener<DbBackedOnChainConfig>,
     vtxn_pool: VTxnPoolState,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
-) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
+) -> (
+    Runtime,
+    Arc<StorageWriteProxy>,
+    Arc<QuorumStoreDB>,
+    Option<Arc<LocalnetClock>>,
+) {
     let runtime = aptos_runtimes::spawn_named_runtime("consensus".into(), None);
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
     let quorum_store_db = Arc::new(QuorumStoreDB::new(node_config.storage.dir()));
@@ -64,7 +69,12 @@ pub fn start_consensus(
         node_config.consensus.mempool_executed_txn_timeout_ms,
     ));
 
-    let execution_proxy = ExecutionProxy::new(
+    let localnet_clock = node_config
+        .admin_service
+        .enable_localnet_controls
+        .then(|| Arc::new(LocalnetClock::new(aptos_db.reader.as_ref())));
+
+    let mut execution_proxy = ExecutionProxy::new(
         Arc::new(BlockExecutor::<AptosVM>::new(aptos_db)),
         txn_notifier,
         state_sync_notifier,
@@ -72,6 +82,9 @@ pub fn start_consensus(
         TransactionFilter::new(node_config.execution.transaction_filter.clone()),
         node_config.consensus.enable_pre_commit,
     );
+    if let Some(localnet_clock) = &localnet_clock {
+        execution_proxy = execution_proxy.with_localnet_clock(localnet_clock.clone());
+    }
 
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
 
@@ -122,7 +135,7 @@ pub fn start_consensus(
     runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
 
     debug!("Consensus started.");
-    (runtime, storage, quorum_store_db)
+    (runtime, storage, quorum_store_db, localnet_clock)
 }
 
 /// A helper function to start the consensus observer
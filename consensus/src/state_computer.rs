@@ -16,6 +16,7 @@ use crate::{
     transaction_filter::TransactionFilter,
     transaction_shuffler::TransactionShuffler,
     txn_notifier::TxnNotifier,
+    util::localnet_clock::LocalnetClock,
 };
 use anyhow::Result;
 use aptos_consensus_notifications::ConsensusNotificationSender;
@@ -29,8 +30,8 @@ use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
 use aptos_types::{
     account_address::AccountAddress, block_executor::config::BlockExecutorConfigFromOnchain,
-    contract_event::ContractEvent, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
-    randomness::Randomness, transaction::Transaction,
+    block_metadata_ext::BlockMetadataExt, contract_event::ContractEvent, epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures, randomness::Randomness, transaction::Transaction,
 };
 use fail::fail_point;
 use futures::{future::BoxFuture, SinkExt, StreamExt};
@@ -78,6 +79,7 @@ pub struct ExecutionProxy {
     transaction_filter: Arc<TransactionFilter>,
     execution_pipeline: ExecutionPipeline,
     state: RwLock<Option<MutableState>>,
+    localnet_clock: Option<Arc<LocalnetClock>>,
 }
 
 impl ExecutionProxy {
@@ -115,6 +117,31 @@ impl ExecutionProxy {
             transaction_filter: Arc::new(txn_filter),
             execution_pipeline,
             state: RwLock::new(None),
+            localnet_clock: None,
+        }
+    }
+
+    /// Moves the on-chain time by the given clock, only meant for localnets.
+    pub fn with_localnet_clock(mut self, localnet_clock: Arc<LocalnetClock>) -> Self {
+        self.localnet_clock = Some(localnet_clock);
+        self
+    }
+
+    fn new_block_metadata(
+        &self,
+        block: &Block,
+        validators: &[AccountAddress],
+        randomness_enabled: bool,
+        randomness: Option<Randomness>,
+    ) -> BlockMetadataExt {
+        let metadata = if randomness_enabled {
+            block.new_metadata_with_randomness(validators, randomness)
+        } else {
+            block.new_block_metadata(validators).into()
+        };
+        match &self.localnet_clock {
+            Some(localnet_clock) => localnet_clock.adjust(metadata),
+            None => metadata,
         }
     }
 
@@ -131,13 +158,12 @@ impl ExecutionProxy {
 
         let user_txns = executed_block.input_transactions().clone();
         let validator_txns = executed_block.validator_txns().cloned().unwrap_or_default();
-        let metadata = if randomness_enabled {
-            executed_block
-                .block()
-                .new_metadata_with_randomness(validators, executed_block.randomness().cloned())
-        } else {
-            executed_block.block().new_block_metadata(validators).into()
-        };
+        let metadata = self.new_block_metadata(
+            executed_block.block(),
+            validators,
+            randomness_enabled,
+            executed_block.randomness().cloned(),
+        );
 
         let input_txns = Block::combine_to_input_transactions(validator_txns, user_txns, metadata);
 
@@ -190,11 +216,8 @@ impl StateComputer for ExecutionProxy {
         let block_executor_onchain_config = block_executor_onchain_config.clone();
 
         let timestamp = block.timestamp_usecs();
-        let metadata = if is_randomness_enabled {
-            block.new_metadata_with_randomness(&validators, randomness)
-        } else {
-            block.new_block_metadata(&validators).into()
-        };
+        let metadata =
+            self.new_block_metadata(block, &validators, is_randomness_enabled, randomness);
 
         let pipeline_entry_time = Instant::now();
         let fut = self
@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_infallible::{duration_since_epoch, Mutex};
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReader;
+use aptos_types::{block_metadata::BlockMetadata, block_metadata_ext::BlockMetadataExt};
+use std::time::Duration;
+
+/// Moves the on-chain time of a localnet forward, see `enable_localnet_controls` of the admin
+/// service config.
+///
+/// Only the timestamps of the block metadata transactions are moved, the timestamps of the blocks
+/// themselves keep following the clock, which the round deadlines and quorum store rely on.
+/// The offset of a block only depends on the block timestamp, so a block gets the same metadata
+/// every time it is executed, and a NIL block keeps the on-chain time of its parent.
+pub struct LocalnetClock {
+    /// Offsets in microseconds, each applying to the blocks after the given block timestamp
+    offsets: Mutex<Vec<(u64, u64)>>,
+}
+
+impl LocalnetClock {
+    /// Picks up how far the on-chain time is already ahead, e.g. when restarting a localnet.
+    pub fn new(db: &dyn DbReader) -> Self {
+        let offset = Self::committed_offset(db).unwrap_or_else(|err| {
+            // E.g. no block was committed yet
+            debug!("No on-chain time offset found: {}", err);
+            0
+        });
+        Self::with_offset(offset)
+    }
+
+    fn with_offset(offset_usecs: u64) -> Self {
+        Self {
+            offsets: Mutex::new(vec![(0, offset_usecs)]),
+        }
+    }
+
+    fn committed_offset(db: &dyn DbReader) -> Result<u64> {
+        let ledger_info_with_sigs = db.get_latest_ledger_info()?;
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let on_chain_timestamp_usecs = db.get_block_timestamp(ledger_info.version())?;
+        Ok(on_chain_timestamp_usecs.saturating_sub(ledger_info.timestamp_usecs()))
+    }
+
+    /// Moves the on-chain time forward by `duration`, starting with the blocks proposed from now
+    /// on. Returns how far the on-chain time is ahead in total.
+    pub fn advance(&self, duration: Duration) -> Duration {
+        let delta_usecs = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        let now_usecs = duration_since_epoch().as_micros() as u64;
+
+        let mut offsets = self.offsets.lock();
+        let (last_from_usecs, last_offset_usecs) = *offsets.last().expect("Always has an offset");
+        let offset_usecs = last_offset_usecs.saturating_add(delta_usecs);
+        offsets.push((now_usecs.max(last_from_usecs), offset_usecs));
+        Duration::from_micros(offset_usecs)
+    }
+
+    fn offset_usecs(&self, block_timestamp_usecs: u64) -> u64 {
+        self.offsets
+            .lock()
+            .iter()
+            .rev()
+            .find(|(from_usecs, _)| *from_usecs < block_timestamp_usecs)
+            .map_or(0, |(_, offset_usecs)| *offset_usecs)
+    }
+
+    /// Moves the timestamp of the metadata of a block by the offset of the block.
+    pub fn adjust(&self, metadata: BlockMetadataExt) -> BlockMetadataExt {
+        let offset_usecs = self.offset_usecs(metadata.timestamp_usecs());
+        if offset_usecs == 0 {
+            return metadata;
+        }
+
+        match metadata {
+            BlockMetadataExt::V0(metadata) => BlockMetadataExt::V0(BlockMetadata::new(
+                metadata.id(),
+                metadata.epoch(),
+                metadata.round(),
+                metadata.proposer(),
+                metadata.previous_block_votes_bitvec().clone(),
+                metadata.failed_proposer_indices().clone(),
+                metadata.timestamp_usecs().saturating_add(offset_usecs),
+            )),
+            BlockMetadataExt::V1(mut metadata) => {
+                metadata.timestamp_usecs = metadata.timestamp_usecs.saturating_add(offset_usecs);
+                BlockMetadataExt::V1(metadata)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::HashValue;
+    use aptos_types::account_address::AccountAddress;
+
+    fn metadata(timestamp_usecs: u64) -> BlockMetadataExt {
+        BlockMetadata::new(
+            HashValue::zero(),
+            1,
+            1,
+            AccountAddress::ONE,
+            vec![],
+            vec![],
+            timestamp_usecs,
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_advance() {
+        let clock = LocalnetClock::with_offset(5);
+        let before_usecs = duration_since_epoch().as_micros() as u64;
+        assert_eq!(clock.adjust(metadata(10)).timestamp_usecs(), 15);
+
+        assert_eq!(
+            clock.advance(Duration::from_secs(60)),
+            Duration::from_micros(60_000_005)
+        );
+        assert_eq!(
+            clock.advance(Duration::from_secs(60)),
+            Duration::from_micros(120_000_005)
+        );
+        let after_usecs = duration_since_epoch().as_micros() as u64;
+
+        // Blocks proposed before moving the time forward keep their offset
+        assert_eq!(clock.adjust(metadata(10)).timestamp_usecs(), 15);
+        assert_eq!(
+            clock.adjust(metadata(before_usecs)).timestamp_usecs(),
+            before_usecs + 5
+        );
+        assert_eq!(
+            clock.adjust(metadata(after_usecs + 1)).timestamp_usecs(),
+            after_usecs + 120_000_006
+        );
+    }
+}
@@ -8,6 +8,7 @@ use aptos_types::{
 };
 
 pub mod db_tool;
+pub mod localnet_clock;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock_time_service;
 pub mod time_service;
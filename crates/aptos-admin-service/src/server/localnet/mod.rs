@@ -0,0 +1,71 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus::util::localnet_clock::LocalnetClock;
+use aptos_logger::info;
+use aptos_system_utils::utils::reply_with_status;
+use hyper::{Body, Request, Response, StatusCode};
+use std::{collections::HashMap, net::IpAddr, sync::Arc, time::Duration};
+
+/// Whether a request comes from the machine of the node, as only local clients may control a
+/// localnet.
+pub fn is_local_client(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback(),
+        IpAddr::V6(ip) => ip
+            .to_ipv4_mapped()
+            .map_or(ip.is_loopback(), |ip| ip.is_loopback()),
+    }
+}
+
+/// Moves the on-chain time forward by the `seconds` query parameter.
+///
+/// The on-chain time moves forward with the next block, which starts a new epoch if the epoch
+/// interval has passed, as on a real network. The clock of the node itself doesn't change.
+pub async fn handle_advance_time_request(
+    req: Request<Body>,
+    localnet_clock: Arc<LocalnetClock>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let seconds: u64 = match query_pairs.get("seconds").map(|val| val.parse()) {
+        Some(Ok(seconds)) if seconds > 0 => seconds,
+        _ => {
+            return Ok(reply_with_status(
+                StatusCode::BAD_REQUEST,
+                "A positive number of seconds is required.",
+            ))
+        },
+    };
+
+    info!("Moving the on-chain time forward by {seconds}s.");
+    let offset = localnet_clock.advance(Duration::from_secs(seconds));
+    Ok(reply_with_status(
+        StatusCode::OK,
+        format!(
+            "Moved the on-chain time forward by {seconds}s, {}s in total.",
+            offset.as_secs()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_is_local_client() {
+        assert!(is_local_client(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert!(is_local_client(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert!(is_local_client(IpAddr::V6(
+            Ipv4Addr::LOCALHOST.to_ipv6_mapped()
+        )));
+        assert!(!is_local_client(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2))));
+        assert!(!is_local_client(IpAddr::V4(Ipv4Addr::UNSPECIFIED)));
+        assert!(!is_local_client(IpAddr::V6(
+            Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped()
+        )));
+    }
+}
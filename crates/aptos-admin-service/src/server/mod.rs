@@ -4,6 +4,7 @@
 use aptos_config::config::{AuthenticationConfig, NodeConfig};
 use aptos_consensus::{
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
+    util::localnet_clock::LocalnetClock,
 };
 use aptos_infallible::RwLock;
 use aptos_logger::info;
@@ -14,6 +15,7 @@ use aptos_system_utils::{
     profiling::handle_cpu_profiling_request, thread_dump::handle_thread_dump_request,
 };
 use hyper::{
+    server::conn::AddrStream,
     service::{make_service_fn, service_fn},
     Body, Request, Response, Server, StatusCode,
 };
@@ -26,14 +28,17 @@ use std::{
 use tokio::runtime::Runtime;
 
 mod consensus;
+pub mod localnet;
 
 #[derive(Default)]
 pub struct Context {
     authentication_configs: Vec<AuthenticationConfig>,
+    localnet_controls_enabled: bool,
 
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+    localnet_clock: RwLock<Option<Arc<LocalnetClock>>>,
 }
 
 impl Context {
@@ -49,6 +54,10 @@ impl Context {
         *self.consensus_db.write() = Some(consensus_db);
         *self.quorum_store_db.write() = Some(quorum_store_db);
     }
+
+    fn set_localnet_clock(&self, localnet_clock: Arc<LocalnetClock>) {
+        *self.localnet_clock.write() = Some(localnet_clock);
+    }
 }
 
 pub struct AdminService {
@@ -83,6 +92,7 @@ impl AdminService {
             runtime,
             context: Arc::new(Context {
                 authentication_configs: node_config.admin_service.authentication_configs.clone(),
+                localnet_controls_enabled: node_config.admin_service.enable_localnet_controls,
                 ..Default::default()
             }),
         };
@@ -107,14 +117,19 @@ impl AdminService {
             .set_consensus_dbs(consensus_db, quorum_store_db)
     }
 
+    pub fn set_localnet_clock(&self, localnet_clock: Arc<LocalnetClock>) {
+        self.context.set_localnet_clock(localnet_clock)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
-            let make_service = make_service_fn(move |_conn| {
+            let make_service = make_service_fn(move |conn: &AddrStream| {
                 let context = context.clone();
+                let remote_addr = conn.remote_addr();
                 async move {
                     Ok::<_, Infallible>(service_fn(move |req| {
-                        Self::serve_requests(context.clone(), req, enabled)
+                        Self::serve_requests(context.clone(), req, enabled, remote_addr)
                     }))
                 }
             });
@@ -129,6 +144,7 @@ impl AdminService {
         context: Arc<Context>,
         req: Request<Body>,
         enabled: bool,
+        remote_addr: SocketAddr,
     ) -> hyper::Result<Response<Body>> {
         if !enabled {
             return Ok(reply_with_status(
@@ -210,6 +226,27 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::POST, "/localnet/advance_time") => {
+                let localnet_clock = context.localnet_clock.read().clone();
+                if !context.localnet_controls_enabled {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Localnet controls are not enabled.",
+                    ))
+                } else if !localnet::is_local_client(remote_addr.ip()) {
+                    Ok(reply_with_status(
+                        StatusCode::FORBIDDEN,
+                        "Localnet controls are only served to local clients.",
+                    ))
+                } else if let Some(localnet_clock) = localnet_clock {
+                    localnet::handle_advance_time_request(req, localnet_clock).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Consensus is not available.",
+                    ))
+                }
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
@@ -4,13 +4,17 @@
 //! # aptos-ledger
 //!
 //! `aptos-ledger` provides convenience methods to communicate with the Aptos app on ledger
+//!
+//! The Aptos app only holds Ed25519 keys, so all keys and signatures here are Ed25519 ones.
+//! Accounts with secp256k1 keys can't be used with a Ledger.
 
 #![deny(missing_docs)]
 
-use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_crypto::{ed25519::Ed25519Signature, signing_message, CryptoMaterialError};
 pub use aptos_crypto::{ed25519::Ed25519PublicKey, ValidCryptoMaterialStringExt};
 pub use aptos_types::{
-    account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+    account_address::AccountAddress,
+    transaction::{authenticator::AuthenticationKey, RawTransaction, RawTransactionWithData},
 };
 use hex::encode;
 use ledger_apdu::APDUCommand;
@@ -37,6 +41,17 @@ const INS_GET_PUB_KEY: u8 = 0x05; // Get public key instruction code
 const INS_SIGN_TXN: u8 = 0x06; // Sign the transaction
 const APDU_CODE_SUCCESS: u16 = 36864; // Success code for transport.exchange
 
+/// First version of the Aptos app able to sign multi-agent and fee payer transactions
+///
+/// Older versions only parse the signing messages of `RawTransaction`s, not of
+/// `RawTransactionWithData`s.
+/// See the releases of the app: <https://github.com/aptos-labs/ledger-app-aptos/blob/main/CHANGELOG.md>
+pub const MIN_APP_VERSION_TRANSACTION_WITH_DATA: Version = Version::new(0, 7, 0);
+
+/// The maximum number of accounts fetched at once by [`fetch_accounts`], as the Ledger derives
+/// every key one at a time
+pub const MAX_FETCHED_ACCOUNTS: u32 = 100;
+
 const MAX_APDU_LEN: usize = 255;
 const P1_NON_CONFIRM: u8 = 0x00;
 const P1_CONFIRM: u8 = 0x01;
@@ -55,6 +70,20 @@ pub enum AptosLedgerError {
     #[error("Error - {0}")]
     AptosError(AptosLedgerStatusCode),
 
+    /// The Aptos app on Ledger is too old for the request
+    #[error(
+        "The Aptos app on the Ledger is version {current}, but {feature} requires version \
+        {required} or later, please update it with Ledger Live"
+    )]
+    AppVersionTooOld {
+        /// Version of the app on the device
+        current: Version,
+        /// First version of the app supporting the request
+        required: Version,
+        /// What the request needs the newer version for
+        feature: &'static str,
+    },
+
     /// Unexpected error, the `Option<u16>` is the retcode received from ledger transport
     #[error("Unexpected Error: {0} (StatusCode {1:?})")]
     UnexpectedError(String, Option<u16>),
@@ -66,6 +95,12 @@ impl From<LedgerHIDError> for AptosLedgerError {
     }
 }
 
+impl From<CryptoMaterialError> for AptosLedgerError {
+    fn from(e: CryptoMaterialError) -> Self {
+        AptosLedgerError::UnexpectedError(e.to_string(), None)
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 #[repr(u16)]
 /// Status code returned when communicating with ledger
@@ -190,13 +225,37 @@ impl Display for AptosLedgerStatusCode {
 }
 
 /// Aptos version in format major.minor.patch
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
     major: u8,
     minor: u8,
     patch: u8,
 }
 
+impl Version {
+    /// Creates the version major.minor.patch
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// An account derived by the Aptos app on Ledger
+#[derive(Debug)]
+pub struct LedgerAccount {
+    /// BIP44 account index of the account
+    pub index: u32,
+    /// Derivation path of the key of the account
+    pub derivation_path: String,
+    /// Public key of the account
+    pub public_key: Ed25519PublicKey,
+    /// Address of the account, unless its authentication key was rotated
+    pub address: AccountAddress,
+}
+
 impl Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
@@ -249,14 +308,13 @@ pub fn get_app_version() -> Result<Version, AptosLedgerError> {
         Ok(response) => {
             // Received response from Ledger
             if response.retcode() == APDU_CODE_SUCCESS {
-                let major = response.data()[0];
-                let minor = response.data()[1];
-                let patch = response.data()[2];
-                Ok(Version {
-                    major,
-                    minor,
-                    patch,
-                })
+                match response.data() {
+                    [major, minor, patch, ..] => Ok(Version::new(*major, *minor, *patch)),
+                    _ => Err(AptosLedgerError::UnexpectedError(
+                        "Invalid version response".to_string(),
+                        Some(response.retcode()),
+                    )),
+                }
             } else {
                 let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
                 Err(AptosLedgerError::AptosError(error_code))
@@ -266,6 +324,25 @@ pub fn get_app_version() -> Result<Version, AptosLedgerError> {
     }
 }
 
+/// Fails with [`AptosLedgerError::AppVersionTooOld`] if the Aptos app on Ledger is older than
+/// `required`
+///
+/// # Arguments
+///
+/// * `required` - the first version of the app supporting `feature`
+/// * `feature` - what the app is needed for, to explain the error
+pub fn check_app_version(required: Version, feature: &'static str) -> Result<(), AptosLedgerError> {
+    let current = get_app_version()?;
+    if current < required {
+        return Err(AptosLedgerError::AppVersionTooOld {
+            current,
+            required,
+            feature,
+        });
+    }
+    Ok(())
+}
+
 /// Returns the official app name register in Ledger
 pub fn get_app_name() -> Result<String, AptosLedgerError> {
     // Open connection to ledger
@@ -317,51 +394,42 @@ pub fn fetch_batch_accounts(
         ));
     }
 
+    Ok(fetch_accounts(range)?
+        .into_iter()
+        .map(|account| (account.derivation_path, account.address))
+        .collect())
+}
+
+/// Returns the accounts for the account indices in index_range, in order of index
+/// Note: We only allow a range of [`MAX_FETCHED_ACCOUNTS`] as every key is derived one at a time
+///
+/// # Arguments
+///
+/// * `index_range` - start(inclusive) - end(exclusive) BIP44 account indices of the accounts
+pub fn fetch_accounts(index_range: Range<u32>) -> Result<Vec<LedgerAccount>, AptosLedgerError> {
+    if index_range.end.saturating_sub(index_range.start) > MAX_FETCHED_ACCOUNTS {
+        return Err(AptosLedgerError::UnexpectedError(
+            format!(
+                "Unexpected Error: Make sure the range is less than or equal to {}",
+                MAX_FETCHED_ACCOUNTS
+            ),
+            None,
+        ));
+    }
+
     // Open connection to ledger
     let transport = open_ledger_transport()?;
 
-    let mut accounts = BTreeMap::new();
-    for i in range {
-        let path = DERIVATION_PATH.replace("{index}", &i.to_string());
-        let cdata = serialize_bip32(&path);
-
-        match transport.exchange(&APDUCommand {
-            cla: CLA_APTOS,
-            ins: INS_GET_PUB_KEY,
-            p1: P1_NON_CONFIRM,
-            p2: P2_LAST,
-            data: cdata,
-        }) {
-            Ok(response) => {
-                // Got the response from ledger after user has confirmed on the ledger wallet
-                if response.retcode() == APDU_CODE_SUCCESS {
-                    // Extract the Public key from the response data
-                    let mut offset = 0;
-                    let response_buffer = response.data();
-                    let pub_key_len: usize = (response_buffer[offset] - 1).into();
-                    offset += 1;
-
-                    // Skipping weird 0x04 - because of how the Aptos Ledger parse works when return pub key
-                    offset += 1;
-
-                    let pub_key_buffer = response_buffer[offset..offset + pub_key_len].to_vec();
-                    let hex_string = encode(pub_key_buffer);
-                    let public_key = match Ed25519PublicKey::from_encoded_string(&hex_string) {
-                        Ok(pk) => Ok(pk),
-                        Err(err) => Err(AptosLedgerError::UnexpectedError(
-                            err.to_string(),
-                            Some(response.retcode()),
-                        )),
-                    };
-                    let account = account_address_from_public_key(&public_key?);
-                    accounts.insert(path, account);
-                } else {
-                    let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
-                    return Err(AptosLedgerError::AptosError(error_code));
-                }
-            },
-            Err(err) => return Err(AptosLedgerError::from(err)),
-        }
+    let mut accounts = vec![];
+    for index in index_range {
+        let derivation_path = DERIVATION_PATH.replace("{index}", &index.to_string());
+        let public_key = exchange_public_key(&transport, &derivation_path, P1_NON_CONFIRM)?;
+        accounts.push(LedgerAccount {
+            index,
+            address: account_address_from_public_key(&public_key),
+            derivation_path,
+            public_key,
+        });
     }
 
     Ok(accounts)
@@ -376,20 +444,29 @@ pub fn get_public_key(path: &str, display: bool) -> Result<Ed25519PublicKey, Apt
     // Open connection to ledger
     let transport = open_ledger_transport()?;
 
-    // Serialize the derivation path
-    let cdata = serialize_bip32(path);
-
     // APDU command's instruction parameter 1 or p1
     let p1: u8 = match display {
         true => P1_CONFIRM,
         false => P1_NON_CONFIRM,
     };
 
+    exchange_public_key(&transport, path, p1)
+}
+
+/// Asks the Ledger for the public key at the derivation path
+fn exchange_public_key(
+    transport: &TransportNativeHID,
+    path: &str,
+    p1: u8,
+) -> Result<Ed25519PublicKey, AptosLedgerError> {
+    // Serialize the derivation path
+    let cdata = serialize_bip32(path);
+
     match transport.exchange(&APDUCommand {
         cla: CLA_APTOS,
         ins: INS_GET_PUB_KEY,
         p1,
-        p2: 0,
+        p2: P2_LAST,
         data: cdata,
     }) {
         Ok(response) => {
@@ -408,7 +485,10 @@ pub fn get_public_key(path: &str, display: bool) -> Result<Ed25519PublicKey, Apt
                 let hex_string = encode(pub_key_buffer);
                 match Ed25519PublicKey::from_encoded_string(&hex_string) {
                     Ok(pk) => Ok(pk),
-                    Err(err) => Err(AptosLedgerError::UnexpectedError(err.to_string(), None)),
+                    Err(err) => Err(AptosLedgerError::UnexpectedError(
+                        err.to_string(),
+                        Some(response.retcode()),
+                    )),
                 }
             } else {
                 let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
@@ -419,6 +499,39 @@ pub fn get_public_key(path: &str, display: bool) -> Result<Ed25519PublicKey, Apt
     }
 }
 
+/// Returns the signature of a transaction without any other signer than the sender
+///
+/// # Arguments
+///
+/// * `path` - derivation path of the ledger account
+/// * `raw_txn` - the transaction to sign
+pub fn sign_transaction(
+    path: &str,
+    raw_txn: &RawTransaction,
+) -> Result<Ed25519Signature, AptosLedgerError> {
+    sign_message(path, &signing_message(raw_txn)?)
+}
+
+/// Returns the signature of a multi-agent or fee payer transaction, for any of its signers
+///
+/// This requires version [`MIN_APP_VERSION_TRANSACTION_WITH_DATA`] of the Aptos app, older
+/// versions fail to parse such transactions.
+///
+/// # Arguments
+///
+/// * `path` - derivation path of the ledger account
+/// * `txn` - the transaction to sign, along with its other signers
+pub fn sign_transaction_with_data(
+    path: &str,
+    txn: &RawTransactionWithData,
+) -> Result<Ed25519Signature, AptosLedgerError> {
+    check_app_version(
+        MIN_APP_VERSION_TRANSACTION_WITH_DATA,
+        "signing multi-agent and fee payer transactions",
+    )?;
+    sign_message(path, &signing_message(txn)?)
+}
+
 /// Returns the signed signature of the raw transaction user provided
 ///
 /// # Arguments
@@ -441,8 +554,13 @@ pub fn sign_message(path: &str, raw_message: &[u8]) -> Result<Ed25519Signature,
         data: derivation_path_bytes,
     });
 
-    if let Err(err) = sign_start {
-        return Err(AptosLedgerError::UnexpectedError(err.to_string(), None));
+    match sign_start {
+        Ok(response) if response.retcode() != APDU_CODE_SUCCESS => {
+            let error_code = AptosLedgerStatusCode::map_status_code(response.retcode());
+            return Err(AptosLedgerError::AptosError(error_code));
+        },
+        Ok(_) => {},
+        Err(err) => return Err(AptosLedgerError::UnexpectedError(err.to_string(), None)),
     }
 
     let chunks = raw_message.chunks(MAX_APDU_LEN);
@@ -174,6 +174,24 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects every key whose bucket is unused and full again, i.e. behaves the same
+    /// as a new bucket. Returns the number of keys removed.
+    ///
+    /// Useful when the keys aren't known in advance, e.g. for client IP addresses.
+    pub fn garbage_collect_full_buckets(&self) -> usize {
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        buckets.retain(|_, bucket| {
+            if Arc::strong_count(bucket) > 1 {
+                return true;
+            }
+            let mut bucket = bucket.lock();
+            bucket.refill();
+            bucket.tokens < bucket.size
+        });
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -549,4 +567,24 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+
+        // A full bucket, an emptied bucket, and a full bucket still in use
+        {
+            let _full = rate_limiter.bucket("full");
+            let empty = rate_limiter.bucket("empty");
+            empty.lock().acquire_all_tokens(2).unwrap();
+        }
+        let _in_use = rate_limiter.bucket("in use");
+        assert_num_keys(&rate_limiter, 3);
+
+        // Only the unused full bucket can be dropped
+        assert_eq!(1, rate_limiter.garbage_collect_full_buckets());
+        assert_num_keys(&rate_limiter, 2);
+        assert!(rate_limiter.buckets.read().contains_key("empty"));
+        assert!(rate_limiter.buckets.read().contains_key("in use"));
+    }
 }
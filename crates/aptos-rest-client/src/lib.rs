@@ -13,6 +13,9 @@ pub use response::Response;
 pub mod client_builder;
 pub mod state;
 pub mod types;
+pub mod verifier;
+pub mod verifying_client;
+pub use verifying_client::VerifyingClient;
 
 pub use crate::client_builder::{AptosBaseUrl, ClientBuilder};
 use crate::{
@@ -26,9 +29,13 @@ pub use aptos_api_types::{
 use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION, BCS_VIEW_FUNCTION, JSON},
-    AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId,
-    TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
-    VersionedEvent, ViewFunction, ViewRequest,
+    AccountBalance, AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse,
+    MoveModuleId, SignedTransactionWithStateOverrides, SimulateBundleResult,
+    SimulateBundleResultBcs, StateOverrideBcs, StateValueWithProof, TransactionData,
+    TransactionOnChainData, TransactionWithProofBcs, TransactionsBatchSubmissionResult,
+    UserTransaction, VersionedEvent, ViewFunction, ViewFunctionBatchResult,
+    ViewFunctionBatchResultBcs, ViewFunctionWithStateOverrides, ViewRequest,
+    ViewRequestWithStateOverrides,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::{debug, info, sample, sample::SampleRate};
@@ -36,6 +43,7 @@ use aptos_types::{
     account_address::AccountAddress,
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
     contract_event::EventWithVersion,
+    state_proof::StateProof,
     state_store::state_key::StateKey,
     transaction::SignedTransaction,
     CoinType,
@@ -252,6 +260,58 @@ impl Client {
         })
     }
 
+    /// Retrieves the balance of a coin type, or of a fungible asset given its metadata address
+    pub async fn get_account_balance_by_asset(
+        &self,
+        address: AccountAddress,
+        asset_type: &str,
+        version: Option<u64>,
+    ) -> AptosResult<Response<AccountBalance>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/balance/{}",
+            address.to_hex_literal(),
+            asset_type
+        ))?;
+        let mut request = self.inner.get(url);
+        if let Some(version) = version {
+            request = request.query(&[("ledger_version", version)])
+        }
+
+        let response = request.send().await?;
+        self.json(response).await
+    }
+
+    /// Retrieves the changes of a balance, from the latest to the earliest
+    pub async fn get_account_balance_history(
+        &self,
+        address: AccountAddress,
+        asset_type: &str,
+        start_version: Option<u64>,
+        end_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<AccountBalance>>> {
+        let url = self.build_path(&format!(
+            "accounts/{}/balance/{}/history",
+            address.to_hex_literal(),
+            asset_type
+        ))?;
+        let mut request = self.inner.get(url);
+        if let Some(start_version) = start_version {
+            request = request.query(&[("start_version", start_version)])
+        }
+
+        if let Some(end_version) = end_version {
+            request = request.query(&[("end_version", end_version)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+        self.json(response).await
+    }
+
     pub async fn get_index(&self) -> AptosResult<Response<IndexResponse>> {
         self.get(self.build_path("")?).await
     }
@@ -262,6 +322,17 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Retrieves the latest signed ledger info, along with the epoch change proof from the epoch
+    /// of `known_version` to the latest epoch.
+    pub async fn get_state_proof_bcs(
+        &self,
+        known_version: u64,
+    ) -> AptosResult<Response<StateProof>> {
+        let url = self.build_path(&format!("state_proof?known_version={}", known_version))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     // TODO: Remove this, just use `get_index`: https://github.com/aptos-labs/aptos-core/issues/5597.
     pub async fn get_ledger_information(&self) -> AptosResult<Response<State>> {
         let response = self.get_index_bcs().await?.map(|r| State {
@@ -351,6 +422,101 @@ impl Client {
         self.json(response).await
     }
 
+    /// Executes a view function on top of the given state overrides
+    pub async fn view_with_state_overrides(
+        &self,
+        request: &ViewRequestWithStateOverrides,
+        version: Option<u64>,
+    ) -> AptosResult<Response<Vec<serde_json::Value>>> {
+        let request = serde_json::to_string(request)?;
+        let mut url = self.build_path("view/with_state_overrides")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, JSON)
+            .body(request)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
+    pub async fn view_with_state_overrides_bcs<T: DeserializeOwned>(
+        &self,
+        request: &ViewFunctionWithStateOverrides,
+        version: Option<u64>,
+    ) -> AptosResult<Response<T>> {
+        let txn_payload = bcs::to_bytes(request)?;
+        let mut url = self.build_path("view/with_state_overrides")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
+            .header(ACCEPT, BCS)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
+    }
+
+    /// Executes all the view functions at the same ledger version, returning the
+    /// result or error of each of them in order.
+    pub async fn view_batch(
+        &self,
+        requests: &[ViewRequest],
+        version: Option<u64>,
+    ) -> AptosResult<Response<Vec<ViewFunctionBatchResult>>> {
+        let requests = serde_json::to_string(requests)?;
+        let mut url = self.build_path("view/batch")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, JSON)
+            .body(requests)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
+    pub async fn view_batch_bcs(
+        &self,
+        requests: &[ViewFunction],
+        version: Option<u64>,
+    ) -> AptosResult<Response<Vec<ViewFunctionBatchResultBcs>>> {
+        let txn_payload = bcs::to_bytes(requests)?;
+        let mut url = self.build_path("view/batch")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_VIEW_FUNCTION)
+            .header(ACCEPT, BCS)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
+    }
+
     pub async fn simulate(
         &self,
         txn: &SignedTransaction,
@@ -369,6 +535,53 @@ impl Client {
         self.json(response).await
     }
 
+    /// Simulates a transaction on top of the given state overrides
+    pub async fn simulate_with_state_overrides(
+        &self,
+        txn: &SignedTransaction,
+        state_overrides: Vec<StateOverrideBcs>,
+    ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        let txn_payload = bcs::to_bytes(&SignedTransactionWithStateOverrides {
+            transaction: txn.clone(),
+            state_overrides,
+        })?;
+        let url = self.build_path("transactions/simulate/with_state_overrides")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
+    pub async fn simulate_with_state_overrides_bcs(
+        &self,
+        txn: &SignedTransaction,
+        state_overrides: Vec<StateOverrideBcs>,
+    ) -> AptosResult<Response<TransactionOnChainData>> {
+        let txn_payload = bcs::to_bytes(&SignedTransactionWithStateOverrides {
+            transaction: txn.clone(),
+            state_overrides,
+        })?;
+        let url = self.build_path("transactions/simulate/with_state_overrides")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+            .header(ACCEPT, BCS)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
+    }
+
     pub async fn simulate_with_gas_estimation(
         &self,
         txn: &SignedTransaction,
@@ -393,6 +606,26 @@ impl Client {
         self.json(response).await
     }
 
+    /// Simulates the transaction with the gas profiler, the breakdown of the gas used is in
+    /// the `gas_profile` of the transaction.
+    pub async fn simulate_with_gas_profile(
+        &self,
+        txn: &SignedTransaction,
+    ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        let txn_payload = bcs::to_bytes(txn)?;
+        let url = self.build_path("transactions/simulate?profile_gas=true")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn simulate_bcs(
         &self,
         txn: &SignedTransaction,
@@ -438,6 +671,45 @@ impl Client {
         Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
     }
 
+    /// Simulates the transactions in order, each one on top of the changes of the previous ones
+    pub async fn simulate_bundle(
+        &self,
+        txns: &[SignedTransaction],
+    ) -> AptosResult<Response<SimulateBundleResult>> {
+        let txn_payload = bcs::to_bytes(txns)?;
+        let url = self.build_path("transactions/simulate_bundle")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
+    pub async fn simulate_bundle_bcs(
+        &self,
+        txns: &[SignedTransaction],
+    ) -> AptosResult<Response<SimulateBundleResultBcs>> {
+        let txn_payload = bcs::to_bytes(txns)?;
+        let url = self.build_path("transactions/simulate_bundle")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+            .header(ACCEPT, BCS)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
+    }
+
     pub async fn submit(
         &self,
         txn: &SignedTransaction,
@@ -925,6 +1197,19 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Retrieves a committed transaction along with a proof of it.
+    pub async fn get_transaction_by_version_with_proof(
+        &self,
+        version: u64,
+    ) -> AptosResult<Response<TransactionWithProofBcs>> {
+        let url = self.build_path(&format!(
+            "transactions/by_version/{}?with_proof=true",
+            version
+        ))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         version: u64,
@@ -1120,6 +1405,30 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    /// Retrieves the state value holding a resource, along with a proof of it.
+    ///
+    /// The proof should be checked with [`verifier::verify_state_value_with_proof`] before
+    /// trusting the value.
+    pub async fn get_account_resource_with_proof(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let mut url = self.build_path(&format!(
+            "accounts/{}/resource/{}?with_proof=true",
+            address.to_hex(),
+            resource_type
+        ))?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account_resource_at_version(
         &self,
         address: AccountAddress,
@@ -1379,6 +1688,33 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Retrieves a table item along with a proof of it.
+    ///
+    /// The proof should be checked with [`verifier::verify_state_value_with_proof`] before
+    /// trusting the value.
+    pub async fn get_table_item_with_proof<K: Serialize>(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let mut url = self.build_path(&format!("tables/{}/item?with_proof=true", table_handle))?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+        let data = json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": json!(key),
+        });
+
+        let response = self.post_bcs(url, data).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_raw_table_item(
         &self,
         table_handle: AccountAddress,
@@ -1574,7 +1910,7 @@ impl Client {
                     | RestError::Json(_)
                     | RestError::Timeout(_)
                     | RestError::Unknown(_) => true,
-                    RestError::UrlParse(_) => false,
+                    RestError::UrlParse(_) | RestError::InvalidProof(_) => false,
                 },
             };
 
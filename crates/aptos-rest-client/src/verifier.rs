@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_api_types::StateValueWithProof;
+use aptos_types::{epoch_change::Verifier, trusted_state::TrustedState, waypoint::Waypoint};
+
+/// Verifies a [`StateValueWithProof`] returned by a full node against a trusted state.
+///
+/// The ledger info the proof is anchored to must be verifiable by the trusted state: either
+/// it's signed by the validator set of the trusted epoch, or it's the ledger info committed
+/// to by the trusted waypoint. Light clients starting from a waypoint need to ratchet their
+/// trusted state into the current epoch first.
+pub fn verify_state_value_with_proof(
+    proof: &StateValueWithProof,
+    trusted_state: &TrustedState,
+) -> Result<()> {
+    let ledger_info_with_signatures = &proof.ledger_info_with_signatures;
+    match trusted_state {
+        TrustedState::EpochWaypoint(waypoint) => {
+            waypoint.verify(ledger_info_with_signatures.ledger_info())?
+        },
+        TrustedState::EpochState { epoch_state, .. } => {
+            epoch_state.verify(ledger_info_with_signatures)?
+        },
+    }
+    proof.verify(ledger_info_with_signatures.ledger_info())
+}
+
+/// Verifies a [`StateValueWithProof`] against a trusted epoch waypoint.
+pub fn verify_state_value_with_proof_by_waypoint(
+    proof: &StateValueWithProof,
+    waypoint: Waypoint,
+) -> Result<()> {
+    verify_state_value_with_proof(proof, &TrustedState::from_epoch_waypoint(waypoint))
+}
@@ -0,0 +1,387 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::RestError, AptosResult, Client};
+use anyhow::{anyhow, Context};
+use aptos_api_types::StateValueWithProof;
+use aptos_infallible::RwLock;
+use aptos_types::{
+    account_address::AccountAddress,
+    epoch_change::{EpochChangeProof, Verifier},
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::TransactionWithProof,
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use move_core_types::language_storage::StructTag;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr, sync::Arc};
+
+/// A client that verifies every response of a possibly untrusted full node.
+///
+/// It keeps a [`TrustedState`], starting from a trusted waypoint, and ratchets it forward with
+/// the epoch change proofs served by the full node. Every read is served along with a proof,
+/// which is checked against the trusted state before the value is returned.
+///
+/// If a storage path is given, the trusted state is persisted there every time it changes, so
+/// the next session doesn't need to start from the waypoint again.
+#[derive(Clone, Debug)]
+pub struct VerifyingClient {
+    inner: Client,
+    trusted_state: Arc<RwLock<TrustedState>>,
+    storage_path: Option<PathBuf>,
+}
+
+impl VerifyingClient {
+    pub fn new(inner: Client, trusted_state: TrustedState) -> Self {
+        Self {
+            inner,
+            trusted_state: Arc::new(RwLock::new(trusted_state)),
+            storage_path: None,
+        }
+    }
+
+    pub fn from_waypoint(inner: Client, waypoint: Waypoint) -> Self {
+        Self::new(inner, TrustedState::from_epoch_waypoint(waypoint))
+    }
+
+    /// Creates a client persisting its trusted state at `storage_path`.
+    ///
+    /// The trusted state is loaded from `storage_path` if it exists, otherwise the client
+    /// starts from `waypoint`.
+    pub fn with_storage(
+        inner: Client,
+        storage_path: impl Into<PathBuf>,
+        waypoint: Waypoint,
+    ) -> AptosResult<Self> {
+        let storage_path = storage_path.into();
+        let trusted_state = if storage_path.exists() {
+            let bytes = fs::read(&storage_path).with_context(|| {
+                format!(
+                    "Failed to read trusted state from {}",
+                    storage_path.display()
+                )
+            })?;
+            bcs::from_bytes(&bytes)?
+        } else {
+            TrustedState::from_epoch_waypoint(waypoint)
+        };
+
+        let client = Self {
+            inner,
+            trusted_state: Arc::new(RwLock::new(trusted_state.clone())),
+            storage_path: Some(storage_path),
+        };
+        client.persist(&trusted_state)?;
+        Ok(client)
+    }
+
+    /// The underlying client, which doesn't verify anything
+    pub fn inner(&self) -> &Client {
+        &self.inner
+    }
+
+    pub fn trusted_state(&self) -> TrustedState {
+        self.trusted_state.read().clone()
+    }
+
+    /// Ratchets the trusted state to the latest ledger info of the full node.
+    pub async fn sync(&self) -> AptosResult<TrustedState> {
+        loop {
+            let trusted_state = self.trusted_state();
+            let state_proof = self
+                .inner
+                .get_state_proof_bcs(trusted_state.version())
+                .await?
+                .into_inner();
+            let change = trusted_state
+                .verify_and_ratchet(&state_proof)
+                .map_err(RestError::InvalidProof)?;
+            if let Some(new_state) = change.new_state() {
+                self.update(new_state)?;
+            }
+
+            // The epoch change proof is capped, so there may be more epochs to catch up with
+            if !state_proof.epoch_changes().more {
+                return Ok(self.trusted_state());
+            }
+        }
+    }
+
+    /// Retrieves a resource, verified against the trusted state.
+    ///
+    /// Resources in a resource group are proven as part of the whole group, so the group must be
+    /// given for them: a proof is only accepted for the exact state key the resource is stored
+    /// under. Returns `None` if the resource provably doesn't exist.
+    pub async fn get_account_resource_bcs<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        resource_group: Option<&str>,
+    ) -> AptosResult<Option<T>> {
+        let tag = StructTag::from_str(resource_type)?;
+        let group_tag = resource_group.map(StructTag::from_str).transpose()?;
+        let proof = self
+            .inner
+            .get_account_resource_with_proof(address, resource_type, None)
+            .await?
+            .into_inner();
+        let bytes = resource_bytes(
+            address,
+            &tag,
+            group_tag.as_ref(),
+            &proof.state_key,
+            proof.bytes(),
+        )?;
+        self.verify_state_value(&proof).await?;
+        Ok(bytes.map(|bytes| bcs::from_bytes(&bytes)).transpose()?)
+    }
+
+    /// Retrieves a table item, verified against the trusted state.
+    ///
+    /// The key must serialize to the same BCS bytes as its Move representation. Returns `None`
+    /// if the item provably doesn't exist.
+    pub async fn get_table_item_bcs<K: Serialize, T: DeserializeOwned>(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+    ) -> AptosResult<Option<T>> {
+        let expected_state_key =
+            StateKey::table_item(&TableHandle(table_handle), &bcs::to_bytes(&key)?);
+        let proof = self
+            .inner
+            .get_table_item_with_proof(table_handle, key_type, value_type, key, None)
+            .await?
+            .into_inner();
+        if proof.state_key != expected_state_key {
+            return Err(unexpected_state_key(&proof.state_key));
+        }
+        self.verify_state_value(&proof).await?;
+        Ok(proof.bytes().map(bcs::from_bytes).transpose()?)
+    }
+
+    /// Retrieves a committed transaction and its events, verified against the trusted state.
+    pub async fn get_transaction_by_version_bcs(
+        &self,
+        version: u64,
+    ) -> AptosResult<TransactionWithProof> {
+        let response = self
+            .inner
+            .get_transaction_by_version_with_proof(version)
+            .await?
+            .into_inner();
+        if response.transaction_with_proof.version != version {
+            return Err(RestError::InvalidProof(anyhow!(
+                "Transaction version {} doesn't match the requested version {}",
+                response.transaction_with_proof.version,
+                version
+            )));
+        }
+        let ledger_info_with_signatures = &response.ledger_info_with_signatures;
+        self.verify_ledger_info(ledger_info_with_signatures).await?;
+        response
+            .verify(ledger_info_with_signatures.ledger_info())
+            .map_err(RestError::InvalidProof)?;
+        Ok(response.transaction_with_proof)
+    }
+
+    async fn verify_state_value(&self, proof: &StateValueWithProof) -> AptosResult<()> {
+        let ledger_info_with_signatures = &proof.ledger_info_with_signatures;
+        self.verify_ledger_info(ledger_info_with_signatures).await?;
+        proof
+            .verify(ledger_info_with_signatures.ledger_info())
+            .map_err(RestError::InvalidProof)
+    }
+
+    /// Verifies the ledger info against the trusted state, syncing to its epoch if needed, and
+    /// ratchets the trusted state to it if it's newer.
+    async fn verify_ledger_info(
+        &self,
+        ledger_info_with_signatures: &LedgerInfoWithSignatures,
+    ) -> AptosResult<()> {
+        let ledger_info = ledger_info_with_signatures.ledger_info();
+        let mut trusted_state = self.trusted_state();
+        if trusted_state.epoch_change_verification_required(ledger_info.epoch()) {
+            trusted_state = self.sync().await?;
+        }
+        trusted_state
+            .verify(ledger_info_with_signatures)
+            .map_err(RestError::InvalidProof)?;
+
+        if ledger_info.version() > trusted_state.version() {
+            // An epoch ending ledger info moves us into the next epoch by itself
+            let epoch_change_proof = if ledger_info.ends_epoch() {
+                EpochChangeProof::new(vec![ledger_info_with_signatures.clone()], false)
+            } else {
+                EpochChangeProof::new(vec![], false)
+            };
+            let new_state = trusted_state
+                .verify_and_ratchet_inner(ledger_info_with_signatures, &epoch_change_proof)
+                .map_err(RestError::InvalidProof)?
+                .new_state();
+            if let Some(new_state) = new_state {
+                self.update(new_state)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the trusted state, unless a newer one was stored concurrently.
+    fn update(&self, new_state: TrustedState) -> AptosResult<()> {
+        let mut trusted_state = self.trusted_state.write();
+        if new_state.version() <= trusted_state.version() {
+            return Ok(());
+        }
+        self.persist(&new_state)?;
+        *trusted_state = new_state;
+        Ok(())
+    }
+
+    fn persist(&self, trusted_state: &TrustedState) -> AptosResult<()> {
+        if let Some(storage_path) = &self.storage_path {
+            // Write to a temporary file first, so a crash can't leave a corrupted state behind
+            let temp_path = storage_path.with_extension("tmp");
+            fs::write(&temp_path, bcs::to_bytes(trusted_state)?)
+                .and_then(|_| fs::rename(&temp_path, storage_path))
+                .with_context(|| {
+                    format!(
+                        "Failed to persist trusted state to {}",
+                        storage_path.display()
+                    )
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts a resource from the state value proven under `state_key`, which must be the state key
+/// the resource is stored under: its resource group if it's in one, the resource itself otherwise.
+fn resource_bytes(
+    address: AccountAddress,
+    tag: &StructTag,
+    resource_group: Option<&StructTag>,
+    state_key: &StateKey,
+    bytes: Option<&[u8]>,
+) -> AptosResult<Option<Vec<u8>>> {
+    let expected_state_key = match resource_group {
+        Some(group_tag) => StateKey::resource_group(&address, group_tag),
+        None => StateKey::resource(&address, tag)?,
+    };
+    if state_key != &expected_state_key {
+        return Err(unexpected_state_key(state_key));
+    }
+
+    match (resource_group, bytes) {
+        (Some(_), Some(bytes)) => {
+            let group: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(bytes)?;
+            Ok(group.get(tag).cloned())
+        },
+        (_, bytes) => Ok(bytes.map(<[u8]>::to_vec)),
+    }
+}
+
+fn unexpected_state_key(state_key: &StateKey) -> RestError {
+    RestError::InvalidProof(anyhow!(
+        "Proof is for an unexpected state key {:?}",
+        state_key
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(s: &str) -> StructTag {
+        StructTag::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_resource_bytes() {
+        let address = AccountAddress::ONE;
+        let resource = tag("0x1::account::Account");
+        let state_key = StateKey::resource(&address, &resource).unwrap();
+
+        assert_eq!(
+            resource_bytes(address, &resource, None, &state_key, Some(&[1, 2])).unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            resource_bytes(address, &resource, None, &state_key, None).unwrap(),
+            None
+        );
+        // The proof must be for the requested account
+        let other_state_key = StateKey::resource(&AccountAddress::TWO, &resource).unwrap();
+        assert!(resource_bytes(address, &resource, None, &other_state_key, None).is_err());
+    }
+
+    #[test]
+    fn test_resource_bytes_in_group() {
+        let address = AccountAddress::ONE;
+        let group = tag("0x1::object::ObjectGroup");
+        let member = tag("0x1::object::ObjectCore");
+        let state_key = StateKey::resource_group(&address, &group);
+        let bytes = bcs::to_bytes(&BTreeMap::from([(member.clone(), vec![1, 2])])).unwrap();
+
+        assert_eq!(
+            resource_bytes(address, &member, Some(&group), &state_key, Some(&bytes)).unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            resource_bytes(address, &member, Some(&group), &state_key, None).unwrap(),
+            None
+        );
+        let other_member = tag("0x1::object::Untransferable");
+        assert_eq!(
+            resource_bytes(
+                address,
+                &other_member,
+                Some(&group),
+                &state_key,
+                Some(&bytes)
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resource_bytes_rejects_forged_absence() {
+        let address = AccountAddress::ONE;
+        let group = tag("0x1::object::ObjectGroup");
+        let member = tag("0x1::object::ObjectCore");
+
+        // A resource in a group is stored under the group, so a proof of absence of the
+        // resource's own state key proves nothing
+        let resource_state_key = StateKey::resource(&address, &member).unwrap();
+        assert!(resource_bytes(address, &member, Some(&group), &resource_state_key, None).is_err());
+
+        // Nor does a proof of absence of the group for a resource outside of it
+        let group_state_key = StateKey::resource_group(&address, &group);
+        assert!(resource_bytes(address, &member, None, &group_state_key, None).is_err());
+    }
+
+    #[test]
+    fn test_resource_bytes_rejects_mismatched_group() {
+        let address = AccountAddress::ONE;
+        let group = tag("0x1::object::ObjectGroup");
+        let other_group = tag("0x1::fungible_asset::FungibleAssetGroup");
+        let member = tag("0x1::object::ObjectCore");
+        let other_group_state_key = StateKey::resource_group(&address, &other_group);
+        let bytes = bcs::to_bytes(&BTreeMap::from([(member.clone(), vec![1, 2])])).unwrap();
+
+        assert!(resource_bytes(
+            address,
+            &member,
+            Some(&group),
+            &other_group_state_key,
+            Some(&bytes)
+        )
+        .is_err());
+        assert!(
+            resource_bytes(address, &member, Some(&group), &other_group_state_key, None).is_err()
+        );
+    }
+}
@@ -304,6 +304,12 @@ impl From<RestError> for ApiError {
                     ApiError::InternalError(Some(err.error.message))
                 },
                 AptosErrorCode::MempoolIsFull => ApiError::MempoolIsFull(Some(err.error.message)),
+                AptosErrorCode::RateLimitExceeded => {
+                    ApiError::InternalError(Some(err.error.message))
+                },
+                AptosErrorCode::ServiceUnavailable => {
+                    ApiError::InternalError(Some(err.error.message))
+                },
                 AptosErrorCode::WebFrameworkError => {
                     ApiError::InternalError(Some(err.error.message))
                 },
@@ -320,6 +326,7 @@ impl From<RestError> for ApiError {
             RestError::UrlParse(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Timeout(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Unknown(err) => ApiError::InternalError(Some(err.to_string())),
+            RestError::InvalidProof(err) => ApiError::InternalError(Some(err.to_string())),
         }
     }
 }
@@ -5,15 +5,17 @@ use crate::common::types::{
     CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode, ProfileOptions, RestOptions,
 };
 use aptos_api_types::ViewFunction;
+use aptos_rest_client::Client;
 use aptos_types::{account_address::AccountAddress, AptosCoinType, CoinType};
 use async_trait::async_trait;
 use clap::Parser;
 use move_core_types::{ident_str, language_storage::ModuleId, parser::parse_type_tag};
 use serde::Serialize;
 
-/// Show the account's balance of different coins
+/// Show the account's balance of different coins or fungible assets
 ///
-/// TODO: Fungible assets
+/// With `--at-version` or `--history`, past balances are reconstructed from the node's versioned
+/// state, without requiring an indexer.
 #[derive(Debug, Parser)]
 pub struct Balance {
     /// Address of the account you want to list resources/modules/balance for
@@ -21,9 +23,28 @@ pub struct Balance {
     pub(crate) account: Option<AccountAddress>,
 
     /// Coin type to lookup.  Defaults to 0x1::aptos_coin::AptosCoin
-    #[clap(long)]
+    #[clap(long, group = "asset")]
     pub(crate) coin_type: Option<String>,
 
+    /// Metadata address of the fungible asset to lookup, instead of a coin type
+    #[clap(long, group = "asset")]
+    pub(crate) fungible_asset: Option<AccountAddress>,
+
+    /// Ledger version to lookup the balance at
+    ///
+    /// Defaults to the latest version.  The balance is reconstructed from the node's state, so
+    /// the version must not have been pruned.
+    #[clap(long)]
+    pub(crate) at_version: Option<u64>,
+
+    /// Show every change of the balance, from `--at-version` back to the oldest unpruned version
+    #[clap(long)]
+    pub(crate) history: bool,
+
+    /// Max number of balance changes to show with `--history`
+    #[clap(long, requires = "history")]
+    pub(crate) limit: Option<u16>,
+
     #[clap(flatten)]
     pub(crate) rest_options: RestOptions,
     #[clap(flatten)]
@@ -35,7 +56,55 @@ pub struct AccountBalance {
     asset_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     coin_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<AccountAddress>,
     balance: u64,
+    /// Version at which the balance was last changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<u64>,
+}
+
+impl Balance {
+    /// Retrieves the balance history page by page, following the cursor returned by the node
+    async fn balance_history(
+        &self,
+        client: &Client,
+        account: AccountAddress,
+        asset_type: &str,
+    ) -> CliTypedResult<Vec<aptos_api_types::AccountBalance>> {
+        let mut balances: Vec<aptos_api_types::AccountBalance> = vec![];
+        let mut end_version = self.at_version;
+        loop {
+            let limit = self.limit.map(|limit| limit - balances.len() as u16);
+            let response = client
+                .get_account_balance_history(account, asset_type, None, end_version, limit)
+                .await?;
+            let cursor = response.state().cursor.clone();
+            for balance in response.into_inner() {
+                // A page can end while the balance doesn't change, the next page then starts
+                // with the same balance
+                match balances.last_mut() {
+                    Some(last) if last.balance == balance.balance => last.version = balance.version,
+                    _ => balances.push(balance),
+                }
+            }
+
+            let done = self
+                .limit
+                .map_or(false, |limit| balances.len() >= limit as usize);
+            match cursor {
+                Some(cursor) if !done => {
+                    end_version = Some(cursor.parse().map_err(|err| {
+                        CliError::UnexpectedError(format!(
+                            "Invalid balance history cursor '{}': {}",
+                            cursor, err
+                        ))
+                    })?)
+                },
+                _ => return Ok(balances),
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -70,6 +139,38 @@ impl CliCommand<Vec<AccountBalance>> for Balance {
         };
 
         let client = self.rest_options.client(&self.profile_options)?;
+
+        // Historical balances, and fungible assets, are reconstructed from the state by the node
+        if self.history || self.at_version.is_some() || self.fungible_asset.is_some() {
+            let (asset_type, coin_type, metadata) = match self.fungible_asset {
+                Some(metadata) => (metadata.to_hex_literal(), None, Some(metadata)),
+                None => (coin_type.to_string(), Some(coin_type.to_string()), None),
+            };
+            let balances = if self.history {
+                self.balance_history(&client, account, &asset_type).await?
+            } else {
+                vec![client
+                    .get_account_balance_by_asset(account, &asset_type, self.at_version)
+                    .await?
+                    .into_inner()]
+            };
+            let asset_kind = if metadata.is_some() {
+                "fungible_asset"
+            } else {
+                "coin"
+            };
+            return Ok(balances
+                .into_iter()
+                .map(|balance| AccountBalance {
+                    asset_type: asset_kind.to_string(),
+                    coin_type: coin_type.clone(),
+                    metadata,
+                    balance: balance.balance.0,
+                    version: Some(balance.version.0),
+                })
+                .collect());
+        }
+
         let response = client
             .view_bcs_with_json_response(
                 &ViewFunction {
@@ -91,7 +192,9 @@ impl CliCommand<Vec<AccountBalance>> for Balance {
         return Ok(vec![AccountBalance {
             asset_type: "coin".to_string(),
             coin_type: Some(coin_type.to_string()),
+            metadata: None,
             balance,
+            version: None,
         }]);
     }
 }
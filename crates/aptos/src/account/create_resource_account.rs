@@ -80,11 +80,20 @@ impl CliCommand<CreateResourceAccountSummary> for CreateResourceAccount {
         } else {
             vec![]
         };
+        let payload =
+            resource_account_create_resource_account(self.seed_args.seed()?, authentication_key);
+        if self.txn_options.output_unsigned.is_some() {
+            return self
+                .txn_options
+                .submit_transaction(payload)
+                .await
+                .map(|transaction_summary| CreateResourceAccountSummary {
+                    resource_account: None,
+                    transaction_summary,
+                });
+        }
         self.txn_options
-            .submit_transaction(resource_account_create_resource_account(
-                self.seed_args.seed()?,
-                authentication_key,
-            ))
+            .submit_transaction_for_output(payload)
             .await
             .map(CreateResourceAccountSummary::from)
     }
@@ -1,11 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{
-    account_address_from_auth_key, account_address_from_public_key, AuthenticationKeyInputOptions,
-    CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode, EncodingOptions,
-    ExtractPublicKey, HardwareWalletOptions, ParsePrivateKey, ProfileConfig, ProfileOptions,
-    PublicKeyInputOptions, RestOptions, TransactionOptions, TransactionSummary,
+use crate::common::{
+    key_encryption::read_passphrase,
+    signing::AccountSigningKey,
+    types::{
+        account_address_from_auth_key, account_address_from_public_key,
+        AuthenticationKeyInputOptions, CliCommand, CliConfig, CliError, CliTypedResult,
+        ConfigSearchMode, EncodingOptions, ExtractPublicKey, HardwareWalletOptions,
+        ParsePrivateKey, ProfileConfig, ProfileOptions, PublicKeyInputOptions, RestOptions,
+        TransactionOptions, TransactionSummary,
+    },
 };
 use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::{
@@ -103,6 +108,22 @@ impl RotateKey {
             self.new_auth_key_options.new_private_key.clone(),
         )
     }
+
+    /// The current key of the account and its address, which must be a legacy Ed25519 key, as
+    /// only those can sign the rotation proof
+    fn current_key_and_address(&self) -> CliTypedResult<(Ed25519PrivateKey, AccountAddress)> {
+        match self.txn_options.get_signing_key_and_address()? {
+            (AccountSigningKey::Ed25519(private_key), address) => Ok((private_key, address)),
+            (AccountSigningKey::Ledger { .. }, _) => Err(CliError::CommandArgumentError(
+                "Select the profile of the Ledger account with --profile to rotate its key"
+                    .to_string(),
+            )),
+            _ => Err(CliError::CommandArgumentError(
+                "Only the key of accounts using a legacy Ed25519 authentication key can be rotated"
+                    .to_string(),
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -147,22 +168,22 @@ impl CliCommand<RotateSummary> for RotateKey {
         } else {
             None
         };
-        let (current_private_key, current_address, current_public_key) = if current_derivation_path
-            .is_some()
-        {
-            (
-                None,
-                self.txn_options.profile_options.account_address()?,
-                self.txn_options.profile_options.public_key()?,
-            )
-        } else {
-            let (current_private_key, current_address) = self.txn_options.get_key_and_address()?;
-            (
-                Some(current_private_key),
-                current_address,
-                self.txn_options.get_public_key()?,
-            )
-        };
+        let (current_private_key, current_address, current_public_key) =
+            if current_derivation_path.is_some() {
+                (
+                    None,
+                    self.txn_options.profile_options.account_address()?,
+                    self.txn_options.profile_options.public_key()?,
+                )
+            } else {
+                let (current_private_key, current_address) = self.current_key_and_address()?;
+                let current_public_key = current_private_key.public_key();
+                (
+                    Some(current_private_key),
+                    current_address,
+                    current_public_key,
+                )
+            };
 
         // Get new signer options.
         let new_hardware_wallet_options = HardwareWalletOptions {
@@ -191,6 +212,22 @@ impl CliCommand<RotateSummary> for RotateKey {
             ));
         }
 
+        // Encrypt the new private key if the current one is, reading the passphrase before the
+        // rotation so that a mistyped one can't leave the new key unsaved.
+        let new_profile_passphrase = if self.new_profile_options.save_to_profile.is_some()
+            && new_private_key.is_some()
+            && self
+                .txn_options
+                .profile_options
+                .profile()
+                .map_or(false, |profile| profile.encrypted_keys.is_some())
+        {
+            eprintln!("The current profile is encrypted, so the new one will be too");
+            Some(read_passphrase(true)?)
+        } else {
+            None
+        };
+
         // Construct rotation proof challenge.
         let sequence_number = self.txn_options.sequence_number(current_address).await?;
         let auth_key = self.txn_options.auth_key(current_address).await?;
@@ -252,9 +289,11 @@ impl CliCommand<RotateSummary> for RotateKey {
         if current_derivation_path.is_some() {
             eprintln!("Approve transaction on your Ledger device");
         };
+        // The profile is only updated once the rotation is committed, so it can't be written
+        // unsigned
         let txn_summary = self
             .txn_options
-            .submit_transaction(aptos_stdlib::account_rotate_authentication_key(
+            .submit_transaction_for_output(aptos_stdlib::account_rotate_authentication_key(
                 0,
                 current_public_key.to_bytes().to_vec(),
                 0,
@@ -308,12 +347,17 @@ impl CliCommand<RotateSummary> for RotateKey {
             account: Some(current_address),
             private_key: new_private_key,
             derivation_path: new_derivation_path,
+            account_key: None,
+            encrypted_keys: None,
             ..self.txn_options.profile_options.profile()?
         };
 
         if let Some(url) = self.txn_options.rest_options.url {
             new_profile_config.rest_url = Some(url.into());
         }
+        if let Some(passphrase) = new_profile_passphrase {
+            new_profile_config.encrypt_keys(&passphrase)?;
+        }
 
         config
             .profiles
@@ -0,0 +1,71 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult};
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+
+/// List the accounts of a Ledger
+///
+/// The Aptos app on the Ledger derives the key of the account at BIP44 account index `n` with
+/// the derivation path `m/44'/637'/n'/0'/0'`. Use the index with `--derivation-index`, e.g. in
+/// `aptos init`, to use one of these accounts. The addresses are the ones derived from the keys,
+/// use `aptos account lookup-address` to find the address of an account whose key was rotated to
+/// a key of the Ledger.
+///
+/// The Aptos app only holds Ed25519 keys, so only Ed25519 accounts are listed. Accounts with
+/// secp256k1 keys can't be used with a Ledger.
+///
+/// This requires the Ledger to be connected and unlocked, with the Aptos app open.
+#[derive(Debug, Parser)]
+pub struct ListLedgerAccounts {
+    /// BIP44 account index of the first account to list
+    #[clap(long, default_value_t = 0)]
+    pub(crate) start_index: u32,
+
+    /// Number of accounts to list
+    #[clap(long, default_value_t = 10)]
+    pub(crate) count: u32,
+}
+
+/// An account of a Ledger
+#[derive(Debug, Serialize)]
+pub struct LedgerAccountSummary {
+    pub derivation_index: u32,
+    pub derivation_path: String,
+    pub account: AccountAddress,
+    pub public_key: Ed25519PublicKey,
+}
+
+#[async_trait]
+impl CliCommand<Vec<LedgerAccountSummary>> for ListLedgerAccounts {
+    fn command_name(&self) -> &'static str {
+        "ListLedgerAccounts"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<LedgerAccountSummary>> {
+        let end_index = self
+            .start_index
+            .checked_add(self.count)
+            .ok_or_else(|| CliError::CommandArgumentError("Invalid --count".to_string()))?;
+        if self.count > aptos_ledger::MAX_FETCHED_ACCOUNTS {
+            return Err(CliError::CommandArgumentError(format!(
+                "At most {} accounts can be listed at once, use --start-index to list more",
+                aptos_ledger::MAX_FETCHED_ACCOUNTS
+            )));
+        }
+
+        Ok(aptos_ledger::fetch_accounts(self.start_index..end_index)?
+            .into_iter()
+            .map(|account| LedgerAccountSummary {
+                derivation_index: account.index,
+                derivation_path: account.derivation_path,
+                account: account.address,
+                public_key: account.public_key,
+            })
+            .collect())
+    }
+}
@@ -11,6 +11,7 @@ pub mod derive_resource_account;
 pub mod fund;
 pub mod key_rotation;
 pub mod list;
+pub mod list_ledger_accounts;
 pub mod multisig_account;
 pub mod transfer;
 
@@ -26,6 +27,7 @@ pub enum AccountTool {
     FundWithFaucet(fund::FundWithFaucet),
     Balance(balance::Balance),
     List(list::ListAccount),
+    ListLedgerAccounts(list_ledger_accounts::ListLedgerAccounts),
     LookupAddress(key_rotation::LookupAddress),
     RotateKey(key_rotation::RotateKey),
     Transfer(transfer::TransferCoins),
@@ -40,6 +42,7 @@ impl AccountTool {
             AccountTool::FundWithFaucet(tool) => tool.execute_serialized().await,
             AccountTool::Balance(tool) => tool.execute_serialized().await,
             AccountTool::List(tool) => tool.execute_serialized().await,
+            AccountTool::ListLedgerAccounts(tool) => tool.execute_serialized().await,
             AccountTool::LookupAddress(tool) => tool.execute_serialized().await,
             AccountTool::RotateKey(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
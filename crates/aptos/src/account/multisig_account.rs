@@ -142,7 +142,6 @@ impl CliCommand<TransactionSummary> for CreateTransaction {
         self.txn_options
             .submit_transaction(transaction_payload)
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -251,7 +250,6 @@ impl CliCommand<TransactionSummary> for Approve {
                 self.multisig_account_with_sequence_number.sequence_number,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -283,7 +281,6 @@ impl CliCommand<TransactionSummary> for Reject {
                 self.multisig_account_with_sequence_number.sequence_number,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -309,7 +306,6 @@ impl CliCommand<TransactionSummary> for Execute {
                 transaction_payload: None,
             }))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -336,7 +332,6 @@ impl CliCommand<TransactionSummary> for ExecuteWithPayload {
                 transaction_payload: Some(self.entry_function_args.try_into()?),
             }))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -364,6 +359,5 @@ impl CliCommand<TransactionSummary> for ExecuteReject {
                 self.multisig_account.multisig_address,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliTypedResult, TransactionOptions};
+use crate::common::types::{
+    CliCommand, CliError, CliTypedResult, TransactionOptions, TransactionSummary,
+};
 use aptos_cached_packages::aptos_stdlib;
 use aptos_rest_client::{
     aptos_api_types::{HashValue, WriteResource, WriteSetChange},
@@ -38,11 +40,16 @@ impl CliCommand<TransferSummary> for TransferCoins {
     }
 
     async fn execute(self) -> CliTypedResult<TransferSummary> {
+        let payload = aptos_stdlib::aptos_account_transfer(self.account, self.amount);
+        if let Some(output_file) = &self.txn_options.output_unsigned {
+            return self
+                .txn_options
+                .output_unsigned_transaction(payload, &[], output_file)
+                .await
+                .and_then(TransferSummary::try_from);
+        }
         self.txn_options
-            .submit_transaction(aptos_stdlib::aptos_account_transfer(
-                self.account,
-                self.amount,
-            ))
+            .submit_transaction_for_output(payload)
             .await
             .map(TransferSummary::from)
     }
@@ -51,21 +58,50 @@ impl CliCommand<TransferSummary> for TransferCoins {
 const SUPPORTED_COINS: [&str; 1] = ["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"];
 
 /// A shortened transaction output
+///
+/// The execution fields are only missing if the transaction was written unsigned with
+/// `--output-unsigned` instead of being submitted.
 #[derive(Clone, Debug, Serialize)]
 pub struct TransferSummary {
     pub gas_unit_price: u64,
-    pub gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
     pub balance_changes: BTreeMap<AccountAddress, serde_json::Value>,
     pub sender: AccountAddress,
-    pub success: bool,
-    pub version: u64,
-    pub vm_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_status: Option<String>,
     pub transaction_hash: HashValue,
 }
 
 impl TransferSummary {
     pub fn octa_spent(&self) -> u64 {
-        self.gas_unit_price * self.gas_used
+        self.gas_unit_price * self.gas_used.unwrap_or_default()
+    }
+}
+
+impl TryFrom<TransactionSummary> for TransferSummary {
+    type Error = CliError;
+
+    fn try_from(summary: TransactionSummary) -> CliTypedResult<Self> {
+        let missing = |field: &str| {
+            CliError::UnexpectedError(format!("The transaction summary has no {}", field))
+        };
+        Ok(TransferSummary {
+            gas_unit_price: summary
+                .gas_unit_price
+                .ok_or_else(|| missing("gas unit price"))?,
+            gas_used: summary.gas_used,
+            balance_changes: BTreeMap::new(),
+            sender: summary.sender.ok_or_else(|| missing("sender"))?,
+            success: summary.success,
+            version: summary.version,
+            vm_status: summary.vm_status,
+            transaction_hash: summary.transaction_hash,
+        })
     }
 }
 
@@ -100,12 +136,12 @@ impl From<Transaction> for TransferSummary {
 
             TransferSummary {
                 gas_unit_price,
-                gas_used,
+                gas_used: Some(gas_used),
                 balance_changes,
                 sender,
-                success,
-                version,
-                vm_status,
+                success: Some(success),
+                version: Some(version),
+                vm_status: Some(vm_status),
                 transaction_hash,
             }
         } else {
@@ -4,6 +4,7 @@
 use crate::{
     account::key_rotation::lookup_address,
     common::{
+        signing::{parse_any_public_key, AccountKeyConfig, AnyPrivateKey, SignatureScheme},
         types::{
             account_address_from_public_key, CliCommand, CliConfig, CliError, CliTypedResult,
             ConfigSearchMode, EncodingOptions, HardwareWalletOptions, PrivateKeyInputOptions,
@@ -18,6 +19,7 @@ use aptos_rest_client::{
     aptos_api_types::{AptosError, AptosErrorCode},
     error::{AptosErrorResponse, RestError},
 };
+use aptos_types::transaction::authenticator::AnyPublicKey;
 use async_trait::async_trait;
 use clap::Parser;
 use reqwest::Url;
@@ -69,6 +71,31 @@ pub struct InitTool {
     #[clap(flatten)]
     pub(crate) hardware_wallet_options: HardwareWalletOptions,
 
+    /// Signature scheme of the key of the account
+    ///
+    /// With `secp256k1-ecdsa`, e.g. to use a key from Ethereum tooling, the account uses a
+    /// `SingleKey` authentication key, and `--private-key` is a secp256k1 ECDSA key.
+    #[clap(long, value_enum, default_value_t = SignatureScheme::Ed25519)]
+    pub signature_scheme: SignatureScheme,
+
+    /// Public keys of a K-of-N multi-key account, to create a profile holding some of its keys
+    ///
+    /// Each key is prefixed by its scheme, e.g. `ed25519:0x...` or `secp256k1-ecdsa:0x...`. The
+    /// private key, of the scheme given by `--signature-scheme`, must be one of them. Running this
+    /// again with another private key of the same account adds it to the profile, until it holds
+    /// enough keys to sign.
+    #[clap(
+        long,
+        num_args = 1..,
+        value_parser = parse_any_public_key,
+        requires = "signatures_required"
+    )]
+    pub multi_key_public_keys: Vec<AnyPublicKey>,
+
+    /// Number of signatures required by the multi-key account of `--multi-key-public-keys`
+    #[clap(long, requires = "multi_key_public_keys")]
+    pub signatures_required: Option<u8>,
+
     #[clap(flatten)]
     pub rng_args: RngArgs,
     #[clap(flatten)]
@@ -101,6 +128,12 @@ impl CliCommand<()> for InitTool {
 
         // Select profile we're using
         let mut profile_config = if let Some(profile_config) = config.remove_profile(profile_name) {
+            if profile_config.encrypted_keys.is_some() {
+                return Err(CliError::CommandArgumentError(format!(
+                    "The private keys of profile {} are encrypted, decrypt them first with `aptos config decrypt-profile`",
+                    profile_name
+                )));
+            }
             prompt_yes_with_override(&format!("Aptos already initialized for profile {}, do you want to overwrite the existing config?", profile_name), self.prompt_options)?;
             profile_config
         } else {
@@ -153,6 +186,17 @@ impl CliCommand<()> for InitTool {
             Network::Custom => self.custom_network(&mut profile_config)?,
         }
 
+        if self.is_hardware_wallet()
+            && (self.signature_scheme != SignatureScheme::Ed25519
+                || !self.multi_key_public_keys.is_empty())
+        {
+            return Err(CliError::CommandArgumentError(
+                "Hardware wallets only support Ed25519 keys, the Aptos app on Ledger can't sign \
+                with secp256k1 ECDSA or multi-key accounts"
+                    .to_string(),
+            ));
+        }
+
         // Check if any ledger flag is set
         let derivation_path = if let Some(deri_path) =
             self.hardware_wallet_options.extract_derivation_path()?
@@ -192,64 +236,6 @@ impl CliCommand<()> for InitTool {
         // Set the derivation_path to the one user chose
         profile_config.derivation_path.clone_from(&derivation_path);
 
-        // Private key
-        let private_key = if self.is_hardware_wallet() {
-            // Private key stays in ledger
-            None
-        } else {
-            let ed25519_private_key = if let Some(key) = self
-                .private_key_options
-                .extract_private_key_cli(self.encoding_options.encoding)?
-            {
-                eprintln!("Using command line argument for private key");
-                key
-            } else {
-                eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", profile_config.private_key.as_ref().map(|_| "Redacted").unwrap_or("None"));
-                let input = read_line("Private key")?;
-                let input = input.trim();
-                if input.is_empty() {
-                    if let Some(key) = profile_config.private_key {
-                        eprintln!("No key given, keeping existing key...");
-                        key
-                    } else {
-                        eprintln!("No key given, generating key...");
-                        self.rng_args
-                            .key_generator()?
-                            .generate_ed25519_private_key()
-                    }
-                } else {
-                    Ed25519PrivateKey::from_encoded_string(input).map_err(|err| {
-                        CliError::UnableToParse("Ed25519PrivateKey", err.to_string())
-                    })?
-                }
-            };
-
-            Some(ed25519_private_key)
-        };
-
-        // Public key
-        let public_key = if self.is_hardware_wallet() {
-            let pub_key = match aptos_ledger::get_public_key(
-                derivation_path
-                    .ok_or(CliError::UnexpectedError(
-                        "Invalid derivation path".to_string(),
-                    ))?
-                    .as_str(),
-                false,
-            ) {
-                Ok(pub_key_str) => pub_key_str,
-                Err(err) => {
-                    return Err(CliError::UnexpectedError(format!(
-                        "Unexpected Ledger Error: {:?}",
-                        err.to_string()
-                    )))
-                },
-            };
-            pub_key
-        } else {
-            private_key.clone().unwrap().public_key()
-        };
-
         let rest_url = Url::parse(
             profile_config
                 .rest_url
@@ -261,11 +247,103 @@ impl CliCommand<()> for InitTool {
 
         // lookup the address from onchain instead of deriving it
         // if this is the rotated key, deriving it will outputs an incorrect address
-        let derived_address = account_address_from_public_key(&public_key);
-        let address = lookup_address(&client, derived_address, false).await?;
+        let derived_address = match self.signature_scheme {
+            _ if !self.multi_key_public_keys.is_empty() => {
+                let mut account_key = AccountKeyConfig::multi_key(
+                    self.multi_key_public_keys.clone(),
+                    self.signatures_required
+                        .expect("Required along with the multi-key public keys"),
+                    self.any_private_key(None)?,
+                )?;
+                if let Some(existing_key) = profile_config.account_key.take() {
+                    account_key.add_private_keys_of(existing_key);
+                }
+                let derived_address = account_key.authentication_key()?.account_address();
 
-        profile_config.private_key = private_key;
-        profile_config.public_key = Some(public_key);
+                profile_config.private_key = None;
+                profile_config.public_key = None;
+                profile_config.account_key = Some(account_key);
+                derived_address
+            },
+            SignatureScheme::Ed25519 => {
+                // Private key
+                let private_key = if self.is_hardware_wallet() {
+                    // Private key stays in ledger
+                    None
+                } else {
+                    let ed25519_private_key = if let Some(key) = self
+                        .private_key_options
+                        .extract_private_key_cli(self.encoding_options.encoding)?
+                    {
+                        eprintln!("Using command line argument for private key");
+                        key
+                    } else {
+                        eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", profile_config.private_key.as_ref().map(|_| "Redacted").unwrap_or("None"));
+                        let input = read_line("Private key")?;
+                        let input = input.trim();
+                        if input.is_empty() {
+                            if let Some(key) = profile_config.private_key {
+                                eprintln!("No key given, keeping existing key...");
+                                key
+                            } else {
+                                eprintln!("No key given, generating key...");
+                                self.rng_args
+                                    .key_generator()?
+                                    .generate_ed25519_private_key()
+                            }
+                        } else {
+                            Ed25519PrivateKey::from_encoded_string(input).map_err(|err| {
+                                CliError::UnableToParse("Ed25519PrivateKey", err.to_string())
+                            })?
+                        }
+                    };
+
+                    Some(ed25519_private_key)
+                };
+
+                // Public key
+                let public_key = if self.is_hardware_wallet() {
+                    let pub_key = match aptos_ledger::get_public_key(
+                        derivation_path
+                            .ok_or(CliError::UnexpectedError(
+                                "Invalid derivation path".to_string(),
+                            ))?
+                            .as_str(),
+                        false,
+                    ) {
+                        Ok(pub_key_str) => pub_key_str,
+                        Err(err) => {
+                            return Err(CliError::UnexpectedError(format!(
+                                "Unexpected Ledger Error: {:?}",
+                                err.to_string()
+                            )))
+                        },
+                    };
+                    pub_key
+                } else {
+                    private_key.clone().unwrap().public_key()
+                };
+
+                profile_config.private_key = private_key;
+                profile_config.public_key = Some(public_key.clone());
+                profile_config.account_key = None;
+                account_address_from_public_key(&public_key)
+            },
+            SignatureScheme::Secp256k1Ecdsa => {
+                let existing_key = match profile_config.account_key.take() {
+                    Some(AccountKeyConfig::SingleKey { private_key, .. }) => private_key,
+                    _ => None,
+                };
+                let account_key = AccountKeyConfig::single_key(self.any_private_key(existing_key)?);
+                let derived_address = account_key.authentication_key()?.account_address();
+
+                profile_config.private_key = None;
+                profile_config.public_key = None;
+                profile_config.account_key = Some(account_key);
+                derived_address
+            },
+        };
+        let address = lookup_address(&client, derived_address, false).await?;
         profile_config.account = Some(address);
 
         // Create account if it doesn't exist (and there's a faucet)
@@ -430,6 +508,57 @@ impl InitTool {
         Ok(())
     }
 
+    /// The private key of `--signature-scheme` from the command line or the user, or else the
+    /// existing key of the profile if it's of the same scheme, or a new one
+    fn any_private_key(
+        &self,
+        existing_key: Option<AnyPrivateKey>,
+    ) -> CliTypedResult<AnyPrivateKey> {
+        let encoding = self.encoding_options.encoding;
+        let key = match self.signature_scheme {
+            SignatureScheme::Ed25519 => self
+                .private_key_options
+                .extract_private_key_cli(encoding)?
+                .map(AnyPrivateKey::Ed25519),
+            SignatureScheme::Secp256k1Ecdsa => self
+                .private_key_options
+                .extract_secp256k1_ecdsa_private_key_cli(encoding)?
+                .map(AnyPrivateKey::Secp256k1Ecdsa),
+        };
+        if let Some(key) = key {
+            eprintln!("Using command line argument for private key");
+            return Ok(key);
+        }
+
+        let existing_key =
+            existing_key.filter(|key| key.signature_scheme() == self.signature_scheme);
+        eprintln!(
+            "Enter your {:?} private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]",
+            self.signature_scheme,
+            existing_key.as_ref().map(|_| "Redacted").unwrap_or("None")
+        );
+        let input = read_line("Private key")?;
+        let input = input.trim();
+        if !input.is_empty() {
+            return AnyPrivateKey::from_encoded_string(self.signature_scheme, input);
+        }
+        if let Some(key) = existing_key {
+            eprintln!("No key given, keeping existing key...");
+            return Ok(key);
+        }
+
+        eprintln!("No key given, generating key...");
+        let mut key_generator = self.rng_args.key_generator()?;
+        Ok(match self.signature_scheme {
+            SignatureScheme::Ed25519 => {
+                AnyPrivateKey::Ed25519(key_generator.generate_ed25519_private_key())
+            },
+            SignatureScheme::Secp256k1Ecdsa => {
+                AnyPrivateKey::Secp256k1Ecdsa(key_generator.generate_secp256k1_ecdsa_private_key())
+            },
+        })
+    }
+
     fn is_hardware_wallet(&self) -> bool {
         self.hardware_wallet_options.is_hardware_wallet() || self.ledger
     }
@@ -0,0 +1,282 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encryption of the private keys of profiles with a passphrase
+//!
+//! The key is derived from the passphrase with scrypt, and the private keys are encrypted with
+//! AES-256-GCM.  The parameters of the key derivation are stored along with the ciphertext, so
+//! that they can be strengthened later without breaking existing profiles.
+
+use crate::common::types::{CliError, CliTypedResult};
+use aes_gcm::{
+    aead::{generic_array::typenum::Unsigned, rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use serde::{Deserialize, Serialize};
+
+/// Environment variable holding the passphrase of encrypted profiles, to not be prompted for it
+pub const PROFILE_PASSPHRASE_ENV: &str = "APTOS_PROFILE_PASSPHRASE";
+
+/// scrypt cost parameters recommended for interactive use, which take ~100ms and 32MiB
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LENGTH: usize = 32;
+
+/// Private keys encrypted with a key derived from a passphrase
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedKeys {
+    pub kdf: KeyDerivation,
+    #[serde(with = "hex")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex")]
+    ciphertext: Vec<u8>,
+}
+
+/// How the encryption key is derived from the passphrase
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum KeyDerivation {
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        #[serde(with = "hex")]
+        salt: Vec<u8>,
+    },
+}
+
+impl KeyDerivation {
+    fn derive_key(&self, passphrase: &str) -> CliTypedResult<Key<Aes256Gcm>> {
+        let mut key = Key::<Aes256Gcm>::default();
+        match self {
+            KeyDerivation::Scrypt { log_n, r, p, salt } => {
+                let params = scrypt::Params::new(*log_n, *r, *p, key.len()).map_err(|err| {
+                    CliError::UnexpectedError(format!("Invalid scrypt parameters: {}", err))
+                })?;
+                scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key).map_err(|err| {
+                    CliError::UnexpectedError(format!("Failed to derive key: {}", err))
+                })?;
+            },
+        }
+        Ok(key)
+    }
+}
+
+impl EncryptedKeys {
+    pub fn encrypt(plaintext: &[u8], passphrase: &str) -> CliTypedResult<Self> {
+        let mut salt = vec![0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let kdf = KeyDerivation::Scrypt {
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt,
+        };
+
+        let cipher = Aes256Gcm::new(&kdf.derive_key(passphrase)?);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|err| CliError::UnexpectedError(format!("Failed to encrypt: {}", err)))?;
+
+        Ok(EncryptedKeys {
+            kdf,
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> CliTypedResult<Vec<u8>> {
+        if self.nonce.len() != <Aes256Gcm as AeadCore>::NonceSize::USIZE {
+            return Err(CliError::UnexpectedError(
+                "Invalid nonce of the encrypted private keys".to_string(),
+            ));
+        }
+
+        let cipher = Aes256Gcm::new(&self.kdf.derive_key(passphrase)?);
+        cipher
+            .decrypt(Nonce::from_slice(&self.nonce), self.ciphertext.as_ref())
+            .map_err(|_| {
+                CliError::CommandArgumentError(
+                    "Failed to decrypt the private keys, the passphrase is incorrect".to_string(),
+                )
+            })
+    }
+}
+
+/// Reads the passphrase of encrypted profiles from the environment, or prompts for it
+///
+/// When `confirm` is set, as for a new passphrase, it's prompted for twice.
+pub fn read_passphrase(confirm: bool) -> CliTypedResult<String> {
+    read_passphrase_from(std::env::var(PROFILE_PASSPHRASE_ENV).ok(), confirm)
+}
+
+/// Like [`read_passphrase`], with the passphrase of the environment, if it's set
+fn read_passphrase_from(env_passphrase: Option<String>, confirm: bool) -> CliTypedResult<String> {
+    let prompt = |prompt: &str| {
+        rpassword::prompt_password(prompt)
+            .map_err(|err| CliError::IO("Failed to read passphrase".to_string(), err))
+    };
+    let (passphrase, from_env) = match env_passphrase {
+        Some(passphrase) => (passphrase, true),
+        None => (prompt("Enter the passphrase of the profile: ")?, false),
+    };
+    if confirm {
+        check_new_passphrase(&passphrase)?;
+        if !from_env && prompt("Confirm the passphrase: ")? != passphrase {
+            return Err(CliError::CommandArgumentError(
+                "The passphrases don't match".to_string(),
+            ));
+        }
+    }
+    Ok(passphrase)
+}
+
+/// Checks a passphrase to encrypt private keys with, wherever it's read from
+fn check_new_passphrase(passphrase: &str) -> CliTypedResult<()> {
+    if passphrase.is_empty() {
+        return Err(CliError::CommandArgumentError(format!(
+            "The passphrase must not be empty, check {} if it's set",
+            PROFILE_PASSPHRASE_ENV
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        signing::{AccountKeyConfig, AnyPrivateKey},
+        types::ProfileConfig,
+    };
+    use aptos_crypto::PrivateKey;
+    use aptos_keygen::KeyGen;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let encrypted = EncryptedKeys::encrypt(b"private keys", "passphrase").unwrap();
+        assert_eq!(
+            encrypted.decrypt("passphrase").unwrap(),
+            b"private keys".to_vec()
+        );
+        assert!(encrypted.decrypt("wrong passphrase").is_err());
+
+        // The encrypted keys are stored in the YAML config
+        let encrypted: EncryptedKeys =
+            serde_yaml::from_str(&serde_yaml::to_string(&encrypted).unwrap()).unwrap();
+        assert_eq!(
+            encrypted.decrypt("passphrase").unwrap(),
+            b"private keys".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_profile() {
+        let private_key = KeyGen::from_seed([0; 32]).generate_ed25519_private_key();
+        let public_key = private_key.public_key();
+        let mut profile = ProfileConfig {
+            private_key: Some(private_key),
+            public_key: Some(public_key.clone()),
+            ..Default::default()
+        };
+
+        profile.encrypt_keys("passphrase").unwrap();
+        assert!(profile.private_key.is_none());
+        assert!(profile.has_private_key());
+        assert_eq!(profile.public_key, Some(public_key.clone()));
+        assert!(profile.encrypt_keys("passphrase").is_err());
+
+        // The encrypted profile is stored in the YAML config
+        let mut profile: ProfileConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&profile).unwrap()).unwrap();
+        assert!(profile
+            .decrypt_keys_with_passphrase("wrong passphrase")
+            .is_err());
+        assert!(profile.encrypted_keys.is_some());
+
+        profile.decrypt_keys_with_passphrase("passphrase").unwrap();
+        assert!(profile.encrypted_keys.is_none());
+        assert_eq!(profile.private_key.unwrap().public_key(), public_key);
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_multi_key_profile() {
+        let mut key_gen = KeyGen::from_seed([0; 32]);
+        let private_keys = vec![
+            AnyPrivateKey::Ed25519(key_gen.generate_ed25519_private_key()),
+            AnyPrivateKey::Ed25519(key_gen.generate_ed25519_private_key()),
+            AnyPrivateKey::Secp256k1Ecdsa(key_gen.generate_secp256k1_ecdsa_private_key()),
+        ];
+        let public_keys: Vec<_> = private_keys.iter().map(AnyPrivateKey::public_key).collect();
+        let held_keys: BTreeMap<_, _> = private_keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, private_key)| (index as u8, private_key))
+            .filter(|(index, _)| *index != 1)
+            .collect();
+        let mut profile = ProfileConfig {
+            account_key: Some(AccountKeyConfig::MultiKey {
+                public_keys: public_keys.clone(),
+                signatures_required: 2,
+                private_keys: held_keys,
+            }),
+            ..Default::default()
+        };
+        let authentication_key = profile
+            .account_key
+            .as_ref()
+            .unwrap()
+            .authentication_key()
+            .unwrap();
+
+        profile.encrypt_keys("passphrase").unwrap();
+        assert!(!profile.account_key.as_ref().unwrap().has_private_key());
+        assert!(profile.has_private_key());
+
+        let mut profile: ProfileConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&profile).unwrap()).unwrap();
+        profile.decrypt_keys_with_passphrase("passphrase").unwrap();
+        match profile.account_key.take().unwrap() {
+            AccountKeyConfig::MultiKey {
+                public_keys: restored_public_keys,
+                private_keys,
+                ..
+            } => {
+                assert_eq!(restored_public_keys, public_keys);
+                assert_eq!(private_keys.keys().copied().collect::<Vec<_>>(), vec![0, 2]);
+                for (index, private_key) in &private_keys {
+                    assert_eq!(private_key.public_key(), public_keys[*index as usize]);
+                }
+                let signing_key = AccountKeyConfig::MultiKey {
+                    public_keys,
+                    signatures_required: 2,
+                    private_keys,
+                }
+                .into_signing_key()
+                .unwrap();
+                assert_eq!(signing_key.authentication_key(), authentication_key);
+            },
+            account_key => panic!("Unexpected account key {:?}", account_key),
+        }
+    }
+
+    #[test]
+    fn test_new_passphrase_must_not_be_empty() {
+        assert!(check_new_passphrase("").is_err());
+        assert!(check_new_passphrase("passphrase").is_ok());
+
+        // Neither when it's read from the environment
+        assert!(read_passphrase_from(Some(String::new()), true).is_err());
+        assert_eq!(
+            read_passphrase_from(Some(String::new()), false).unwrap(),
+            ""
+        );
+        assert_eq!(
+            read_passphrase_from(Some("passphrase".to_string()), true).unwrap(),
+            "passphrase"
+        );
+    }
+}
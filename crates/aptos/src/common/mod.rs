@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod init;
+pub mod key_encryption;
 pub mod local_simulation;
+pub mod signing;
 pub mod types;
 pub mod utils;
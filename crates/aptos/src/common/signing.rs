@@ -0,0 +1,721 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliError, CliTypedResult};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    secp256k1_ecdsa, signing_message, PrivateKey, SigningKey, ValidCryptoMaterialStringExt,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{
+        authenticator::{
+            AccountAuthenticator, AnyPublicKey, AnySignature, AuthenticationKey, MultiKey,
+            MultiKeyAuthenticator, SingleKeyAuthenticator, TransactionAuthenticator,
+        },
+        RawTransaction, RawTransactionWithData, SignedTransaction,
+    },
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Signature scheme of the key of a new profile
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SignatureScheme {
+    /// Ed25519 key, with a legacy Ed25519 authentication key
+    #[default]
+    Ed25519,
+    /// Secp256k1 ECDSA key, e.g. from Ethereum tooling, with a `SingleKey` authentication key
+    Secp256k1Ecdsa,
+}
+
+/// Private key of any scheme the CLI can sign with
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnyPrivateKey {
+    Ed25519(Ed25519PrivateKey),
+    Secp256k1Ecdsa(secp256k1_ecdsa::PrivateKey),
+}
+
+impl AnyPrivateKey {
+    /// Parses a private key of the given scheme, encoded as a hex literal (0x...)
+    pub fn from_encoded_string(scheme: SignatureScheme, encoded: &str) -> CliTypedResult<Self> {
+        match scheme {
+            SignatureScheme::Ed25519 => Ed25519PrivateKey::from_encoded_string(encoded)
+                .map(AnyPrivateKey::Ed25519)
+                .map_err(|err| CliError::UnableToParse("Ed25519PrivateKey", err.to_string())),
+            SignatureScheme::Secp256k1Ecdsa => {
+                secp256k1_ecdsa::PrivateKey::from_encoded_string(encoded)
+                    .map(AnyPrivateKey::Secp256k1Ecdsa)
+                    .map_err(|err| {
+                        CliError::UnableToParse("secp256k1_ecdsa::PrivateKey", err.to_string())
+                    })
+            },
+        }
+    }
+
+    /// Encodes the private key as a hex literal (0x...)
+    pub fn to_encoded_string(&self) -> CliTypedResult<String> {
+        let encoded = match self {
+            AnyPrivateKey::Ed25519(private_key) => private_key.to_encoded_string(),
+            AnyPrivateKey::Secp256k1Ecdsa(private_key) => private_key.to_encoded_string(),
+        };
+        encoded.map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+
+    pub fn signature_scheme(&self) -> SignatureScheme {
+        match self {
+            AnyPrivateKey::Ed25519(_) => SignatureScheme::Ed25519,
+            AnyPrivateKey::Secp256k1Ecdsa(_) => SignatureScheme::Secp256k1Ecdsa,
+        }
+    }
+
+    pub fn public_key(&self) -> AnyPublicKey {
+        match self {
+            AnyPrivateKey::Ed25519(private_key) => AnyPublicKey::ed25519(private_key.public_key()),
+            AnyPrivateKey::Secp256k1Ecdsa(private_key) => {
+                AnyPublicKey::secp256k1_ecdsa(private_key.public_key())
+            },
+        }
+    }
+
+    fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> CliTypedResult<AnySignature> {
+        let signature = match self {
+            AnyPrivateKey::Ed25519(private_key) => {
+                private_key.sign(message).map(AnySignature::ed25519)
+            },
+            AnyPrivateKey::Secp256k1Ecdsa(private_key) => {
+                private_key.sign(message).map(AnySignature::secp256k1_ecdsa)
+            },
+        };
+        signature.map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+
+    /// A signature of this key's scheme that's all zeroes, to simulate transactions with
+    fn simulation_signature(&self) -> AnySignature {
+        match self {
+            AnyPrivateKey::Ed25519(_) => {
+                AnySignature::ed25519(Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap())
+            },
+            AnyPrivateKey::Secp256k1Ecdsa(_) => AnySignature::secp256k1_ecdsa(
+                secp256k1_ecdsa::Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            ),
+        }
+    }
+}
+
+/// Parses a public key prefixed by its scheme, e.g. `ed25519:0x...` or `secp256k1-ecdsa:0x...`
+pub fn parse_any_public_key(key: &str) -> CliTypedResult<AnyPublicKey> {
+    let (scheme, encoded) = key.split_once(':').ok_or_else(|| {
+        CliError::CommandArgumentError(format!(
+            "Public key {} must be prefixed by its scheme, e.g. ed25519:0x...",
+            key
+        ))
+    })?;
+    match SignatureScheme::from_str(scheme, true).map_err(CliError::CommandArgumentError)? {
+        SignatureScheme::Ed25519 => Ed25519PublicKey::from_encoded_string(encoded)
+            .map(AnyPublicKey::ed25519)
+            .map_err(|err| CliError::UnableToParse("Ed25519PublicKey", err.to_string())),
+        SignatureScheme::Secp256k1Ecdsa => secp256k1_ecdsa::PublicKey::from_encoded_string(encoded)
+            .map(AnyPublicKey::secp256k1_ecdsa)
+            .map_err(|err| CliError::UnableToParse("secp256k1_ecdsa::PublicKey", err.to_string())),
+    }
+}
+
+/// Keys of the account of a profile using a `SingleKey` or a `MultiKey` authentication key
+///
+/// Profiles of accounts using a legacy Ed25519 authentication key store it in `private_key` and
+/// `public_key` instead.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKeyConfig {
+    SingleKey {
+        public_key: AnyPublicKey,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key: Option<AnyPrivateKey>,
+    },
+    /// K-of-N keys, of which the profile may only hold some private keys
+    MultiKey {
+        public_keys: Vec<AnyPublicKey>,
+        signatures_required: u8,
+        /// Private keys held by the profile, by index of their public key in `public_keys`
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        private_keys: BTreeMap<u8, AnyPrivateKey>,
+    },
+}
+
+impl AccountKeyConfig {
+    pub fn single_key(private_key: AnyPrivateKey) -> Self {
+        AccountKeyConfig::SingleKey {
+            public_key: private_key.public_key(),
+            private_key: Some(private_key),
+        }
+    }
+
+    pub fn authentication_key(&self) -> CliTypedResult<AuthenticationKey> {
+        match self {
+            AccountKeyConfig::SingleKey { public_key, .. } => {
+                Ok(AuthenticationKey::any_key(public_key.clone()))
+            },
+            AccountKeyConfig::MultiKey {
+                public_keys,
+                signatures_required,
+                ..
+            } => Ok(AuthenticationKey::multi_key(MultiKey::new(
+                public_keys.clone(),
+                *signatures_required,
+            )?)),
+        }
+    }
+
+    pub fn has_private_key(&self) -> bool {
+        match self {
+            AccountKeyConfig::SingleKey { private_key, .. } => private_key.is_some(),
+            AccountKeyConfig::MultiKey { private_keys, .. } => !private_keys.is_empty(),
+        }
+    }
+
+    /// K-of-N keys holding `private_key`, which must be one of `public_keys`
+    pub fn multi_key(
+        public_keys: Vec<AnyPublicKey>,
+        signatures_required: u8,
+        private_key: AnyPrivateKey,
+    ) -> CliTypedResult<Self> {
+        // Checks the number of keys and signatures required
+        MultiKey::new(public_keys.clone(), signatures_required)?;
+        let index = public_keys
+            .iter()
+            .position(|public_key| *public_key == private_key.public_key())
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(
+                    "The private key isn't one of the public keys of the multi-key account"
+                        .to_string(),
+                )
+            })?;
+
+        Ok(AccountKeyConfig::MultiKey {
+            public_keys,
+            signatures_required,
+            private_keys: BTreeMap::from([(index as u8, private_key)]),
+        })
+    }
+
+    /// Adds the private keys of `other` if it's the same multi-key account, so a profile can
+    /// be given its keys one at a time
+    pub fn add_private_keys_of(&mut self, other: AccountKeyConfig) {
+        if let (
+            AccountKeyConfig::MultiKey {
+                public_keys,
+                signatures_required,
+                private_keys,
+            },
+            AccountKeyConfig::MultiKey {
+                public_keys: other_public_keys,
+                signatures_required: other_signatures_required,
+                private_keys: other_private_keys,
+            },
+        ) = (self, other)
+        {
+            if *public_keys == other_public_keys
+                && *signatures_required == other_signatures_required
+            {
+                for (index, private_key) in other_private_keys {
+                    private_keys.entry(index).or_insert(private_key);
+                }
+            }
+        }
+    }
+
+    /// Removes the private keys, by index of their public key, e.g. to encrypt them
+    pub fn take_private_keys(&mut self) -> BTreeMap<u8, AnyPrivateKey> {
+        match self {
+            AccountKeyConfig::SingleKey { private_key, .. } => {
+                private_key.take().map(|key| (0, key)).into_iter().collect()
+            },
+            AccountKeyConfig::MultiKey { private_keys, .. } => std::mem::take(private_keys),
+        }
+    }
+
+    /// Restores private keys removed by [`AccountKeyConfig::take_private_keys`]
+    pub fn restore_private_keys(&mut self, keys: BTreeMap<u8, AnyPrivateKey>) {
+        match self {
+            AccountKeyConfig::SingleKey { private_key, .. } => {
+                *private_key = keys.into_values().next();
+            },
+            AccountKeyConfig::MultiKey { private_keys, .. } => *private_keys = keys,
+        }
+    }
+
+    /// The private keys held by the profile, encoded as hex literals (0x...)
+    ///
+    /// Keys of a multi-key account are prefixed by the index of their public key.
+    pub fn encoded_private_keys(&self) -> CliTypedResult<Vec<String>> {
+        match self {
+            AccountKeyConfig::SingleKey { private_key, .. } => private_key
+                .iter()
+                .map(AnyPrivateKey::to_encoded_string)
+                .collect(),
+            AccountKeyConfig::MultiKey { private_keys, .. } => private_keys
+                .iter()
+                .map(|(index, private_key)| {
+                    Ok(format!("{}: {}", index, private_key.to_encoded_string()?))
+                })
+                .collect(),
+        }
+    }
+
+    /// The key to sign with, if the profile holds enough private keys
+    pub fn into_signing_key(self) -> CliTypedResult<AccountSigningKey> {
+        match self {
+            AccountKeyConfig::SingleKey {
+                private_key: Some(private_key),
+                ..
+            } => Ok(AccountSigningKey::SingleKey(private_key)),
+            AccountKeyConfig::SingleKey {
+                private_key: None, ..
+            } => Err(CliError::CommandArgumentError(
+                "The profile has no private key to sign with".to_string(),
+            )),
+            AccountKeyConfig::MultiKey {
+                public_keys,
+                signatures_required,
+                private_keys,
+            } => {
+                if private_keys.len() < signatures_required as usize {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "The profile holds {} private keys, but {} signatures are required",
+                        private_keys.len(),
+                        signatures_required
+                    )));
+                }
+                for (index, private_key) in &private_keys {
+                    if public_keys.get(*index as usize) != Some(&private_key.public_key()) {
+                        return Err(CliError::CommandArgumentError(format!(
+                            "The private key {} of the profile doesn't match its public key",
+                            index
+                        )));
+                    }
+                }
+
+                Ok(AccountSigningKey::MultiKey {
+                    public_keys: MultiKey::new(public_keys, signatures_required)?,
+                    // More signatures than required would only cost more gas
+                    private_keys: private_keys
+                        .into_iter()
+                        .take(signatures_required as usize)
+                        .collect(),
+                })
+            },
+        }
+    }
+}
+
+/// A key the CLI signs transactions with, along with the authentication scheme of the account
+#[derive(Debug)]
+pub enum AccountSigningKey {
+    /// Legacy Ed25519 authentication key
+    Ed25519(Ed25519PrivateKey),
+    SingleKey(AnyPrivateKey),
+    /// Just enough private keys of the `MultiKey` to sign, by index of their public key
+    MultiKey {
+        public_keys: MultiKey,
+        private_keys: Vec<(u8, AnyPrivateKey)>,
+    },
+    /// Ed25519 key held by a Ledger, with a legacy Ed25519 authentication key
+    Ledger {
+        derivation_path: String,
+        public_key: Ed25519PublicKey,
+    },
+}
+
+impl AccountSigningKey {
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            AccountSigningKey::Ed25519(private_key) => {
+                AuthenticationKey::ed25519(&private_key.public_key())
+            },
+            AccountSigningKey::SingleKey(private_key) => {
+                AuthenticationKey::any_key(private_key.public_key())
+            },
+            AccountSigningKey::MultiKey { public_keys, .. } => {
+                AuthenticationKey::multi_key(public_keys.clone())
+            },
+            AccountSigningKey::Ledger { public_key, .. } => AuthenticationKey::ed25519(public_key),
+        }
+    }
+
+    /// Address of the account, unless its authentication key was rotated
+    pub fn derived_address(&self) -> AccountAddress {
+        self.authentication_key().account_address()
+    }
+
+    pub fn sign<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+    ) -> CliTypedResult<AccountAuthenticator> {
+        match self {
+            AccountSigningKey::Ed25519(private_key) => {
+                let signature = private_key
+                    .sign(message)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+                Ok(AccountAuthenticator::ed25519(
+                    private_key.public_key(),
+                    signature,
+                ))
+            },
+            AccountSigningKey::SingleKey(private_key) => Ok(AccountAuthenticator::single_key(
+                SingleKeyAuthenticator::new(private_key.public_key(), private_key.sign(message)?),
+            )),
+            AccountSigningKey::MultiKey {
+                public_keys,
+                private_keys,
+            } => {
+                let signatures = private_keys
+                    .iter()
+                    .map(|(index, private_key)| Ok((*index, private_key.sign(message)?)))
+                    .collect::<CliTypedResult<Vec<_>>>()?;
+                Ok(AccountAuthenticator::multi_key(MultiKeyAuthenticator::new(
+                    public_keys.clone(),
+                    signatures,
+                )?))
+            },
+            AccountSigningKey::Ledger {
+                derivation_path,
+                public_key,
+            } => {
+                let message = signing_message(message)?;
+                Ok(AccountAuthenticator::ed25519(
+                    public_key.clone(),
+                    aptos_ledger::sign_message(derivation_path, &message)?,
+                ))
+            },
+        }
+    }
+
+    /// Signs a multi-agent or fee payer transaction, for any of its signers
+    ///
+    /// Ledgers only sign such transactions with recent versions of the Aptos app, which is
+    /// checked first.
+    pub fn sign_transaction_with_data(
+        &self,
+        txn: &RawTransactionWithData,
+    ) -> CliTypedResult<AccountAuthenticator> {
+        match self {
+            AccountSigningKey::Ledger {
+                derivation_path,
+                public_key,
+            } => Ok(AccountAuthenticator::ed25519(
+                public_key.clone(),
+                aptos_ledger::sign_transaction_with_data(derivation_path, txn)?,
+            )),
+            _ => self.sign(txn),
+        }
+    }
+
+    /// Signs a transaction without any other signer than the sender
+    pub fn sign_transaction(&self, raw_txn: RawTransaction) -> CliTypedResult<SignedTransaction> {
+        let sender = self.sign(&raw_txn)?;
+        Ok(SignedTransaction::new_signed_transaction(
+            raw_txn,
+            single_signer_authenticator(sender),
+        ))
+    }
+
+    /// An authenticator to simulate transactions with, as simulated transactions must not be
+    /// signed
+    pub fn simulation_authenticator(&self) -> AccountAuthenticator {
+        match self {
+            AccountSigningKey::Ed25519(private_key) => AccountAuthenticator::ed25519(
+                private_key.public_key(),
+                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            ),
+            AccountSigningKey::Ledger { public_key, .. } => AccountAuthenticator::ed25519(
+                public_key.clone(),
+                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            ),
+            AccountSigningKey::SingleKey(private_key) => {
+                AccountAuthenticator::single_key(SingleKeyAuthenticator::new(
+                    private_key.public_key(),
+                    private_key.simulation_signature(),
+                ))
+            },
+            // Sign with as many keys as the transaction will be, as it costs gas
+            AccountSigningKey::MultiKey {
+                public_keys,
+                private_keys,
+            } => AccountAuthenticator::multi_key(
+                MultiKeyAuthenticator::new(
+                    public_keys.clone(),
+                    private_keys
+                        .iter()
+                        .map(|(index, private_key)| (*index, private_key.simulation_signature()))
+                        .collect(),
+                )
+                .expect("Signing keys were checked against the public keys"),
+            ),
+        }
+    }
+}
+
+/// The authenticator of a transaction without any other signer than the sender
+pub fn single_signer_authenticator(sender: AccountAuthenticator) -> TransactionAuthenticator {
+    match sender {
+        AccountAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        } => TransactionAuthenticator::ed25519(public_key, signature),
+        sender => TransactionAuthenticator::single_sender(sender),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_keygen::KeyGen;
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{Script, TransactionPayload},
+    };
+
+    fn raw_txn(sequence_number: u64) -> RawTransaction {
+        RawTransaction::new(
+            AccountAddress::ONE,
+            sequence_number,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        )
+    }
+
+    /// The same Ed25519, Ed25519 and secp256k1 ECDSA private keys on every call
+    fn private_keys() -> Vec<AnyPrivateKey> {
+        let mut key_gen = KeyGen::from_seed([0; 32]);
+        vec![
+            AnyPrivateKey::Ed25519(key_gen.generate_ed25519_private_key()),
+            AnyPrivateKey::Ed25519(key_gen.generate_ed25519_private_key()),
+            AnyPrivateKey::Secp256k1Ecdsa(key_gen.generate_secp256k1_ecdsa_private_key()),
+        ]
+    }
+
+    /// A 2-of-3 multi-key account of the keys of [`private_keys`], holding the given ones
+    fn multi_key_config(indices: &[u8]) -> AccountKeyConfig {
+        let private_keys = private_keys();
+        AccountKeyConfig::MultiKey {
+            public_keys: private_keys.iter().map(AnyPrivateKey::public_key).collect(),
+            signatures_required: 2,
+            private_keys: private_keys
+                .into_iter()
+                .enumerate()
+                .map(|(index, private_key)| (index as u8, private_key))
+                .filter(|(index, _)| indices.contains(index))
+                .collect(),
+        }
+    }
+
+    fn private_key_indices(account_key: &AccountKeyConfig) -> Vec<u8> {
+        match account_key {
+            AccountKeyConfig::MultiKey { private_keys, .. } => {
+                private_keys.keys().copied().collect()
+            },
+            AccountKeyConfig::SingleKey { .. } => vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_any_private_key() {
+        let mut key_gen = KeyGen::from_seed([0; 32]);
+        let ed25519 = key_gen.generate_ed25519_private_key();
+        let secp256k1 = key_gen.generate_secp256k1_ecdsa_private_key();
+
+        let parsed = AnyPrivateKey::from_encoded_string(
+            SignatureScheme::Ed25519,
+            &ed25519.to_encoded_string().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(parsed.signature_scheme(), SignatureScheme::Ed25519);
+        assert_eq!(
+            parsed.public_key(),
+            AnyPublicKey::ed25519(ed25519.public_key())
+        );
+        let parsed = AnyPrivateKey::from_encoded_string(
+            SignatureScheme::Secp256k1Ecdsa,
+            &secp256k1.to_encoded_string().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(parsed.signature_scheme(), SignatureScheme::Secp256k1Ecdsa);
+        assert_eq!(
+            parsed.public_key(),
+            AnyPublicKey::secp256k1_ecdsa(secp256k1.public_key())
+        );
+        assert!(AnyPrivateKey::from_encoded_string(SignatureScheme::Ed25519, "0x1234").is_err());
+        assert!(
+            AnyPrivateKey::from_encoded_string(SignatureScheme::Secp256k1Ecdsa, "0x1234").is_err()
+        );
+
+        // The keys are stored in the YAML config
+        let account_key = multi_key_config(&[0, 2]);
+        let parsed: AccountKeyConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&account_key).unwrap()).unwrap();
+        assert_eq!(
+            parsed.authentication_key().unwrap(),
+            account_key.authentication_key().unwrap()
+        );
+        assert_eq!(private_key_indices(&parsed), vec![0, 2]);
+        assert!(parsed.into_signing_key().is_ok());
+    }
+
+    #[test]
+    fn test_parse_any_public_key() {
+        let mut key_gen = KeyGen::from_seed([0; 32]);
+        let ed25519 = key_gen.generate_ed25519_private_key().public_key();
+        let secp256k1 = key_gen.generate_secp256k1_ecdsa_private_key().public_key();
+        let encoded_ed25519 = ed25519.to_encoded_string().unwrap();
+
+        assert_eq!(
+            parse_any_public_key(&format!("ed25519:{}", encoded_ed25519)).unwrap(),
+            AnyPublicKey::ed25519(ed25519)
+        );
+        assert_eq!(
+            parse_any_public_key(&format!(
+                "secp256k1-ecdsa:{}",
+                secp256k1.to_encoded_string().unwrap()
+            ))
+            .unwrap(),
+            AnyPublicKey::secp256k1_ecdsa(secp256k1)
+        );
+        assert!(parse_any_public_key(&encoded_ed25519).is_err());
+        assert!(parse_any_public_key(&format!("secp256r1:{}", encoded_ed25519)).is_err());
+        assert!(parse_any_public_key("ed25519:0x1234").is_err());
+    }
+
+    #[test]
+    fn test_multi_key_into_signing_key() {
+        // Only as many keys as required sign
+        match multi_key_config(&[0, 1, 2]).into_signing_key().unwrap() {
+            AccountSigningKey::MultiKey { private_keys, .. } => assert_eq!(
+                private_keys
+                    .iter()
+                    .map(|(index, _)| *index)
+                    .collect::<Vec<_>>(),
+                vec![0, 1]
+            ),
+            signing_key => panic!("Unexpected signing key {:?}", signing_key),
+        }
+
+        // Fewer private keys than signatures required
+        assert!(multi_key_config(&[2]).into_signing_key().is_err());
+
+        // A private key at the index of another public key
+        let mut account_key = multi_key_config(&[0, 2]);
+        if let AccountKeyConfig::MultiKey { private_keys, .. } = &mut account_key {
+            let private_key = private_keys.remove(&2).unwrap();
+            private_keys.insert(1, private_key);
+        }
+        assert!(account_key.into_signing_key().is_err());
+
+        // A private key at an index out of bounds
+        let mut account_key = multi_key_config(&[0, 2]);
+        if let AccountKeyConfig::MultiKey { private_keys, .. } = &mut account_key {
+            let private_key = private_keys.remove(&2).unwrap();
+            private_keys.insert(3, private_key);
+        }
+        assert!(account_key.into_signing_key().is_err());
+
+        // More signatures required than public keys
+        let mut account_key = multi_key_config(&[0, 1, 2]);
+        if let AccountKeyConfig::MultiKey {
+            signatures_required,
+            ..
+        } = &mut account_key
+        {
+            *signatures_required = 4;
+        }
+        assert!(account_key.into_signing_key().is_err());
+
+        // A single key without its private key
+        let mut account_key = AccountKeyConfig::single_key(private_keys().remove(2));
+        account_key.take_private_keys();
+        assert!(account_key.into_signing_key().is_err());
+    }
+
+    #[test]
+    fn test_multi_key_sign() {
+        let account_key = multi_key_config(&[0, 2]);
+        let authentication_key = account_key.authentication_key().unwrap();
+        let signing_key = account_key.into_signing_key().unwrap();
+        assert_eq!(signing_key.authentication_key(), authentication_key);
+
+        let authenticator = signing_key.sign(&raw_txn(0)).unwrap();
+        assert_eq!(authenticator.authentication_key(), Some(authentication_key));
+        assert_eq!(authenticator.number_of_signatures(), 2);
+        authenticator.verify(&raw_txn(0)).unwrap();
+        assert!(authenticator.verify(&raw_txn(1)).is_err());
+
+        let signed_txn = signing_key.sign_transaction(raw_txn(0)).unwrap();
+        signed_txn.verify_signature().unwrap();
+
+        // Simulations are signed by as many keys, without valid signatures
+        let simulation = signing_key.simulation_authenticator();
+        assert_eq!(simulation.authentication_key(), Some(authentication_key));
+        assert_eq!(simulation.number_of_signatures(), 2);
+        assert!(simulation.verify(&raw_txn(0)).is_err());
+    }
+
+    #[test]
+    fn test_single_key_simulation_authenticator() {
+        let signing_key = AccountKeyConfig::single_key(private_keys().remove(2))
+            .into_signing_key()
+            .unwrap();
+        signing_key
+            .sign(&raw_txn(0))
+            .unwrap()
+            .verify(&raw_txn(0))
+            .unwrap();
+
+        let simulation = signing_key.simulation_authenticator();
+        assert_eq!(
+            simulation.authentication_key(),
+            Some(signing_key.authentication_key())
+        );
+        assert!(simulation.verify(&raw_txn(0)).is_err());
+    }
+
+    #[test]
+    fn test_add_private_keys_of() {
+        let public_keys: Vec<_> = private_keys()
+            .iter()
+            .map(AnyPrivateKey::public_key)
+            .collect();
+
+        // The keys of a multi-key account can be given one at a time
+        let mut account_key =
+            AccountKeyConfig::multi_key(public_keys.clone(), 2, private_keys().remove(2)).unwrap();
+        account_key.add_private_keys_of(
+            AccountKeyConfig::multi_key(public_keys.clone(), 2, private_keys().remove(0)).unwrap(),
+        );
+        assert_eq!(private_key_indices(&account_key), vec![0, 2]);
+        assert!(account_key.into_signing_key().is_ok());
+
+        // But not along with those of another account
+        let mut account_key =
+            AccountKeyConfig::multi_key(public_keys.clone(), 2, private_keys().remove(2)).unwrap();
+        account_key.add_private_keys_of(
+            AccountKeyConfig::multi_key(public_keys.clone(), 1, private_keys().remove(0)).unwrap(),
+        );
+        assert_eq!(private_key_indices(&account_key), vec![2]);
+
+        // The private key must be one of the public keys
+        let mut key_gen = KeyGen::from_seed([1; 32]);
+        assert!(AccountKeyConfig::multi_key(
+            public_keys.clone(),
+            2,
+            AnyPrivateKey::Ed25519(key_gen.generate_ed25519_private_key())
+        )
+        .is_err());
+        assert!(AccountKeyConfig::multi_key(public_keys, 4, private_keys().remove(0)).is_err());
+    }
+}
@@ -1,11 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::utils::{explorer_transaction_link, fund_account};
+use super::utils::{fund_account, submit_signed_transaction};
 use crate::{
     common::{
         init::Network,
+        key_encryption::{read_passphrase, EncryptedKeys},
         local_simulation,
+        signing::{
+            single_signer_authenticator, AccountKeyConfig, AccountSigningKey, AnyPrivateKey,
+        },
         utils::{
             check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
             get_account_with_state, get_auth_key, get_sequence_number, parse_json_file,
@@ -17,13 +21,15 @@ use crate::{
     config::GlobalConfig,
     genesis::git::from_yaml,
     move_tool::{ArgWithType, FunctionArgType, MemberId},
+    transaction::PartiallySignedTransaction,
 };
 use anyhow::{bail, Context};
 use aptos_api_types::ViewFunction;
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     encoding_type::{EncodingError, EncodingType},
-    x25519, PrivateKey, ValidCryptoMaterialStringExt,
+    hash::CryptoHash,
+    secp256k1_ecdsa, x25519, PrivateKey, ValidCryptoMaterialStringExt,
 };
 use aptos_global_constants::adjust_gas_headroom;
 use aptos_keygen::KeyGen;
@@ -36,19 +42,21 @@ use aptos_rest_client::{
 };
 use aptos_sdk::{
     transaction_builder::TransactionFactory,
-    types::{HardwareWalletAccount, HardwareWalletType, LocalAccount, TransactionSigner},
+    types::{HardwareWalletAccount, HardwareWalletType, TransactionSigner},
 };
 use aptos_types::{
     chain_id::ChainId,
     transaction::{
-        authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload, Script,
-        SignedTransaction, TransactionArgument, TransactionPayload, TransactionStatus,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
+        EntryFunction, MultisigTransactionPayload, Script, SignedTransaction, TransactionArgument,
+        TransactionPayload, TransactionStatus,
     },
 };
 use aptos_vm_types::output::VMOutput;
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
 use hex::FromHexError;
+use itertools::Itertools;
 use move_core_types::{
     account_address::AccountAddress, language_storage::TypeTag, vm_status::VMStatus,
 };
@@ -62,7 +70,7 @@ use std::{
     convert::TryFrom,
     fmt::{Debug, Display, Formatter},
     fs::OpenOptions,
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
@@ -250,6 +258,13 @@ pub struct ProfileConfig {
     /// Public key for commands
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_key: Option<Ed25519PublicKey>,
+    /// Keys for commands, if the account uses a `SingleKey` or `MultiKey` authentication key
+    /// rather than the Ed25519 keys above
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_key: Option<AccountKeyConfig>,
+    /// Private keys above encrypted with a passphrase, if the profile is encrypted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encrypted_keys: Option<EncryptedKeys>,
     /// Account for commands
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account: Option<AccountAddress>,
@@ -268,9 +283,13 @@ pub struct ProfileConfig {
 #[derive(Debug, Serialize)]
 pub struct ProfileSummary {
     pub has_private_key: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub has_encrypted_private_key: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_key: Option<Ed25519PublicKey>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub authentication_key: Option<AuthenticationKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub account: Option<AccountAddress>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rest_url: Option<String>,
@@ -281,8 +300,13 @@ pub struct ProfileSummary {
 impl From<&ProfileConfig> for ProfileSummary {
     fn from(config: &ProfileConfig) -> Self {
         ProfileSummary {
-            has_private_key: config.private_key.is_some(),
+            has_private_key: config.has_private_key(),
+            has_encrypted_private_key: config.encrypted_keys.is_some(),
             public_key: config.public_key.clone(),
+            authentication_key: config
+                .account_key
+                .as_ref()
+                .and_then(|account_key| account_key.authentication_key().ok()),
             account: config.account,
             rest_url: config.rest_url.clone(),
             faucet_url: config.faucet_url.clone(),
@@ -290,6 +314,93 @@ impl From<&ProfileConfig> for ProfileSummary {
     }
 }
 
+/// The private keys of a profile, as they're encrypted
+#[derive(Serialize, Deserialize)]
+struct ProfilePrivateKeys {
+    private_key: Option<Ed25519PrivateKey>,
+    account_private_keys: BTreeMap<u8, AnyPrivateKey>,
+}
+
+impl ProfileConfig {
+    /// Address derived from the keys of the profile, whichever their signature scheme, which is
+    /// the address of the account unless its authentication key was rotated
+    pub fn derived_address(&self) -> CliTypedResult<Option<AccountAddress>> {
+        if let Some(account_key) = &self.account_key {
+            return Ok(Some(account_key.authentication_key()?.account_address()));
+        }
+        Ok(self
+            .private_key
+            .as_ref()
+            .map(|private_key| private_key.public_key())
+            .or_else(|| self.public_key.clone())
+            .map(|public_key| account_address_from_public_key(&public_key)))
+    }
+
+    /// Whether the profile holds a private key, possibly encrypted
+    pub fn has_private_key(&self) -> bool {
+        self.private_key.is_some()
+            || self
+                .account_key
+                .as_ref()
+                .map_or(false, AccountKeyConfig::has_private_key)
+            || self.encrypted_keys.is_some()
+    }
+
+    /// Encrypts the private keys of the profile with the passphrase
+    ///
+    /// The public keys stay in plaintext, so that only signing requires the passphrase.
+    pub fn encrypt_keys(&mut self, passphrase: &str) -> CliTypedResult<()> {
+        if self.encrypted_keys.is_some() {
+            return Err(CliError::CommandArgumentError(
+                "The private keys of the profile are already encrypted".to_string(),
+            ));
+        }
+        if !self.has_private_key() {
+            return Err(CliError::CommandArgumentError(
+                "The profile has no private key to encrypt".to_string(),
+            ));
+        }
+
+        let keys = ProfilePrivateKeys {
+            private_key: self.private_key.take(),
+            account_private_keys: self
+                .account_key
+                .as_mut()
+                .map(AccountKeyConfig::take_private_keys)
+                .unwrap_or_default(),
+        };
+        let plaintext = bcs::to_bytes(&keys).map_err(|err| CliError::BCS("private keys", err))?;
+        self.encrypted_keys = Some(EncryptedKeys::encrypt(&plaintext, passphrase)?);
+        Ok(())
+    }
+
+    /// Decrypts the private keys of the profile, if they're encrypted, reading the passphrase
+    /// from the environment or prompting for it
+    pub fn decrypt_keys(&mut self) -> CliTypedResult<()> {
+        if self.encrypted_keys.is_some() {
+            self.decrypt_keys_with_passphrase(&read_passphrase(false)?)?;
+        }
+        Ok(())
+    }
+
+    pub fn decrypt_keys_with_passphrase(&mut self, passphrase: &str) -> CliTypedResult<()> {
+        let Some(encrypted_keys) = &self.encrypted_keys else {
+            return Err(CliError::CommandArgumentError(
+                "The private keys of the profile aren't encrypted".to_string(),
+            ));
+        };
+
+        let keys: ProfilePrivateKeys = bcs::from_bytes(&encrypted_keys.decrypt(passphrase)?)
+            .map_err(|err| CliError::BCS("private keys", err))?;
+        self.private_key = keys.private_key;
+        if let Some(account_key) = &mut self.account_key {
+            account_key.restore_private_keys(keys.account_private_keys);
+        }
+        self.encrypted_keys = None;
+        Ok(())
+    }
+}
+
 impl Default for CliConfig {
     fn default() -> Self {
         CliConfig {
@@ -361,6 +472,20 @@ impl CliConfig {
         }
     }
 
+    /// Loads a profile like [`CliConfig::load_profile`], with its private keys decrypted if
+    /// they're encrypted
+    pub fn load_profile_with_keys(
+        profile: Option<&str>,
+        mode: ConfigSearchMode,
+    ) -> CliTypedResult<Option<ProfileConfig>> {
+        Self::load_profile(profile, mode)?
+            .map(|mut profile_config| {
+                profile_config.decrypt_keys()?;
+                Ok(profile_config)
+            })
+            .transpose()
+    }
+
     pub fn remove_profile(&mut self, profile: &str) -> Option<ProfileConfig> {
         if let Some(ref mut profiles) = self.profiles {
             profiles.remove(&profile.to_string())
@@ -827,7 +952,7 @@ impl PrivateKeyInputOptions {
                 let address = account_address_from_public_key(&key.public_key());
                 Ok((key, address))
             }
-        } else if let Some((Some(key), maybe_config_address)) = CliConfig::load_profile(
+        } else if let Some((Some(key), maybe_config_address)) = CliConfig::load_profile_with_keys(
             profile.profile_name(),
             ConfigSearchMode::CurrentDirAndParents,
         )?
@@ -848,6 +973,47 @@ impl PrivateKeyInputOptions {
         }
     }
 
+    /// Extract the key to sign transactions with from CLI args with fallback to config, along
+    /// with the address of the account
+    ///
+    /// Unlike [`PrivateKeyInputOptions::extract_private_key_and_address`], this supports
+    /// profiles of accounts using a `SingleKey` or `MultiKey` authentication key.
+    pub fn extract_signing_key_and_address(
+        &self,
+        encoding: EncodingType,
+        profile: &ProfileOptions,
+        maybe_address: Option<AccountAddress>,
+    ) -> CliTypedResult<(AccountSigningKey, AccountAddress)> {
+        if self.extract_private_key_cli(encoding)?.is_none() {
+            if let Some(mut profile_config) = CliConfig::load_profile(
+                profile.profile_name(),
+                ConfigSearchMode::CurrentDirAndParents,
+            )? {
+                let signing_key = if profile_config.account_key.is_some() {
+                    // Only decrypt here for such profiles, to prompt for the passphrase only once
+                    profile_config.decrypt_keys()?;
+                    profile_config
+                        .account_key
+                        .take()
+                        .map(AccountKeyConfig::into_signing_key)
+                        .transpose()?
+                } else {
+                    ledger_signing_key(&profile_config)
+                };
+                if let Some(signing_key) = signing_key {
+                    let address = maybe_address
+                        .or(profile_config.account)
+                        .unwrap_or_else(|| signing_key.derived_address());
+                    return Ok((signing_key, address));
+                }
+            }
+        }
+
+        let (private_key, address) =
+            self.extract_private_key_and_address(encoding, profile, maybe_address)?;
+        Ok((AccountSigningKey::Ed25519(private_key), address))
+    }
+
     /// Extract private key from CLI args with fallback to config
     pub fn extract_private_key(
         &self,
@@ -856,7 +1022,7 @@ impl PrivateKeyInputOptions {
     ) -> CliTypedResult<Ed25519PrivateKey> {
         if let Some(key) = self.extract_private_key_cli(encoding)? {
             Ok(key)
-        } else if let Some(Some(private_key)) = CliConfig::load_profile(
+        } else if let Some(Some(private_key)) = CliConfig::load_profile_with_keys(
             profile.profile_name(),
             ConfigSearchMode::CurrentDirAndParents,
         )?
@@ -870,6 +1036,24 @@ impl PrivateKeyInputOptions {
         }
     }
 
+    /// Extract a secp256k1 ECDSA private key from CLI args
+    pub fn extract_secp256k1_ecdsa_private_key_cli(
+        &self,
+        encoding: EncodingType,
+    ) -> CliTypedResult<Option<secp256k1_ecdsa::PrivateKey>> {
+        if let Some(ref file) = self.private_key_file {
+            Ok(Some(
+                encoding.load_key("--private-key-file", file.as_path())?,
+            ))
+        } else if let Some(ref key) = self.private_key {
+            Ok(Some(
+                encoding.decode_key("--private-key", key.as_bytes().to_vec())?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Extract private key from CLI args
     pub fn extract_private_key_cli(
         &self,
@@ -1239,17 +1423,8 @@ impl FromStr for AccountAddressWrapper {
 pub fn load_account_arg(str: &str) -> Result<AccountAddress, CliError> {
     if let Ok(account_address) = AccountAddress::from_str(str) {
         Ok(account_address)
-    } else if let Some(Some(account_address)) =
-        CliConfig::load_profile(Some(str), ConfigSearchMode::CurrentDirAndParents)?
-            .map(|p| p.account)
-    {
+    } else if let Some(account_address) = load_profile_address(str)? {
         Ok(account_address)
-    } else if let Some(Some(private_key)) =
-        CliConfig::load_profile(Some(str), ConfigSearchMode::CurrentDirAndParents)?
-            .map(|p| p.private_key)
-    {
-        let public_key = private_key.public_key();
-        Ok(account_address_from_public_key(&public_key))
     } else {
         Err(CliError::CommandArgumentError(
             "'--account' or '--profile' after using aptos init must be provided".to_string(),
@@ -1257,6 +1432,18 @@ pub fn load_account_arg(str: &str) -> Result<AccountAddress, CliError> {
     }
 }
 
+/// Loads the account of a profile, or the address derived from its keys
+fn load_profile_address(profile: &str) -> CliTypedResult<Option<AccountAddress>> {
+    match CliConfig::load_profile(Some(profile), ConfigSearchMode::CurrentDirAndParents)? {
+        Some(ProfileConfig {
+            account: Some(account),
+            ..
+        }) => Ok(Some(account)),
+        Some(profile) => profile.derived_address(),
+        None => Ok(None),
+    }
+}
+
 /// A wrapper around `AccountAddress` to allow for "_"
 #[derive(Clone, Copy, Debug)]
 pub struct MoveManifestAccountWrapper {
@@ -1279,12 +1466,8 @@ pub fn load_manifest_account_arg(str: &str) -> Result<Option<AccountAddress>, Cl
         Ok(None)
     } else if let Ok(account_address) = AccountAddress::from_str(str) {
         Ok(Some(account_address))
-    } else if let Some(Some(private_key)) =
-        CliConfig::load_profile(Some(str), ConfigSearchMode::CurrentDirAndParents)?
-            .map(|p| p.private_key)
-    {
-        let public_key = private_key.public_key();
-        Ok(Some(account_address_from_public_key(&public_key)))
+    } else if let Some(account_address) = load_profile_address(str)? {
+        Ok(Some(account_address))
     } else {
         Err(CliError::CommandArgumentError(
             "Invalid Move manifest account address".to_string(),
@@ -1596,12 +1779,187 @@ impl Default for GasOptions {
     }
 }
 
+/// Options to sponsor a transaction, so that another account than the sender pays for its gas
+#[derive(Debug, Default, Parser)]
+pub struct FeePayerOptions {
+    /// Profile of the account paying for the gas of the transaction
+    ///
+    /// The fee payer signs the transaction in addition to the sender.
+    #[clap(long)]
+    pub(crate) fee_payer_profile: Option<String>,
+    /// Address of the account paying for the gas of the transaction
+    ///
+    /// Defaults to the account of `--fee-payer-profile`. Without `--fee-payer-profile`, there's
+    /// no key to sign with for the fee payer, so write the transaction with `--output-unsigned`
+    /// and have the fee payer co-sign it with `aptos transaction sign`.
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) fee_payer_address: Option<AccountAddress>,
+}
+
+impl FeePayerOptions {
+    pub fn is_set(&self) -> bool {
+        self.fee_payer_profile.is_some() || self.fee_payer_address.is_some()
+    }
+
+    /// The fee payer of the transaction, if it's sponsored
+    pub fn fee_payer(&self) -> CliTypedResult<Option<FeePayer>> {
+        let (private_key, address) = match (&self.fee_payer_profile, self.fee_payer_address) {
+            (Some(profile), address) => {
+                let (private_key, profile_address) = load_signer_profile(profile)?;
+                let address = address.or(profile_address).ok_or_else(|| {
+                    CliError::CommandArgumentError(
+                        "The fee payer profile has no account, use --fee-payer-address".to_string(),
+                    )
+                })?;
+                (private_key, address)
+            },
+            (None, Some(address)) => (None, address),
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(FeePayer {
+            address,
+            private_key,
+        }))
+    }
+}
+
+/// The key of a profile to sign with, if it holds one, and the address of its account
+fn load_signer_profile(
+    profile: &str,
+) -> CliTypedResult<(Option<AccountSigningKey>, Option<AccountAddress>)> {
+    let mut profile = ProfileOptions {
+        profile: Some(profile.to_string()),
+    }
+    .profile()?;
+    profile.decrypt_keys()?;
+
+    let ledger_key = ledger_signing_key(&profile);
+    let private_key = match (profile.account_key, profile.private_key) {
+        (Some(account_key), _) if account_key.has_private_key() => {
+            Some(account_key.into_signing_key()?)
+        },
+        (None, Some(private_key)) => Some(AccountSigningKey::Ed25519(private_key)),
+        _ => ledger_key,
+    };
+    let address = profile
+        .account
+        .or_else(|| private_key.as_ref().map(AccountSigningKey::derived_address))
+        .or_else(|| {
+            profile
+                .public_key
+                .as_ref()
+                .map(account_address_from_public_key)
+        });
+    Ok((private_key, address))
+}
+
+/// The key of a hardware wallet profile, which only holds the derivation path of its key
+fn ledger_signing_key(profile: &ProfileConfig) -> Option<AccountSigningKey> {
+    match profile {
+        ProfileConfig {
+            private_key: None,
+            account_key: None,
+            encrypted_keys: None,
+            derivation_path: Some(derivation_path),
+            public_key: Some(public_key),
+            ..
+        } => Some(AccountSigningKey::Ledger {
+            derivation_path: derivation_path.clone(),
+            public_key: public_key.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// Options to add secondary signers to a transaction, for functions taking several `&signer`
+/// arguments
+#[derive(Debug, Default, Parser)]
+pub struct MultiAgentOptions {
+    /// Profiles of the secondary signers of the transaction
+    ///
+    /// The secondary signers are the `&signer` arguments after the first one, which is the
+    /// sender, in the order of the arguments.
+    #[clap(long, num_args = 1.., conflicts_with = "secondary_signer_addresses")]
+    pub(crate) secondary_signer_profiles: Vec<String>,
+    /// Addresses of the secondary signers of the transaction
+    ///
+    /// There's no key to sign with for these signers, so write the transaction with
+    /// `--output-unsigned` and have each of them co-sign it with `aptos transaction sign`.
+    #[clap(long, num_args = 1.., value_parser = crate::common::types::load_account_arg)]
+    pub(crate) secondary_signer_addresses: Vec<AccountAddress>,
+}
+
+impl MultiAgentOptions {
+    /// The secondary signers of the transaction, in the order of their `&signer` arguments
+    pub fn secondary_signers(&self) -> CliTypedResult<Vec<SecondarySigner>> {
+        if !self.secondary_signer_addresses.is_empty() {
+            return Ok(self
+                .secondary_signer_addresses
+                .iter()
+                .map(|address| SecondarySigner {
+                    address: *address,
+                    private_key: None,
+                })
+                .collect());
+        }
+
+        self.secondary_signer_profiles
+            .iter()
+            .map(|profile| {
+                let (private_key, address) = load_signer_profile(profile)?;
+                let address = address.ok_or_else(|| {
+                    CliError::CommandArgumentError(format!(
+                        "The secondary signer profile {} has no account",
+                        profile
+                    ))
+                })?;
+                Ok(SecondarySigner {
+                    address,
+                    private_key,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An account signing a multi-agent transaction in addition to the sender
+pub struct SecondarySigner {
+    pub address: AccountAddress,
+    /// Only known if the secondary signer can sign the transaction here
+    pub private_key: Option<AccountSigningKey>,
+}
+
+/// The account paying for the gas of a sponsored transaction
+pub struct FeePayer {
+    pub address: AccountAddress,
+    /// Only known if the fee payer can sign the transaction here
+    pub private_key: Option<AccountSigningKey>,
+}
+
 #[derive(Debug)]
 pub enum AccountType {
     Local,
     HardwareWallet,
 }
 
+/// What signs the transactions of the sender, hardware wallets only hold Ed25519 keys
+enum SenderKey {
+    Local(AccountSigningKey),
+    HardwareWallet(Ed25519PublicKey),
+}
+
+impl SenderKey {
+    fn simulation_authenticator(&self) -> AccountAuthenticator {
+        match self {
+            SenderKey::Local(signing_key) => signing_key.simulation_authenticator(),
+            SenderKey::HardwareWallet(public_key) => AccountAuthenticator::ed25519(
+                public_key.clone(),
+                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            ),
+        }
+    }
+}
+
 /// Common options for interacting with an account for a validator
 #[derive(Debug, Default, Parser)]
 pub struct TransactionOptions {
@@ -1624,6 +1982,8 @@ pub struct TransactionOptions {
     pub(crate) gas_options: GasOptions,
     #[clap(flatten)]
     pub(crate) prompt_options: PromptOptions,
+    #[clap(flatten)]
+    pub(crate) fee_payer_options: FeePayerOptions,
 
     /// If this option is set, simulate the transaction locally.
     #[clap(long)]
@@ -1637,6 +1997,15 @@ pub struct TransactionOptions {
     /// flamegraphs that reflect the gas usage.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// If this option is set, write the transaction unsigned to this file instead of submitting
+    /// it, e.g. to sign it on an air-gapped machine with `aptos transaction sign`.
+    ///
+    /// The transaction is written as JSON if the file name ends with `.json`, and as BCS
+    /// otherwise. The reported hash is the one of the unsigned transaction. Commands submitting
+    /// several transactions, e.g. for several stake pools, don't support it.
+    #[clap(long, value_parser)]
+    pub(crate) output_unsigned: Option<PathBuf>,
 }
 
 impl TransactionOptions {
@@ -1654,7 +2023,7 @@ impl TransactionOptions {
             self.profile_options.profile_name(),
             ConfigSearchMode::CurrentDirAndParents,
         )? {
-            if profile.private_key.is_some() {
+            if profile.has_private_key() {
                 Ok(AccountType::Local)
             } else {
                 Ok(AccountType::HardwareWallet)
@@ -1669,24 +2038,60 @@ impl TransactionOptions {
 
     /// Retrieves the private key and the associated address
     /// TODO: Cache this information
-    pub fn get_key_and_address(&self) -> CliTypedResult<(Ed25519PrivateKey, AccountAddress)> {
-        self.private_key_options.extract_private_key_and_address(
+    pub fn get_public_key_and_address(&self) -> CliTypedResult<(Ed25519PublicKey, AccountAddress)> {
+        self.private_key_options.extract_public_key_and_address(
             self.encoding_options.encoding,
             &self.profile_options,
             self.sender_account,
         )
     }
 
-    pub fn get_public_key_and_address(&self) -> CliTypedResult<(Ed25519PublicKey, AccountAddress)> {
-        self.private_key_options.extract_public_key_and_address(
+    /// Retrieves the key to sign transactions with, of any signature scheme, and the associated
+    /// address
+    pub fn get_signing_key_and_address(
+        &self,
+    ) -> CliTypedResult<(AccountSigningKey, AccountAddress)> {
+        self.private_key_options.extract_signing_key_and_address(
             self.encoding_options.encoding,
             &self.profile_options,
             self.sender_account,
         )
     }
 
+    /// Retrieves what signs the transactions of the sender, and the associated address
+    fn get_sender_key_and_address(&self) -> CliTypedResult<(SenderKey, AccountAddress)> {
+        match self.get_transaction_account_type()? {
+            AccountType::Local => {
+                let (signing_key, address) = self.get_signing_key_and_address()?;
+                Ok((SenderKey::Local(signing_key), address))
+            },
+            AccountType::HardwareWallet => {
+                let (public_key, address) = self.get_public_key_and_address()?;
+                Ok((SenderKey::HardwareWallet(public_key), address))
+            },
+        }
+    }
+
     pub fn sender_address(&self) -> CliTypedResult<AccountAddress> {
-        Ok(self.get_key_and_address()?.1)
+        // Don't prompt for the passphrase of encrypted profiles, only to get their account
+        if self
+            .private_key_options
+            .extract_private_key_cli(self.encoding_options.encoding)?
+            .is_none()
+        {
+            if let Some(ProfileConfig {
+                encrypted_keys: Some(_),
+                account: Some(account),
+                ..
+            }) = CliConfig::load_profile(
+                self.profile_options.profile_name(),
+                ConfigSearchMode::CurrentDirAndParents,
+            )? {
+                return Ok(self.sender_account.unwrap_or(account));
+            }
+        }
+
+        Ok(self.get_signing_key_and_address()?.1)
     }
 
     pub fn get_public_key(&self) -> CliTypedResult<Ed25519PublicKey> {
@@ -1717,14 +2122,19 @@ impl TransactionOptions {
             .into_inner())
     }
 
-    /// Submit a transaction
-    pub async fn submit_transaction(
+    /// Fetches what's needed to build a transaction of the sender, and estimates its gas if it
+    /// isn't given.
+    ///
+    /// Returns the factory of the transaction, along with the sequence number of the sender.
+    async fn transaction_factory(
         &self,
-        payload: TransactionPayload,
-    ) -> CliTypedResult<Transaction> {
-        let client = self.rest_client()?;
-        let (sender_public_key, sender_address) = self.get_public_key_and_address()?;
-
+        client: &Client,
+        payload: &TransactionPayload,
+        sender_address: AccountAddress,
+        sender_authenticator: AccountAuthenticator,
+        secondary_signers: &[SecondarySigner],
+        fee_payer: Option<&FeePayer>,
+    ) -> CliTypedResult<(TransactionFactory, u64)> {
         // Ask to confirm price if the gas unit price is estimated above the lowest value when
         // it is automatically estimated
         let ask_to_confirm_price;
@@ -1739,7 +2149,7 @@ impl TransactionOptions {
         };
 
         // Get sequence number for account
-        let (account, state) = get_account_with_state(&client, sender_address).await?;
+        let (account, state) = get_account_with_state(client, sender_address).await?;
         let sequence_number = account.sequence_number;
 
         // Retrieve local time, and ensure it's within an expected skew of the blockchain
@@ -1777,11 +2187,42 @@ impl TransactionOptions {
                 .expiration_timestamp_secs(expiration_time_secs)
                 .build();
 
-            let signed_transaction = SignedTransaction::new(
-                unsigned_transaction,
-                sender_public_key.clone(),
-                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
-            );
+            let simulation_authenticator = |private_key: &Option<AccountSigningKey>| {
+                private_key.as_ref().map_or(
+                    AccountAuthenticator::NoAccountAuthenticator,
+                    AccountSigningKey::simulation_authenticator,
+                )
+            };
+            let secondary_signer_addresses = secondary_signers
+                .iter()
+                .map(|signer| signer.address)
+                .collect::<Vec<_>>();
+            let secondary_signer_authenticators = secondary_signers
+                .iter()
+                .map(|signer| simulation_authenticator(&signer.private_key))
+                .collect::<Vec<_>>();
+
+            let signed_transaction = match fee_payer {
+                // The gas is charged to the fee payer, so simulate it as a sponsored transaction
+                Some(fee_payer) => SignedTransaction::new_fee_payer(
+                    unsigned_transaction,
+                    sender_authenticator,
+                    secondary_signer_addresses,
+                    secondary_signer_authenticators,
+                    fee_payer.address,
+                    simulation_authenticator(&fee_payer.private_key),
+                ),
+                None if !secondary_signers.is_empty() => SignedTransaction::new_multi_agent(
+                    unsigned_transaction,
+                    sender_authenticator,
+                    secondary_signer_addresses,
+                    secondary_signer_authenticators,
+                ),
+                None => SignedTransaction::new_signed_transaction(
+                    unsigned_transaction,
+                    single_signer_authenticator(sender_authenticator),
+                ),
+            };
 
             let txns = client
                 .simulate_with_gas_estimation(&signed_transaction, true, false)
@@ -1819,18 +2260,154 @@ impl TransactionOptions {
             .with_max_gas_amount(max_gas)
             .with_transaction_expiration_time(self.gas_options.expiration_secs);
 
+        Ok((transaction_factory, sequence_number))
+    }
+
+    /// Submit a transaction, or write it unsigned to the `--output-unsigned` file if it's set
+    pub async fn submit_transaction(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        self.submit_multi_agent_transaction(payload, vec![]).await
+    }
+
+    /// Like [`Self::submit_transaction`], for a transaction signed by secondary signers too
+    pub async fn submit_multi_agent_transaction(
+        &self,
+        payload: TransactionPayload,
+        secondary_signers: Vec<SecondarySigner>,
+    ) -> CliTypedResult<TransactionSummary> {
+        if let Some(output_file) = &self.output_unsigned {
+            return self
+                .output_unsigned_transaction(payload, &secondary_signers, output_file)
+                .await;
+        }
+        self.sign_and_submit_transaction(payload, secondary_signers)
+            .await
+            .map(TransactionSummary::from)
+    }
+
+    /// Submit a transaction, and return it as committed for commands reading its output
+    ///
+    /// There is no output until the transaction is signed and committed, so `--output-unsigned`
+    /// isn't supported here.
+    pub async fn submit_transaction_for_output(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<Transaction> {
+        if self.output_unsigned.is_some() {
+            return Err(CliError::CommandArgumentError(
+                "--output-unsigned isn't supported by this command, as it reads the output of \
+                the committed transaction"
+                    .to_string(),
+            ));
+        }
+        self.sign_and_submit_transaction(payload, vec![]).await
+    }
+
+    /// Fails if `--output-unsigned` is set for a command submitting several transactions
+    ///
+    /// The transactions would all be written to the same file, with the same sequence number, so
+    /// only commands submitting a single transaction support `--output-unsigned`.
+    pub fn check_output_unsigned(&self, num_transactions: usize) -> CliTypedResult<()> {
+        if self.output_unsigned.is_some() && num_transactions > 1 {
+            return Err(CliError::CommandArgumentError(format!(
+                "--output-unsigned only supports a single transaction, but this command would \
+                submit {} transactions",
+                num_transactions
+            )));
+        }
+        Ok(())
+    }
+
+    /// Signs a transaction with the sender's and secondary signers' keys, and submits it
+    async fn sign_and_submit_transaction(
+        &self,
+        payload: TransactionPayload,
+        secondary_signers: Vec<SecondarySigner>,
+    ) -> CliTypedResult<Transaction> {
+        let transaction = self.sign_transaction(payload, secondary_signers).await?;
+        self.submit_signed_transaction(&transaction).await
+    }
+
+    /// Signs a transaction with the sender's and secondary signers' keys, without submitting it
+    pub async fn sign_transaction(
+        &self,
+        payload: TransactionPayload,
+        secondary_signers: Vec<SecondarySigner>,
+    ) -> CliTypedResult<SignedTransaction> {
+        let client = self.rest_client()?;
+        let (sender_key, sender_address) = self.get_sender_key_and_address()?;
+        let fee_payer = self.fee_payer_options.fee_payer()?;
+        let (transaction_factory, sequence_number) = self
+            .transaction_factory(
+                &client,
+                &payload,
+                sender_address,
+                sender_key.simulation_authenticator(),
+                &secondary_signers,
+                fee_payer.as_ref(),
+            )
+            .await?;
+        let transaction_builder = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(sequence_number);
+        let has_other_signers = fee_payer.is_some() || !secondary_signers.is_empty();
+
         // Sign it with the appropriate signer
-        let transaction = match self.get_transaction_account_type() {
-            Ok(AccountType::Local) => {
-                let (private_key, _) = self.get_key_and_address()?;
-                let sender_account =
-                    &mut LocalAccount::new(sender_address, private_key, sequence_number);
-                sender_account.sign_with_transaction_builder(transaction_factory.payload(payload))
+        let transaction = match sender_key {
+            sender_key if has_other_signers => {
+                let signing_key = match sender_key {
+                    SenderKey::Local(signing_key) => signing_key,
+                    SenderKey::HardwareWallet(public_key) => {
+                        let derivation_path =
+                            self.profile_options.derivation_path()?.ok_or_else(|| {
+                                CliError::CommandArgumentError(
+                                    "The profile has no derivation path to sign with".to_string(),
+                                )
+                            })?;
+                        AccountSigningKey::Ledger {
+                            derivation_path,
+                            public_key,
+                        }
+                    },
+                };
+                let mut transaction = PartiallySignedTransaction::new(
+                    transaction_builder.build(),
+                    secondary_signers
+                        .iter()
+                        .map(|signer| signer.address)
+                        .collect(),
+                    fee_payer.as_ref().map(|fee_payer| fee_payer.address),
+                );
+                transaction.sign(&signing_key, sender_address)?;
+                let other_signers = secondary_signers
+                    .into_iter()
+                    .map(|signer| (signer.address, signer.private_key))
+                    .chain(fee_payer.map(|fee_payer| (fee_payer.address, fee_payer.private_key)));
+                for (address, private_key) in other_signers {
+                    if let Some(private_key) = private_key {
+                        transaction.sign(&private_key, address)?;
+                    }
+                }
+
+                let missing_signers = transaction.missing_signers();
+                transaction.into_signed_transaction().ok_or_else(|| {
+                    CliError::CommandArgumentError(format!(
+                        "There is no private key to sign with for {}, write the transaction with \
+                        --output-unsigned to have them co-sign it with `aptos transaction sign`",
+                        missing_signers.iter().join(", ")
+                    ))
+                })?
             },
-            Ok(AccountType::HardwareWallet) => {
+            SenderKey::Local(signing_key) => {
+                signing_key.sign_transaction(transaction_builder.build())?
+            },
+            SenderKey::HardwareWallet(public_key) => {
                 let sender_account = &mut HardwareWalletAccount::new(
                     sender_address,
-                    sender_public_key,
+                    public_key,
                     self.profile_options
                         .derivation_path()
                         .expect("derivative path is missing from profile")
@@ -1838,33 +2415,84 @@ impl TransactionOptions {
                     HardwareWalletType::Ledger,
                     sequence_number,
                 );
-                sender_account
-                    .sign_with_transaction_builder(transaction_factory.payload(payload))?
+                sender_account.sign_with_transaction_builder(transaction_builder)?
             },
-            Err(err) => return Err(err),
         };
+        Ok(transaction)
+    }
 
-        // Submit the transaction, printing out a useful transaction link
-        client
-            .submit_bcs(&transaction)
-            .await
-            .map_err(|err| CliError::ApiError(err.to_string()))?;
-        let transaction_hash = transaction.clone().committed_hash();
+    /// Submits a signed transaction, and waits for it to be committed
+    pub async fn submit_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+    ) -> CliTypedResult<Transaction> {
         let network = self
             .profile_options
             .profile()
             .ok()
             .and_then(|profile| profile.network);
+        submit_signed_transaction(&self.rest_client()?, transaction, network).await
+    }
+
+    /// Builds a transaction without signing it, and writes it to `output_file`
+    ///
+    /// The transaction can then be signed offline with `aptos transaction sign`.
+    pub async fn output_unsigned_transaction(
+        &self,
+        payload: TransactionPayload,
+        secondary_signers: &[SecondarySigner],
+        output_file: &Path,
+    ) -> CliTypedResult<TransactionSummary> {
+        check_if_file_exists(output_file, self.prompt_options)?;
+
+        let client = self.rest_client()?;
+        let (sender_key, sender_address) = self.get_sender_key_and_address()?;
+        let fee_payer = self.fee_payer_options.fee_payer()?;
+        let (transaction_factory, sequence_number) = self
+            .transaction_factory(
+                &client,
+                &payload,
+                sender_address,
+                sender_key.simulation_authenticator(),
+                secondary_signers,
+                fee_payer.as_ref(),
+            )
+            .await?;
+        let gas_unit_price = transaction_factory.get_gas_unit_price();
+        let raw_txn = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(sequence_number)
+            .build();
+
+        PartiallySignedTransaction::new(
+            raw_txn.clone(),
+            secondary_signers
+                .iter()
+                .map(|signer| signer.address)
+                .collect(),
+            fee_payer.map(|fee_payer| fee_payer.address),
+        )
+        .write(output_file, "Unsigned transaction")?;
         eprintln!(
-            "Transaction submitted: {}",
-            explorer_transaction_link(transaction_hash, network)
+            "Unsigned transaction written to {}, sign it with `aptos transaction sign`",
+            output_file.display()
         );
-        let response = client
-            .wait_for_signed_transaction(&transaction)
-            .await
-            .map_err(|err| CliError::ApiError(err.to_string()))?;
 
-        Ok(response.into_inner())
+        // The hash of the transaction on chain depends on its signature, so only the hash of the
+        // unsigned transaction is known at this point
+        Ok(TransactionSummary {
+            transaction_hash: raw_txn.hash().into(),
+            gas_used: None,
+            gas_unit_price: Some(gas_unit_price),
+            pending: None,
+            sender: Some(sender_address),
+            sequence_number: Some(sequence_number),
+            success: None,
+            timestamp_us: None,
+            version: None,
+            vm_status: None,
+        })
     }
 
     /// Simulates a transaction locally, using the debugger to fetch required data from remote.
@@ -1881,6 +2509,11 @@ impl TransactionOptions {
             aptos_crypto::HashValue,
         ) -> CliTypedResult<(VMStatus, VMOutput)>,
     {
+        if self.fee_payer_options.is_set() {
+            return Err(CliError::CommandArgumentError(
+                "Sponsored transactions can't be simulated locally yet".to_string(),
+            ));
+        }
         let client = self.rest_client()?;
 
         // Fetch the chain states required for the simulation
@@ -1888,7 +2521,7 @@ impl TransactionOptions {
         const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
         const DEFAULT_MAX_GAS: u64 = 2_000_000;
 
-        let (sender_key, sender_address) = self.get_key_and_address()?;
+        let (sender_key, sender_address) = self.get_signing_key_and_address()?;
         let gas_unit_price = self
             .gas_options
             .gas_unit_price
@@ -1916,9 +2549,13 @@ impl TransactionOptions {
             .with_gas_unit_price(gas_unit_price)
             .with_max_gas_amount(max_gas)
             .with_transaction_expiration_time(self.gas_options.expiration_secs);
-        let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
-        let transaction =
-            sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
+        let transaction = sender_key.sign_transaction(
+            transaction_factory
+                .payload(payload)
+                .sender(sender_address)
+                .sequence_number(sequence_number)
+                .build(),
+        )?;
         let hash = transaction.committed_hash();
 
         let debugger = AptosDebugger::rest_client(client).unwrap();
@@ -6,7 +6,7 @@ use crate::{
         init::Network,
         types::{
             account_address_from_public_key, CliError, CliTypedResult, PromptOptions,
-            TransactionOptions, TransactionSummary,
+            SecondarySigner, TransactionOptions, TransactionSummary,
         },
     },
     config::GlobalConfig,
@@ -16,13 +16,15 @@ use aptos_build_info::build_information;
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
 use aptos_keygen::KeyGen;
 use aptos_logger::{debug, Level};
-use aptos_rest_client::{aptos_api_types::HashValue, Account, Client, FaucetClient, State};
+use aptos_rest_client::{
+    aptos_api_types::HashValue, Account, Client, FaucetClient, State, Transaction,
+};
 use aptos_telemetry::service::telemetry_is_disabled;
 use aptos_types::{
     account_address::create_multisig_account_address,
     chain_id::ChainId,
     on_chain_config::{FeatureFlag, Features},
-    transaction::{authenticator::AuthenticationKey, TransactionPayload},
+    transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionPayload},
 };
 use itertools::Itertools;
 use move_core_types::{account_address::AccountAddress, language_storage::CORE_CODE_ADDRESS};
@@ -499,12 +501,28 @@ pub fn start_logger(level: Level) {
 pub async fn profile_or_submit(
     payload: TransactionPayload,
     txn_options_ref: &TransactionOptions,
+) -> CliTypedResult<TransactionSummary> {
+    profile_or_submit_multi_agent(payload, txn_options_ref, vec![]).await
+}
+
+/// Like [`profile_or_submit`], for a transaction signed by secondary signers too
+pub async fn profile_or_submit_multi_agent(
+    payload: TransactionPayload,
+    txn_options_ref: &TransactionOptions,
+    secondary_signers: Vec<SecondarySigner>,
 ) -> CliTypedResult<TransactionSummary> {
     if txn_options_ref.profile_gas && txn_options_ref.benchmark {
         return Err(CliError::UnexpectedError(
             "Cannot perform benchmarking and gas profiling at the same time.".to_string(),
         ));
     }
+    if (txn_options_ref.profile_gas || txn_options_ref.benchmark || txn_options_ref.local)
+        && !secondary_signers.is_empty()
+    {
+        return Err(CliError::CommandArgumentError(
+            "Multi-agent transactions can't be simulated locally yet".to_string(),
+        ));
+    }
 
     // Profile gas if needed.
     if txn_options_ref.profile_gas {
@@ -514,11 +532,10 @@ pub async fn profile_or_submit(
     } else if txn_options_ref.local {
         txn_options_ref.simulate_locally(payload).await
     } else {
-        // Otherwise submit the transaction.
+        // Otherwise submit the transaction, or write it unsigned if asked to.
         txn_options_ref
-            .submit_transaction(payload)
+            .submit_multi_agent_transaction(payload, secondary_signers)
             .await
-            .map(TransactionSummary::from)
     }
 }
 
@@ -579,6 +596,28 @@ pub fn explorer_account_link(hash: AccountAddress, network: Option<Network>) ->
     }
 }
 
+/// Submits a signed transaction, printing out a useful transaction link, and waits for it
+pub async fn submit_signed_transaction(
+    client: &Client,
+    transaction: &SignedTransaction,
+    network: Option<Network>,
+) -> CliTypedResult<Transaction> {
+    client
+        .submit_bcs(transaction)
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?;
+    eprintln!(
+        "Transaction submitted: {}",
+        explorer_transaction_link(transaction.committed_hash(), network)
+    );
+    let response = client
+        .wait_for_signed_transaction(transaction)
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?;
+
+    Ok(response.into_inner())
+}
+
 pub fn explorer_transaction_link(
     hash: aptos_crypto::HashValue,
     network: Option<Network>,
@@ -3,9 +3,10 @@
 
 use crate::{
     common::{
+        key_encryption::read_passphrase,
         types::{
             CliCommand, CliConfig, CliError, CliResult, CliTypedResult, ConfigSearchMode,
-            ProfileSummary, CONFIG_FOLDER,
+            ProfileConfig, ProfileSummary, CONFIG_FOLDER,
         },
         utils::{create_dir_if_not_exist, current_dir, read_from_file, write_to_user_only_file},
     },
@@ -25,7 +26,9 @@ use std::{collections::BTreeMap, fmt::Formatter, path::PathBuf, str::FromStr};
 /// default configuration, and user specific settings.
 #[derive(Parser)]
 pub enum ConfigTool {
+    DecryptProfile(DecryptProfile),
     DeleteProfile(DeleteProfile),
+    EncryptProfile(EncryptProfile),
     GenerateShellCompletions(GenerateShellCompletions),
     RenameProfile(RenameProfile),
     SetGlobalConfig(SetGlobalConfig),
@@ -37,7 +40,9 @@ pub enum ConfigTool {
 impl ConfigTool {
     pub async fn execute(self) -> CliResult {
         match self {
+            ConfigTool::DecryptProfile(tool) => tool.execute_serialized().await,
             ConfigTool::DeleteProfile(tool) => tool.execute_serialized().await,
+            ConfigTool::EncryptProfile(tool) => tool.execute_serialized().await,
             ConfigTool::GenerateShellCompletions(tool) => tool.execute_serialized_success().await,
             ConfigTool::RenameProfile(tool) => tool.execute_serialized().await,
             ConfigTool::SetGlobalConfig(tool) => tool.execute_serialized().await,
@@ -135,10 +140,20 @@ impl CliCommand<String> for ShowPrivateKey {
     async fn execute(self) -> CliTypedResult<String> {
         let config = CliConfig::load(ConfigSearchMode::CurrentDir)?;
 
-        if let Some(profiles) = &config.profiles {
-            if let Some(profile) = profiles.get(&self.profile.clone()) {
-                if let Some(private_key) = &profile.private_key {
-                    Ok(format!("0x{}", hex::encode(private_key.to_bytes())))
+        if let Some(mut profiles) = config.profiles {
+            if let Some(profile) = profiles.get_mut(&self.profile) {
+                // Encrypted keys can only be shown with the passphrase
+                profile.decrypt_keys()?;
+                let private_keys = match &profile.account_key {
+                    Some(account_key) => account_key.encoded_private_keys()?,
+                    None => profile
+                        .private_key
+                        .iter()
+                        .map(|private_key| format!("0x{}", hex::encode(private_key.to_bytes())))
+                        .collect(),
+                };
+                if !private_keys.is_empty() {
+                    Ok(private_keys.join("\n"))
                 } else {
                     Err(CliError::CommandArgumentError(format!(
                         "Profile {} does not have a private key",
@@ -159,6 +174,88 @@ impl CliCommand<String> for ShowPrivateKey {
     }
 }
 
+/// Encrypt the private keys of the given profile with a passphrase
+///
+/// Commands signing with the profile then prompt for the passphrase, or read it from the
+/// `APTOS_PROFILE_PASSPHRASE` environment variable.
+#[derive(Parser, Debug)]
+pub struct EncryptProfile {
+    /// Which profile's private keys to encrypt
+    #[clap(long)]
+    profile: String,
+}
+
+#[async_trait]
+impl CliCommand<String> for EncryptProfile {
+    fn command_name(&self) -> &'static str {
+        "EncryptProfile"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let mut config = CliConfig::load(ConfigSearchMode::CurrentDir)?;
+        let profile = get_profile_mut(&mut config, &self.profile)?;
+        if profile.encrypted_keys.is_some() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Profile {} is already encrypted",
+                self.profile
+            )));
+        }
+
+        profile.encrypt_keys(&read_passphrase(true)?)?;
+        config.save()?;
+        Ok(format!(
+            "Encrypted the private keys of profile {}",
+            self.profile
+        ))
+    }
+}
+
+/// Decrypt the private keys of the given profile, storing them in plaintext again
+#[derive(Parser, Debug)]
+pub struct DecryptProfile {
+    /// Which profile's private keys to decrypt
+    #[clap(long)]
+    profile: String,
+}
+
+#[async_trait]
+impl CliCommand<String> for DecryptProfile {
+    fn command_name(&self) -> &'static str {
+        "DecryptProfile"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let mut config = CliConfig::load(ConfigSearchMode::CurrentDir)?;
+        let profile = get_profile_mut(&mut config, &self.profile)?;
+        if profile.encrypted_keys.is_none() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Profile {} is not encrypted",
+                self.profile
+            )));
+        }
+
+        profile.decrypt_keys()?;
+        config.save()?;
+        Ok(format!(
+            "Decrypted the private keys of profile {}",
+            self.profile
+        ))
+    }
+}
+
+fn get_profile_mut<'a>(
+    config: &'a mut CliConfig,
+    profile: &str,
+) -> CliTypedResult<&'a mut ProfileConfig> {
+    config
+        .profiles
+        .as_mut()
+        .and_then(|profiles| profiles.get_mut(profile))
+        .ok_or_else(|| {
+            CliError::CommandArgumentError(format!("Profile {} does not exist", profile))
+        })
+}
+
 /// Shows the current profiles available
 ///
 /// This will only show public information and will not show
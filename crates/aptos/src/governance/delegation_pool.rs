@@ -71,7 +71,7 @@ impl CliCommand<ProposalSubmissionSummary> for SubmitProposal {
         let txn: Transaction = self
             .args
             .txn_options
-            .submit_transaction(aptos_stdlib::delegation_pool_create_proposal(
+            .submit_transaction_for_output(aptos_stdlib::delegation_pool_create_proposal(
                 self.delegation_pool_address,
                 script_hash.to_vec(),
                 self.args.metadata_url.to_string().as_bytes().to_vec(),
@@ -161,8 +161,7 @@ impl CliCommand<Vec<TransactionSummary>> for SubmitVote {
                     voting_power,
                     vote,
                 ))
-                .await
-                .map(TransactionSummary::from)?,
+                .await?,
         );
 
         Ok(summaries)
@@ -198,8 +197,7 @@ async fn delegation_pool_governance_precheck(
             .submit_transaction(
                 aptos_stdlib::delegation_pool_enable_partial_governance_voting(pool_address),
             )
-            .await
-            .map(TransactionSummary::from)?;
+            .await?;
         Ok(Some(txn_summary))
     }
 }
@@ -385,7 +385,7 @@ impl CliCommand<ProposalSubmissionSummary> for SubmitProposal {
         let txn: Transaction = if self.args.is_multi_step {
             self.args
                 .txn_options
-                .submit_transaction(aptos_stdlib::aptos_governance_create_proposal_v2(
+                .submit_transaction_for_output(aptos_stdlib::aptos_governance_create_proposal_v2(
                     self.pool_address_args.pool_address,
                     script_hash.to_vec(),
                     self.args.metadata_url.to_string().as_bytes().to_vec(),
@@ -396,7 +396,7 @@ impl CliCommand<ProposalSubmissionSummary> for SubmitProposal {
         } else {
             self.args
                 .txn_options
-                .submit_transaction(aptos_stdlib::aptos_governance_create_proposal(
+                .submit_transaction_for_output(aptos_stdlib::aptos_governance_create_proposal(
                     self.pool_address_args.pool_address,
                     script_hash.to_vec(),
                     self.args.metadata_url.to_string().as_bytes().to_vec(),
@@ -587,8 +587,7 @@ impl SubmitVote {
                         proposal_id,
                         vote,
                     ))
-                    .await
-                    .map(TransactionSummary::from)?,
+                    .await?,
             );
         }
         Ok(summaries)
@@ -683,8 +682,7 @@ impl SubmitVote {
                         voting_power,
                         vote,
                     ))
-                    .await
-                    .map(TransactionSummary::from)?,
+                    .await?,
             );
         }
         Ok(summaries)
@@ -700,6 +698,9 @@ impl CliCommand<Vec<TransactionSummary>> for SubmitVote {
     async fn execute(mut self) -> CliTypedResult<Vec<TransactionSummary>> {
         // The vote option is a group, so only one of yes and no must be true.
         let vote = self.args.yes;
+        self.args
+            .txn_options
+            .check_output_unsigned(self.pool_addresses.len())?;
 
         let client: &Client = &self
             .args
@@ -736,13 +737,11 @@ impl CliCommand<TransactionSummary> for ApproveExecutionHash {
     }
 
     async fn execute(mut self) -> CliTypedResult<TransactionSummary> {
-        Ok(self
-            .txn_options
+        self.txn_options
             .submit_transaction(
                 aptos_stdlib::aptos_governance_add_approved_script_hash_script(self.proposal_id),
             )
             .await
-            .map(TransactionSummary::from)?)
     }
 }
 
@@ -886,10 +885,7 @@ impl CliCommand<TransactionSummary> for ExecuteProposal {
         let args = vec![TransactionArgument::U64(self.proposal_id)];
         let txn = TransactionPayload::Script(Script::new(bytecode, vec![], args));
 
-        self.txn_options
-            .submit_transaction(txn)
-            .await
-            .map(TransactionSummary::from)
+        self.txn_options.submit_transaction(txn).await
     }
 }
 
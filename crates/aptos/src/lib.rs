@@ -15,6 +15,7 @@ pub mod op;
 pub mod stake;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod test;
+pub mod transaction;
 pub mod update;
 
 use crate::common::{
@@ -50,6 +51,8 @@ pub enum Tool {
     #[clap(subcommand)]
     Stake(stake::StakeTool),
     #[clap(subcommand)]
+    Transaction(transaction::TransactionTool),
+    #[clap(subcommand)]
     Update(update::UpdateTool),
 }
 
@@ -69,6 +72,7 @@ impl Tool {
             Multisig(tool) => tool.execute().await,
             Node(tool) => tool.execute().await,
             Stake(tool) => tool.execute().await,
+            Transaction(tool) => tool.execute().await,
             Update(tool) => tool.execute().await,
         }
     }
@@ -8,13 +8,14 @@ use crate::{
         types::{
             load_account_arg, ArgWithTypeJSON, ChunkedPublishOption, CliConfig, CliError,
             CliTypedResult, ConfigSearchMode, EntryFunctionArguments, EntryFunctionArgumentsJSON,
-            MoveManifestAccountWrapper, MovePackageDir, OptimizationLevel, OverrideSizeCheckOption,
-            ProfileOptions, PromptOptions, RestOptions, SaveFile, ScriptFunctionArguments,
-            TransactionOptions, TransactionSummary,
+            MoveManifestAccountWrapper, MovePackageDir, MultiAgentOptions, OptimizationLevel,
+            OverrideSizeCheckOption, ProfileOptions, PromptOptions, RestOptions, SaveFile,
+            ScriptFunctionArguments, TransactionOptions, TransactionSummary,
         },
         utils::{
             check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
-            profile_or_submit, prompt_yes_with_override, write_to_file,
+            profile_or_submit, profile_or_submit_multi_agent, prompt_yes_with_override,
+            write_to_file,
         },
     },
     governance::CompileScriptFunction,
@@ -747,7 +748,7 @@ pub struct PublishPackage {
 pub(crate) struct PackagePublicationData {
     metadata_serialized: Vec<u8>,
     compiled_units: Vec<Vec<u8>>,
-    payload: TransactionPayload,
+    pub(crate) payload: TransactionPayload,
 }
 
 pub(crate) struct ChunkedPublishPayloads {
@@ -951,7 +952,7 @@ impl IncludedArtifacts {
 pub const MAX_PUBLISH_PACKAGE_SIZE: usize = 60_000;
 
 // Get publication data for standard publish mode, which submits a single transaction for publishing.
-fn create_package_publication_data(
+pub(crate) fn create_package_publication_data(
     package: BuiltPackage,
     publish_type: PublishType,
     object_address: Option<AccountAddress>,
@@ -1185,13 +1186,11 @@ impl CliCommand<TransactionSummary> for CreateObjectAndPublishPackage {
                     MAX_PUBLISH_PACKAGE_SIZE,
                 ));
             }
-            self.txn_options
-                .submit_transaction(payload)
-                .await
-                .map(TransactionSummary::from)
+            self.txn_options.submit_transaction(payload).await
         };
 
-        if result.is_ok() {
+        // Nothing was deployed yet if the transaction was only written unsigned
+        if result.is_ok() && self.txn_options.output_unsigned.is_none() {
             println!(
                 "Code was successfully deployed to object address {}",
                 object_address
@@ -1291,10 +1290,7 @@ impl CliCommand<TransactionSummary> for UpgradeObjectPackage {
                     MAX_PUBLISH_PACKAGE_SIZE,
                 ));
             }
-            self.txn_options
-                .submit_transaction(payload)
-                .await
-                .map(TransactionSummary::from)
+            self.txn_options.submit_transaction(payload).await
         };
 
         if result.is_ok() {
@@ -1397,13 +1393,11 @@ impl CliCommand<TransactionSummary> for DeployObjectCode {
                     MAX_PUBLISH_PACKAGE_SIZE,
                 ));
             }
-            self.txn_options
-                .submit_transaction(payload)
-                .await
-                .map(TransactionSummary::from)
+            self.txn_options.submit_transaction(payload).await
         };
 
-        if result.is_ok() {
+        // Nothing was deployed yet if the transaction was only written unsigned
+        if result.is_ok() && self.txn_options.output_unsigned.is_none() {
             println!(
                 "Code was successfully deployed to object address {}",
                 object_address
@@ -1509,10 +1503,7 @@ impl CliCommand<TransactionSummary> for UpgradeCodeObject {
                     MAX_PUBLISH_PACKAGE_SIZE,
                 ));
             }
-            self.txn_options
-                .submit_transaction(payload)
-                .await
-                .map(TransactionSummary::from)
+            self.txn_options.submit_transaction(payload).await
         };
 
         if result.is_ok() {
@@ -1525,7 +1516,7 @@ impl CliCommand<TransactionSummary> for UpgradeCodeObject {
     }
 }
 
-fn build_package_options(
+pub(crate) fn build_package_options(
     move_options: &MovePackageDir,
     included_artifacts_args: &IncludedArtifactsArgs,
 ) -> anyhow::Result<BuiltPackage> {
@@ -1542,6 +1533,7 @@ async fn submit_chunked_publish_transactions(
     let mut publishing_result = Err(CliError::UnexpectedError(
         "No payload provided for batch transaction run".to_string(),
     ));
+    txn_options.check_output_unsigned(payloads.len())?;
     let payloads_length = payloads.len() as u64;
     let mut tx_hashes = vec![];
 
@@ -1561,10 +1553,7 @@ async fn submit_chunked_publish_transactions(
 
     for (idx, payload) in payloads.into_iter().enumerate() {
         println!("Transaction {} of {}", idx + 1, payloads_length);
-        let result = txn_options
-            .submit_transaction(payload)
-            .await
-            .map(TransactionSummary::from);
+        let result = txn_options.submit_transaction(payload).await;
 
         match result {
             Ok(tx_summary) => {
@@ -1655,10 +1644,7 @@ impl CliCommand<TransactionSummary> for ClearStagingArea {
             self.txn_options.profile_options.account_address()?
         );
         let payload = large_packages_cleanup_staging_area();
-        self.txn_options
-            .submit_transaction(payload)
-            .await
-            .map(TransactionSummary::from)
+        self.txn_options.submit_transaction(payload).await
     }
 }
 
@@ -1748,10 +1734,7 @@ impl CliCommand<TransactionSummary> for CreateResourceAccountAndPublishPackage {
                 MAX_PUBLISH_PACKAGE_SIZE, size
             )));
         }
-        txn_options
-            .submit_transaction(payload)
-            .await
-            .map(TransactionSummary::from)
+        txn_options.submit_transaction(payload).await
     }
 }
 
@@ -2012,6 +1995,8 @@ pub struct RunFunction {
     #[clap(flatten)]
     pub(crate) entry_function_args: EntryFunctionArguments,
     #[clap(flatten)]
+    pub(crate) multi_agent_options: MultiAgentOptions,
+    #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
 }
 
@@ -2022,9 +2007,10 @@ impl CliCommand<TransactionSummary> for RunFunction {
     }
 
     async fn execute(self) -> CliTypedResult<TransactionSummary> {
-        profile_or_submit(
+        profile_or_submit_multi_agent(
             TransactionPayload::EntryFunction(self.entry_function_args.try_into()?),
             &self.txn_options,
+            self.multi_agent_options.secondary_signers()?,
         )
         .await
     }
@@ -0,0 +1,445 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands controlling a localnet beyond what a real network allows, e.g. moving time forward
+
+use super::{
+    fork::{ADMIN_API_PREFIX, ADVANCE_TIME_PATH, FORCE_END_EPOCH_PATH, IMPERSONATE_PATH},
+    get_derived_test_dir,
+};
+use crate::common::{
+    types::{
+        load_account_arg, CliCommand, CliError, CliResult, CliTypedResult, EntryFunctionArguments,
+        GasOptions, ProfileOptions, RestOptions, TransactionSummary,
+    },
+    utils::read_from_file,
+};
+use aptos_api_types::{mime_types::BCS_SIGNED_TRANSACTION, AptosError, Transaction};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::aptos_test_root_address,
+    chain_id::ChainId,
+    on_chain_config::{CurrentTimeMicroseconds, OnChainConfig},
+    transaction::{
+        authenticator::AccountAuthenticator, RawTransaction, SignedTransaction, TransactionPayload,
+    },
+};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// The max gas of impersonated transactions if not given, same as the default of the node API
+const DEFAULT_MAX_GAS: u64 = 2_000_000;
+
+/// Path of the admin service endpoint moving the on-chain time of a localnet node forward
+const NODE_ADVANCE_TIME_PATH: &str = "/localnet/advance_time";
+
+/// How long to wait for a localnet node to commit a block at the time it was moved to
+const BLOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool for controlling a localnet started with `aptos node run-localnet`
+///
+/// These commands move time forward, start new epochs and run transactions as any account,
+/// e.g. to test time locked staking and vesting without waiting. They only work against
+/// localnets, which run as local testing chains, from the machine running them. Impersonating
+/// accounts additionally requires a forked localnet, started with `--fork-url`.
+#[derive(Debug, Subcommand)]
+pub enum LocalnetTool {
+    AdvanceTime(AdvanceTime),
+    ForceEndEpoch(ForceEndEpoch),
+    Impersonate(Impersonate),
+}
+
+impl LocalnetTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            LocalnetTool::AdvanceTime(tool) => tool.execute_serialized().await,
+            LocalnetTool::ForceEndEpoch(tool) => tool.execute_serialized().await,
+            LocalnetTool::Impersonate(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// The state of the localnet after an admin operation
+#[derive(Debug, Serialize)]
+pub struct LocalnetState {
+    pub epoch: u64,
+    pub timestamp_us: u64,
+    /// The transaction the localnet committed for the operation, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionSummary>,
+}
+
+/// Move the on-chain time of the localnet forward
+///
+/// A block is committed at the new time, which starts a new epoch if the epoch interval has
+/// passed. On a localnet running a node, the on-chain time then stays ahead of the clock of the
+/// node, so time keeps moving on from there. Transactions expire according to the on-chain time,
+/// so after moving time forward, use `--expiration-secs` to give transactions built by other
+/// commands a later expiration.
+#[derive(Debug, Parser)]
+pub struct AdvanceTime {
+    /// Number of seconds to move time forward by
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub(crate) seconds: u64,
+    /// Port of the admin service of the localnet node
+    ///
+    /// Unused for forked localnets, which are controlled through their API.
+    #[clap(long, default_value_t = 9102)]
+    pub(crate) admin_port: u16,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<LocalnetState> for AdvanceTime {
+    fn command_name(&self) -> &'static str {
+        "AdvanceTime"
+    }
+
+    async fn execute(self) -> CliTypedResult<LocalnetState> {
+        let admin_client = AdminClient::new(&self.rest_options, &self.profile_options).await?;
+        let query = format!("seconds={}", self.seconds);
+        if let Some(state) = admin_client
+            .post(ADVANCE_TIME_PATH, Some(query.clone()), None)
+            .await?
+        {
+            return Ok(state);
+        }
+
+        // Not a fork, so the on-chain time is moved forward through the admin service of the node
+        let mut url = admin_client.url.clone();
+        url.set_port(Some(self.admin_port)).map_err(|_| {
+            CliError::CommandArgumentError(format!("{} has no admin service", admin_client.url))
+        })?;
+        url.set_path(NODE_ADVANCE_TIME_PATH);
+        url.set_query(Some(&query));
+        let response = reqwest::Client::new()
+            .post(url.clone())
+            .send()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        let status = response.status();
+        let message = response
+            .text()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        if !status.is_success() {
+            return Err(CliError::ApiError(format!(
+                "The admin service at {} failed to move time forward: {}",
+                url, message
+            )));
+        }
+
+        // The on-chain time follows with the next block
+        admin_client
+            .wait_for_timestamp(
+                admin_client
+                    .timestamp_usecs
+                    .saturating_add(self.seconds.saturating_mul(1_000_000)),
+            )
+            .await
+    }
+}
+
+/// Start a new epoch on the localnet right away
+///
+/// This is the same as a governance proposal ending the epoch, so changes waiting for the next
+/// epoch, e.g. to the validator set or stake, are applied. On a localnet running a node, the
+/// transaction is signed by the root account, with the key the localnet saved in its directory.
+#[derive(Debug, Parser)]
+pub struct ForceEndEpoch {
+    /// The directory of the localnet node, to read the key of its root account from
+    ///
+    /// Defaults to .aptos/testnet, as for `aptos node run-localnet`. Unused for forked
+    /// localnets, which end epochs by themselves.
+    #[clap(long, value_parser)]
+    pub(crate) test_dir: Option<PathBuf>,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<LocalnetState> for ForceEndEpoch {
+    fn command_name(&self) -> &'static str {
+        "ForceEndEpoch"
+    }
+
+    async fn execute(self) -> CliTypedResult<LocalnetState> {
+        let admin_client = AdminClient::new(&self.rest_options, &self.profile_options).await?;
+        if let Some(state) = admin_client.post(FORCE_END_EPOCH_PATH, None, None).await? {
+            return Ok(state);
+        }
+
+        // Not a fork, so the epoch is ended as the root account, which testing chains allow
+        let key_path = get_derived_test_dir(&self.test_dir)?.join("mint.key");
+        let root_key: Ed25519PrivateKey =
+            bcs::from_bytes(&read_from_file(&key_path)?).map_err(|err| {
+                CliError::UnableToReadFile(key_path.display().to_string(), err.to_string())
+            })?;
+        let client = &admin_client.client;
+        let sequence_number = client
+            .get_account_bcs(aptos_test_root_address())
+            .await?
+            .into_inner()
+            .sequence_number();
+        let gas_unit_price = client.estimate_gas_price().await?.into_inner().gas_estimate;
+        let txn = RawTransaction::new(
+            aptos_test_root_address(),
+            sequence_number,
+            aptos_stdlib::aptos_governance_force_end_epoch_test_only(),
+            DEFAULT_MAX_GAS,
+            gas_unit_price,
+            admin_client.expiration_timestamp_secs(60)?,
+            ChainId::test(),
+        )
+        .sign(&root_key, root_key.public_key())
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        .into_inner();
+        let (transaction, state) = client.submit_and_wait(&txn).await?.into_parts();
+        if !transaction.success() {
+            return Err(CliError::ApiError(format!(
+                "Failed to end the epoch: {}",
+                transaction.vm_status()
+            )));
+        }
+        Ok(LocalnetState {
+            epoch: state.epoch,
+            timestamp_us: state.timestamp_usecs,
+            transaction: Some(TransactionSummary::from(&transaction)),
+        })
+    }
+}
+
+/// Run a Move function as any account, without its private key
+///
+/// The transaction is executed as if the account signed it, and committed to the localnet. Only
+/// forked localnets, started with `--fork-url`, can do this: a localnet running a node checks the
+/// signatures of every transaction, as a real network does.
+#[derive(Debug, Parser)]
+pub struct Impersonate {
+    /// Account to run the function as
+    #[clap(long, value_parser = load_account_arg)]
+    pub(crate) sender_account: AccountAddress,
+    /// Don't charge the sender for gas, e.g. for accounts without coins such as `0x1`
+    #[clap(long)]
+    pub(crate) skip_gas_payment: bool,
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+    #[clap(flatten)]
+    pub(crate) gas_options: GasOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<LocalnetState> for Impersonate {
+    fn command_name(&self) -> &'static str {
+        "Impersonate"
+    }
+
+    async fn execute(self) -> CliTypedResult<LocalnetState> {
+        let admin_client = AdminClient::new(&self.rest_options, &self.profile_options).await?;
+        let client = self.rest_options.client(&self.profile_options)?;
+        let sequence_number = client
+            .get_account_bcs(self.sender_account)
+            .await?
+            .into_inner()
+            .sequence_number();
+        let gas_unit_price = match self.gas_options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => client.estimate_gas_price().await?.into_inner().gas_estimate,
+        };
+        let expiration_timestamp_secs =
+            admin_client.expiration_timestamp_secs(self.gas_options.expiration_secs)?;
+        let raw_txn = RawTransaction::new(
+            self.sender_account,
+            sequence_number,
+            TransactionPayload::EntryFunction(self.entry_function_args.try_into()?),
+            self.gas_options.max_gas.unwrap_or(DEFAULT_MAX_GAS),
+            gas_unit_price,
+            expiration_timestamp_secs,
+            ChainId::test(),
+        );
+
+        // Signers without authenticators are impersonated, and a fee payer of 0x0 pays nothing
+        let txn = if self.skip_gas_payment {
+            SignedTransaction::new_fee_payer(
+                raw_txn,
+                AccountAuthenticator::NoAccountAuthenticator,
+                vec![],
+                vec![],
+                AccountAddress::ZERO,
+                AccountAuthenticator::NoAccountAuthenticator,
+            )
+        } else {
+            SignedTransaction::new_single_sender(
+                raw_txn,
+                AccountAuthenticator::NoAccountAuthenticator,
+            )
+        };
+        admin_client
+            .post(IMPERSONATE_PATH, None, Some(bcs::to_bytes(&txn)?))
+            .await?
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "The localnet at {} can't impersonate accounts, as its node checks the \
+                    signatures of transactions. Only localnets started with `aptos node \
+                    run-localnet --fork-url` can.",
+                    admin_client.url
+                ))
+            })
+    }
+}
+
+/// Client controlling a localnet, through the admin API of a forked localnet or the node API
+struct AdminClient {
+    url: reqwest::Url,
+    client: Client,
+    /// The on-chain time of the localnet when the client was created
+    timestamp_usecs: u64,
+}
+
+/// The on-chain time, which may be ahead of the ledger timestamp of a localnet node
+async fn on_chain_timestamp_usecs(client: &Client) -> CliTypedResult<u64> {
+    let resource_type = format!(
+        "0x1::{}::{}",
+        CurrentTimeMicroseconds::MODULE_IDENTIFIER,
+        CurrentTimeMicroseconds::TYPE_IDENTIFIER
+    );
+    let time = client
+        .get_account_resource_bcs::<CurrentTimeMicroseconds>(AccountAddress::ONE, &resource_type)
+        .await?
+        .into_inner();
+    Ok(time.microseconds)
+}
+
+impl AdminClient {
+    /// Creates a client, after checking the API is the one of a local testing chain
+    async fn new(
+        rest_options: &RestOptions,
+        profile_options: &ProfileOptions,
+    ) -> CliTypedResult<Self> {
+        let url = rest_options.url(profile_options)?;
+        let client = rest_options.client(profile_options)?;
+        let state = client.get_ledger_information().await?.into_inner();
+        if state.chain_id != ChainId::test().id() {
+            return Err(CliError::CommandArgumentError(format!(
+                "{} isn't a localnet (chain ID {}), localnet admin commands only work against \
+                local testing chains",
+                url, state.chain_id
+            )));
+        }
+        let timestamp_usecs = on_chain_timestamp_usecs(&client).await?;
+        Ok(Self {
+            url,
+            client,
+            timestamp_usecs,
+        })
+    }
+
+    /// The expiration of a transaction expiring in `expiration_secs`
+    ///
+    /// Transactions expire according to the on-chain time, which may be ahead of the clock.
+    fn expiration_timestamp_secs(&self, expiration_secs: u64) -> CliTypedResult<u64> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .as_secs();
+        Ok((self.timestamp_usecs / 1_000_000).max(now) + expiration_secs)
+    }
+
+    /// Waits for the localnet to commit a block at or after the given time
+    async fn wait_for_timestamp(&self, timestamp_usecs: u64) -> CliTypedResult<LocalnetState> {
+        let start = Instant::now();
+        loop {
+            let on_chain_timestamp_usecs = on_chain_timestamp_usecs(&self.client).await?;
+            if on_chain_timestamp_usecs >= timestamp_usecs {
+                let state = self.client.get_ledger_information().await?.into_inner();
+                return Ok(LocalnetState {
+                    epoch: state.epoch,
+                    timestamp_us: on_chain_timestamp_usecs,
+                    transaction: None,
+                });
+            }
+            if start.elapsed() > BLOCK_TIMEOUT {
+                return Err(CliError::UnexpectedError(format!(
+                    "The localnet at {} didn't commit a block at the new time within {}s",
+                    self.url,
+                    BLOCK_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Posts to the admin API of a forked localnet, returns `None` if the localnet isn't forked
+    async fn post(
+        &self,
+        path: &str,
+        query: Option<String>,
+        body: Option<Vec<u8>>,
+    ) -> CliTypedResult<Option<LocalnetState>> {
+        let mut url = self.url.clone();
+        url.set_path(&format!("{}{}", ADMIN_API_PREFIX, path));
+        url.set_query(query.as_deref());
+
+        let mut request = reqwest::Client::new().post(url.clone());
+        if let Some(body) = body {
+            request = request
+                .header(CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+                .body(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        let status = response.status();
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        let (epoch, timestamp_us) = (
+            header(aptos_api_types::X_APTOS_EPOCH),
+            header(aptos_api_types::X_APTOS_LEDGER_TIMESTAMP),
+        );
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let message = serde_json::from_slice::<AptosError>(&body)
+                .map(|error| error.message)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&body).to_string());
+            return Err(CliError::ApiError(message));
+        }
+        let transaction: Transaction = serde_json::from_slice(&body)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        Ok(Some(LocalnetState {
+            epoch: epoch.unwrap_or_default(),
+            timestamp_us: timestamp_us.unwrap_or_default(),
+            transaction: Some(TransactionSummary::from(&transaction)),
+        }))
+    }
+}
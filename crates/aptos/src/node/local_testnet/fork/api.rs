@@ -0,0 +1,750 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A subset of the node REST API, served from a [`ForkedChain`]
+//!
+//! Responses have the same format and ledger headers as the ones of a node, so that the CLI and
+//! SDKs can be used against the fork as is.
+
+use super::chain::ForkedChain;
+use anyhow::Result;
+use aptos_admin_service::localnet::is_local_client;
+use aptos_api_types::{
+    mime_types::{BCS, BCS_SIGNED_TRANSACTION, BCS_VIEW_FUNCTION},
+    AccountData, Address, AptosError, AptosErrorCode, GasEstimation, IndexResponse,
+    IndexResponseBcs, LedgerInfo, MoveConverter, Transaction, TransactionData,
+    TransactionOnChainData, UserTransaction, ViewFunction, ViewRequest, X_APTOS_BLOCK_HEIGHT,
+    X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP,
+    X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use aptos_bcs_utils::serialize_uleb128;
+use aptos_config::config::RoleType;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::AccountResource,
+    state_store::{state_key::StateKey, TStateView},
+    transaction::{RawTransaction, SignedTransaction},
+    utility_coin::{AptosCoinType, CoinType},
+};
+use itertools::Itertools;
+use move_core_types::{
+    ident_str,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    vm_status::VMStatus,
+};
+use poem::{
+    get, handler,
+    http::StatusCode,
+    listener::TcpListener,
+    middleware::Tracing,
+    post,
+    web::{Data, Path, Query},
+    EndpointExt, Request, Response, Route, Server,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
+};
+
+/// Prefix of the admin endpoints, which control the forked chain itself and are only served to
+/// local clients
+pub const ADMIN_API_PREFIX: &str = "/admin";
+/// Moves the on-chain time forward by the `seconds` query parameter
+pub const ADVANCE_TIME_PATH: &str = "/advance_time";
+/// Starts a new epoch
+pub const FORCE_END_EPOCH_PATH: &str = "/force_end_epoch";
+/// Executes a BCS transaction as its signers, which don't sign it
+pub const IMPERSONATE_PATH: &str = "/impersonate";
+
+/// Endpoints of the node API served by the fork, other ones fail with a 404 listing these
+const SUPPORTED_ENDPOINTS: &[&str] = &[
+    "GET /v1",
+    "GET /v1/accounts/:address",
+    "GET /v1/accounts/:address/resource/:resource_type",
+    "POST /v1/transactions",
+    "POST /v1/transactions/simulate",
+    "GET /v1/transactions/by_hash/:hash",
+    "GET /v1/transactions/by_version/:version",
+    "GET /v1/estimate_gas_price",
+    "POST /v1/view",
+    "POST /admin/advance_time?seconds=:seconds",
+    "POST /admin/force_end_epoch",
+    "POST /admin/impersonate",
+];
+
+/// Runs the API of the forked chain until it fails
+pub async fn run_fork_api(chain: Arc<ForkedChain>, bind_to: Ipv4Addr, port: u16) -> Result<()> {
+    let api = Route::new()
+        .at("/", get(index))
+        .at("/accounts/:address", get(account))
+        .at(
+            "/accounts/:address/resource/:resource_type",
+            get(account_resource),
+        )
+        .at("/transactions", post(submit_transaction))
+        .at("/transactions/simulate", post(simulate_transaction))
+        .at("/transactions/by_hash/:hash", get(transaction_by_hash))
+        .at(
+            "/transactions/by_version/:version",
+            get(transaction_by_version),
+        )
+        .at("/estimate_gas_price", get(estimate_gas_price))
+        .at("/view", post(view))
+        .at("/*path", unsupported);
+    let admin = Route::new()
+        .at(ADVANCE_TIME_PATH, post(advance_time))
+        .at(FORCE_END_EPOCH_PATH, post(force_end_epoch))
+        .at(IMPERSONATE_PATH, post(impersonate))
+        .at("/*path", unsupported);
+    let app = Route::new()
+        .nest("/v1", api)
+        .nest(ADMIN_API_PREFIX, admin)
+        .at("/*path", unsupported)
+        .data(chain)
+        .with(Tracing);
+    Server::new(TcpListener::bind(SocketAddrV4::new(bind_to, port)))
+        .name("fork-api")
+        .run(app)
+        .await?;
+    Err(anyhow::anyhow!("Fork API exited unexpectedly"))
+}
+
+/// An error, in the same format as the errors of the node API
+struct ApiError {
+    status: StatusCode,
+    error: AptosError,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, err: impl Display, error_code: AptosErrorCode) -> Self {
+        Self {
+            status,
+            error: AptosError::new_with_error_code(err, error_code),
+        }
+    }
+
+    fn bad_request(err: impl Display, error_code: AptosErrorCode) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, err, error_code)
+    }
+
+    fn not_found(err: impl Display, error_code: AptosErrorCode) -> Self {
+        Self::new(StatusCode::NOT_FOUND, err, error_code)
+    }
+
+    fn internal(err: impl Display) -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            err,
+            AptosErrorCode::InternalError,
+        )
+    }
+}
+
+/// The body of a successful response
+struct Output {
+    status: StatusCode,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Output {
+    fn json(value: &impl Serialize) -> Result<Self, ApiError> {
+        Ok(Self {
+            status: StatusCode::OK,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).map_err(ApiError::internal)?,
+        })
+    }
+
+    fn bcs(value: &impl Serialize) -> Result<Self, ApiError> {
+        Ok(Self::raw_bcs(
+            bcs::to_bytes(value).map_err(ApiError::internal)?,
+        ))
+    }
+
+    /// Bytes which are already BCS encoded
+    fn raw_bcs(body: Vec<u8>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            content_type: BCS,
+            body,
+        }
+    }
+
+    fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Builds the response, with the ledger headers as of after the request was handled
+fn respond(chain: &ForkedChain, result: Result<Output, ApiError>) -> Response {
+    let output = result.unwrap_or_else(|err| Output {
+        status: err.status,
+        content_type: "application/json",
+        body: serde_json::to_vec(&err.error).unwrap_or_default(),
+    });
+    let LedgerInfo {
+        chain_id,
+        epoch,
+        ledger_version,
+        oldest_ledger_version,
+        block_height,
+        oldest_block_height,
+        ledger_timestamp,
+    } = chain.ledger_info();
+    Response::builder()
+        .status(output.status)
+        .content_type(output.content_type)
+        .header(X_APTOS_CHAIN_ID, chain_id.to_string())
+        .header(X_APTOS_EPOCH, epoch.0.to_string())
+        .header(X_APTOS_LEDGER_VERSION, ledger_version.0.to_string())
+        .header(
+            X_APTOS_LEDGER_OLDEST_VERSION,
+            oldest_ledger_version.0.to_string(),
+        )
+        .header(X_APTOS_BLOCK_HEIGHT, block_height.0.to_string())
+        .header(
+            X_APTOS_OLDEST_BLOCK_HEIGHT,
+            oldest_block_height.0.to_string(),
+        )
+        .header(X_APTOS_LEDGER_TIMESTAMP, ledger_timestamp.0.to_string())
+        .body(output.body)
+}
+
+fn accepts_bcs(req: &Request) -> bool {
+    req.header("Accept")
+        .map_or(false, |accept| accept.contains(BCS))
+}
+
+/// Runs `f` on a blocking thread, since reading the state of the fork waits on the remote network
+async fn blocking<T: Send + 'static>(
+    chain: &Arc<ForkedChain>,
+    f: impl FnOnce(Arc<ForkedChain>) -> Result<T, ApiError> + Send + 'static,
+) -> Result<T, ApiError> {
+    let chain = chain.clone();
+    tokio::task::spawn_blocking(move || f(chain))
+        .await
+        .map_err(ApiError::internal)?
+}
+
+/// The converter to render Move values in JSON
+fn converter(chain: &Arc<ForkedChain>) -> MoveConverter<'_, ForkedChain> {
+    let db: Arc<dyn DbReader> = chain.clone();
+    MoveConverter::new(&**chain, db, None)
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress, ApiError> {
+    address
+        .parse::<Address>()
+        .map(Into::into)
+        .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))
+}
+
+/// Answers the requests to endpoints which aren't served by the fork, listing the ones which are
+#[handler]
+async fn unsupported(req: &Request, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let err = ApiError::not_found(
+        format!(
+            "{} {} isn't supported by a forked network, the supported endpoints are: {}",
+            req.method(),
+            req.original_uri().path(),
+            SUPPORTED_ENDPOINTS.join(", ")
+        ),
+        AptosErrorCode::WebFrameworkError,
+    );
+    respond(chain, Err(err))
+}
+
+#[handler]
+async fn index(req: &Request, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let ledger_info = chain.ledger_info();
+    let result = if accepts_bcs(req) {
+        Output::bcs(&IndexResponseBcs::new(ledger_info, RoleType::FullNode))
+    } else {
+        Output::json(&IndexResponse::new(ledger_info, RoleType::FullNode, None))
+    };
+    respond(chain, result)
+}
+
+#[handler]
+async fn account(
+    req: &Request,
+    Path(address): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = async {
+        let address = parse_address(&address)?;
+        blocking(chain, move |chain| {
+            let state_key = StateKey::resource_typed::<AccountResource>(&address)
+                .map_err(ApiError::internal)?;
+            let bytes = chain
+                .get_state_value_bytes(&state_key)
+                .map_err(ApiError::internal)?
+                .ok_or_else(|| {
+                    ApiError::not_found(
+                        format!("Account not found by Address({})", address),
+                        AptosErrorCode::AccountNotFound,
+                    )
+                })?;
+            if bcs {
+                return Ok(Output::raw_bcs(bytes.to_vec()));
+            }
+            let account: AccountResource = bcs::from_bytes(&bytes).map_err(ApiError::internal)?;
+            Output::json(&AccountData::from(account))
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+#[handler]
+async fn account_resource(
+    req: &Request,
+    Path((address, resource_type)): Path<(String, String)>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = async {
+        let address = parse_address(&address)?;
+        let tag = resource_type
+            .parse::<StructTag>()
+            .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        blocking(chain, move |chain| {
+            let converter = converter(&chain);
+            let bytes = converter
+                .find_resource(&*chain, address.into(), &tag)
+                .map_err(ApiError::internal)?
+                .ok_or_else(|| {
+                    ApiError::not_found(
+                        format!(
+                            "Resource not found by Address({}), Struct tag({})",
+                            address, tag
+                        ),
+                        AptosErrorCode::ResourceNotFound,
+                    )
+                })?;
+            if bcs {
+                return Ok(Output::raw_bcs(bytes.to_vec()));
+            }
+            let resource = converter
+                .try_into_resource(&tag, &bytes)
+                .map_err(ApiError::internal)?;
+            Output::json(&resource)
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+fn parse_signed_transaction(req: &Request, body: &[u8]) -> Result<SignedTransaction, ApiError> {
+    if req.content_type() != Some(BCS_SIGNED_TRANSACTION) {
+        return Err(ApiError::bad_request(
+            format!(
+                "Transactions must be submitted to a forked network as {}",
+                BCS_SIGNED_TRANSACTION
+            ),
+            AptosErrorCode::InvalidInput,
+        ));
+    }
+    bcs::from_bytes(body).map_err(|err| {
+        ApiError::bad_request(
+            format!("Failed to deserialize the signed transaction: {}", err),
+            AptosErrorCode::InvalidInput,
+        )
+    })
+}
+
+#[handler]
+async fn submit_transaction(
+    req: &Request,
+    body: Vec<u8>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = async {
+        let txn = parse_signed_transaction(req, &body)?;
+        blocking(chain, move |chain| {
+            chain
+                .execute(txn.clone())
+                .map_err(|err| ApiError::bad_request(err, AptosErrorCode::VmError))?;
+            if bcs {
+                return Ok(Output::raw_bcs(vec![]).with_status(StatusCode::ACCEPTED));
+            }
+            let pending_txn = converter(&chain)
+                .try_into_pending_transaction_poem(txn)
+                .map_err(ApiError::internal)?;
+            Ok(Output::json(&pending_txn)?.with_status(StatusCode::ACCEPTED))
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulateParams {
+    estimate_max_gas_amount: Option<bool>,
+    estimate_gas_unit_price: Option<bool>,
+    estimate_prioritized_gas_unit_price: Option<bool>,
+}
+
+#[handler]
+async fn simulate_transaction(
+    req: &Request,
+    body: Vec<u8>,
+    Query(params): Query<SimulateParams>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = async {
+        let txn = parse_signed_transaction(req, &body)?;
+        if txn.verify_signature().is_ok() {
+            return Err(ApiError::bad_request(
+                "Simulated transactions must not have a valid signature",
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+        blocking(chain, move |chain| {
+            let txn = estimate_gas(&chain, txn, &params)?;
+            let (vm_status, data) = chain.simulate(txn);
+            if bcs {
+                return Output::bcs(&data);
+            }
+            Output::json(&vec![render_simulated_transaction(
+                &chain, vm_status, data,
+            )?])
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+/// Replaces the gas parameters of the transaction with estimated ones, if asked to
+///
+/// This follows the node API: the estimated max gas amount is as much as the sender can afford.
+fn estimate_gas(
+    chain: &ForkedChain,
+    txn: SignedTransaction,
+    params: &SimulateParams,
+) -> Result<SignedTransaction, ApiError> {
+    let estimate_gas_unit_price = params.estimate_gas_unit_price.unwrap_or_default()
+        || params
+            .estimate_prioritized_gas_unit_price
+            .unwrap_or_default();
+    let estimate_max_gas_amount = params.estimate_max_gas_amount.unwrap_or_default();
+    if !estimate_gas_unit_price && !estimate_max_gas_amount {
+        return Ok(txn);
+    }
+
+    let gas_params = chain.gas_parameters().map_err(ApiError::internal)?;
+    let gas_unit_price = if estimate_gas_unit_price {
+        u64::from(gas_params.vm.txn.min_price_per_gas_unit)
+    } else {
+        txn.gas_unit_price()
+    };
+    let max_gas_amount = if estimate_max_gas_amount {
+        let output = chain.view(
+            ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+            ident_str!("balance").into(),
+            vec![AptosCoinType::type_tag()],
+            vec![txn.sender().to_vec()],
+        );
+        let balance: u64 = output
+            .values
+            .and_then(|values| {
+                let balance = values
+                    .first()
+                    .ok_or_else(|| anyhow::anyhow!("The balance of the sender is missing"))?;
+                Ok(bcs::from_bytes(balance)?)
+            })
+            .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
+            / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+        let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+        let max_account_gas_units = balance.checked_div(gas_unit_price).unwrap_or(balance);
+        std::cmp::min(
+            std::cmp::max(min_number_of_gas_units, max_account_gas_units),
+            max_number_of_gas_units,
+        )
+    } else {
+        txn.max_gas_amount()
+    };
+
+    let raw_txn = RawTransaction::new(
+        txn.sender(),
+        txn.sequence_number(),
+        txn.payload().clone(),
+        max_gas_amount,
+        gas_unit_price,
+        txn.expiration_timestamp_secs(),
+        txn.chain_id(),
+    );
+    Ok(SignedTransaction::new_signed_transaction(
+        raw_txn,
+        txn.authenticator(),
+    ))
+}
+
+/// Renders a simulated transaction as JSON, adding the VM error message to its status
+fn render_simulated_transaction(
+    chain: &Arc<ForkedChain>,
+    vm_status: VMStatus,
+    data: TransactionOnChainData,
+) -> Result<UserTransaction, ApiError> {
+    let timestamp = chain.ledger_info().timestamp();
+    match converter(chain).try_into_onchain_transaction(timestamp, data) {
+        Ok(Transaction::UserTransaction(mut user_txn)) => {
+            if let VMStatus::Error {
+                message: Some(msg), ..
+            }
+            | VMStatus::ExecutionFailure {
+                message: Some(msg), ..
+            } = &vm_status
+            {
+                user_txn.info.vm_status +=
+                    format!("\nExecution failed with message: {}", msg).as_str();
+            }
+            Ok(user_txn)
+        },
+        Ok(_) => Err(ApiError::internal(
+            "Simulation transaction resulted in a non-UserTransaction",
+        )),
+        Err(err) => Err(ApiError::internal(err)),
+    }
+}
+
+/// Renders a committed transaction, in JSON or BCS
+fn render_transaction(
+    chain: &Arc<ForkedChain>,
+    bcs: bool,
+    transaction: Option<(TransactionOnChainData, u64)>,
+    not_found_message: String,
+) -> Result<Output, ApiError> {
+    let (data, timestamp) = transaction.ok_or_else(|| {
+        ApiError::not_found(
+            format!(
+                "{}, only transactions executed since the fork at version {} are available",
+                not_found_message,
+                chain.fork_version()
+            ),
+            AptosErrorCode::TransactionNotFound,
+        )
+    })?;
+    render_committed_transaction(chain, bcs, data, timestamp)
+}
+
+fn render_committed_transaction(
+    chain: &Arc<ForkedChain>,
+    bcs: bool,
+    data: TransactionOnChainData,
+    timestamp: u64,
+) -> Result<Output, ApiError> {
+    if bcs {
+        return Output::bcs(&TransactionData::OnChain(data));
+    }
+    let transaction = converter(chain)
+        .try_into_onchain_transaction(timestamp, data)
+        .map_err(ApiError::internal)?;
+    Output::json(&transaction)
+}
+
+#[handler]
+async fn transaction_by_hash(
+    req: &Request,
+    Path(hash): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = async {
+        let hash: aptos_api_types::HashValue = hash
+            .parse()
+            .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        blocking(chain, move |chain| {
+            render_transaction(
+                &chain,
+                bcs,
+                chain.get_transaction_by_hash(hash.into()),
+                format!("Transaction not found by Transaction hash({})", hash),
+            )
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+#[handler]
+async fn transaction_by_version(
+    req: &Request,
+    Path(version): Path<u64>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let result = blocking(chain, move |chain| {
+        render_transaction(
+            &chain,
+            bcs,
+            chain.get_transaction_by_version(version),
+            format!("Transaction not found by Ledger version({})", version),
+        )
+    })
+    .await;
+    respond(chain, result)
+}
+
+/// There is no competition for block space on a fork, so the minimum gas unit price is enough
+#[handler]
+async fn estimate_gas_price(Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let result = blocking(chain, |chain| {
+        let gas_params = chain.gas_parameters().map_err(ApiError::internal)?;
+        let gas_estimate = u64::from(gas_params.vm.txn.min_price_per_gas_unit);
+        Output::json(&GasEstimation {
+            deprioritized_gas_estimate: Some(gas_estimate),
+            gas_estimate,
+            prioritized_gas_estimate: Some(gas_estimate),
+        })
+    })
+    .await;
+    respond(chain, result)
+}
+
+#[derive(Debug, Deserialize)]
+struct ViewParams {
+    ledger_version: Option<u64>,
+}
+
+#[handler]
+async fn view(
+    req: &Request,
+    body: Vec<u8>,
+    Query(params): Query<ViewParams>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    let bcs_request = req.content_type() == Some(BCS_VIEW_FUNCTION);
+    let result = async {
+        if let Some(ledger_version) = params.ledger_version {
+            if ledger_version != chain.version() {
+                return Err(ApiError::bad_request(
+                    "Only the latest version of a forked network can be viewed",
+                    AptosErrorCode::VersionPruned,
+                ));
+            }
+        }
+        blocking(chain, move |chain| {
+            let converter = converter(&chain);
+            let view_function = if bcs_request {
+                bcs::from_bytes::<ViewFunction>(&body)
+                    .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?
+            } else {
+                serde_json::from_slice::<ViewRequest>(&body)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|request| converter.convert_view_function(request))
+                    .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?
+            };
+            let values = chain
+                .view(
+                    view_function.module.clone(),
+                    view_function.function.clone(),
+                    view_function.ty_args.clone(),
+                    view_function.args.clone(),
+                )
+                .values
+                .map_err(|err| ApiError::bad_request(err, AptosErrorCode::InvalidInput))?;
+
+            if bcs {
+                // The return values are already BCS encoded, only the outside vector is encoded
+                let mut length = vec![];
+                serialize_uleb128(&mut length, values.len() as u64).map_err(ApiError::internal)?;
+                return Ok(Output::raw_bcs(
+                    [length, values.into_iter().concat()].concat(),
+                ));
+            }
+            let return_types = converter
+                .function_return_types(&view_function)
+                .and_then(|tys| {
+                    tys.into_iter()
+                        .map(TypeTag::try_from)
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+                .map_err(ApiError::internal)?;
+            let move_values = values
+                .into_iter()
+                .zip(return_types)
+                .map(|(value, ty)| converter.try_into_move_value(&ty, &value))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(ApiError::internal)?;
+            Output::json(&move_values)
+        })
+        .await
+    }
+    .await;
+    respond(chain, result)
+}
+
+/// Runs an admin operation on the chain and renders the transaction it committed
+async fn run_admin_operation(
+    req: &Request,
+    chain: &Arc<ForkedChain>,
+    operation: impl FnOnce(&ForkedChain) -> Result<TransactionOnChainData> + Send + 'static,
+) -> Response {
+    let remote_ip = req.remote_addr().as_socket_addr().map(SocketAddr::ip);
+    if !remote_ip.map_or(false, is_local_client) {
+        let err = ApiError::new(
+            StatusCode::FORBIDDEN,
+            "Admin endpoints are only served to local clients",
+            AptosErrorCode::WebFrameworkError,
+        );
+        return respond(chain, Err(err));
+    }
+
+    let bcs = accepts_bcs(req);
+    let result = blocking(chain, move |chain| {
+        let data =
+            operation(&chain).map_err(|err| ApiError::bad_request(err, AptosErrorCode::VmError))?;
+        let timestamp = chain.ledger_info().timestamp();
+        render_committed_transaction(&chain, bcs, data, timestamp)
+    })
+    .await;
+    respond(chain, result)
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvanceTimeParams {
+    seconds: u64,
+}
+
+#[handler]
+async fn advance_time(
+    req: &Request,
+    Query(params): Query<AdvanceTimeParams>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    run_admin_operation(req, chain, move |chain| chain.advance_time(params.seconds)).await
+}
+
+#[handler]
+async fn force_end_epoch(req: &Request, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    run_admin_operation(req, chain, |chain| chain.force_end_epoch()).await
+}
+
+#[handler]
+async fn impersonate(
+    req: &Request,
+    body: Vec<u8>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let txn = match parse_signed_transaction(req, &body) {
+        Ok(txn) => txn,
+        Err(err) => return respond(chain, Err(err)),
+    };
+    run_admin_operation(req, chain, move |chain| chain.impersonate(txn)).await
+}
@@ -0,0 +1,893 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_api_types::{LedgerInfo, TransactionOnChainData};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_gas_schedule::{AptosGasParameters, FromOnChainGasSchedule};
+use aptos_rest_client::Client;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{AccountResource, ChainIdResource, CORE_CODE_ADDRESS},
+    block_metadata::BlockMetadata,
+    chain_id::ChainId,
+    on_chain_config::{
+        ConfigurationResource, CurrentTimeMicroseconds, FeatureFlag, Features, GasSchedule,
+        GasScheduleV2, OnChainConfig, ValidatorSet,
+    },
+    state_store::{
+        errors::StateviewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, StateViewId, TStateView,
+    },
+    transaction::{
+        authenticator::AccountAuthenticator,
+        signature_verified_transaction::SignatureVerifiedTransaction, ExecutionStatus,
+        RawTransaction, SignedTransaction, Transaction, TransactionAuxiliaryData, TransactionInfo,
+        TransactionOutput, TransactionStatus, Version, ViewFunctionOutput,
+    },
+    write_set::TransactionWrite,
+};
+use aptos_validator_interface::{
+    AptosValidatorInterface, DebuggerStateView, RestDebuggerInterface,
+};
+use aptos_vm::{data_cache::AsMoveResolver, AptosSimulationVM, AptosVM};
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    vm_status::VMStatus,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// The maximum amount of gas a view function can use, same as the default of the node API
+const MAX_GAS_VIEW_FUNCTION: u64 = 2_000_000;
+
+/// The maximum amount of gas of the transactions the chain runs by itself, e.g. to end an epoch
+const MAX_GAS_ADMIN_TRANSACTION: u64 = 2_000_000;
+
+/// A chain forked from a remote network at a pinned version
+///
+/// State is fetched lazily from the remote network's REST API as it is read, and writes of
+/// locally executed transactions are kept in memory on top of it. There is no consensus: each
+/// submitted transaction is executed and committed right away, with versions continuing from the
+/// fork version.
+pub struct ForkedChain {
+    remote: DebuggerStateView,
+    fork_version: Version,
+    fork_block_height: u64,
+    /// `None` means the value was deleted
+    overlay: RwLock<HashMap<StateKey, Option<StateValue>>>,
+    ledger: RwLock<Ledger>,
+    /// Held while executing a transaction, so that transactions are applied one at a time
+    execution_lock: Mutex<()>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    transactions: Vec<CommittedTransaction>,
+    versions_by_hash: HashMap<HashValue, Version>,
+    epoch: u64,
+    timestamp_usecs: u64,
+}
+
+struct CommittedTransaction {
+    data: TransactionOnChainData,
+    auxiliary_data: TransactionAuxiliaryData,
+    timestamp_usecs: u64,
+}
+
+impl ForkedChain {
+    /// Forks the network behind `client` at `version`, or at its latest version if not given
+    pub async fn fork(client: Client, version: Option<Version>) -> Result<Arc<Self>> {
+        let latest_version = client
+            .get_ledger_information()
+            .await
+            .context("Failed to get the ledger info of the network to fork")?
+            .into_inner()
+            .version;
+        let fork_version = version.unwrap_or(latest_version);
+        if fork_version > latest_version {
+            bail!(
+                "Can't fork at version {}, the latest version of the network is {}",
+                fork_version,
+                latest_version
+            );
+        }
+        let fork_block_height = client
+            .get_block_by_version_bcs(fork_version, false)
+            .await
+            .with_context(|| format!("Failed to get the block of version {}", fork_version))?
+            .into_inner()
+            .block_height;
+
+        Self::new(
+            Arc::new(RestDebuggerInterface::new(client)),
+            fork_version,
+            fork_block_height,
+        )
+        .await
+    }
+
+    /// Forks the network behind `remote` at `fork_version`, the last version of the block at
+    /// `fork_block_height`
+    async fn new(
+        remote: Arc<dyn AptosValidatorInterface + Send>,
+        fork_version: Version,
+        fork_block_height: u64,
+    ) -> Result<Arc<Self>> {
+        // The state view reads the state as of the version before the one it's given
+        let chain = Arc::new(Self {
+            remote: DebuggerStateView::new(remote, fork_version + 1),
+            fork_version,
+            fork_block_height,
+            overlay: RwLock::new(HashMap::new()),
+            ledger: RwLock::new(Ledger::default()),
+            execution_lock: Mutex::new(()),
+        });
+
+        let (chain, result) = tokio::task::spawn_blocking(move || {
+            let result = chain.init();
+            (chain, result)
+        })
+        .await?;
+        result?;
+        Ok(chain)
+    }
+
+    /// Replaces the chain ID and reads the on-chain epoch and time at the fork version
+    ///
+    /// The chain runs as a testing chain, so that transactions signed for the fork can't be
+    /// replayed on the network it was forked from. The simulation enhancements are enabled, as
+    /// impersonation relies on simulations skipping the authentication and gas of signers.
+    fn init(&self) -> Result<()> {
+        let mut features = Features::fetch_config(self).unwrap_or_default();
+        features.enable(FeatureFlag::TRANSACTION_SIMULATION_ENHANCEMENT);
+        let mut overlay = self.overlay.write().unwrap();
+        overlay.insert(
+            StateKey::on_chain_config::<ChainIdResource>()?,
+            Some(StateValue::new_legacy(
+                bcs::to_bytes(&ChainId::test().id())?.into(),
+            )),
+        );
+        overlay.insert(
+            StateKey::on_chain_config::<Features>()?,
+            Some(StateValue::new_legacy(bcs::to_bytes(&features)?.into())),
+        );
+        drop(overlay);
+        self.refresh_ledger();
+        if self.ledger.read().unwrap().epoch == 0 {
+            bail!("Failed to read the on-chain configuration of the network to fork");
+        }
+        Ok(())
+    }
+
+    /// Updates the epoch and time in the ledger info from the on-chain state
+    fn refresh_ledger(&self) {
+        let epoch = ConfigurationResource::fetch_config(self).map(|config| config.epoch());
+        let timestamp_usecs =
+            CurrentTimeMicroseconds::fetch_config(self).map(|time| time.microseconds);
+        let mut ledger = self.ledger.write().unwrap();
+        ledger.epoch = epoch.unwrap_or(ledger.epoch);
+        ledger.timestamp_usecs = timestamp_usecs.unwrap_or(ledger.timestamp_usecs);
+    }
+
+    pub fn fork_version(&self) -> Version {
+        self.fork_version
+    }
+
+    /// The latest version of the forked chain
+    pub fn version(&self) -> Version {
+        self.fork_version + self.ledger.read().unwrap().transactions.len() as u64
+    }
+
+    pub fn ledger_info(&self) -> LedgerInfo {
+        let ledger = self.ledger.read().unwrap();
+        let num_transactions = ledger.transactions.len() as u64;
+        LedgerInfo::new_ledger_info(
+            &ChainId::test(),
+            ledger.epoch,
+            self.fork_version + num_transactions,
+            self.fork_version,
+            self.fork_block_height,
+            self.fork_block_height + num_transactions,
+            ledger.timestamp_usecs,
+        )
+    }
+
+    /// Returns a committed transaction, with the time at which it was executed
+    ///
+    /// Only the transactions executed since the fork are available.
+    pub fn get_transaction_by_version(
+        &self,
+        version: Version,
+    ) -> Option<(TransactionOnChainData, u64)> {
+        let index = version.checked_sub(self.fork_version + 1)?;
+        self.ledger
+            .read()
+            .unwrap()
+            .transactions
+            .get(index as usize)
+            .map(|txn| (txn.data.clone(), txn.timestamp_usecs))
+    }
+
+    pub fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Option<(TransactionOnChainData, u64)> {
+        let version = *self.ledger.read().unwrap().versions_by_hash.get(&hash)?;
+        self.get_transaction_by_version(version)
+    }
+
+    /// Executes a transaction and commits its changes
+    ///
+    /// Transactions which would be discarded by a node, e.g. with an invalid signature or sequence
+    /// number, are rejected with an error.
+    pub fn execute(&self, txn: SignedTransaction) -> Result<TransactionOnChainData> {
+        let txn = txn
+            .check_signature()
+            .map_err(|_| anyhow!("Invalid transaction: signature verification failed"))?
+            .into_inner();
+
+        let _execution_guard = self.execution_lock.lock().unwrap();
+        // Modules may have been published since the fork, so the code cache must not be shared
+        let vm = AptosVM::new_with_isolated_code_cache(self);
+        let log_context = AdapterLogSchema::new(self.id(), 0);
+        let resolver = self.as_move_resolver();
+        let (vm_status, vm_output) = vm.execute_user_transaction(&resolver, &txn, &log_context);
+        let output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .map_err(|status| anyhow!("Invalid transaction: {:?}", status))?;
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status) => {
+                bail!("Invalid transaction: {:?} ({:?})", status, vm_status)
+            },
+            TransactionStatus::Retry => {
+                bail!("Invalid transaction: the transaction must be retried")
+            },
+        };
+
+        Ok(self.commit(Transaction::UserTransaction(txn), &output, status))
+    }
+
+    /// Executes a transaction as its signers, without checking their signatures, and commits its
+    /// changes
+    ///
+    /// Signers must use a [`AccountAuthenticator::NoAccountAuthenticator`], as for simulations,
+    /// and the transaction is paid by its fee payer as usual, unless the fee payer is `0x0`.
+    pub fn impersonate(&self, txn: SignedTransaction) -> Result<TransactionOnChainData> {
+        if txn.verify_signature().is_ok() {
+            bail!("Impersonated transactions must not be signed, submit them instead");
+        }
+
+        let _execution_guard = self.execution_lock.lock().unwrap();
+        self.impersonate_locked(txn)
+    }
+
+    /// [`Self::impersonate`], with the execution lock already held
+    fn impersonate_locked(&self, txn: SignedTransaction) -> Result<TransactionOnChainData> {
+        let vm = AptosSimulationVM::new_with_isolated_code_cache(self);
+        let (vm_status, output) = vm.simulate_signed_transaction(&txn, self);
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status) => {
+                bail!("Invalid transaction: {:?} ({:?})", status, vm_status)
+            },
+            TransactionStatus::Retry => {
+                bail!("Invalid transaction: the transaction must be retried")
+            },
+        };
+        Ok(self.commit(Transaction::UserTransaction(txn), &output, status))
+    }
+
+    /// Moves the on-chain time forward, by committing an empty block at the new time
+    ///
+    /// The block is proposed by a validator of the forked network, since only blocks from
+    /// validators can move time. As on a real network, the block starts a new epoch if the epoch
+    /// interval has passed.
+    pub fn advance_time(&self, seconds: u64) -> Result<TransactionOnChainData> {
+        if seconds == 0 {
+            bail!("Time can only move forward");
+        }
+
+        let _execution_guard = self.execution_lock.lock().unwrap();
+        self.advance_time_locked(seconds)
+    }
+
+    /// [`Self::advance_time`], with the execution lock already held
+    fn advance_time_locked(&self, seconds: u64) -> Result<TransactionOnChainData> {
+        let proposer = ValidatorSet::fetch_config(self)
+            .and_then(|validator_set| validator_set.active_validators().first().copied())
+            .ok_or_else(|| anyhow!("The forked network has no active validator"))?;
+        let (epoch, timestamp_usecs) = {
+            let ledger = self.ledger.read().unwrap();
+            (ledger.epoch, ledger.timestamp_usecs)
+        };
+        let timestamp_usecs = seconds
+            .checked_mul(1_000_000)
+            .and_then(|usecs| usecs.checked_add(timestamp_usecs))
+            .ok_or_else(|| anyhow!("Can't advance time by {} seconds", seconds))?;
+        let block = Transaction::BlockMetadata(BlockMetadata::new(
+            HashValue::random(),
+            epoch,
+            self.version() - self.fork_version,
+            proposer,
+            vec![],
+            vec![],
+            timestamp_usecs,
+        ));
+
+        let vm = AptosVM::new_with_isolated_code_cache(self);
+        let log_context = AdapterLogSchema::new(self.id(), 0);
+        let resolver = self.as_move_resolver();
+        let (_, vm_output) = vm
+            .execute_single_transaction(
+                &SignatureVerifiedTransaction::Valid(block.clone()),
+                &resolver,
+                &log_context,
+            )
+            .map_err(|status| anyhow!("Failed to execute the block: {:?}", status))?;
+        let output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .map_err(|status| anyhow!("Failed to execute the block: {:?}", status))?;
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            status => bail!("Failed to execute the block: {:?}", status),
+        };
+        Ok(self.commit(block, &output, status))
+    }
+
+    /// Starts a new epoch right away, as a governance proposal would
+    ///
+    /// This runs `aptos_governance::force_end_epoch` as the framework account, without charging
+    /// gas. Only one epoch can start at a given time, so if the current epoch started at the
+    /// current time, time is moved forward by a second first.
+    pub fn force_end_epoch(&self) -> Result<TransactionOnChainData> {
+        // Held throughout, so that no other transaction starts an epoch in between
+        let _execution_guard = self.execution_lock.lock().unwrap();
+        let last_reconfiguration_time = ConfigurationResource::fetch_config(self)
+            .ok_or_else(|| anyhow!("Failed to read the on-chain configuration"))?
+            .last_reconfiguration_time();
+        if last_reconfiguration_time == self.ledger.read().unwrap().timestamp_usecs {
+            self.advance_time_locked(1)?;
+        }
+
+        let sequence_number = self
+            .get_state_value_bytes(&StateKey::resource_typed::<AccountResource>(
+                &CORE_CODE_ADDRESS,
+            )?)?
+            .map(|bytes| bcs::from_bytes::<AccountResource>(&bytes))
+            .transpose()?
+            .ok_or_else(|| anyhow!("The framework account doesn't exist"))?
+            .sequence_number();
+        let gas_unit_price = u64::from(self.gas_parameters()?.vm.txn.min_price_per_gas_unit);
+        let raw_txn = RawTransaction::new(
+            CORE_CODE_ADDRESS,
+            sequence_number,
+            aptos_stdlib::aptos_governance_force_end_epoch(),
+            MAX_GAS_ADMIN_TRANSACTION,
+            gas_unit_price,
+            self.ledger.read().unwrap().timestamp_usecs / 1_000_000 + 60,
+            ChainId::test(),
+        );
+        // A fee payer of 0x0 isn't charged when simulating, and the framework holds no coins
+        self.impersonate_locked(SignedTransaction::new_fee_payer(
+            raw_txn,
+            AccountAuthenticator::NoAccountAuthenticator,
+            vec![],
+            vec![],
+            AccountAddress::ZERO,
+            AccountAuthenticator::NoAccountAuthenticator,
+        ))
+    }
+
+    /// Applies the changes of an executed transaction and adds it to the ledger
+    fn commit(
+        &self,
+        transaction: Transaction,
+        output: &TransactionOutput,
+        status: ExecutionStatus,
+    ) -> TransactionOnChainData {
+        {
+            let mut overlay = self.overlay.write().unwrap();
+            for (state_key, write_op) in output.write_set() {
+                overlay.insert(state_key.clone(), write_op.as_state_value());
+            }
+        }
+        // The transaction may have changed the epoch or time, which the ledger info follows
+        self.refresh_ledger();
+
+        let mut ledger = self.ledger.write().unwrap();
+        let version = self.fork_version + ledger.transactions.len() as u64 + 1;
+        let data = transaction_on_chain_data(version, transaction, output, status);
+        ledger
+            .versions_by_hash
+            .insert(data.transaction.hash(), version);
+        let timestamp_usecs = ledger.timestamp_usecs;
+        ledger.transactions.push(CommittedTransaction {
+            data: data.clone(),
+            auxiliary_data: output.auxiliary_data().clone(),
+            timestamp_usecs,
+        });
+        data
+    }
+
+    /// Executes a transaction without committing anything
+    ///
+    /// The transaction must not have a valid signature, as for the simulation endpoint of the node.
+    pub fn simulate(&self, txn: SignedTransaction) -> (VMStatus, TransactionOnChainData) {
+        let vm = AptosSimulationVM::new_with_isolated_code_cache(self);
+        let (vm_status, output) = vm.simulate_signed_transaction(&txn, self);
+        let status = ExecutionStatus::conmbine_vm_status_for_simulation(
+            output.auxiliary_data(),
+            output.status().clone(),
+        );
+        let data = transaction_on_chain_data(
+            self.version() + 1,
+            Transaction::UserTransaction(txn),
+            &output,
+            status,
+        );
+        (vm_status, data)
+    }
+
+    pub fn view(
+        &self,
+        module_id: ModuleId,
+        function: Identifier,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) -> ViewFunctionOutput {
+        AptosVM::new_with_isolated_code_cache(self).execute_view_function_with_vm(
+            self,
+            module_id,
+            function,
+            ty_args,
+            args,
+            MAX_GAS_VIEW_FUNCTION,
+        )
+    }
+
+    /// The gas parameters currently on chain
+    pub fn gas_parameters(&self) -> Result<AptosGasParameters> {
+        let gas_parameters = match GasScheduleV2::fetch_config(self) {
+            Some(gas_schedule) => {
+                let feature_version = gas_schedule.feature_version;
+                AptosGasParameters::from_on_chain_gas_schedule(
+                    &gas_schedule.into_btree_map(),
+                    feature_version,
+                )
+            },
+            None => GasSchedule::fetch_config(self)
+                .ok_or_else(|| "no gas schedule found".to_string())
+                .and_then(|gas_schedule| {
+                    AptosGasParameters::from_on_chain_gas_schedule(
+                        &gas_schedule.into_btree_map(),
+                        0,
+                    )
+                }),
+        };
+        gas_parameters.map_err(|err| anyhow!("Failed to read the gas schedule: {}", err))
+    }
+}
+
+/// Builds the ledger representation of an executed transaction
+///
+/// The chain isn't authenticated, so all hashes of the ledger and state are 0s.
+fn transaction_on_chain_data(
+    version: Version,
+    transaction: Transaction,
+    output: &TransactionOutput,
+    status: ExecutionStatus,
+) -> TransactionOnChainData {
+    let zero_hash = HashValue::zero();
+    let info = TransactionInfo::new(
+        transaction.hash(),
+        zero_hash,
+        zero_hash,
+        None,
+        output.gas_used(),
+        status,
+    );
+    TransactionOnChainData {
+        version,
+        transaction,
+        info,
+        events: output.events().to_vec(),
+        accumulator_root_hash: zero_hash,
+        changes: output.write_set().clone(),
+    }
+}
+
+impl TStateView for ForkedChain {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.remote.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        if let Some(value) = self.overlay.read().unwrap().get(state_key) {
+            return Ok(value.clone());
+        }
+        self.remote.get_state_value(state_key)
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+/// Only what's needed to render transactions, other reads aren't supported
+impl DbReader for ForkedChain {
+    fn get_transaction_auxiliary_data_by_version(
+        &self,
+        version: Version,
+    ) -> aptos_storage_interface::Result<Option<TransactionAuxiliaryData>> {
+        let Some(index) = version.checked_sub(self.fork_version + 1) else {
+            return Ok(None);
+        };
+        Ok(self
+            .ledger
+            .read()
+            .unwrap()
+            .transactions
+            .get(index as usize)
+            .map(|txn| txn.auxiliary_data.clone()))
+    }
+
+    fn indexer_enabled(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_framework::natives::code::PackageMetadata;
+    use aptos_types::{
+        account_config::aptos_test_root_address,
+        transaction::TransactionPayload,
+        utility_coin::{AptosCoinType, CoinType},
+    };
+    use aptos_validator_interface::FilterCondition;
+    use aptos_vm_genesis::GENESIS_KEYPAIR;
+    use move_core_types::ident_str;
+
+    /// A network made of its genesis state only, recording the reads of its state
+    struct GenesisNetwork {
+        state: HashMap<StateKey, StateValue>,
+        reads: Mutex<Vec<StateKey>>,
+    }
+
+    impl GenesisNetwork {
+        fn new() -> Self {
+            let (change_set, _) = aptos_vm_genesis::generate_test_genesis(
+                aptos_cached_packages::head_release_bundle(),
+                Some(1),
+            );
+            Self {
+                state: change_set
+                    .write_set()
+                    .iter()
+                    .filter_map(|(state_key, write_op)| {
+                        Some((state_key.clone(), write_op.as_state_value()?))
+                    })
+                    .collect(),
+                reads: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AptosValidatorInterface for GenesisNetwork {
+        async fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> Result<Option<StateValue>> {
+            assert_eq!(version, 0, "The fork reads the state at the fork version");
+            self.reads.lock().unwrap().push(state_key.clone());
+            Ok(self.state.get(state_key).cloned())
+        }
+
+        async fn get_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+            bail!("The fork only reads the state of the network")
+        }
+
+        async fn get_and_filter_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+            _filter_condition: FilterCondition,
+            _package_cache: &mut HashMap<
+                ModuleId,
+                (
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                ),
+            >,
+        ) -> Result<
+            Vec<(
+                u64,
+                Transaction,
+                Option<(
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                )>,
+            )>,
+        > {
+            bail!("The fork only reads the state of the network")
+        }
+
+        async fn get_latest_ledger_info_version(&self) -> Result<Version> {
+            Ok(0)
+        }
+
+        async fn get_version_by_account_sequence(
+            &self,
+            _account: AccountAddress,
+            _seq: u64,
+        ) -> Result<Option<Version>> {
+            bail!("The fork only reads the state of the network")
+        }
+    }
+
+    async fn fork_genesis() -> (Arc<GenesisNetwork>, Arc<ForkedChain>) {
+        let network = Arc::new(GenesisNetwork::new());
+        let chain = ForkedChain::new(network.clone(), 0, 0).await.unwrap();
+        (network, chain)
+    }
+
+    /// Runs `f` on a blocking thread, as reading the remote state waits on the runtime
+    async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+        tokio::task::spawn_blocking(f).await.unwrap()
+    }
+
+    fn account(chain: &ForkedChain, address: AccountAddress) -> Option<AccountResource> {
+        chain
+            .get_state_value_bytes(&StateKey::resource_typed::<AccountResource>(&address).unwrap())
+            .unwrap()
+            .map(|bytes| bcs::from_bytes(&bytes).unwrap())
+    }
+
+    fn balance(chain: &ForkedChain, address: AccountAddress) -> u64 {
+        let values = chain
+            .view(
+                ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+                ident_str!("balance").into(),
+                vec![AptosCoinType::type_tag()],
+                vec![address.to_vec()],
+            )
+            .values
+            .unwrap();
+        bcs::from_bytes(&values[0]).unwrap()
+    }
+
+    fn transaction(
+        chain: &ForkedChain,
+        sequence_number: u64,
+        payload: TransactionPayload,
+    ) -> SignedTransaction {
+        let gas_parameters = chain.gas_parameters().unwrap();
+        RawTransaction::new(
+            aptos_test_root_address(),
+            sequence_number,
+            payload,
+            100_000,
+            u64::from(gas_parameters.vm.txn.min_price_per_gas_unit),
+            u64::MAX,
+            ChainId::test(),
+        )
+        .sign(&GENESIS_KEYPAIR.0, GENESIS_KEYPAIR.1.clone())
+        .unwrap()
+        .into_inner()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_read_remote_state() {
+        let (network, chain) = fork_genesis().await;
+        assert_eq!(chain.fork_version(), 0);
+        assert_eq!(chain.version(), 0);
+        assert_eq!(chain.ledger_info().epoch.0, 1);
+
+        blocking(move || {
+            let state_key =
+                StateKey::resource_typed::<AccountResource>(&aptos_test_root_address()).unwrap();
+            assert_eq!(
+                chain.get_state_value(&state_key).unwrap(),
+                network.state.get(&state_key).cloned()
+            );
+            assert!(network.reads.lock().unwrap().contains(&state_key));
+            assert!(account(&chain, AccountAddress::from_hex_literal("0xcafe").unwrap()).is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_local_writes_override_remote_state() {
+        let (network, chain) = fork_genesis().await;
+
+        blocking(move || {
+            // The chain ID is replaced when forking
+            let chain_id_key = StateKey::on_chain_config::<ChainIdResource>().unwrap();
+            network.reads.lock().unwrap().clear();
+            assert_eq!(
+                chain.get_state_value_bytes(&chain_id_key).unwrap(),
+                Some(bcs::to_bytes(&ChainId::test().id()).unwrap().into())
+            );
+            assert!(!network.reads.lock().unwrap().contains(&chain_id_key));
+
+            // Deleted values aren't read from the network either
+            let state_key =
+                StateKey::resource_typed::<AccountResource>(&aptos_test_root_address()).unwrap();
+            assert!(network.state.contains_key(&state_key));
+            chain
+                .overlay
+                .write()
+                .unwrap()
+                .insert(state_key.clone(), None);
+            assert_eq!(chain.get_state_value(&state_key).unwrap(), None);
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_then_read() {
+        let (network, chain) = fork_genesis().await;
+
+        blocking(move || {
+            let sender = aptos_test_root_address();
+            let receiver = AccountAddress::from_hex_literal("0xcafe").unwrap();
+            let sender_balance = balance(&chain, sender);
+
+            let txn = transaction(
+                &chain,
+                0,
+                aptos_stdlib::aptos_account_transfer(receiver, 1_000),
+            );
+            let hash = txn.committed_hash();
+            let data = chain.execute(txn).unwrap();
+            assert!(data.info.status().is_success());
+            assert_eq!(data.version, 1);
+            assert_eq!(chain.version(), 1);
+            assert_eq!(chain.ledger_info().ledger_version.0, 1);
+
+            // The changes are read from the fork, while the network is left as is
+            assert_eq!(balance(&chain, receiver), 1_000);
+            assert!(balance(&chain, sender) < sender_balance - 1_000);
+            assert_eq!(account(&chain, sender).unwrap().sequence_number(), 1);
+            let state_key = StateKey::resource_typed::<AccountResource>(&sender).unwrap();
+            let remote_account: AccountResource =
+                bcs::from_bytes(network.state[&state_key].bytes()).unwrap();
+            assert_eq!(remote_account.sequence_number(), 0);
+
+            // The transaction is in the ledger of the fork
+            let (by_hash, _) = chain.get_transaction_by_hash(hash).unwrap();
+            assert_eq!(by_hash.version, 1);
+            let (by_version, _) = chain.get_transaction_by_version(1).unwrap();
+            assert_eq!(by_version.transaction.hash(), data.transaction.hash());
+            assert!(chain.get_transaction_by_version(0).is_none());
+            assert!(chain.get_transaction_by_version(2).is_none());
+
+            // Transactions a node would discard aren't committed
+            let txn = transaction(
+                &chain,
+                0,
+                aptos_stdlib::aptos_account_transfer(receiver, 1_000),
+            );
+            assert!(chain.execute(txn).is_err());
+            assert_eq!(chain.version(), 1);
+            assert_eq!(balance(&chain, receiver), 1_000);
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_advance_time() {
+        let (_, chain) = fork_genesis().await;
+
+        blocking(move || {
+            let timestamp_usecs = chain.ledger_info().timestamp();
+            assert!(chain.advance_time(0).is_err());
+
+            let data = chain.advance_time(10).unwrap();
+            assert!(data.info.status().is_success());
+            assert!(matches!(data.transaction, Transaction::BlockMetadata(_)));
+            assert_eq!(chain.version(), 1);
+            assert_eq!(
+                chain.ledger_info().timestamp(),
+                timestamp_usecs + 10_000_000
+            );
+            assert_eq!(
+                CurrentTimeMicroseconds::fetch_config(&*chain)
+                    .unwrap()
+                    .microseconds,
+                timestamp_usecs + 10_000_000
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_force_end_epoch() {
+        let (_, chain) = fork_genesis().await;
+
+        blocking(move || {
+            let epoch = chain.ledger_info().epoch.0;
+            // The first epoch started at the current time, so time is moved forward first
+            let data = chain.force_end_epoch().unwrap();
+            assert!(data.info.status().is_success());
+            assert_eq!(chain.version(), 2);
+            assert_eq!(chain.ledger_info().epoch.0, epoch + 1);
+
+            let data = chain.force_end_epoch().unwrap();
+            assert!(data.info.status().is_success());
+            assert_eq!(chain.ledger_info().epoch.0, epoch + 2);
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_force_end_epoch_concurrently() {
+        let (_, chain) = fork_genesis().await;
+        let epoch = chain.ledger_info().epoch.0;
+
+        // Each epoch must start at its own time, whatever the order the threads run in
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let chain = chain.clone();
+                tokio::task::spawn_blocking(move || chain.force_end_epoch())
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap().info.status().is_success());
+        }
+        assert_eq!(chain.ledger_info().epoch.0, epoch + 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_impersonate() {
+        let (_, chain) = fork_genesis().await;
+
+        blocking(move || {
+            let receiver = AccountAddress::from_hex_literal("0xcafe").unwrap();
+            let payload = aptos_stdlib::aptos_account_transfer(receiver, 1_000);
+
+            // Signed transactions must be submitted instead
+            assert!(chain
+                .impersonate(transaction(&chain, 0, payload.clone()))
+                .is_err());
+            assert_eq!(chain.version(), 0);
+
+            let raw_txn = transaction(&chain, 0, payload).into_raw_transaction();
+            let data = chain
+                .impersonate(SignedTransaction::new_single_sender(
+                    raw_txn,
+                    AccountAuthenticator::NoAccountAuthenticator,
+                ))
+                .unwrap();
+            assert!(data.info.status().is_success());
+            assert_eq!(chain.version(), 1);
+            assert_eq!(balance(&chain, receiver), 1_000);
+            assert_eq!(
+                account(&chain, aptos_test_root_address())
+                    .unwrap()
+                    .sequence_number(),
+                1
+            );
+        })
+        .await;
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod api;
+mod chain;
+
+pub use self::api::{ADMIN_API_PREFIX, ADVANCE_TIME_PATH, FORCE_END_EPOCH_PATH, IMPERSONATE_PATH};
+use self::{api::run_fork_api, chain::ForkedChain};
+use super::{
+    health_checker::HealthChecker, traits::ServiceManager, utils::socket_addr_to_url, RunLocalnet,
+};
+use anyhow::{anyhow, Context, Result};
+use aptos_rest_client::Client;
+use async_trait::async_trait;
+use clap::Parser;
+use maplit::hashset;
+use reqwest::Url;
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+};
+
+/// Args related to forking a remote network instead of starting the localnet from a
+/// fresh genesis.
+#[derive(Debug, Clone, Parser)]
+pub struct ForkArgs {
+    /// Fork the network with the REST API at this URL instead of running a new chain
+    ///
+    /// State is fetched from the network as it is read, and transactions submitted to the
+    /// localnet are executed on top of it, e.g. to test an upgrade against mainnet data. In
+    /// this mode only the node API runs, without a faucet, transaction stream or indexer
+    /// API.
+    ///
+    /// The node API only serves accounts, resources, transactions, gas price estimates and view
+    /// functions, other endpoints fail with a 404 error listing the supported ones. Only the
+    /// transactions executed since the fork can be read.
+    ///
+    /// The fork runs as a testing chain (chain ID 4), so transactions signed for it can't
+    /// be replayed on the forked network. Its state lives in memory, so every run of the
+    /// localnet starts a new fork. Use `aptos node localnet` to move its time forward, end
+    /// epochs, or run transactions as any account.
+    #[clap(long)]
+    pub fork_url: Option<Url>,
+
+    /// The version to fork the network at. Defaults to its latest version
+    #[clap(long, requires = "fork_url")]
+    pub fork_version: Option<u64>,
+
+    /// The port at which to expose the node API of the fork
+    #[clap(long, default_value_t = 8080)]
+    pub fork_api_port: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct ForkManager {
+    fork_url: Url,
+    fork_version: Option<u64>,
+    api_address: SocketAddrV4,
+}
+
+impl ForkManager {
+    pub fn new(args: &RunLocalnet, bind_to: Ipv4Addr) -> Result<Self> {
+        let fork_url = args
+            .fork_args
+            .fork_url
+            .clone()
+            .ok_or_else(|| anyhow!("--fork-url must be set to fork a network"))?;
+        Ok(ForkManager {
+            fork_url,
+            fork_version: args.fork_args.fork_version,
+            api_address: SocketAddrV4::new(bind_to, args.fork_args.fork_api_port),
+        })
+    }
+
+    pub fn get_node_api_url(&self) -> Url {
+        socket_addr_to_url(&SocketAddr::V4(self.api_address), "http").unwrap()
+    }
+}
+
+#[async_trait]
+impl ServiceManager for ForkManager {
+    fn get_name(&self) -> String {
+        "Forked Node API".to_string()
+    }
+
+    fn get_health_checkers(&self) -> HashSet<HealthChecker> {
+        hashset! {HealthChecker::NodeApi(self.get_node_api_url())}
+    }
+
+    fn get_prerequisite_health_checkers(&self) -> HashSet<&HealthChecker> {
+        // The fork doesn't depend on anything local, only on the remote network.
+        hashset! {}
+    }
+
+    async fn run_service(self: Box<Self>) -> Result<()> {
+        let chain = ForkedChain::fork(Client::new(self.fork_url.clone()), self.fork_version)
+            .await
+            .with_context(|| format!("Failed to fork {}", self.fork_url))?;
+        eprintln!(
+            "Forked {} at version {}",
+            self.fork_url,
+            chain.fork_version()
+        );
+        run_fork_api(chain, *self.api_address.ip(), self.api_address.port()).await
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod docker;
+mod fork;
 mod health_checker;
 mod indexer_api;
 mod logging;
@@ -11,12 +12,14 @@ mod ready_server;
 mod utils;
 
 // This is to allow external crates to use the localnode.
+pub mod admin;
 pub mod faucet;
 pub mod node;
 pub mod traits;
 
 use self::{
     faucet::FaucetArgs,
+    fork::{ForkArgs, ForkManager},
     health_checker::HealthChecker,
     indexer_api::IndexerApiArgs,
     logging::ThreadNameMakeWriter,
@@ -59,6 +62,9 @@ const TESTNET_FOLDER: &str = "testnet";
 /// This localnet will run it's own genesis and run as a single node network
 /// locally. A faucet and grpc transaction stream will run alongside the node unless
 /// you specify otherwise with --no-faucet and --no-txn-stream respectively.
+///
+/// With --fork-url, the localnet instead forks an existing network, so transactions
+/// can be tested against its state.
 #[derive(Parser)]
 pub struct RunLocalnet {
     /// The directory to save all files for the node
@@ -78,6 +84,9 @@ pub struct RunLocalnet {
     #[clap(flatten)]
     node_args: NodeArgs,
 
+    #[clap(flatten)]
+    fork_args: ForkArgs,
+
     #[clap(flatten)]
     faucet_args: FaucetArgs,
 
@@ -175,6 +184,79 @@ impl RunLocalnet {
 
         Ok(())
     }
+
+    /// Builds the managers of the node and of the services running alongside it.
+    fn build_node_managers(
+        &self,
+        bind_to: Ipv4Addr,
+        test_dir: &Path,
+    ) -> Result<Vec<Box<dyn ServiceManager>>> {
+        let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
+
+        // Build the node manager. We do this unconditionally.
+        let node_manager = NodeManager::new(self, bind_to, test_dir.to_path_buf())
+            .context("Failed to build node service manager")?;
+        let node_health_checkers = node_manager.get_health_checkers();
+
+        // If configured to do so, build the faucet manager.
+        if !self.faucet_args.no_faucet {
+            let faucet_manager = FaucetManager::new(
+                self,
+                node_health_checkers.clone(),
+                bind_to,
+                test_dir.to_path_buf(),
+                node_manager.get_node_api_url(),
+            )
+            .context("Failed to build faucet service manager")?;
+            managers.push(Box::new(faucet_manager));
+        }
+
+        if self.indexer_api_args.with_indexer_api {
+            let postgres_manager = postgres::PostgresManager::new(self, test_dir.to_path_buf())
+                .context("Failed to build postgres service manager")?;
+            let postgres_health_checkers = postgres_manager.get_health_checkers();
+            managers.push(Box::new(postgres_manager));
+
+            let processor_preqrequisite_healthcheckers =
+                [node_health_checkers, postgres_health_checkers]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+            let processor_managers = ProcessorManager::many_new(
+                self,
+                processor_preqrequisite_healthcheckers,
+                node_manager.get_data_service_url(),
+                self.postgres_args.get_connection_string(None, true),
+            )
+            .context("Failed to build processor service managers")?;
+
+            let processor_health_checkers = processor_managers
+                .iter()
+                .flat_map(|m| m.get_health_checkers())
+                .collect();
+
+            let mut processor_managers = processor_managers
+                .into_iter()
+                .map(|m| Box::new(m) as Box<dyn ServiceManager>)
+                .collect();
+            managers.append(&mut processor_managers);
+
+            let indexer_api_manager = IndexerApiManager::new(
+                self,
+                processor_health_checkers,
+                test_dir.to_path_buf(),
+                self.postgres_args.get_connection_string(None, false),
+            )
+            .context("Failed to build indexer API service manager")?;
+            managers.push(Box::new(indexer_api_manager));
+        }
+
+        // We put the node manager into managers at the end just so we have access to
+        // it before this so we can call things like `node_manager.get_node_api_url()`.
+        managers.push(Box::new(node_manager));
+
+        Ok(managers)
+    }
 }
 
 #[async_trait]
@@ -245,68 +327,24 @@ impl CliCommand<()> for RunLocalnet {
 
         let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
 
-        // Build the node manager. We do this unconditionally.
-        let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
-            .context("Failed to build node service manager")?;
-        let node_health_checkers = node_manager.get_health_checkers();
-
-        // If configured to do so, build the faucet manager.
-        if !self.faucet_args.no_faucet {
-            let faucet_manager = FaucetManager::new(
-                &self,
-                node_health_checkers.clone(),
-                bind_to,
-                test_dir.clone(),
-                node_manager.get_node_api_url(),
-            )
-            .context("Failed to build faucet service manager")?;
-            managers.push(Box::new(faucet_manager));
-        }
-
-        if self.indexer_api_args.with_indexer_api {
-            let postgres_manager = postgres::PostgresManager::new(&self, test_dir.clone())
-                .context("Failed to build postgres service manager")?;
-            let postgres_health_checkers = postgres_manager.get_health_checkers();
-            managers.push(Box::new(postgres_manager));
-
-            let processor_preqrequisite_healthcheckers =
-                [node_health_checkers, postgres_health_checkers]
-                    .into_iter()
-                    .flatten()
-                    .collect();
-            let processor_managers = ProcessorManager::many_new(
-                &self,
-                processor_preqrequisite_healthcheckers,
-                node_manager.get_data_service_url(),
-                self.postgres_args.get_connection_string(None, true),
-            )
-            .context("Failed to build processor service managers")?;
-
-            let processor_health_checkers = processor_managers
-                .iter()
-                .flat_map(|m| m.get_health_checkers())
-                .collect();
-
-            let mut processor_managers = processor_managers
-                .into_iter()
-                .map(|m| Box::new(m) as Box<dyn ServiceManager>)
-                .collect();
-            managers.append(&mut processor_managers);
-
-            let indexer_api_manager = IndexerApiManager::new(
-                &self,
-                processor_health_checkers,
-                test_dir.clone(),
-                self.postgres_args.get_connection_string(None, false),
-            )
-            .context("Failed to build indexer API service manager")?;
-            managers.push(Box::new(indexer_api_manager));
+        if self.fork_args.fork_url.is_some() {
+            // When forking a network only the node API runs, the other services rely on
+            // running the chain from genesis.
+            if self.indexer_api_args.with_indexer_api {
+                return Err(CliError::CommandArgumentError(
+                    "--with-indexer-api can't be used with --fork-url".to_string(),
+                ));
+            }
+            if !self.faucet_args.no_faucet {
+                eprintln!("The faucet doesn't run when forking a network");
+            }
+            let fork_manager = ForkManager::new(&self, bind_to)
+                .context("Failed to build fork service manager")?;
+            managers.push(Box::new(fork_manager));
+        } else {
+            managers.append(&mut self.build_node_managers(bind_to, &test_dir)?);
         }
 
-        // We put the node manager into managers at the end just so we have access to
-        // it before this so we can call things like `node_manager.get_node_api_url()`.
-        managers.push(Box::new(node_manager));
-
         // Get the healthcheckers from all the managers. We'll pass to this
         // `wait_for_startup`.
         let health_checkers: HashSet<HealthChecker> = managers
@@ -163,6 +163,11 @@ impl NodeManager {
         node_config.admin_service.address = bind_to.to_string();
         node_config.inspection_service.address = bind_to.to_string();
 
+        // Serve the controls of the localnet, e.g. moving its time forward, through the admin
+        // service. They are only served to clients on this machine.
+        node_config.admin_service.enabled = Some(true);
+        node_config.admin_service.enable_localnet_controls = true;
+
         Ok(NodeManager {
             config: node_config,
             test_dir,
@@ -4,7 +4,7 @@
 pub mod analyze;
 pub mod local_testnet;
 
-use self::local_testnet::RunLocalnet;
+use self::local_testnet::{admin::LocalnetTool, RunLocalnet};
 use crate::{
     common::{
         types::{
@@ -72,6 +72,8 @@ pub enum NodeTool {
     InitializeValidator(InitializeValidator),
     JoinValidatorSet(JoinValidatorSet),
     LeaveValidatorSet(LeaveValidatorSet),
+    #[clap(subcommand)]
+    Localnet(LocalnetTool),
     ShowEpochInfo(ShowEpochInfo),
     ShowValidatorConfig(ShowValidatorConfig),
     ShowValidatorSet(ShowValidatorSet),
@@ -97,6 +99,7 @@ impl NodeTool {
             InitializeValidator(tool) => tool.execute_serialized().await,
             JoinValidatorSet(tool) => tool.execute_serialized().await,
             LeaveValidatorSet(tool) => tool.execute_serialized().await,
+            Localnet(tool) => tool.execute().await,
             ShowEpochInfo(tool) => tool.execute_serialized().await,
             ShowValidatorSet(tool) => tool.execute_serialized().await,
             ShowValidatorStake(tool) => tool.execute_serialized().await,
@@ -647,7 +650,6 @@ impl CliCommand<TransactionSummary> for InitializeValidator {
                 bcs::to_bytes(&full_node_network_addresses)?,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -708,7 +710,6 @@ impl CliCommand<TransactionSummary> for JoinValidatorSet {
         self.txn_options
             .submit_transaction(aptos_stdlib::stake_join_validator_set(address))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -738,7 +739,6 @@ impl CliCommand<TransactionSummary> for LeaveValidatorSet {
         self.txn_options
             .submit_transaction(aptos_stdlib::stake_leave_validator_set(address))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -1083,7 +1083,6 @@ impl CliCommand<TransactionSummary> for UpdateConsensusKey {
                 consensus_proof_of_possession.to_bytes().to_vec(),
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -1144,7 +1143,6 @@ impl CliCommand<TransactionSummary> for UpdateValidatorNetworkAddresses {
                 bcs::to_bytes(&full_node_network_addresses)?,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
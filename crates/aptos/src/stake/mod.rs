@@ -85,14 +85,15 @@ impl CliCommand<Vec<TransactionSummary>> for AddStake {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
                     transaction_summaries.push(
                         self.txn_options
                             .submit_transaction(aptos_stdlib::stake_add_stake(amount))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -102,8 +103,7 @@ impl CliCommand<Vec<TransactionSummary>> for AddStake {
                                 stake_pool.operator_address,
                                 amount,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -146,14 +146,15 @@ impl CliCommand<Vec<TransactionSummary>> for UnlockStake {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
                     transaction_summaries.push(
                         self.txn_options
                             .submit_transaction(aptos_stdlib::stake_unlock(amount))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -163,8 +164,7 @@ impl CliCommand<Vec<TransactionSummary>> for UnlockStake {
                                 stake_pool.operator_address,
                                 amount,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -210,14 +210,15 @@ impl CliCommand<Vec<TransactionSummary>> for WithdrawStake {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
                     transaction_summaries.push(
                         self.node_op_options
                             .submit_transaction(aptos_stdlib::stake_withdraw(amount))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -227,8 +228,7 @@ impl CliCommand<Vec<TransactionSummary>> for WithdrawStake {
                                 owner_address,
                                 stake_pool.operator_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -267,14 +267,15 @@ impl CliCommand<Vec<TransactionSummary>> for IncreaseLockup {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
                     transaction_summaries.push(
                         self.txn_options
                             .submit_transaction(aptos_stdlib::stake_increase_lockup())
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -283,8 +284,7 @@ impl CliCommand<Vec<TransactionSummary>> for IncreaseLockup {
                             .submit_transaction(aptos_stdlib::staking_contract_reset_lockup(
                                 stake_pool.operator_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -293,8 +293,7 @@ impl CliCommand<Vec<TransactionSummary>> for IncreaseLockup {
                             .submit_transaction(aptos_stdlib::vesting_reset_lockup(
                                 stake_pool.vesting_contract.unwrap(),
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
             }
@@ -340,7 +339,6 @@ impl CliCommand<TransactionSummary> for InitializeStakeOwner {
                 self.voter_address.unwrap_or(owner_address),
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -376,6 +374,8 @@ impl CliCommand<Vec<TransactionSummary>> for SetOperator {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
@@ -384,8 +384,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetOperator {
                             .submit_transaction(aptos_stdlib::stake_set_operator(
                                 new_operator_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -397,8 +396,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetOperator {
                                     new_operator_address,
                                 ),
                             )
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -410,8 +408,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetOperator {
                                     new_operator_address,
                                 ),
                             )
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
             }
@@ -452,6 +449,8 @@ impl CliCommand<Vec<TransactionSummary>> for SetDelegatedVoter {
         let mut transaction_summaries: Vec<TransactionSummary> = vec![];
 
         let stake_pool_results = get_stake_pools(&client, owner_address).await?;
+        self.txn_options
+            .check_output_unsigned(stake_pool_results.len())?;
         for stake_pool in stake_pool_results {
             match stake_pool.pool_type {
                 StakePoolType::Direct => {
@@ -460,8 +459,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetDelegatedVoter {
                             .submit_transaction(aptos_stdlib::stake_set_delegated_voter(
                                 new_voter_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::StakingContract => {
@@ -471,8 +469,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetDelegatedVoter {
                                 stake_pool.operator_address,
                                 new_voter_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
                 StakePoolType::Vesting => {
@@ -482,8 +479,7 @@ impl CliCommand<Vec<TransactionSummary>> for SetDelegatedVoter {
                                 stake_pool.vesting_contract.unwrap(),
                                 new_voter_address,
                             ))
-                            .await
-                            .map(|inner| inner.into())?,
+                            .await?,
                     );
                 },
             }
@@ -545,7 +541,6 @@ impl CliCommand<TransactionSummary> for CreateStakingContract {
                 vec![],
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -574,7 +569,6 @@ impl CliCommand<TransactionSummary> for DistributeVestedCoins {
         self.txn_options
             .submit_transaction(aptos_stdlib::vesting_distribute(vesting_contract_address))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -607,7 +601,6 @@ impl CliCommand<TransactionSummary> for UnlockVestedCoins {
         self.txn_options
             .submit_transaction(aptos_stdlib::vesting_vest(vesting_contract_address))
             .await
-            .map(|inner| inner.into())
     }
 }
 
@@ -663,6 +656,5 @@ impl CliCommand<TransactionSummary> for RequestCommission {
                 self.operator_address,
             ))
             .await
-            .map(|inner| inner.into())
     }
 }
@@ -16,11 +16,11 @@ use crate::{
         types::{
             account_address_from_public_key, AccountAddressWrapper, ArgWithTypeVec,
             AuthenticationKeyInputOptions, ChunkedPublishOption, CliError, CliTypedResult,
-            EncodingOptions, EntryFunctionArguments, FaucetOptions, GasOptions, KeyType,
-            MoveManifestAccountWrapper, MovePackageDir, OptionalPoolAddressArgs,
-            OverrideSizeCheckOption, PoolAddressArgs, PrivateKeyInputOptions, PromptOptions,
-            PublicKeyInputOptions, RestOptions, RngArgs, SaveFile, ScriptFunctionArguments,
-            TransactionOptions, TransactionSummary, TypeArgVec,
+            EncodingOptions, EntryFunctionArguments, FaucetOptions, FeePayerOptions, GasOptions,
+            KeyType, MoveManifestAccountWrapper, MovePackageDir, MultiAgentOptions,
+            OptionalPoolAddressArgs, OverrideSizeCheckOption, PoolAddressArgs,
+            PrivateKeyInputOptions, PromptOptions, PublicKeyInputOptions, RestOptions, RngArgs,
+            SaveFile, ScriptFunctionArguments, TransactionOptions, TransactionSummary, TypeArgVec,
         },
         utils::write_to_file,
     },
@@ -45,6 +45,7 @@ use crate::{
         AddStake, IncreaseLockup, InitializeStakeOwner, SetDelegatedVoter, SetOperator,
         UnlockStake, WithdrawStake,
     },
+    transaction::{build::BuildTransaction, sign::SignTransaction, submit::SubmitTransaction},
     CliCommand,
 };
 use aptos_config::config::Peer;
@@ -311,6 +312,27 @@ impl CliTestFramework {
         .await
     }
 
+    /// Writes a transfer unsigned to `output_file` with `--output-unsigned`, instead of
+    /// submitting it
+    pub async fn transfer_coins_unsigned(
+        &self,
+        sender_index: usize,
+        receiver_index: usize,
+        amount: u64,
+        output_file: PathBuf,
+    ) -> CliTypedResult<TransferSummary> {
+        TransferCoins {
+            txn_options: TransactionOptions {
+                output_unsigned: Some(output_file),
+                ..self.transaction_options(sender_index, None)
+            },
+            account: self.account_id(receiver_index),
+            amount,
+        }
+        .execute()
+        .await
+    }
+
     pub async fn transfer_invalid_addr(
         &self,
         sender_index: usize,
@@ -339,6 +361,7 @@ impl CliTestFramework {
                 },
                 json_file: None,
             },
+            multi_agent_options: Default::default(),
             txn_options: self.transaction_options(sender_index, gas_options),
         }
         .execute()
@@ -568,6 +591,9 @@ impl CliTestFramework {
             skip_faucet: false,
             ledger: false,
             hardware_wallet_options: Default::default(),
+            signature_scheme: Default::default(),
+            multi_key_public_keys: vec![],
+            signatures_required: None,
         }
         .execute()
         .await
@@ -628,6 +654,7 @@ impl CliTestFramework {
                 type_arg_vec: TypeArgVec { type_args: vec![] },
                 json_file: None,
             },
+            multi_agent_options: Default::default(),
             txn_options: self.transaction_options(owner_index, None),
         }
         .execute()
@@ -953,12 +980,126 @@ impl CliTestFramework {
                 },
                 json_file: None,
             },
+            multi_agent_options: Default::default(),
             txn_options: self.transaction_options(index, gas_options),
         }
         .execute()
         .await
     }
 
+    /// Builds a transaction running the given function without signing it, like
+    /// `aptos transaction build`, optionally with its gas paid by the fee payer
+    pub async fn build_transaction(
+        &self,
+        index: usize,
+        function_id: MemberId,
+        args: Vec<&str>,
+        fee_payer_index: Option<usize>,
+        output_file: PathBuf,
+    ) -> CliTypedResult<TransactionSummary> {
+        let mut parsed_args = Vec::new();
+        for arg in args {
+            parsed_args.push(
+                ArgWithType::from_str(arg)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+            )
+        }
+
+        BuildTransaction {
+            entry_function_args: EntryFunctionArguments {
+                function_id: Some(function_id),
+                arg_vec: ArgWithTypeVec { args: parsed_args },
+                type_arg_vec: TypeArgVec { type_args: vec![] },
+                json_file: None,
+            },
+            multi_agent_options: Default::default(),
+            txn_options: TransactionOptions {
+                fee_payer_options: FeePayerOptions {
+                    fee_payer_profile: None,
+                    fee_payer_address: fee_payer_index.map(|index| self.account_id(index)),
+                },
+                ..self.transaction_options(index, None)
+            },
+            output_file,
+        }
+        .execute()
+        .await
+    }
+
+    /// Like [`CliTestFramework::build_transaction`], for a function also taking the `&signer`
+    /// of the secondary signers, which co-sign it with [`CliTestFramework::sign_transaction`]
+    pub async fn build_multi_agent_transaction(
+        &self,
+        index: usize,
+        function_id: MemberId,
+        args: Vec<&str>,
+        secondary_signer_indices: Vec<usize>,
+        output_file: PathBuf,
+    ) -> CliTypedResult<TransactionSummary> {
+        let mut parsed_args = Vec::new();
+        for arg in args {
+            parsed_args.push(
+                ArgWithType::from_str(arg)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+            )
+        }
+
+        BuildTransaction {
+            entry_function_args: EntryFunctionArguments {
+                function_id: Some(function_id),
+                arg_vec: ArgWithTypeVec { args: parsed_args },
+                type_arg_vec: TypeArgVec { type_args: vec![] },
+                json_file: None,
+            },
+            multi_agent_options: MultiAgentOptions {
+                secondary_signer_profiles: vec![],
+                secondary_signer_addresses: secondary_signer_indices
+                    .into_iter()
+                    .map(|index| self.account_id(index))
+                    .collect(),
+            },
+            txn_options: self.transaction_options(index, None),
+            output_file,
+        }
+        .execute()
+        .await
+    }
+
+    /// Signs a transaction built with [`CliTestFramework::build_transaction`], without
+    /// connecting to the network
+    pub async fn sign_transaction(
+        &self,
+        index: usize,
+        unsigned_transaction_file: PathBuf,
+        output_file: PathBuf,
+    ) -> CliTypedResult<TransactionSummary> {
+        SignTransaction {
+            unsigned_transaction_file,
+            output_file,
+            signer_account: Some(self.account_id(index)),
+            private_key_options: PrivateKeyInputOptions::from_private_key(self.private_key(index))?,
+            encoding_options: Default::default(),
+            profile_options: Default::default(),
+            prompt_options: PromptOptions::yes(),
+        }
+        .execute()
+        .await
+    }
+
+    /// Submits a transaction signed with [`CliTestFramework::sign_transaction`]
+    pub async fn submit_signed_transaction(
+        &self,
+        signed_transaction_file: PathBuf,
+    ) -> CliTypedResult<TransactionSummary> {
+        SubmitTransaction {
+            signed_transaction_file,
+            rest_options: self.rest_options(),
+            profile_options: Default::default(),
+        }
+        .execute()
+        .await
+    }
+
     /// Runs the given script contents using the local aptos_framework directory.
     pub async fn run_script(
         &self,
@@ -0,0 +1,699 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            load_account_arg, AccountAddressWrapper, CliCommand, CliError, CliResult,
+            CliTypedResult, EntryFunctionArguments, EntryFunctionArgumentsJSON, MovePackageDir,
+            TransactionOptions, TransactionSummary,
+        },
+        utils::{read_from_file, write_to_file},
+    },
+    move_tool::{
+        build_package_options, create_package_publication_data, IncludedArtifacts,
+        IncludedArtifactsArgs, MAX_PUBLISH_PACKAGE_SIZE,
+    },
+};
+use aptos_api_types::{HashValue, Transaction};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_framework::chunked_publish::PublishType;
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode},
+    error::{AptosErrorResponse, RestError},
+};
+use aptos_types::transaction::{
+    EntryFunction, SignedTransaction, TransactionPayload, ViewFunction,
+};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Tool for running plans of several transactions
+#[derive(Debug, Subcommand)]
+pub enum BatchTool {
+    Run(RunBatch),
+}
+
+impl BatchTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            BatchTool::Run(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Run the steps of a YAML plan one after the other, stopping at the first failure
+///
+/// Each step runs an entry function (`run`), publishes a package (`publish`), transfers APT
+/// (`transfer`), or calls a view function and checks its result (`view`), e.g.:
+///
+/// variables:
+///   amount: "1000"
+/// steps:
+///   - name: fund
+///     transfer: { account: "0xcafe", amount: "${amount}" }
+///   - name: publish
+///     publish: { package_dir: ./package, named_addresses: { my_addr: "${sender}" } }
+///   - name: initialize
+///     run: { function_id: "${sender}::module::initialize", type_args: [], args: [] }
+///   - name: check
+///     view: { function_id: "0x1::coin::balance", type_args: ["0x1::aptos_coin::AptosCoin"],
+///             args: [{ type: address, value: "0xcafe" }], expect: ["${amount}"] }
+///
+/// `${name}` is replaced by a variable, by `sender` for the sender address, or by the output of
+/// a previous step, e.g. `${fund.transaction_hash}` or `${check.0}`. Completed steps are recorded
+/// in the state file, so that running the plan again after a failure resumes after them. The
+/// transaction of a step is recorded there too before it's submitted, so that it isn't submitted
+/// twice if the run stops before it's committed.
+#[derive(Debug, Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct RunBatch {
+    /// YAML file of the plan
+    #[clap(value_parser)]
+    pub(crate) plan_file: PathBuf,
+    /// File recording the completed steps, `<PLAN_FILE>.state.json` by default
+    ///
+    /// Delete it to run the plan from the start again.
+    #[clap(long, value_parser)]
+    pub(crate) state_file: Option<PathBuf>,
+    /// Simulate the remaining steps instead of submitting them
+    ///
+    /// Every step is simulated against the current state of the chain, so steps relying on the
+    /// changes of previous steps may fail, and the failures don't stop the dry run.
+    #[clap(long)]
+    pub(crate) dry_run: bool,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+/// Plan of `aptos transaction batch run`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Plan {
+    #[serde(default)]
+    variables: BTreeMap<String, Value>,
+    /// Steps are deserialized once their variables are substituted
+    steps: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct Step {
+    name: String,
+    #[serde(flatten)]
+    action: StepAction,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StepAction {
+    Run(EntryFunctionArgumentsJSON),
+    Publish(PublishStep),
+    Transfer(TransferStep),
+    View(ViewStep),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PublishStep {
+    /// Relative to the directory of the plan
+    package_dir: PathBuf,
+    /// Addresses or profiles, by named address
+    #[serde(default)]
+    named_addresses: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransferStep {
+    /// Address or profile to send APT to
+    account: String,
+    /// Amount of Octas, as a number or a string
+    amount: Value,
+}
+
+#[derive(Deserialize)]
+struct ViewStep {
+    #[serde(flatten)]
+    function: EntryFunctionArgumentsJSON,
+    /// Expected values returned by the function, in their JSON representation
+    expect: Option<Vec<Value>>,
+}
+
+/// Steps completed by previous runs of a plan
+#[derive(Default, Deserialize, Serialize)]
+struct BatchState {
+    completed: Vec<StepSummary>,
+    /// Transaction of the next step, which may be committed even if the run stopped before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending: Option<PendingTransaction>,
+}
+
+impl BatchState {
+    fn write(&self, path: &Path) -> CliTypedResult<()> {
+        let bytes = serde_json::to_vec_pretty(self).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to serialize state: {}", err))
+        })?;
+        write_to_file(path, "Batch state", &bytes)
+    }
+}
+
+/// Transaction submitted for a step
+#[derive(Deserialize, Serialize)]
+struct PendingTransaction {
+    step: String,
+    transaction_hash: HashValue,
+    sequence_number: u64,
+    /// BCS encoded signed transaction, to submit it again as is
+    signed_transaction: String,
+}
+
+impl PendingTransaction {
+    fn new(step: String, signed_txn: &SignedTransaction) -> CliTypedResult<Self> {
+        Ok(PendingTransaction {
+            step,
+            transaction_hash: signed_txn.committed_hash().into(),
+            sequence_number: signed_txn.sequence_number(),
+            signed_transaction: hex::encode(bcs::to_bytes(signed_txn)?),
+        })
+    }
+
+    fn signed_transaction(&self) -> CliTypedResult<SignedTransaction> {
+        let bytes = hex::decode(&self.signed_transaction)
+            .map_err(|err| CliError::UnableToParse("pending transaction", err.to_string()))?;
+        bcs::from_bytes(&bytes).map_err(|err| CliError::BCS("pending transaction", err))
+    }
+}
+
+/// Outcome of a step of a plan
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StepSummary {
+    pub name: String,
+    /// Summary of the transaction, or values returned by the view function
+    pub output: Value,
+    /// Whether the step was completed by a previous run
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumed: bool,
+    /// Why the step failed, for dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<StepSummary>> for RunBatch {
+    fn command_name(&self) -> &'static str {
+        "RunBatch"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<StepSummary>> {
+        let plan: Plan = serde_yaml::from_slice(&read_from_file(&self.plan_file)?)
+            .map_err(|err| CliError::UnableToParse("plan", err.to_string()))?;
+        let plan_dir = self
+            .plan_file
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let state_file = self
+            .state_file
+            .clone()
+            .unwrap_or_else(|| self.plan_file.with_extension("state.json"));
+        let mut state = if state_file.exists() {
+            serde_json::from_slice::<BatchState>(&read_from_file(&state_file)?)
+                .map_err(|err| CliError::UnableToParse("state file", err.to_string()))?
+        } else {
+            BatchState::default()
+        };
+
+        let mut variables = plan.variables;
+        variables.insert(
+            "sender".to_string(),
+            Value::String(self.txn_options.sender_address()?.to_hex_literal()),
+        );
+
+        let mut summaries = vec![];
+        for (index, step) in plan.steps.into_iter().enumerate() {
+            let name = step
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    CliError::CommandArgumentError(format!("Step {} has no name", index + 1))
+                })?
+                .to_string();
+            if variables.contains_key(&name) {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Step '{}' has the name of a variable or of a previous step",
+                    name
+                )));
+            }
+
+            if let Some(completed) = state.completed.get(index) {
+                if completed.name != name {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "The state file {} doesn't match the plan, its step {} is '{}' instead \
+                        of '{}'",
+                        state_file.display(),
+                        index + 1,
+                        completed.name,
+                        name
+                    )));
+                }
+                variables.insert(name, completed.output.clone());
+                summaries.push(StepSummary {
+                    resumed: true,
+                    ..completed.clone()
+                });
+                continue;
+            }
+
+            let step: Step = serde_json::from_value(substitute(step, &variables)?)
+                .map_err(|err| CliError::UnableToParse("step", format!("{}: {}", name, err)))?;
+            eprintln!("Running step '{}'", name);
+            let result = if self.dry_run {
+                self.simulate_step(step.action, &plan_dir).await
+            } else {
+                self.run_step(&name, step.action, &plan_dir, &mut state, &state_file)
+                    .await
+            };
+
+            let summary = match result {
+                Ok((output, error)) => StepSummary {
+                    name: name.clone(),
+                    output,
+                    resumed: false,
+                    error,
+                },
+                Err(err) => {
+                    if !self.dry_run {
+                        eprintln!(
+                            "Step '{}' failed, running the plan again resumes from it",
+                            name
+                        );
+                    }
+                    return Err(err);
+                },
+            };
+            variables.insert(name, summary.output.clone());
+            if !self.dry_run {
+                state.completed.push(summary.clone());
+                state.pending = None;
+                state.write(&state_file)?;
+            }
+            summaries.push(summary);
+        }
+
+        Ok(summaries)
+    }
+}
+
+impl RunBatch {
+    /// Runs a step, failing if its transaction fails or its view doesn't return what's expected
+    ///
+    /// The transaction is recorded in the state file before it's submitted, and the transaction
+    /// recorded by a previous run is checked before submitting a new one.
+    async fn run_step(
+        &self,
+        name: &str,
+        action: StepAction,
+        plan_dir: &Path,
+        state: &mut BatchState,
+        state_file: &Path,
+    ) -> CliTypedResult<(Value, Option<String>)> {
+        let payload = match action {
+            StepAction::View(view) => {
+                let (output, error) = self.view(view).await?;
+                return match error {
+                    Some(error) => Err(CliError::UnexpectedError(error)),
+                    None => Ok((output, None)),
+                };
+            },
+            action => step_payload(action, plan_dir)?,
+        };
+        if self.txn_options.output_unsigned.is_some() {
+            return Err(CliError::CommandArgumentError(
+                "--output-unsigned isn't supported by this command, as steps may rely on the \
+                output of the previous ones"
+                    .to_string(),
+            ));
+        }
+
+        let resumed = match state.pending.take() {
+            Some(pending) if pending.step == name => {
+                self.resume_transaction(&pending, state_file).await?
+            },
+            _ => None,
+        };
+        let transaction = match resumed {
+            Some(transaction) => transaction,
+            None => {
+                let signed_txn = self.txn_options.sign_transaction(payload, vec![]).await?;
+                state.pending = Some(PendingTransaction::new(name.to_string(), &signed_txn)?);
+                state.write(state_file)?;
+                self.txn_options
+                    .submit_signed_transaction(&signed_txn)
+                    .await?
+            },
+        };
+        Ok((to_value(&TransactionSummary::from(transaction))?, None))
+    }
+
+    /// Waits for the transaction submitted by a previous run, or submits it again if it may
+    /// still be committed
+    ///
+    /// Returns `None` if it can't be committed anymore, so that a new one is submitted, and fails
+    /// if it's unknown whether it was committed.
+    async fn resume_transaction(
+        &self,
+        pending: &PendingTransaction,
+        state_file: &Path,
+    ) -> CliTypedResult<Option<Transaction>> {
+        let client = self
+            .txn_options
+            .rest_options
+            .client(&self.txn_options.profile_options)?;
+        let signed_txn = pending.signed_transaction()?;
+        match client
+            .get_transaction_by_hash(pending.transaction_hash.into())
+            .await
+        {
+            Ok(response) => {
+                let transaction = response.into_inner();
+                if transaction.is_pending() {
+                    eprintln!(
+                        "Waiting for transaction {} submitted by the previous run",
+                        pending.transaction_hash
+                    );
+                    let response = client
+                        .wait_for_signed_transaction(&signed_txn)
+                        .await
+                        .map_err(|err| CliError::ApiError(err.to_string()))?;
+                    return Ok(Some(response.into_inner()));
+                }
+                if transaction.success() {
+                    eprintln!(
+                        "Transaction {} submitted by the previous run was committed",
+                        pending.transaction_hash
+                    );
+                    return Ok(Some(transaction));
+                }
+                // A failed transaction used its sequence number, so submitting it again would
+                // be rejected
+                eprintln!(
+                    "Transaction {} submitted by the previous run failed: {}",
+                    pending.transaction_hash,
+                    transaction.vm_status()
+                );
+                Ok(None)
+            },
+            Err(RestError::Api(AptosErrorResponse {
+                error:
+                    AptosError {
+                        error_code: AptosErrorCode::TransactionNotFound,
+                        ..
+                    },
+                ..
+            })) => {
+                // The transaction isn't known to the node, but could still be committed if it was
+                // submitted elsewhere, unless its sequence number was used or it expired
+                let account = client.get_account(signed_txn.sender()).await?;
+                if account.inner().sequence_number > pending.sequence_number {
+                    // Either another transaction used the sequence number, or the node pruned
+                    // this one, so submitting a new one could run the step twice
+                    return Err(CliError::UnexpectedError(format!(
+                        "Transaction {} of step '{}' isn't known to the node, but its sequence \
+                        number {} was used. Check whether the step was applied, and remove the \
+                        pending transaction from the state file {} to run it again",
+                        pending.transaction_hash,
+                        pending.step,
+                        pending.sequence_number,
+                        state_file.display()
+                    )));
+                }
+                let now_secs = account.state().timestamp_usecs / 1_000_000;
+                if now_secs >= signed_txn.expiration_timestamp_secs() {
+                    return Ok(None);
+                }
+                eprintln!(
+                    "Submitting transaction {} of the previous run again",
+                    pending.transaction_hash
+                );
+                Ok(Some(
+                    self.txn_options
+                        .submit_signed_transaction(&signed_txn)
+                        .await?,
+                ))
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Simulates a step, returning why it would fail instead of failing
+    async fn simulate_step(
+        &self,
+        action: StepAction,
+        plan_dir: &Path,
+    ) -> CliTypedResult<(Value, Option<String>)> {
+        let payload = match action {
+            StepAction::View(view) => return self.view(view).await,
+            action => step_payload(action, plan_dir)?,
+        };
+        match self.txn_options.simulate_locally(payload).await {
+            Ok(summary) => {
+                let error = match summary.success {
+                    Some(true) => None,
+                    _ => summary.vm_status.clone(),
+                };
+                Ok((to_value(&summary)?, error))
+            },
+            Err(err) => Ok((Value::Null, Some(err.to_string()))),
+        }
+    }
+
+    /// Calls a view function, along with the mismatch with the expected values if any
+    async fn view(&self, view: ViewStep) -> CliTypedResult<(Value, Option<String>)> {
+        let arguments: EntryFunctionArguments = view.function.try_into()?;
+        let function: ViewFunction = arguments.try_into()?;
+        let values = self.txn_options.view(function).await?;
+        let error = match view.expect {
+            Some(expected) if expected != values => Some(format!(
+                "The view function returned {} instead of {}",
+                Value::from(values.clone()),
+                Value::from(expected)
+            )),
+            _ => None,
+        };
+        Ok((Value::from(values), error))
+    }
+}
+
+/// Builds the payload of a step submitting a transaction
+fn step_payload(action: StepAction, plan_dir: &Path) -> CliTypedResult<TransactionPayload> {
+    match action {
+        StepAction::Run(function) => {
+            let arguments: EntryFunctionArguments = function.try_into()?;
+            let function: EntryFunction = arguments.try_into()?;
+            Ok(TransactionPayload::EntryFunction(function))
+        },
+        StepAction::Publish(publish) => {
+            let mut move_options = MovePackageDir::new();
+            move_options.package_dir = Some(plan_dir.join(publish.package_dir));
+            for (name, address) in publish.named_addresses {
+                move_options.named_addresses.insert(name, AccountAddressWrapper {
+                    account_address: load_account_arg(&address)?,
+                });
+            }
+            let package = build_package_options(&move_options, &IncludedArtifactsArgs {
+                included_artifacts: IncludedArtifacts::Sparse,
+            })?;
+            let payload =
+                create_package_publication_data(package, PublishType::AccountDeploy, None)?.payload;
+            let size = bcs::serialized_size(&payload)?;
+            if size > MAX_PUBLISH_PACKAGE_SIZE {
+                return Err(CliError::PackageSizeExceeded(
+                    size,
+                    MAX_PUBLISH_PACKAGE_SIZE,
+                ));
+            }
+            Ok(payload)
+        },
+        StepAction::Transfer(transfer) => {
+            let amount = match &transfer.amount {
+                Value::Number(amount) => amount.as_u64(),
+                Value::String(amount) => amount.parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Invalid amount {} to transfer",
+                    transfer.amount
+                ))
+            })?;
+            Ok(aptos_stdlib::aptos_account_transfer(
+                load_account_arg(&transfer.account)?,
+                amount,
+            ))
+        },
+        StepAction::View(_) => Err(CliError::UnexpectedError(
+            "View steps don't submit transactions".to_string(),
+        )),
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> CliTypedResult<Value> {
+    serde_json::to_value(value)
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to serialize output: {}", err)))
+}
+
+/// Replaces the `${name}` references in the strings of a step
+///
+/// A string made of a single reference is replaced by the referenced value as is, so that
+/// numbers and vectors keep their type; other references are replaced by their text.
+fn substitute(value: Value, variables: &BTreeMap<String, Value>) -> CliTypedResult<Value> {
+    Ok(match value {
+        Value::String(string) => substitute_string(&string, variables)?,
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| substitute(value, variables))
+                .collect::<CliTypedResult<_>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| Ok((key, substitute(value, variables)?)))
+                .collect::<CliTypedResult<_>>()?,
+        ),
+        value => value,
+    })
+}
+
+fn substitute_string(string: &str, variables: &BTreeMap<String, Value>) -> CliTypedResult<Value> {
+    if let Some(name) = string
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+    {
+        if !name.contains('}') {
+            return lookup(name, variables).cloned();
+        }
+    }
+
+    let mut result = String::new();
+    let mut rest = string;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            CliError::CommandArgumentError(format!("Unterminated reference in '{}'", string))
+        })? + start;
+        match lookup(&rest[start + 2..end], variables)? {
+            Value::String(value) => result.push_str(value),
+            value => result.push_str(&value.to_string()),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(Value::String(result))
+}
+
+/// Looks up a variable, or a field or element of it, e.g. `step.transaction_hash` or `step.0`
+fn lookup<'a>(name: &str, variables: &'a BTreeMap<String, Value>) -> CliTypedResult<&'a Value> {
+    let mut path = name.split('.');
+    let mut value = path.next().and_then(|variable| variables.get(variable));
+    for field in path {
+        value = value.and_then(|value| match value {
+            Value::Object(fields) => fields.get(field),
+            Value::Array(values) => field
+                .parse()
+                .ok()
+                .and_then(|index: usize| values.get(index)),
+            _ => None,
+        });
+    }
+    value.ok_or_else(|| CliError::CommandArgumentError(format!("Unknown variable '{}'", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::signing::AccountSigningKey;
+    use aptos_keygen::KeyGen;
+    use aptos_types::{
+        account_address::AccountAddress, chain_id::ChainId, transaction::RawTransaction,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_pending_transaction() {
+        let signing_key =
+            AccountSigningKey::Ed25519(KeyGen::from_seed([0; 32]).generate_ed25519_private_key());
+        let signed_txn = signing_key
+            .sign_transaction(RawTransaction::new(
+                signing_key.derived_address(),
+                3,
+                aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, 100),
+                1000,
+                100,
+                u64::MAX,
+                ChainId::test(),
+            ))
+            .unwrap();
+        let state = BatchState {
+            completed: vec![],
+            pending: Some(PendingTransaction::new("fund".to_string(), &signed_txn).unwrap()),
+        };
+
+        let state: BatchState =
+            serde_json::from_slice(&serde_json::to_vec_pretty(&state).unwrap()).unwrap();
+        let pending = state.pending.unwrap();
+        assert_eq!(pending.step, "fund");
+        assert_eq!(
+            pending.transaction_hash,
+            HashValue::from(signed_txn.committed_hash())
+        );
+        assert_eq!(pending.sequence_number, 3);
+        assert_eq!(pending.signed_transaction().unwrap(), signed_txn);
+
+        // State files of runs which didn't submit a transaction have none pending
+        let state: BatchState = serde_json::from_value(json!({ "completed": [] })).unwrap();
+        assert!(state.pending.is_none());
+        assert_eq!(
+            serde_json::to_value(&state).unwrap(),
+            json!({ "completed": [] })
+        );
+    }
+
+    #[test]
+    fn test_substitute() {
+        let variables = BTreeMap::from([
+            ("amount".to_string(), json!(100)),
+            ("sender".to_string(), json!("0xcafe")),
+            (
+                "step".to_string(),
+                json!({"version": 5, "values": ["1", "2"]}),
+            ),
+        ]);
+
+        assert_eq!(
+            substitute(
+                json!({
+                    "amount": "${amount}",
+                    "function_id": "${sender}::module::function",
+                    "args": ["${step.values.1}", "${step.version}-${amount}", "${step.values}"],
+                }),
+                &variables
+            )
+            .unwrap(),
+            json!({
+                "amount": 100,
+                "function_id": "0xcafe::module::function",
+                "args": ["2", "5-100", ["1", "2"]],
+            })
+        );
+        assert!(substitute(json!("${unknown}"), &variables).is_err());
+        assert!(substitute(json!("${step.values.2}"), &variables).is_err());
+        assert!(substitute(json!("${amount"), &variables).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliError, CliTypedResult, EntryFunctionArguments, MultiAgentOptions,
+    TransactionOptions, TransactionSummary,
+};
+use aptos_types::transaction::TransactionPayload;
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Build a transaction running a Move function, without signing it
+///
+/// The sequence number, gas and expiration of the transaction are set from the current state of
+/// the chain, so make sure `--expiration-secs` leaves enough time to sign and submit it. Most
+/// other commands submitting a transaction can do the same with `--output-unsigned`.
+#[derive(Debug, Parser)]
+pub struct BuildTransaction {
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+    #[clap(flatten)]
+    pub(crate) multi_agent_options: MultiAgentOptions,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+    /// File to write the unsigned transaction to
+    ///
+    /// The transaction is written as JSON if the file name ends with `.json`, and as BCS
+    /// otherwise.
+    #[clap(long, value_parser)]
+    pub(crate) output_file: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for BuildTransaction {
+    fn command_name(&self) -> &'static str {
+        "BuildTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        if self.txn_options.output_unsigned.is_some() {
+            return Err(CliError::CommandArgumentError(
+                "Use --output-file to choose where to write the transaction".to_string(),
+            ));
+        }
+
+        self.txn_options
+            .output_unsigned_transaction(
+                TransactionPayload::EntryFunction(self.entry_function_args.try_into()?),
+                &self.multi_agent_options.secondary_signers()?,
+                &self.output_file,
+            )
+            .await
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    signing::{single_signer_authenticator, AccountSigningKey},
+    types::{CliCommand, CliError, CliResult, CliTypedResult},
+    utils::{parse_json_file, read_from_file, write_to_file},
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{
+        authenticator::{AccountAuthenticator, TransactionAuthenticator},
+        RawTransaction, RawTransactionWithData, SignedTransaction,
+    },
+};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub mod batch;
+pub mod build;
+pub mod show;
+pub mod sign;
+pub mod submit;
+
+/// Tool for building, signing, submitting, inspecting and batching transactions
+///
+/// Building, signing and submitting separately allows keeping the signing key on a machine
+/// without network access: the transaction is built on a machine with access to the network,
+/// signed offline, and then submitted from a machine with access to the network again.
+#[derive(Debug, Subcommand)]
+pub enum TransactionTool {
+    #[clap(subcommand)]
+    Batch(batch::BatchTool),
+    Build(build::BuildTransaction),
+    Show(show::ShowTransaction),
+    Sign(sign::SignTransaction),
+    Submit(submit::SubmitTransaction),
+}
+
+impl TransactionTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            TransactionTool::Batch(tool) => tool.execute().await,
+            TransactionTool::Build(tool) => tool.execute_serialized().await,
+            TransactionTool::Show(tool) => tool.execute_rendered().await,
+            TransactionTool::Sign(tool) => tool.execute_serialized().await,
+            TransactionTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// A transaction collecting the signatures of its signers, one signer at a time
+///
+/// This is what `--output-unsigned` writes and `aptos transaction sign` reads, so that every
+/// signer can sign on its own machine.
+///
+/// Fields are only ever appended, so that transactions written by older versions of the CLI can
+/// still be read.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartiallySignedTransaction {
+    pub raw_txn: RawTransaction,
+    /// Account paying for the gas instead of the sender, which has to sign the transaction too
+    pub fee_payer_address: Option<AccountAddress>,
+    pub sender_authenticator: Option<AccountAuthenticator>,
+    pub fee_payer_authenticator: Option<AccountAuthenticator>,
+    /// Accounts signing the transaction in addition to the sender, for functions taking several
+    /// `&signer` arguments
+    #[serde(default)]
+    pub secondary_signer_addresses: Vec<AccountAddress>,
+    /// Authenticators of the secondary signers, in the order of their addresses
+    #[serde(default)]
+    pub secondary_signer_authenticators: Vec<Option<AccountAuthenticator>>,
+}
+
+/// A [`PartiallySignedTransaction`] as written before secondary signers were supported
+///
+/// BCS has no default values, so the missing fields have to be filled in separately.
+#[derive(Deserialize)]
+struct FeePayerTransaction {
+    raw_txn: RawTransaction,
+    fee_payer_address: Option<AccountAddress>,
+    sender_authenticator: Option<AccountAuthenticator>,
+    fee_payer_authenticator: Option<AccountAuthenticator>,
+}
+
+impl From<FeePayerTransaction> for PartiallySignedTransaction {
+    fn from(transaction: FeePayerTransaction) -> Self {
+        Self {
+            raw_txn: transaction.raw_txn,
+            fee_payer_address: transaction.fee_payer_address,
+            sender_authenticator: transaction.sender_authenticator,
+            fee_payer_authenticator: transaction.fee_payer_authenticator,
+            secondary_signer_addresses: vec![],
+            secondary_signer_authenticators: vec![],
+        }
+    }
+}
+
+impl PartiallySignedTransaction {
+    pub fn new(
+        raw_txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: Option<AccountAddress>,
+    ) -> Self {
+        Self {
+            raw_txn,
+            secondary_signer_authenticators: vec![None; secondary_signer_addresses.len()],
+            secondary_signer_addresses,
+            fee_payer_address,
+            sender_authenticator: None,
+            fee_payer_authenticator: None,
+        }
+    }
+
+    /// Reads a transaction written by [`PartiallySignedTransaction::write`]
+    pub fn read(path: &Path) -> CliTypedResult<Self> {
+        if is_json_file(path) {
+            parse_json_file(path)
+        } else {
+            Self::from_bcs(&read_from_file(path)?)
+        }
+    }
+
+    fn from_bcs(bytes: &[u8]) -> CliTypedResult<Self> {
+        bcs::from_bytes(bytes)
+            .or_else(|err| {
+                bcs::from_bytes::<FeePayerTransaction>(bytes)
+                    .map(Self::from)
+                    .map_err(|_| err)
+            })
+            .map_err(|err| CliError::BCS("partially signed transaction", err))
+    }
+
+    /// Writes the transaction, as JSON if the file name ends with `.json` and as BCS otherwise
+    pub fn write(&self, path: &Path, name: &str) -> CliTypedResult<()> {
+        let bytes = if is_json_file(path) {
+            serde_json::to_vec_pretty(self)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        } else {
+            bcs::to_bytes(self).map_err(|err| CliError::BCS("partially signed transaction", err))?
+        };
+        write_to_file(path, name, &bytes)
+    }
+
+    /// Signs the transaction for every role `signer_address` has in it
+    pub fn sign(
+        &mut self,
+        signing_key: &AccountSigningKey,
+        signer_address: AccountAddress,
+    ) -> CliTypedResult<()> {
+        // Every signer signs the same message, which includes the other signers if there are any
+        let secondary_signer_addresses = self.secondary_signer_addresses.clone();
+        let authenticator = match self.fee_payer_address {
+            Some(fee_payer_address) => {
+                signing_key.sign_transaction_with_data(&RawTransactionWithData::new_fee_payer(
+                    self.raw_txn.clone(),
+                    secondary_signer_addresses,
+                    fee_payer_address,
+                ))?
+            },
+            None if !secondary_signer_addresses.is_empty() => signing_key
+                .sign_transaction_with_data(&RawTransactionWithData::new_multi_agent(
+                    self.raw_txn.clone(),
+                    secondary_signer_addresses,
+                ))?,
+            None => signing_key.sign(&self.raw_txn)?,
+        };
+
+        let mut signed = false;
+        if self.raw_txn.sender() == signer_address {
+            self.sender_authenticator = Some(authenticator.clone());
+            signed = true;
+        }
+        for (address, secondary_signer_authenticator) in self
+            .secondary_signer_addresses
+            .iter()
+            .zip(self.secondary_signer_authenticators.iter_mut())
+        {
+            if *address == signer_address {
+                *secondary_signer_authenticator = Some(authenticator.clone());
+                signed = true;
+            }
+        }
+        if self.fee_payer_address == Some(signer_address) {
+            self.fee_payer_authenticator = Some(authenticator);
+            signed = true;
+        }
+
+        if signed {
+            Ok(())
+        } else {
+            Err(CliError::CommandArgumentError(format!(
+                "Account {} doesn't have to sign the transaction, use --signer-account if the \
+                key of the signer was rotated",
+                signer_address
+            )))
+        }
+    }
+
+    /// Addresses of the signers which haven't signed the transaction yet
+    pub fn missing_signers(&self) -> Vec<AccountAddress> {
+        let mut missing_signers = vec![];
+        if self.sender_authenticator.is_none() {
+            missing_signers.push(self.raw_txn.sender());
+        }
+        for (address, authenticator) in self
+            .secondary_signer_addresses
+            .iter()
+            .zip(&self.secondary_signer_authenticators)
+        {
+            if authenticator.is_none() {
+                missing_signers.push(*address);
+            }
+        }
+        if let (Some(fee_payer_address), None) =
+            (self.fee_payer_address, &self.fee_payer_authenticator)
+        {
+            missing_signers.push(fee_payer_address);
+        }
+        missing_signers
+    }
+
+    /// The transaction to submit, or `None` if some signers haven't signed it yet
+    pub fn into_signed_transaction(self) -> Option<SignedTransaction> {
+        let sender = self.sender_authenticator?;
+        let secondary_signers = self
+            .secondary_signer_authenticators
+            .into_iter()
+            .collect::<Option<Vec<_>>>()?;
+        let authenticator = match self.fee_payer_address {
+            Some(fee_payer_address) => TransactionAuthenticator::fee_payer(
+                sender,
+                self.secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                self.fee_payer_authenticator?,
+            ),
+            None if !secondary_signers.is_empty() => TransactionAuthenticator::multi_agent(
+                sender,
+                self.secondary_signer_addresses,
+                secondary_signers,
+            ),
+            None => single_signer_authenticator(sender),
+        };
+        Some(SignedTransaction::new_signed_transaction(
+            self.raw_txn,
+            authenticator,
+        ))
+    }
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension == "json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_keygen::KeyGen;
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{authenticator::AuthenticationKey, Script, TransactionPayload},
+    };
+
+    /// Signers with their keys, the first one being the sender
+    fn signers(count: usize) -> Vec<(AccountAddress, AccountSigningKey)> {
+        let mut key_gen = KeyGen::from_seed([0; 32]);
+        (0..count)
+            .map(|_| {
+                let signing_key =
+                    AccountSigningKey::Ed25519(key_gen.generate_ed25519_private_key());
+                (signing_key.derived_address(), signing_key)
+            })
+            .collect()
+    }
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        RawTransaction::new(
+            sender,
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        )
+    }
+
+    fn authentication_key(authenticator: &Option<AccountAuthenticator>) -> AuthenticationKey {
+        authenticator
+            .as_ref()
+            .and_then(AccountAuthenticator::authentication_key)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_missing_signers() {
+        let signers = signers(4);
+        let addresses: Vec<_> = signers.iter().map(|(address, _)| *address).collect();
+        let mut transaction = PartiallySignedTransaction::new(
+            raw_txn(addresses[0]),
+            vec![addresses[1], addresses[2]],
+            Some(addresses[3]),
+        );
+        assert_eq!(transaction.missing_signers(), addresses);
+        assert!(transaction.clone().into_signed_transaction().is_none());
+
+        // Signers sign in any order, and are no longer missing once they did
+        for index in [2, 0, 3] {
+            let (address, signing_key) = &signers[index];
+            transaction.sign(signing_key, *address).unwrap();
+        }
+        assert_eq!(transaction.missing_signers(), vec![addresses[1]]);
+        assert!(transaction.clone().into_signed_transaction().is_none());
+
+        // Accounts without a role in the transaction can't sign it
+        let (other, other_key) = &signers(5)[4];
+        assert!(transaction.sign(other_key, *other).is_err());
+
+        let (address, signing_key) = &signers[1];
+        transaction.sign(signing_key, *address).unwrap();
+        assert!(transaction.missing_signers().is_empty());
+        transaction
+            .into_signed_transaction()
+            .unwrap()
+            .verify_signature()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_in_secondary_signer_order() {
+        let signers = signers(3);
+        let addresses: Vec<_> = signers.iter().map(|(address, _)| *address).collect();
+        let mut transaction = PartiallySignedTransaction::new(
+            raw_txn(addresses[0]),
+            vec![addresses[2], addresses[1]],
+            None,
+        );
+
+        // The authenticators are in the order of the secondary signers, not of the signatures
+        for (address, signing_key) in &signers {
+            transaction.sign(signing_key, *address).unwrap();
+        }
+        assert_eq!(
+            authentication_key(&transaction.secondary_signer_authenticators[0]),
+            signers[2].1.authentication_key()
+        );
+        assert_eq!(
+            authentication_key(&transaction.secondary_signer_authenticators[1]),
+            signers[1].1.authentication_key()
+        );
+
+        let signed_txn = transaction.into_signed_transaction().unwrap();
+        signed_txn.verify_signature().unwrap();
+        assert_eq!(
+            signed_txn.authenticator().secondary_signer_addresses(),
+            vec![addresses[2], addresses[1]]
+        );
+    }
+
+    #[test]
+    fn test_sign_every_role_of_the_signer() {
+        let signers = signers(2);
+        let (sender, sender_key) = &signers[0];
+        let (secondary_signer, secondary_signer_key) = &signers[1];
+
+        // The sender pays for its own gas, e.g. to co-sign with a secondary signer
+        let mut transaction = PartiallySignedTransaction::new(
+            raw_txn(*sender),
+            vec![*secondary_signer],
+            Some(*sender),
+        );
+        transaction.sign(sender_key, *sender).unwrap();
+        assert_eq!(transaction.missing_signers(), vec![*secondary_signer]);
+        transaction
+            .sign(secondary_signer_key, *secondary_signer)
+            .unwrap();
+        transaction
+            .into_signed_transaction()
+            .unwrap()
+            .verify_signature()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_read_fee_payer_transaction() {
+        #[derive(Serialize)]
+        struct FeePayerTransaction {
+            raw_txn: RawTransaction,
+            fee_payer_address: Option<AccountAddress>,
+            sender_authenticator: Option<AccountAuthenticator>,
+            fee_payer_authenticator: Option<AccountAuthenticator>,
+        }
+
+        let signers = signers(2);
+        let (sender, sender_key) = &signers[0];
+        let (fee_payer, fee_payer_key) = &signers[1];
+        let mut transaction =
+            PartiallySignedTransaction::new(raw_txn(*sender), vec![], Some(*fee_payer));
+        transaction.sign(sender_key, *sender).unwrap();
+
+        // Transactions written before secondary signers were supported can still be signed
+        let bytes = bcs::to_bytes(&FeePayerTransaction {
+            raw_txn: transaction.raw_txn.clone(),
+            fee_payer_address: transaction.fee_payer_address,
+            sender_authenticator: transaction.sender_authenticator.clone(),
+            fee_payer_authenticator: None,
+        })
+        .unwrap();
+        let mut read = PartiallySignedTransaction::from_bcs(&bytes).unwrap();
+        assert_eq!(read.missing_signers(), vec![*fee_payer]);
+        read.sign(fee_payer_key, *fee_payer).unwrap();
+        read.into_signed_transaction()
+            .unwrap()
+            .verify_signature()
+            .unwrap();
+
+        // And so can those written now
+        let bytes = bcs::to_bytes(&transaction).unwrap();
+        let read = PartiallySignedTransaction::from_bcs(&bytes).unwrap();
+        assert_eq!(read.missing_signers(), vec![*fee_payer]);
+        assert!(PartiallySignedTransaction::from_bcs(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,892 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::PartiallySignedTransaction;
+use crate::common::{
+    types::{CliCommand, CliError, CliResult, CliTypedResult, ProfileOptions, RestOptions},
+    utils::{read_from_file, start_logger},
+};
+use aptos_api_types::{HashValue, MoveStructValue, MoveValue, TransactionData};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::Level;
+use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, FEE_STATEMENT_EVENT_TYPE},
+    fee_statement::FeeStatement,
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        StateView,
+    },
+    transaction::{
+        EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction, Script,
+        SignedTransaction, Transaction, TransactionInfo, TransactionPayload,
+    },
+    write_set::{WriteOp, WriteSet},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf, str::FromStr};
+
+/// Show a transaction, with its Move values decoded using the modules on chain
+///
+/// The transaction is fetched by hash or version, or read from a file written by
+/// `aptos transaction build` or `aptos transaction sign`, e.g. to check what it does before
+/// signing or submitting it. Only committed transactions have a status, gas, events and changes.
+#[derive(Debug, Parser)]
+pub struct ShowTransaction {
+    /// Hash (0x...) or version of the transaction on chain
+    #[clap(required_unless_present = "transaction_file")]
+    pub(crate) transaction: Option<TransactionId>,
+    /// File holding an unsigned, partially signed, or signed transaction
+    #[clap(long, value_parser, conflicts_with = "transaction")]
+    pub(crate) transaction_file: Option<PathBuf>,
+    /// Print the transaction in a human-readable format instead of JSON
+    #[clap(long)]
+    pub(crate) human_readable: bool,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// Identifies a transaction on chain
+#[derive(Clone, Copy, Debug)]
+pub enum TransactionId {
+    Hash(HashValue),
+    Version(u64),
+}
+
+impl FromStr for TransactionId {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(version) = u64::from_str(s) {
+            return Ok(TransactionId::Version(version));
+        }
+        HashValue::from_str(s)
+            .map(TransactionId::Hash)
+            .map_err(|_| {
+                CliError::CommandArgumentError(format!(
+                    "Invalid transaction '{}', expected a hash (0x...) or a version",
+                    s
+                ))
+            })
+    }
+}
+
+impl ShowTransaction {
+    /// Executes the command, printing the transaction as JSON, or as text with
+    /// `--human-readable`
+    pub async fn execute_rendered(self) -> CliResult {
+        if self.human_readable {
+            start_logger(Level::Warn);
+            self.execute()
+                .await
+                .map(|transaction| transaction.render())
+                .map_err(|err| format!("{:#}", err))
+        } else {
+            self.execute_serialized().await
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<TransactionView> for ShowTransaction {
+    fn command_name(&self) -> &'static str {
+        "ShowTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionView> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let debugger = AptosDebugger::rest_client(client.clone())?;
+
+        if let Some(path) = &self.transaction_file {
+            // Decode with the latest modules, as the transaction isn't on chain
+            let version = client.get_ledger_information().await?.into_inner().version;
+            let state_view = debugger.state_view_at_version(version + 1);
+            let annotator = AptosValueAnnotator::new(&state_view);
+            return Ok(match read_signed_transaction(path) {
+                Some(signed_txn) => {
+                    TransactionView::from_user_transaction(&annotator, &signed_txn, None)
+                },
+                None => {
+                    let transaction = PartiallySignedTransaction::read(path)?;
+                    TransactionView::from_raw_transaction(&annotator, &transaction.raw_txn)
+                },
+            });
+        }
+
+        let response = match self.transaction.expect("Required without a file") {
+            TransactionId::Hash(hash) => client.get_transaction_by_hash_bcs(hash.into()).await?,
+            TransactionId::Version(version) => {
+                client.get_transaction_by_version_bcs(version).await?
+            },
+        };
+        match response.into_inner() {
+            TransactionData::OnChain(data) => {
+                // The state right after the transaction has the modules it may have published
+                let state_view = debugger.state_view_at_version(data.version + 1);
+                let annotator = AptosValueAnnotator::new(&state_view);
+                Ok(TransactionView::from_committed_transaction(
+                    &annotator,
+                    data.version,
+                    &data.transaction,
+                    &data.info,
+                    &data.events,
+                    &data.changes,
+                ))
+            },
+            TransactionData::Pending(signed_txn) => {
+                let version = client.get_ledger_information().await?.into_inner().version;
+                let state_view = debugger.state_view_at_version(version + 1);
+                let annotator = AptosValueAnnotator::new(&state_view);
+                Ok(TransactionView::from_user_transaction(
+                    &annotator,
+                    &signed_txn,
+                    None,
+                ))
+            },
+        }
+    }
+}
+
+/// Reads a transaction written by `aptos transaction sign` once fully signed
+fn read_signed_transaction(path: &std::path::Path) -> Option<SignedTransaction> {
+    bcs::from_bytes(&read_from_file(path).ok()?).ok()
+}
+
+/// A transaction with its Move values decoded
+#[derive(Debug, Serialize)]
+pub struct TransactionView {
+    /// Kind of the transaction, e.g. `user_transaction`
+    pub kind: &'static str,
+    pub hash: HashValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_timestamp_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadView>,
+    /// Only known once the transaction is committed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<GasView>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventView>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ChangeView>,
+}
+
+/// What the transaction runs
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PayloadView {
+    EntryFunction(EntryFunctionView),
+    Script(ScriptView),
+    Multisig {
+        multisig_address: AccountAddress,
+        /// Not set if the transaction to run is stored on chain
+        #[serde(skip_serializing_if = "Option::is_none")]
+        entry_function: Option<EntryFunctionView>,
+    },
+    ModuleBundle,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryFunctionView {
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScriptView {
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// Gas used by the transaction, and what it was used for
+#[derive(Debug, Serialize)]
+pub struct GasView {
+    pub gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_unit_price: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gas_amount: Option<u64>,
+    /// Breakdown of the gas used, emitted by the transaction as an event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_statement: Option<FeeStatement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventView {
+    #[serde(rename = "type")]
+    pub type_tag: String,
+    pub data: serde_json::Value,
+}
+
+/// A change of the state by the transaction
+#[derive(Debug, Serialize)]
+pub struct ChangeView {
+    pub kind: ChangeKind,
+    /// What changed, e.g. the type of a resource
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AccountAddress>,
+    pub deleted: bool,
+    /// The value after the transaction, unless it was deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Module,
+    Resource,
+    ResourceGroup,
+    TableItem,
+    Raw,
+}
+
+impl TransactionView {
+    fn from_committed_transaction<S: StateView>(
+        annotator: &AptosValueAnnotator<S>,
+        version: u64,
+        transaction: &Transaction,
+        info: &TransactionInfo,
+        events: &[ContractEvent],
+        changes: &WriteSet,
+    ) -> Self {
+        let mut view = match transaction.try_as_signed_user_txn() {
+            Some(signed_txn) => Self::from_user_transaction(annotator, signed_txn, Some(info)),
+            None => Self::new(transaction.type_name(), info.transaction_hash().into()),
+        };
+        view.version = Some(version);
+        view.success = Some(info.status().is_success());
+        view.vm_status = Some(explain_status(info.status()));
+        let gas = view.gas.get_or_insert(GasView {
+            gas_used: info.gas_used(),
+            gas_unit_price: None,
+            max_gas_amount: None,
+            fee_statement: None,
+        });
+        gas.fee_statement = events.iter().find_map(|event| {
+            event
+                .try_v2_typed::<FeeStatement>(&FEE_STATEMENT_EVENT_TYPE)
+                .ok()
+                .flatten()
+        });
+        view.events = events
+            .iter()
+            .map(|event| EventView {
+                type_tag: event.type_tag().to_string(),
+                data: decode_value(annotator, event.type_tag(), event.event_data()),
+            })
+            .collect();
+        view.changes = changes
+            .iter()
+            .map(|(state_key, write_op)| decode_change(annotator, state_key, write_op))
+            .collect();
+        view
+    }
+
+    fn from_user_transaction<S: StateView>(
+        annotator: &AptosValueAnnotator<S>,
+        signed_txn: &SignedTransaction,
+        info: Option<&TransactionInfo>,
+    ) -> Self {
+        let mut view = Self::new(
+            Transaction::UserTransaction(signed_txn.clone()).type_name(),
+            signed_txn.committed_hash().into(),
+        );
+        view.sender = Some(signed_txn.sender());
+        view.sequence_number = Some(signed_txn.sequence_number());
+        view.expiration_timestamp_secs = Some(signed_txn.expiration_timestamp_secs());
+        view.payload = Some(decode_payload(annotator, signed_txn.payload()));
+        view.gas = info.map(|info| GasView {
+            gas_used: info.gas_used(),
+            gas_unit_price: Some(signed_txn.gas_unit_price()),
+            max_gas_amount: Some(signed_txn.max_gas_amount()),
+            fee_statement: None,
+        });
+        view
+    }
+
+    /// The hash of an unsigned transaction isn't the one it will have on chain
+    fn from_raw_transaction<S: StateView>(
+        annotator: &AptosValueAnnotator<S>,
+        raw_txn: &RawTransaction,
+    ) -> Self {
+        let mut view = Self::new("unsigned_user_transaction", raw_txn.hash().into());
+        view.sender = Some(raw_txn.sender());
+        view.sequence_number = Some(raw_txn.sequence_number());
+        view.expiration_timestamp_secs = Some(raw_txn.expiration_timestamp_secs());
+        view.payload = Some(decode_payload(annotator, raw_txn.payload()));
+        view
+    }
+
+    fn new(kind: &'static str, hash: HashValue) -> Self {
+        TransactionView {
+            kind,
+            hash,
+            version: None,
+            sender: None,
+            sequence_number: None,
+            expiration_timestamp_secs: None,
+            payload: None,
+            success: None,
+            vm_status: None,
+            gas: None,
+            events: vec![],
+            changes: vec![],
+        }
+    }
+
+    /// Renders the transaction as text
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        // Writing to a string can't fail
+        let mut line = |indent: usize, text: String| {
+            writeln!(out, "{:indent$}{}", "", text, indent = indent * 2).unwrap()
+        };
+
+        line(0, format!("Transaction {} ({})", self.hash, self.kind));
+        if let Some(version) = self.version {
+            line(1, format!("Version: {}", version));
+        }
+        if let Some(sender) = self.sender {
+            line(1, format!("Sender: {}", sender.to_standard_string()));
+        }
+        if let Some(sequence_number) = self.sequence_number {
+            line(1, format!("Sequence number: {}", sequence_number));
+        }
+        if let Some(expiration) = self.expiration_timestamp_secs {
+            line(1, format!("Expiration timestamp: {}s", expiration));
+        }
+        if let Some(vm_status) = &self.vm_status {
+            line(1, format!("Status: {}", vm_status));
+        }
+
+        match &self.payload {
+            Some(PayloadView::EntryFunction(function)) => {
+                line(1, "Payload: entry function".to_string());
+                render_entry_function(&mut line, function);
+            },
+            Some(PayloadView::Script(script)) => {
+                line(1, "Payload: script".to_string());
+                render_arguments(&mut line, &script.type_arguments, &script.arguments);
+            },
+            Some(PayloadView::Multisig {
+                multisig_address,
+                entry_function,
+            }) => {
+                line(
+                    1,
+                    format!(
+                        "Payload: multisig transaction of {}",
+                        multisig_address.to_standard_string()
+                    ),
+                );
+                match entry_function {
+                    Some(function) => render_entry_function(&mut line, function),
+                    None => line(2, "Transaction stored on chain".to_string()),
+                }
+            },
+            Some(PayloadView::ModuleBundle) => line(1, "Payload: module bundle".to_string()),
+            None => {},
+        }
+
+        if let Some(gas) = &self.gas {
+            match gas.gas_unit_price {
+                Some(gas_unit_price) => line(
+                    1,
+                    format!(
+                        "Gas: {} units at {} octas per unit, {} octas",
+                        gas.gas_used,
+                        gas_unit_price,
+                        gas.gas_used.saturating_mul(gas_unit_price)
+                    ),
+                ),
+                None => line(1, format!("Gas: {} units", gas.gas_used)),
+            }
+            if let Some(max_gas_amount) = gas.max_gas_amount {
+                line(2, format!("Max gas amount: {} units", max_gas_amount));
+            }
+            if let Some(fee_statement) = &gas.fee_statement {
+                line(
+                    2,
+                    format!("Execution: {} units", fee_statement.execution_gas_used()),
+                );
+                line(2, format!("IO: {} units", fee_statement.io_gas_used()));
+                line(
+                    2,
+                    format!("Storage fee: {} octas", fee_statement.storage_fee_used()),
+                );
+                line(
+                    2,
+                    format!(
+                        "Storage fee refund: {} octas",
+                        fee_statement.storage_fee_refund()
+                    ),
+                );
+            }
+        }
+
+        if !self.events.is_empty() {
+            line(1, format!("Events ({}):", self.events.len()));
+            for event in &self.events {
+                line(2, event.type_tag.clone());
+                line(3, event.data.to_string());
+            }
+        }
+
+        if !self.changes.is_empty() {
+            line(1, format!("Changes ({}):", self.changes.len()));
+            for change in &self.changes {
+                let action = if change.deleted { "Delete" } else { "Write" };
+                let kind = match change.kind {
+                    ChangeKind::Module => "module",
+                    ChangeKind::Resource => "resource",
+                    ChangeKind::ResourceGroup => "resource group",
+                    ChangeKind::TableItem => "table item",
+                    ChangeKind::Raw => "state value",
+                };
+                let address = change
+                    .address
+                    .map(|address| format!(" at {}", address.to_standard_string()))
+                    .unwrap_or_default();
+                line(2, format!("{} {} {}{}", action, kind, change.key, address));
+                if let Some(value) = &change.value {
+                    line(3, value.to_string());
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn render_entry_function(line: &mut impl FnMut(usize, String), function: &EntryFunctionView) {
+    line(2, format!("Function: {}", function.function));
+    render_arguments(line, &function.type_arguments, &function.arguments);
+}
+
+fn render_arguments(
+    line: &mut impl FnMut(usize, String),
+    type_arguments: &[String],
+    arguments: &[serde_json::Value],
+) {
+    if !type_arguments.is_empty() {
+        line(2, format!("Type arguments: {}", type_arguments.join(", ")));
+    }
+    if !arguments.is_empty() {
+        line(2, "Arguments:".to_string());
+        for argument in arguments {
+            line(3, argument.to_string());
+        }
+    }
+}
+
+/// Explains the status like the REST API does, without looking up the abort codes
+fn explain_status(status: &ExecutionStatus) -> String {
+    match status {
+        ExecutionStatus::Success => "Executed successfully".to_string(),
+        ExecutionStatus::OutOfGas => "Out of gas".to_string(),
+        ExecutionStatus::MoveAbort {
+            location,
+            code,
+            info: Some(info),
+        } => format!(
+            "Move abort in {}: {}({:#x}): {}",
+            location, info.reason_name, code, info.description
+        ),
+        ExecutionStatus::MoveAbort {
+            location,
+            code,
+            info: None,
+        } => format!("Move abort in {}: {:#x}", location, code),
+        ExecutionStatus::ExecutionFailure {
+            location,
+            function,
+            code_offset,
+        } => format!(
+            "Execution failed in {}::<#{} function> at code offset {}",
+            location, function, code_offset
+        ),
+        ExecutionStatus::MiscellaneousError(code) => match code {
+            Some(code) => format!("Miscellaneous error: {:?}", code),
+            None => "Unknown miscellaneous error".to_string(),
+        },
+    }
+}
+
+fn decode_payload<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    payload: &TransactionPayload,
+) -> PayloadView {
+    match payload {
+        TransactionPayload::EntryFunction(function) => {
+            PayloadView::EntryFunction(decode_entry_function(annotator, function))
+        },
+        TransactionPayload::Script(script) => PayloadView::Script(decode_script(annotator, script)),
+        TransactionPayload::Multisig(multisig) => PayloadView::Multisig {
+            multisig_address: multisig.multisig_address,
+            entry_function: multisig
+                .transaction_payload
+                .as_ref()
+                .map(|payload| match payload {
+                    MultisigTransactionPayload::EntryFunction(function) => {
+                        decode_entry_function(annotator, function)
+                    },
+                }),
+        },
+        TransactionPayload::ModuleBundle(_) => PayloadView::ModuleBundle,
+    }
+}
+
+/// Decodes the arguments with the ABI of the function on chain, and falls back to their BCS
+/// bytes if the function can't be found
+fn decode_entry_function<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    function: &EntryFunction,
+) -> EntryFunctionView {
+    let arguments = annotator
+        .view_function_arguments(
+            function.module(),
+            function.function(),
+            function.ty_args(),
+            function.args(),
+        )
+        .and_then(|values| {
+            values
+                .into_iter()
+                .map(|value| MoveValue::try_from(value)?.json())
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .unwrap_or_else(|_| function.args().iter().map(|arg| hex_value(arg)).collect());
+
+    EntryFunctionView {
+        function: format!("{}::{}", function.module(), function.function()),
+        type_arguments: function.ty_args().iter().map(TypeTag::to_string).collect(),
+        arguments,
+    }
+}
+
+fn decode_script<S: StateView>(annotator: &AptosValueAnnotator<S>, script: &Script) -> ScriptView {
+    let arguments = annotator
+        .view_script_arguments(script.code(), script.args(), script.ty_args())
+        .and_then(|values| {
+            values
+                .into_iter()
+                .map(|value| MoveValue::try_from(value)?.json())
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .unwrap_or_else(|_| {
+            script
+                .args()
+                .iter()
+                .map(|arg| serde_json::Value::String(format!("{:?}", arg)))
+                .collect()
+        });
+
+    ScriptView {
+        type_arguments: script.ty_args().iter().map(TypeTag::to_string).collect(),
+        arguments,
+    }
+}
+
+fn decode_change<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    state_key: &StateKey,
+    write_op: &WriteOp,
+) -> ChangeView {
+    let bytes = write_op.bytes();
+    let (kind, key, address, value) = match state_key.inner() {
+        StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+            Path::Code(module_id) => (ChangeKind::Module, module_id.to_string(), None, None),
+            Path::Resource(tag) => (
+                ChangeKind::Resource,
+                tag.to_string(),
+                Some(access_path.address),
+                bytes.map(|bytes| decode_resource(annotator, &tag, bytes)),
+            ),
+            Path::ResourceGroup(tag) => (
+                ChangeKind::ResourceGroup,
+                tag.to_string(),
+                Some(access_path.address),
+                bytes.map(|bytes| decode_resource_group(annotator, bytes)),
+            ),
+        },
+        StateKeyInner::TableItem { handle, key } => (
+            ChangeKind::TableItem,
+            format!("{}[0x{}]", handle.0.to_standard_string(), hex::encode(key)),
+            None,
+            // Table items have no type on chain, so they can't be decoded
+            bytes.map(|bytes| hex_value(bytes)),
+        ),
+        StateKeyInner::Raw(key) => (
+            ChangeKind::Raw,
+            format!("0x{}", hex::encode(key)),
+            None,
+            bytes.map(|bytes| hex_value(bytes)),
+        ),
+    };
+
+    ChangeView {
+        kind,
+        key,
+        address,
+        deleted: bytes.is_none(),
+        value,
+    }
+}
+
+fn decode_resource<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    tag: &StructTag,
+    bytes: &[u8],
+) -> serde_json::Value {
+    annotator
+        .view_resource(tag, bytes)
+        .and_then(|value| MoveStructValue::try_from(value)?.json())
+        .unwrap_or_else(|_| hex_value(bytes))
+}
+
+/// Resource groups are stored as a map of the BCS bytes of their members, by type
+fn decode_resource_group<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    bytes: &[u8],
+) -> serde_json::Value {
+    match bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(bytes) {
+        Ok(members) => serde_json::Value::Object(
+            members
+                .iter()
+                .map(|(tag, bytes)| (tag.to_string(), decode_resource(annotator, tag, bytes)))
+                .collect(),
+        ),
+        Err(_) => hex_value(bytes),
+    }
+}
+
+fn decode_value<S: StateView>(
+    annotator: &AptosValueAnnotator<S>,
+    type_tag: &TypeTag,
+    bytes: &[u8],
+) -> serde_json::Value {
+    annotator
+        .view_value(type_tag, bytes)
+        .and_then(|value| MoveValue::try_from(value)?.json())
+        .unwrap_or_else(|_| hex_value(bytes))
+}
+
+fn hex_value(bytes: &[u8]) -> serde_json::Value {
+    serde_json::Value::String(format!("0x{}", hex::encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::state_store::{in_memory_state_view::InMemoryStateView, table::TableHandle};
+
+    fn tag(tag: &str) -> StructTag {
+        StructTag::from_str(tag).unwrap()
+    }
+
+    #[test]
+    fn test_parse_transaction_id() {
+        assert!(matches!(
+            TransactionId::from_str("42").unwrap(),
+            TransactionId::Version(42)
+        ));
+
+        let hash = HashValue::from(aptos_crypto::HashValue::random());
+        assert!(matches!(
+            TransactionId::from_str(&hash.to_string()).unwrap(),
+            TransactionId::Hash(parsed) if parsed == hash
+        ));
+        assert!(matches!(
+            TransactionId::from_str(&format!("{:x}", hash)).unwrap(),
+            TransactionId::Hash(parsed) if parsed == hash
+        ));
+
+        assert!(TransactionId::from_str("-1").is_err());
+        assert!(TransactionId::from_str("0x1234").is_err());
+        assert!(TransactionId::from_str("latest").is_err());
+    }
+
+    #[test]
+    fn test_decode_change() {
+        // Nothing is on chain, so the values are shown as their BCS bytes
+        let state_view = InMemoryStateView::new(Default::default());
+        let annotator = AptosValueAnnotator::new(&state_view);
+        let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let coin_store = tag("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>");
+
+        let change = decode_change(
+            &annotator,
+            &StateKey::resource(&address, &coin_store).unwrap(),
+            &WriteOp::legacy_modification(vec![1, 2].into()),
+        );
+        assert!(matches!(change.kind, ChangeKind::Resource));
+        assert_eq!(change.key, coin_store.to_string());
+        assert_eq!(change.address, Some(address));
+        assert!(!change.deleted);
+        assert_eq!(change.value, Some(serde_json::json!("0x0102")));
+
+        let change = decode_change(
+            &annotator,
+            &StateKey::resource(&address, &coin_store).unwrap(),
+            &WriteOp::legacy_deletion(),
+        );
+        assert!(change.deleted);
+        assert_eq!(change.value, None);
+
+        let change = decode_change(
+            &annotator,
+            &StateKey::table_item(&TableHandle(address), &[3]),
+            &WriteOp::legacy_creation(vec![4].into()),
+        );
+        assert!(matches!(change.kind, ChangeKind::TableItem));
+        assert_eq!(
+            change.key,
+            format!("{}[0x03]", address.to_standard_string())
+        );
+        assert_eq!(change.address, None);
+        assert_eq!(change.value, Some(serde_json::json!("0x04")));
+    }
+
+    #[test]
+    fn test_decode_resource_group() {
+        let state_view = InMemoryStateView::new(Default::default());
+        let annotator = AptosValueAnnotator::new(&state_view);
+        let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let group = tag("0x1::object::ObjectGroup");
+        let object_core = tag("0x1::object::ObjectCore");
+        let token = tag("0x4::token::Token");
+        let members = BTreeMap::from([(object_core.clone(), vec![1]), (token.clone(), vec![2, 3])]);
+
+        let change = decode_change(
+            &annotator,
+            &StateKey::resource_group(&address, &group),
+            &WriteOp::legacy_modification(bcs::to_bytes(&members).unwrap().into()),
+        );
+        assert!(matches!(change.kind, ChangeKind::ResourceGroup));
+        assert_eq!(change.key, group.to_string());
+        assert_eq!(change.address, Some(address));
+        assert_eq!(
+            change.value,
+            Some(serde_json::json!({
+                object_core.to_string(): "0x01",
+                token.to_string(): "0x0203",
+            }))
+        );
+
+        // Bytes which aren't a group are shown as they are
+        assert_eq!(
+            decode_resource_group(&annotator, &[0xff]),
+            serde_json::json!("0xff")
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let sender = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let mut view = TransactionView::new("user_transaction", HashValue::zero());
+        view.version = Some(7);
+        view.sender = Some(sender);
+        view.sequence_number = Some(3);
+        view.expiration_timestamp_secs = Some(100);
+        view.payload = Some(PayloadView::EntryFunction(EntryFunctionView {
+            function: "0x1::coin::transfer".to_string(),
+            type_arguments: vec!["0x1::aptos_coin::AptosCoin".to_string()],
+            arguments: vec![serde_json::json!("0xbeef"), serde_json::json!("10")],
+        }));
+        view.success = Some(true);
+        view.vm_status = Some("Executed successfully".to_string());
+        view.gas = Some(GasView {
+            gas_used: 6,
+            gas_unit_price: Some(100),
+            max_gas_amount: Some(1000),
+            fee_statement: Some(FeeStatement::new(6, 4, 2, 50, 10)),
+        });
+        view.events = vec![EventView {
+            type_tag: "0x1::transaction_fee::FeeStatement".to_string(),
+            data: serde_json::json!({"total_charge_gas_units": "6"}),
+        }];
+        view.changes = vec![
+            ChangeView {
+                kind: ChangeKind::Resource,
+                key: "0x1::account::Account".to_string(),
+                address: Some(sender),
+                deleted: false,
+                value: Some(serde_json::json!({"sequence_number": "4"})),
+            },
+            ChangeView {
+                kind: ChangeKind::TableItem,
+                key: "0x1[0x02]".to_string(),
+                address: None,
+                deleted: true,
+                value: None,
+            },
+        ];
+
+        let expected = format!(
+            "Transaction {hash} (user_transaction)
+  Version: 7
+  Sender: {sender}
+  Sequence number: 3
+  Expiration timestamp: 100s
+  Status: Executed successfully
+  Payload: entry function
+    Function: 0x1::coin::transfer
+    Type arguments: 0x1::aptos_coin::AptosCoin
+    Arguments:
+      \"0xbeef\"
+      \"10\"
+  Gas: 6 units at 100 octas per unit, 600 octas
+    Max gas amount: 1000 units
+    Execution: 4 units
+    IO: 2 units
+    Storage fee: 50 octas
+    Storage fee refund: 10 octas
+  Events (1):
+    0x1::transaction_fee::FeeStatement
+      {{\"total_charge_gas_units\":\"6\"}}
+  Changes (2):
+    Write resource 0x1::account::Account at {sender}
+      {{\"sequence_number\":\"4\"}}
+    Delete table item 0x1[0x02]
+",
+            hash = HashValue::zero(),
+            sender = sender.to_standard_string(),
+        );
+        assert_eq!(view.render(), expected);
+    }
+
+    #[test]
+    fn test_render_saturates_gas_cost() {
+        let mut view = TransactionView::new("user_transaction", HashValue::zero());
+        view.gas = Some(GasView {
+            gas_used: u64::MAX,
+            gas_unit_price: Some(2),
+            max_gas_amount: None,
+            fee_statement: None,
+        });
+        assert!(view
+            .render()
+            .contains(&format!("{} octas per unit, {} octas", 2, u64::MAX)));
+    }
+}
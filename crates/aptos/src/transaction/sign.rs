@@ -0,0 +1,121 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::PartiallySignedTransaction;
+use crate::common::{
+    types::{
+        CliCommand, CliTypedResult, EncodingOptions, PrivateKeyInputOptions, ProfileOptions,
+        PromptOptions, TransactionSummary,
+    },
+    utils::{check_if_file_exists, prompt_yes_with_override, write_to_file},
+};
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use itertools::Itertools;
+use std::path::PathBuf;
+
+/// Sign a transaction built with `aptos transaction build` or `--output-unsigned`
+///
+/// This doesn't connect to the network, so it can run on a machine without network access. Once
+/// every signer signed the transaction, it's written as BCS, to be submitted with
+/// `aptos transaction submit`. Until then, it's written in the same format as the unsigned
+/// transaction, to be signed by the next signer, e.g. a secondary signer or the fee payer.
+#[derive(Debug, Parser)]
+pub struct SignTransaction {
+    /// File holding the unsigned, or partially signed, transaction
+    #[clap(long, value_parser)]
+    pub(crate) unsigned_transaction_file: PathBuf,
+    /// File to write the signed transaction to
+    #[clap(long, value_parser)]
+    pub(crate) output_file: PathBuf,
+    /// Address of the signer
+    ///
+    /// Defaults to the account of the profile, or to the address derived from the private key.
+    /// Set it if the authentication key of the signer was rotated.
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) signer_account: Option<AccountAddress>,
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SignTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let mut transaction = PartiallySignedTransaction::read(&self.unsigned_transaction_file)?;
+        check_if_file_exists(&self.output_file, self.prompt_options)?;
+
+        // The transaction was built elsewhere, so show what is about to be signed
+        let mut other_signers = String::new();
+        if !transaction.secondary_signer_addresses.is_empty() {
+            other_signers += &format!(
+                ", with secondary signers {}",
+                transaction.secondary_signer_addresses.iter().join(", ")
+            );
+        }
+        if let Some(fee_payer_address) = transaction.fee_payer_address {
+            other_signers += &format!(", with gas paid by {}", fee_payer_address);
+        }
+        prompt_yes_with_override(
+            &format!(
+                "Do you want to sign the transaction {:#?}{}?",
+                transaction.raw_txn, other_signers
+            ),
+            self.prompt_options,
+        )?;
+
+        let (signing_key, signer_address) =
+            self.private_key_options.extract_signing_key_and_address(
+                self.encoding_options.encoding,
+                &self.profile_options,
+                self.signer_account,
+            )?;
+        transaction.sign(&signing_key, signer_address)?;
+
+        let raw_txn = transaction.raw_txn.clone();
+        let missing_signers = transaction.missing_signers();
+        let transaction_hash = match transaction.clone().into_signed_transaction() {
+            Some(signed_txn) => {
+                write_to_file(
+                    &self.output_file,
+                    "Signed transaction",
+                    &bcs::to_bytes(&signed_txn)?,
+                )?;
+                signed_txn.committed_hash()
+            },
+            None => {
+                transaction.write(&self.output_file, "Partially signed transaction")?;
+                eprintln!(
+                    "The transaction still needs to be signed by {}",
+                    missing_signers.iter().join(", ")
+                );
+                // Like for the unsigned transaction, the hash on chain isn't known yet
+                raw_txn.hash()
+            },
+        };
+
+        Ok(TransactionSummary {
+            transaction_hash: transaction_hash.into(),
+            gas_used: None,
+            gas_unit_price: Some(raw_txn.gas_unit_price()),
+            pending: None,
+            sender: Some(raw_txn.sender()),
+            sequence_number: Some(raw_txn.sequence_number()),
+            success: None,
+            timestamp_us: None,
+            version: None,
+            vm_status: None,
+        })
+    }
+}
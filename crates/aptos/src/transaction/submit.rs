@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{
+        CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions, TransactionSummary,
+    },
+    utils::{read_from_file, submit_signed_transaction},
+};
+use aptos_types::transaction::SignedTransaction;
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Submit a transaction signed with `aptos transaction sign`, and wait for it to be committed
+#[derive(Debug, Parser)]
+pub struct SubmitTransaction {
+    /// File holding the BCS encoded signed transaction
+    #[clap(long, value_parser)]
+    pub(crate) signed_transaction_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let signed_txn: SignedTransaction =
+            bcs::from_bytes(&read_from_file(&self.signed_transaction_file)?)
+                .map_err(|err| CliError::BCS("signed transaction", err))?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let network = self
+            .profile_options
+            .profile()
+            .ok()
+            .and_then(|profile| profile.network);
+        submit_signed_transaction(&client, &signed_txn, network)
+            .await
+            .map(TransactionSummary::from)
+    }
+}
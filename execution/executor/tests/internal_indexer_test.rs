@@ -148,6 +148,13 @@ fn test_db_indexer_data() {
     let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
 
     let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    // a new indexer DB covers all versions with its events by type index
+    assert_eq!(
+        internal_indexer_db
+            .get_events_by_type_min_version()
+            .unwrap(),
+        0
+    );
     // assert the data matches the expected data
     let version = internal_indexer_db.get_persisted_version().unwrap();
     assert_eq!(version, None);
@@ -176,6 +183,30 @@ fn test_db_indexer_data() {
     let res: Vec<_> = x.collect();
     assert_eq!(res.len(), 27);
 
+    // events are also indexed by type, so the first indexed event can be found by its type
+    let (_, first_event_version, _, first_event_idx) = res[0];
+    let first_event = aptos_db
+        .get_event_by_version_and_index(first_event_version, first_event_idx)
+        .unwrap();
+    let events_by_type = db_indexer
+        .get_events_by_type(
+            first_event.type_tag(),
+            0,
+            total_version,
+            1000,
+            total_version,
+        )
+        .unwrap();
+    assert!(events_by_type
+        .iter()
+        .all(|e| e.event.type_tag() == first_event.type_tag()));
+    assert!(events_by_type
+        .iter()
+        .any(|e| e.transaction_version == first_event_version && e.event == first_event));
+    assert!(events_by_type
+        .windows(2)
+        .all(|w| w[0].transaction_version <= w[1].transaction_version));
+
     let core_kv_iter = db_indexer
         .get_prefixed_state_value_iterator(
             &StateKeyPrefix::from(core_account.address()),
@@ -91,6 +91,17 @@ impl BackupHandler {
         Ok(zipped)
     }
 
+    /// Gets an iterator that yields the write sets of a range of transactions.
+    pub fn get_write_set_iter(
+        &self,
+        start_version: Version,
+        num_transactions: usize,
+    ) -> Result<impl Iterator<Item = Result<WriteSet>> + '_> {
+        self.ledger_db
+            .write_set_db()
+            .get_write_set_iter(start_version, num_transactions)
+    }
+
     /// Gets the proof for a transaction chunk.
     /// N.B. the `LedgerInfo` returned will always be in the same epoch of the `last_version`.
     pub fn get_transaction_range_proof(
@@ -16,7 +16,8 @@ use aptos_crypto::{
     HashValue,
 };
 use aptos_db_indexer_schemas::schema::{
-    event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+    event_by_key::EventByKeySchema, event_by_type::EventByTypeSchema,
+    event_by_version::EventByVersionSchema,
 };
 use aptos_schemadb::{SchemaBatch, DB};
 use aptos_storage_interface::{AptosDbError, Result};
@@ -185,17 +186,28 @@ impl EventDb {
     }
 
     /// Deletes a set of events in the range of version in [begin, end), and all related indices.
+    ///
+    /// The events by type index only lives in the internal indexer DB, so it's pruned only when
+    /// `type_indices_batch` is given.
     pub(crate) fn prune_events(
         &self,
         start: Version,
         end: Version,
         db_batch: &SchemaBatch,
         indices_batch: Option<&SchemaBatch>,
+        type_indices_batch: Option<&SchemaBatch>,
     ) -> anyhow::Result<()> {
         let mut current_version = start;
 
         for events in self.get_events_by_version_iter(start, (end - start) as usize)? {
             for (idx, event) in (events?).into_iter().enumerate() {
+                if let Some(batch) = type_indices_batch {
+                    batch.delete::<EventByTypeSchema>(&(
+                        event.type_tag().clone(),
+                        current_version,
+                        idx as u64,
+                    ))?;
+                }
                 if let ContractEvent::V1(v1) = event {
                     if let Some(batch) = indices_batch {
                         batch.delete::<EventByKeySchema>(&(*v1.key(), v1.sequence_number()))?;
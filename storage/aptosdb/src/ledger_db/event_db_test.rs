@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::db::AptosDB;
+use aptos_config::config::{internal_indexer_db_config::InternalIndexerDBConfig, RocksdbConfig};
+use aptos_db_indexer::{db_indexer::InternalIndexerDB, db_ops::open_internal_indexer_db};
+use aptos_db_indexer_schemas::schema::event_by_type::EventByTypeSchema;
 use aptos_schemadb::SchemaBatch;
 use aptos_storage_interface::Result;
 use aptos_temppath::TempPath;
 use aptos_types::contract_event::ContractEvent;
 use proptest::{collection::vec, prelude::*, proptest};
+use std::sync::Arc;
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -64,4 +68,39 @@ proptest! {
             vec![events1, events2, events3]
         );
     }
+
+    #[test]
+    fn test_prune_events_by_type(events in vec(any::<ContractEvent>().no_shrink(), 1..100)) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let event_db = &db.ledger_db.event_db();
+        let indexer_dir = TempPath::new();
+        let indexer_db = InternalIndexerDB::new(
+            Arc::new(open_internal_indexer_db(indexer_dir.path(), &RocksdbConfig::default()).unwrap()),
+            InternalIndexerDBConfig::new(false, true, false, 1),
+        );
+
+        let batch = SchemaBatch::new();
+        event_db.put_events(100, &events, /*skip_index=*/false, &batch).unwrap();
+        event_db.write_schemas(batch).unwrap();
+        let indexer_batch = SchemaBatch::new();
+        for (idx, event) in events.iter().enumerate() {
+            indexer_batch.put::<EventByTypeSchema>(&(event.type_tag().clone(), 100, idx as u64), &()).unwrap();
+        }
+        indexer_db.get_inner_db_ref().write_schemas(indexer_batch).unwrap();
+
+        let batch = SchemaBatch::new();
+        let indexer_batch = SchemaBatch::new();
+        event_db.prune_events(100, 101, &batch, None, Some(&indexer_batch)).unwrap();
+        event_db.write_schemas(batch).unwrap();
+        indexer_db.get_inner_db_ref().write_schemas(indexer_batch).unwrap();
+
+        prop_assert_eq!(event_db.get_events_by_version(100).unwrap().len(), 0);
+        for event in &events {
+            prop_assert!(indexer_db
+                .lookup_events_by_type(event.type_tag(), 0, 100, 100)
+                .unwrap()
+                .is_empty());
+        }
+    }
 }
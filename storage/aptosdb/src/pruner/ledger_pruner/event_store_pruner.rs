@@ -57,6 +57,7 @@ impl DBSubPruner for EventStorePruner {
             target_version,
             &batch,
             indices_batch,
+            indexer_batch.as_ref(),
         )?;
         batch.put::<DbMetadataSchema>(
             &DbMetadataKey::EventPrunerProgress,
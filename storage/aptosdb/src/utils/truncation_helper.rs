@@ -422,6 +422,7 @@ fn delete_event_data(
                 // with the existence or placement of indices
                 // TODO: prune data from internal indices
                 None,
+                None,
             )?;
         }
     }
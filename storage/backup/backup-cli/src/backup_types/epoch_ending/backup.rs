@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
+    backup_types::{
+        epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
+        file_hashes::BackupFileWriter,
+    },
     metadata::Metadata,
-    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    storage::{BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
         should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
@@ -17,7 +20,6 @@ use aptos_types::{ledger_info::LedgerInfoWithSignatures, waypoint::Waypoint};
 use clap::Parser;
 use once_cell::sync::Lazy;
 use std::{convert::TryInto, str::FromStr, sync::Arc};
-use tokio::io::AsyncWriteExt;
 
 #[derive(Parser)]
 pub struct EpochEndingBackupOpt {
@@ -76,6 +78,7 @@ impl EpochEndingBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let files = BackupFileWriter::new(self.storage.clone(), backup_handle);
 
         let mut chunks = Vec::new();
         let mut waypoints = Vec::new();
@@ -91,12 +94,7 @@ impl EpochEndingBackupController {
         while let Some(record_bytes) = ledger_infos_file.read_record_bytes().await? {
             if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
                 let chunk = self
-                    .write_chunk(
-                        &backup_handle,
-                        &chunk_bytes,
-                        chunk_first_epoch,
-                        current_epoch - 1,
-                    )
+                    .write_chunk(&files, &chunk_bytes, chunk_first_epoch, current_epoch - 1)
                     .await?;
                 chunks.push(chunk);
                 chunk_bytes = vec![];
@@ -112,16 +110,11 @@ impl EpochEndingBackupController {
         assert!(!chunk_bytes.is_empty());
         assert_eq!(current_epoch, self.end_epoch);
         let chunk = self
-            .write_chunk(
-                &backup_handle,
-                &chunk_bytes,
-                chunk_first_epoch,
-                current_epoch - 1,
-            )
+            .write_chunk(&files, &chunk_bytes, chunk_first_epoch, current_epoch - 1)
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, waypoints, chunks).await
+        self.write_manifest(files, waypoints, chunks).await
     }
 
     fn backup_name(&self) -> String {
@@ -151,17 +144,14 @@ impl EpochEndingBackupController {
 
     async fn write_chunk(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: &BackupFileWriter,
         chunk_bytes: &[u8],
         first_epoch: u64,
         last_epoch: u64,
     ) -> Result<EpochEndingChunk> {
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_epoch))
+        let chunk_handle = files
+            .write(&Self::chunk_name(first_epoch), chunk_bytes)
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
         Ok(EpochEndingChunk {
             first_epoch,
             last_epoch,
@@ -171,7 +161,7 @@ impl EpochEndingBackupController {
 
     async fn write_manifest(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: BackupFileWriter,
         waypoints: Vec<Waypoint>,
         chunks: Vec<EpochEndingChunk>,
     ) -> Result<FileHandle> {
@@ -184,14 +174,10 @@ impl EpochEndingBackupController {
             waypoints,
             chunks,
         };
-        let (manifest_handle, mut manifest_file) = self
-            .storage
-            .create_for_write(backup_handle, Self::manifest_name())
-            .await?;
-        manifest_file
-            .write_all(&serde_json::to_vec(&manifest)?)
+        let manifest_handle = files
+            .write(Self::manifest_name(), &serde_json::to_vec(&manifest)?)
             .await?;
-        manifest_file.shutdown().await?;
+        let file_hashes = files.finish().await?;
 
         let metadata = Metadata::new_epoch_ending_backup(
             first_epoch,
@@ -199,6 +185,7 @@ impl EpochEndingBackupController {
             manifest.waypoints.first().expect("No waypoints.").version(),
             manifest.waypoints.last().expect("No waypoints.").version(),
             manifest_handle.clone(),
+            Some(file_hashes),
         );
 
         self.storage
@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata::FileHashesMeta,
+    storage::{BackupHandle, BackupStorage, FileHandle, ShellSafeName},
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use futures::{stream, StreamExt, TryStreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// SHA3-256 hashes of the content of the files of a backup, saved as a file of the backup and
+/// referred to by its metadata, so that corrupted or tampered files can be detected without
+/// verifying the proofs in them.
+#[derive(Default, Deserialize, Serialize)]
+pub struct FileHashes {
+    pub files: BTreeMap<FileHandle, HashValue>,
+}
+
+impl FileHashes {
+    pub fn name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("file_hashes.json").unwrap());
+        &NAME
+    }
+
+    /// Reads back all the files of a backup, checking their content against the recorded hashes.
+    pub async fn verify(
+        storage: &Arc<dyn BackupStorage>,
+        meta: &FileHashesMeta,
+        concurrent_downloads: usize,
+    ) -> Result<()> {
+        let file_hashes: Self = serde_json::from_slice(
+            &read_and_verify(storage.clone(), meta.file.clone(), meta.hash).await?,
+        )?;
+        stream::iter(file_hashes.files)
+            .map(|(file_handle, hash)| read_and_verify(storage.clone(), file_handle, hash))
+            .buffer_unordered(concurrent_downloads.max(1))
+            .try_for_each(|_content| futures::future::ok(()))
+            .await
+    }
+}
+
+/// Returns the content of the file, after checking it matches the hash.
+async fn read_and_verify(
+    storage: Arc<dyn BackupStorage>,
+    file_handle: FileHandle,
+    expected: HashValue,
+) -> Result<Vec<u8>> {
+    let mut file = storage
+        .open_for_read(&file_handle)
+        .await
+        .err_notes(&file_handle)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .await
+        .err_notes(&file_handle)?;
+    let actual = HashValue::sha3_256_of(&content);
+    ensure!(
+        actual == expected,
+        "{} is corrupted or was tampered with, expecting hash {}, got {}.",
+        file_handle,
+        expected,
+        actual,
+    );
+    Ok(content)
+}
+
+/// Writes the files of a backup, recording the hashes of their content.
+pub struct BackupFileWriter {
+    storage: Arc<dyn BackupStorage>,
+    backup_handle: BackupHandle,
+    file_hashes: Mutex<FileHashes>,
+}
+
+impl BackupFileWriter {
+    pub fn new(storage: Arc<dyn BackupStorage>, backup_handle: BackupHandle) -> Self {
+        Self {
+            storage,
+            backup_handle,
+            file_hashes: Mutex::new(FileHashes::default()),
+        }
+    }
+
+    pub async fn write(&self, name: &ShellSafeName, content: &[u8]) -> Result<FileHandle> {
+        let file_handle = self.write_untracked(name, content).await?;
+        self.file_hashes
+            .lock()
+            .files
+            .insert(file_handle.clone(), HashValue::sha3_256_of(content));
+        Ok(file_handle)
+    }
+
+    /// Saves the hashes of the files written, returning the reference to record in the metadata
+    /// of the backup.
+    pub async fn finish(self) -> Result<FileHashesMeta> {
+        let content = serde_json::to_vec(&*self.file_hashes.lock())?;
+        let file = self.write_untracked(FileHashes::name(), &content).await?;
+        Ok(FileHashesMeta {
+            file,
+            hash: HashValue::sha3_256_of(&content),
+        })
+    }
+
+    async fn write_untracked(&self, name: &ShellSafeName, content: &[u8]) -> Result<FileHandle> {
+        let (file_handle, mut file) = self
+            .storage
+            .create_for_write(&self.backup_handle, name)
+            .await?;
+        file.write_all(content).await?;
+        file.shutdown().await?;
+        Ok(file_handle)
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod epoch_ending;
+pub mod file_hashes;
 pub mod state_snapshot;
 pub mod transaction;
 
@@ -3,10 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+    backup_types::{
+        file_hashes::BackupFileWriter,
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+    },
     metadata::Metadata,
     metrics::backup::BACKUP_TIMER,
-    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    storage::{BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
         should_cut_chunk, storage_ext::BackupStorageExt, stream::TryStreamX, GlobalBackupOpt,
@@ -27,7 +30,7 @@ use clap::Parser;
 use futures::{StreamExt, TryStream, TryStreamExt};
 use once_cell::sync::Lazy;
 use std::{convert::TryInto, str::FromStr, sync::Arc, time::Instant};
-use tokio::{io::AsyncWriteExt, sync::mpsc::Sender};
+use tokio::{io::AsyncReadExt, sync::mpsc::Sender};
 use tokio_stream::wrappers::ReceiverStream;
 
 #[derive(Parser)]
@@ -234,11 +237,12 @@ impl StateSnapshotBackupController {
     }
 
     async fn run_impl(mut self) -> Result<FileHandle> {
-        self.version = Some(self.get_version_for_epoch_ending(self.epoch).await?);
+        self.version = Some(get_version_for_epoch_ending(&self.client, self.epoch).await?);
         let backup_handle = self
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let files = BackupFileWriter::new(self.storage.clone(), backup_handle);
 
         let record_stream = Box::pin(self.record_stream(self.concurrent_data_requests).await?);
         let chunker = Chunker::new(record_stream, self.max_chunk_size).await?;
@@ -249,7 +253,7 @@ impl StateSnapshotBackupController {
         });
 
         let chunk_manifest_fut_stream =
-            chunk_stream.map_ok(|chunk| self.write_chunk(&backup_handle, chunk));
+            chunk_stream.map_ok(|chunk| self.write_chunk(&files, chunk));
 
         let chunks: Vec<_> = chunk_manifest_fut_stream
             .try_buffered_x(8, 4) // 4 concurrently, at most 8 results in buffer.
@@ -266,7 +270,7 @@ impl StateSnapshotBackupController {
             .try_collect()
             .await?;
 
-        self.write_manifest(&backup_handle, chunks).await
+        self.write_manifest(files, chunks).await
     }
 
     async fn record_stream(
@@ -314,6 +318,22 @@ impl StateSnapshotBackupController {
     }
 }
 
+pub(super) async fn get_version_for_epoch_ending(
+    client: &BackupServiceClient,
+    epoch: u64,
+) -> Result<Version> {
+    let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
+        client
+            .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+            .await?
+            .read_record_bytes()
+            .await?
+            .ok_or_else(|| anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch))?
+            .as_ref(),
+    )?;
+    Ok(ledger_info.ledger_info().version())
+}
+
 async fn send_records(
     client: Arc<BackupServiceClient>,
     version: Version,
@@ -386,24 +406,9 @@ impl StateSnapshotBackupController {
             .unwrap()
     }
 
-    async fn get_version_for_epoch_ending(&self, epoch: u64) -> Result<u64> {
-        let ledger_info: LedgerInfoWithSignatures = bcs::from_bytes(
-            self.client
-                .get_epoch_ending_ledger_infos(epoch, epoch + 1)
-                .await?
-                .read_record_bytes()
-                .await?
-                .ok_or_else(|| {
-                    anyhow!("Failed to get epoch ending ledger info for epoch {}", epoch)
-                })?
-                .as_ref(),
-        )?;
-        Ok(ledger_info.ledger_info().version())
-    }
-
     async fn write_chunk(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: &BackupFileWriter,
         chunk: Chunk,
     ) -> Result<StateSnapshotChunk> {
         let _timer = BACKUP_TIMER.timer_with(&["state_snapshot_write_chunk"]);
@@ -416,25 +421,16 @@ impl StateSnapshotBackupController {
             last_key,
         } = chunk;
 
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+        let chunk_handle = files.write(&Self::chunk_name(first_idx), &bytes).await?;
+        let mut proof_bytes = Vec::new();
+        self.client
+            .get_account_range_proof(last_key, self.version())
+            .await?
+            .read_to_end(&mut proof_bytes)
             .await?;
-        chunk_file.write_all(&bytes).await?;
-        chunk_file.shutdown().await?;
-        let (proof_handle, mut proof_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
+        let proof_handle = files
+            .write(&Self::chunk_proof_name(first_idx, last_idx), &proof_bytes)
             .await?;
-        tokio::io::copy(
-            &mut self
-                .client
-                .get_account_range_proof(last_key, self.version())
-                .await?,
-            &mut proof_file,
-        )
-        .await?;
-        proof_file.shutdown().await?;
 
         Ok(StateSnapshotChunk {
             first_idx,
@@ -448,19 +444,14 @@ impl StateSnapshotBackupController {
 
     async fn write_manifest(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: BackupFileWriter,
         chunks: Vec<StateSnapshotChunk>,
     ) -> Result<FileHandle> {
         let proof_bytes = self.client.get_state_root_proof(self.version()).await?;
         let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
             bcs::from_bytes(&proof_bytes)?;
 
-        let (proof_handle, mut proof_file) = self
-            .storage
-            .create_for_write(backup_handle, Self::proof_name())
-            .await?;
-        proof_file.write_all(&proof_bytes).await?;
-        proof_file.shutdown().await?;
+        let proof_handle = files.write(Self::proof_name(), &proof_bytes).await?;
 
         let manifest = StateSnapshotBackup {
             epoch: self.epoch,
@@ -470,19 +461,16 @@ impl StateSnapshotBackupController {
             proof: proof_handle,
         };
 
-        let (manifest_handle, mut manifest_file) = self
-            .storage
-            .create_for_write(backup_handle, Self::manifest_name())
-            .await?;
-        manifest_file
-            .write_all(&serde_json::to_vec(&manifest)?)
+        let manifest_handle = files
+            .write(Self::manifest_name(), &serde_json::to_vec(&manifest)?)
             .await?;
-        manifest_file.shutdown().await?;
+        let file_hashes = files.finish().await?;
 
         let metadata = Metadata::new_state_snapshot_backup(
             self.epoch,
             self.version(),
             manifest_handle.clone(),
+            Some(file_hashes),
         );
         self.storage
             .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
@@ -0,0 +1,354 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        file_hashes::BackupFileWriter,
+        state_snapshot::{
+            backup::get_version_for_epoch_ending,
+            manifest::{StateSnapshotDeltaBackup, StateSnapshotDeltaChunk, StateSnapshotManifest},
+        },
+    },
+    metadata::Metadata,
+    metrics::backup::BACKUP_TIMER,
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::prelude::*;
+use aptos_metrics_core::TimerHelper;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    write_set::{TransactionWrite, WriteSet},
+};
+use clap::Parser;
+use futures::{stream, StreamExt, TryStreamExt};
+use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, convert::TryInto, str::FromStr, sync::Arc};
+use tokio::io::AsyncReadExt;
+
+#[derive(Parser)]
+pub struct StateSnapshotDeltaBackupOpt {
+    #[clap(
+        long = "state-snapshot-epoch",
+        help = "Epoch at the end of which a state snapshot delta is to be taken."
+    )]
+    pub epoch: u64,
+    #[clap(
+        long = "base-state-manifest",
+        help = "Manifest of the state snapshot, full or delta, in the same backup storage to take \
+        the delta against. The state keys changed since it are held in memory while the delta is \
+        being taken, so the older it is, the more memory is needed."
+    )]
+    pub base_manifest: FileHandle,
+}
+
+/// Number of keys between the range proofs of a delta.
+const DEFAULT_RANGE_PROOF_INTERVAL: usize = 100_000;
+
+/// Changed state keys by their hashes, with their latest values, `None` meaning deleted.
+type StateChanges = BTreeMap<HashValue, (StateKey, Option<StateValue>)>;
+
+pub struct StateSnapshotDeltaBackupController {
+    epoch: u64,
+    base_manifest: FileHandle,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_data_requests: usize,
+    range_proof_interval: usize,
+}
+
+impl StateSnapshotDeltaBackupController {
+    pub fn new(
+        opt: StateSnapshotDeltaBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            epoch: opt.epoch,
+            base_manifest: opt.base_manifest,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+            concurrent_data_requests: global_opt.concurrent_data_requests,
+            range_proof_interval: DEFAULT_RANGE_PROOF_INTERVAL,
+        }
+    }
+
+    /// Writes a range proof every `range_proof_interval` keys instead of the default interval,
+    /// e.g. to have several of them in tests.
+    pub fn with_range_proof_interval(mut self, range_proof_interval: usize) -> Self {
+        self.range_proof_interval = range_proof_interval;
+        self
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "State snapshot delta backup started, for epoch {}, based on {}.",
+            self.epoch, self.base_manifest
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("State snapshot delta backup failed: {}", e))?;
+        info!("State snapshot delta backup succeeded. Manifest: {}", ret);
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<FileHandle> {
+        let version = get_version_for_epoch_ending(&self.client, self.epoch).await?;
+        let base_version = self
+            .storage
+            .load_json_file::<StateSnapshotManifest>(&self.base_manifest)
+            .await?
+            .version();
+        ensure!(
+            base_version < version,
+            "Base state snapshot at version {} is not older than the one to take at version {}.",
+            base_version,
+            version,
+        );
+
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&self.backup_name(version))
+            .await?;
+        let files = BackupFileWriter::new(self.storage.clone(), backup_handle);
+
+        let changes = self.get_changes(base_version, version).await?;
+        info!(
+            base_version = base_version,
+            version = version,
+            changed_keys = changes.len(),
+            "State changes collected."
+        );
+        let chunks = self.write_chunks(&files, changes).await?;
+        let range_proofs = self.write_range_proofs(&files, version).await?;
+
+        self.write_manifest(files, version, base_version, chunks, range_proofs)
+            .await
+    }
+
+    /// Replays the write sets of the transactions in (`base_version`, `version`], keeping the last
+    /// write to each state key.
+    async fn get_changes(&self, base_version: Version, version: Version) -> Result<StateChanges> {
+        let _timer = BACKUP_TIMER.timer_with(&["state_snapshot_delta_get_changes"]);
+
+        let num_transactions = (version - base_version) as usize;
+        let mut input = self
+            .client
+            .get_write_sets(base_version + 1, num_transactions)
+            .await?;
+        let mut changes = StateChanges::new();
+        let mut count = 0;
+        while let Some(record_bytes) = input.read_record_bytes().await? {
+            let write_set: WriteSet = bcs::from_bytes(&record_bytes)?;
+            for (key, op) in write_set.iter() {
+                changes.insert(key.hash(), (key.clone(), op.as_state_value()));
+            }
+            count += 1;
+        }
+        ensure!(
+            count == num_transactions,
+            "expecting {} write sets, got {}",
+            num_transactions,
+            count
+        );
+        Ok(changes)
+    }
+
+    async fn write_chunks(
+        &self,
+        files: &BackupFileWriter,
+        changes: StateChanges,
+    ) -> Result<Vec<StateSnapshotDeltaChunk>> {
+        let mut chunks = Vec::new();
+        let mut chunk_bytes = Vec::new();
+        let mut chunk_first_key = None;
+        let mut chunk_last_key = HashValue::zero();
+
+        for (key, record) in changes {
+            let record_bytes = bcs::to_bytes(&record)?;
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let first_key = chunk_first_key.take().expect("Chunk is not empty.");
+                let chunk =
+                    Self::write_chunk(files, chunks.len(), first_key, chunk_last_key, &chunk_bytes)
+                        .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+            }
+            chunk_first_key.get_or_insert(key);
+            chunk_last_key = key;
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+        }
+        if let Some(first_key) = chunk_first_key {
+            let chunk =
+                Self::write_chunk(files, chunks.len(), first_key, chunk_last_key, &chunk_bytes)
+                    .await?;
+            chunks.push(chunk);
+        }
+
+        Ok(chunks)
+    }
+
+    async fn write_chunk(
+        files: &BackupFileWriter,
+        chunk_idx: usize,
+        first_key: HashValue,
+        last_key: HashValue,
+        chunk_bytes: &[u8],
+    ) -> Result<StateSnapshotDeltaChunk> {
+        let blobs = files
+            .write(&Self::chunk_name(chunk_idx), chunk_bytes)
+            .await?;
+        Ok(StateSnapshotDeltaChunk {
+            first_key,
+            last_key,
+            blobs,
+        })
+    }
+
+    /// Writes the range proofs for every `range_proof_interval`-th key of the state at `version`
+    /// and its last key, so that a restore can verify the state it composes in chunks of no more
+    /// than `range_proof_interval` keys.
+    async fn write_range_proofs(
+        &self,
+        files: &BackupFileWriter,
+        version: Version,
+    ) -> Result<FileHandle> {
+        let _timer = BACKUP_TIMER.timer_with(&["state_snapshot_delta_write_range_proofs"]);
+
+        let count = self.client.get_state_item_count(version).await?;
+        ensure!(count > 0, "State is empty.");
+        ensure!(
+            self.range_proof_interval > 0,
+            "Range proof interval must be positive."
+        );
+        let leaf_indices = (self.range_proof_interval - 1..count - 1)
+            .step_by(self.range_proof_interval)
+            .chain(std::iter::once(count - 1));
+
+        let records: Vec<Vec<u8>> = stream::iter(leaf_indices)
+            .map(|leaf_idx| self.get_range_proof(version, leaf_idx))
+            .buffered(self.concurrent_data_requests)
+            .try_collect()
+            .await?;
+        let mut bytes = Vec::new();
+        for record_bytes in records {
+            bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            bytes.extend(&record_bytes);
+        }
+
+        files.write(Self::range_proofs_name(), &bytes).await
+    }
+
+    async fn get_range_proof(&self, version: Version, leaf_idx: usize) -> Result<Vec<u8>> {
+        let record_bytes = self
+            .client
+            .get_state_snapshot_chunk(version, leaf_idx, 1)
+            .await?
+            .read_record_bytes()
+            .await?
+            .ok_or_else(|| {
+                anyhow!(
+                    "State key at index {} not found at version {}.",
+                    leaf_idx,
+                    version
+                )
+            })?;
+        let (key, _): (StateKey, StateValue) = bcs::from_bytes(&record_bytes)?;
+        let key = key.hash();
+
+        let mut proof_bytes = Vec::new();
+        self.client
+            .get_account_range_proof(key, version)
+            .await?
+            .read_to_end(&mut proof_bytes)
+            .await?;
+        let proof: SparseMerkleRangeProof = bcs::from_bytes(&proof_bytes)?;
+
+        Ok(bcs::to_bytes(&(leaf_idx, key, proof))?)
+    }
+
+    async fn write_manifest(
+        &self,
+        files: BackupFileWriter,
+        version: Version,
+        base_version: Version,
+        chunks: Vec<StateSnapshotDeltaChunk>,
+        range_proofs: FileHandle,
+    ) -> Result<FileHandle> {
+        let proof_bytes = self.client.get_state_root_proof(version).await?;
+        let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+
+        let proof_handle = files.write(Self::proof_name(), &proof_bytes).await?;
+
+        let manifest = StateSnapshotDeltaBackup {
+            version,
+            epoch: self.epoch,
+            base_version,
+            base_manifest: self.base_manifest.clone(),
+            root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
+            chunks,
+            range_proofs,
+            proof: proof_handle,
+        };
+
+        let manifest_handle = files
+            .write(Self::manifest_name(), &serde_json::to_vec(&manifest)?)
+            .await?;
+        let file_hashes = files.finish().await?;
+
+        let metadata = Metadata::new_state_snapshot_delta_backup(
+            self.epoch,
+            version,
+            base_version,
+            manifest_handle.clone(),
+            Some(file_hashes),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
+
+impl StateSnapshotDeltaBackupController {
+    fn backup_name(&self, version: Version) -> String {
+        format!("state_delta_epoch_{}_ver_{}", self.epoch, version)
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state_delta.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.proof").unwrap());
+        &NAME
+    }
+
+    fn range_proofs_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state.range_proofs").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(chunk_idx: usize) -> ShellSafeName {
+        format!("delta_{}.chunk", chunk_idx).try_into().unwrap()
+    }
+}
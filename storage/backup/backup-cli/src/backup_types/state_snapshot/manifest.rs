@@ -50,3 +50,87 @@ pub struct StateSnapshotBackup {
     /// limits the requirement on such `EpochStateBackup` to no older than the same epoch.
     pub proof: FileHandle,
 }
+
+/// A chunk of a state snapshot delta manifest, representing the state keys in the key range
+/// [`first_key`, `last_key`] (right side inclusive) changed since the base snapshot.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDeltaChunk {
+    /// key of the first changed state key in this chunk.
+    pub first_key: HashValue,
+    /// key of the last changed state key in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, `None` meaning the key is deleted.
+    pub blobs: FileHandle,
+}
+
+/// State snapshot delta backup manifest, representing a complete state view at specified version
+/// by the state keys changed since the snapshot it's based on.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDeltaBackup {
+    /// Version at which this state snapshot is taken.
+    pub version: Version,
+    /// Epoch in which this state snapshot is taken.
+    pub epoch: u64,
+    /// Version of the state snapshot this delta is based on.
+    pub base_version: Version,
+    /// Manifest of the state snapshot this delta is based on, which is either a
+    /// `StateSnapshotBackup` or another `StateSnapshotDeltaBackup`.
+    pub base_manifest: FileHandle,
+    /// Hash of the state tree root.
+    pub root_hash: HashValue,
+    /// Changed state keys in chunks.
+    pub chunks: Vec<StateSnapshotDeltaChunk>,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(leaf_idx, key, SparseMerkleRangeProof)`, each proving that all the state keys up to `key`
+    /// add up to `root_hash`. The keys are evenly spaced in the state at `version`, the last one
+    /// being the last key, so that a restore can cut the state it composes out of the base
+    /// snapshot and the delta into chunks that can be verified.
+    pub range_proofs: FileHandle,
+    /// BCS serialized
+    /// `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, same as
+    /// `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
+
+/// Either kind of state snapshot manifest, told apart by their fields.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StateSnapshotManifest {
+    Delta(StateSnapshotDeltaBackup),
+    Full(StateSnapshotBackup),
+}
+
+impl StateSnapshotManifest {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::Delta(delta) => delta.version,
+            Self::Full(full) => full.version,
+        }
+    }
+
+    pub fn proof(&self) -> &FileHandle {
+        match self {
+            Self::Delta(delta) => &delta.proof,
+            Self::Full(full) => &full.proof,
+        }
+    }
+
+    /// All the files the manifest refers to, excluding the base manifest of a delta.
+    pub fn files(&self) -> Vec<FileHandle> {
+        match self {
+            Self::Delta(delta) => delta
+                .chunks
+                .iter()
+                .map(|chunk| chunk.blobs.clone())
+                .chain([delta.range_proofs.clone(), delta.proof.clone()])
+                .collect(),
+            Self::Full(full) => full
+                .chunks
+                .iter()
+                .flat_map(|chunk| [chunk.blobs.clone(), chunk.proof.clone()])
+                .chain([full.proof.clone()])
+                .collect(),
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod delta_backup;
 pub mod manifest;
 pub mod restore;
 
@@ -4,7 +4,10 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+        epoch_ending::restore::EpochHistory,
+        state_snapshot::manifest::{
+            StateSnapshotBackup, StateSnapshotDeltaBackup, StateSnapshotManifest,
+        },
     },
     metrics::{
         restore::{
@@ -22,16 +25,18 @@ use crate::{
         GlobalRestoreOptions, RestoreRunMode,
     },
 };
-use anyhow::{anyhow, ensure, Result};
-use aptos_db::state_restore::StateSnapshotRestoreMode;
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_db::state_restore::{StateSnapshotRestore, StateSnapshotRestoreMode};
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
+use aptos_push_metrics::IntGauge;
 use aptos_storage_interface::StateSnapshotReceiver;
 use aptos_types::{
     access_path::Path,
     ledger_info::LedgerInfoWithSignatures,
     on_chain_config::Features,
-    proof::TransactionInfoWithProof,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
     state_store::{
         state_key::{inner::StateKeyInner, StateKey},
         state_value::StateValue,
@@ -43,7 +48,8 @@ use clap::Parser;
 use futures::{stream, TryStreamExt};
 use move_binary_format::CompiledModule;
 use move_bytecode_verifier::verify_module_with_config;
-use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use std::{collections::BTreeMap, iter::Peekable, sync::Arc, vec};
 use tokio::time::Instant;
 
 #[derive(Parser)]
@@ -120,23 +126,12 @@ impl StateSnapshotRestoreController {
             return Ok(());
         }
 
-        let manifest: StateSnapshotBackup =
-            self.storage.load_json_file(&self.manifest_handle).await?;
-        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
-            self.storage.load_bcs_file(&manifest.proof).await?;
-        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
-        let state_root_hash = txn_info_with_proof
-            .transaction_info()
-            .ensure_state_checkpoint_hash()?;
-        ensure!(
-            state_root_hash == manifest.root_hash,
-            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
-            manifest.root_hash,
-            state_root_hash,
-        );
-        if let Some(epoch_history) = self.epoch_history.as_ref() {
-            epoch_history.verify_ledger_info(&li)?;
-        }
+        let manifest = match self.storage.load_json_file(&self.manifest_handle).await? {
+            StateSnapshotManifest::Full(manifest) => manifest,
+            StateSnapshotManifest::Delta(manifest) => return self.run_delta(manifest).await,
+        };
+        self.verify_root_hash(manifest.version, manifest.root_hash, &manifest.proof)
+            .await?;
 
         let receiver = Arc::new(Mutex::new(Some(self.run_mode.get_state_restore_receiver(
             self.version,
@@ -144,19 +139,7 @@ impl StateSnapshotRestoreController {
             self.restore_mode,
         )?)));
 
-        let (ver_gauge, tgt_leaf_idx, leaf_idx) = if self.run_mode.is_verify() {
-            (
-                &VERIFY_STATE_SNAPSHOT_VERSION,
-                &VERIFY_STATE_SNAPSHOT_TARGET_LEAF_INDEX,
-                &VERIFY_STATE_SNAPSHOT_LEAF_INDEX,
-            )
-        } else {
-            (
-                &STATE_SNAPSHOT_VERSION,
-                &STATE_SNAPSHOT_TARGET_LEAF_INDEX,
-                &STATE_SNAPSHOT_LEAF_INDEX,
-            )
-        };
+        let (ver_gauge, tgt_leaf_idx, leaf_idx) = self.gauges();
 
         ver_gauge.set(self.version as i64);
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
@@ -232,6 +215,171 @@ impl StateSnapshotRestoreController {
         Ok(())
     }
 
+    /// Restores the state composed of the full snapshot at the bottom of the chain of deltas and
+    /// the changes in all the deltas, verified by the range proofs in the last delta.
+    async fn run_delta(self, manifest: StateSnapshotDeltaBackup) -> Result<()> {
+        self.verify_root_hash(manifest.version, manifest.root_hash, &manifest.proof)
+            .await?;
+        let range_proofs: Vec<(usize, HashValue, SparseMerkleRangeProof)> =
+            Self::read_state_value(&self.storage, manifest.range_proofs.clone()).await?;
+        let receiver = Arc::new(Mutex::new(Some(self.run_mode.get_state_restore_receiver(
+            self.version,
+            manifest.root_hash,
+            self.restore_mode,
+        )?)));
+        let (base, deltas) = load_delta_chain(&self.storage, manifest).await?;
+        info!(
+            base_version = base.version,
+            num_deltas = deltas.len(),
+            "Composing state snapshot out of deltas."
+        );
+
+        let (ver_gauge, tgt_leaf_idx, leaf_idx) = self.gauges();
+        ver_gauge.set(self.version as i64);
+        tgt_leaf_idx.set(range_proofs.last().map_or(0, |p| p.0 as i64));
+
+        let resume_point_opt = receiver.lock().as_mut().unwrap().previous_key_hash()?;
+        if let Some(resume_point) = resume_point_opt {
+            info!(
+                resume_point = resume_point,
+                "Resumed state snapshot restore."
+            );
+        }
+        let changes = self.read_state_changes(&deltas).await?;
+        let mut composer = StateComposer::new(changes, range_proofs, resume_point_opt);
+
+        let storage = self.storage.clone();
+        let futs_iter = base
+            .chunks
+            .into_iter()
+            .filter(|chunk| resume_point_opt.map_or(true, |p| chunk.last_key > p))
+            .map(|chunk| {
+                let storage = storage.clone();
+                async move {
+                    tokio::spawn(async move { Self::read_state_value(&storage, chunk.blobs).await })
+                        .await?
+                }
+            });
+        let con = self.concurrent_downloads;
+        let mut futs_stream = stream::iter(futs_iter).buffered_x(con * 2, con);
+        while let Some(blobs) = futs_stream.try_next().await? {
+            let chunks = composer.add_base_chunk(blobs)?;
+            self.add_composed_chunks(&receiver, chunks, leaf_idx)
+                .await?;
+        }
+        let chunks = composer.finish()?;
+        self.add_composed_chunks(&receiver, chunks, leaf_idx)
+            .await?;
+
+        tokio::task::spawn_blocking(move || receiver.lock().take().unwrap().finish()).await??;
+        self.run_mode.finish();
+        Ok(())
+    }
+
+    /// Reads the changes in the deltas, the newer ones overriding the older ones.
+    async fn read_state_changes(
+        &self,
+        deltas: &[StateSnapshotDeltaBackup],
+    ) -> Result<StateChanges> {
+        let storage = self.storage.clone();
+        let futs_iter = deltas
+            .iter()
+            .flat_map(|delta| delta.chunks.iter())
+            .map(|chunk| {
+                let storage = storage.clone();
+                let blobs = chunk.blobs.clone();
+                async move {
+                    tokio::spawn(async move {
+                        Self::read_state_value::<(StateKey, Option<StateValue>)>(&storage, blobs)
+                            .await
+                    })
+                    .await?
+                }
+            });
+        let con = self.concurrent_downloads;
+        let mut futs_stream = stream::iter(futs_iter).buffered_x(con * 2, con);
+        let mut changes = StateChanges::new();
+        while let Some(records) = futs_stream.try_next().await? {
+            for (key, value) in records {
+                changes.insert(key.hash(), (key, value));
+            }
+        }
+        Ok(changes)
+    }
+
+    async fn add_composed_chunks(
+        &self,
+        receiver: &Arc<Mutex<Option<StateSnapshotRestore<StateKey, StateValue>>>>,
+        chunks: Vec<ComposedChunk>,
+        leaf_idx: &IntGauge,
+    ) -> Result<()> {
+        for chunk in chunks {
+            let _timer = OTHER_TIMERS_SECONDS
+                .with_label_values(&["add_state_chunk"])
+                .start_timer();
+            let ComposedChunk {
+                mut blobs,
+                proof,
+                last_idx,
+            } = chunk;
+            let receiver = receiver.clone();
+            if self.validate_modules {
+                blobs = tokio::task::spawn_blocking(move || {
+                    Self::validate_modules(&blobs);
+                    blobs
+                })
+                .await?;
+            }
+            tokio::task::spawn_blocking(move || {
+                receiver.lock().as_mut().unwrap().add_chunk(blobs, proof)
+            })
+            .await??;
+            leaf_idx.set(last_idx as i64);
+            info!(last_idx = last_idx, "State chunk added.");
+        }
+        Ok(())
+    }
+
+    async fn verify_root_hash(
+        &self,
+        version: Version,
+        root_hash: HashValue,
+        proof: &FileHandle,
+    ) -> Result<()> {
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            root_hash,
+            state_root_hash,
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+        Ok(())
+    }
+
+    fn gauges(&self) -> (&'static IntGauge, &'static IntGauge, &'static IntGauge) {
+        if self.run_mode.is_verify() {
+            (
+                &VERIFY_STATE_SNAPSHOT_VERSION,
+                &VERIFY_STATE_SNAPSHOT_TARGET_LEAF_INDEX,
+                &VERIFY_STATE_SNAPSHOT_LEAF_INDEX,
+            )
+        } else {
+            (
+                &STATE_SNAPSHOT_VERSION,
+                &STATE_SNAPSHOT_TARGET_LEAF_INDEX,
+                &STATE_SNAPSHOT_LEAF_INDEX,
+            )
+        }
+    }
+
     fn validate_modules(blob: &[(StateKey, StateValue)]) {
         // TODO: Instead of using default features, fetch them from the the state.
         let features = Features::default();
@@ -252,10 +400,10 @@ impl StateSnapshotRestoreController {
         }
     }
 
-    async fn read_state_value(
+    async fn read_state_value<T: DeserializeOwned>(
         storage: &Arc<dyn BackupStorage>,
         file_handle: FileHandle,
-    ) -> Result<Vec<(StateKey, StateValue)>> {
+    ) -> Result<Vec<T>> {
         let mut file = storage.open_for_read(&file_handle).await?;
 
         let mut chunk = vec![];
@@ -267,3 +415,139 @@ impl StateSnapshotRestoreController {
         Ok(chunk)
     }
 }
+
+/// Follows the base manifests from a delta down to a full snapshot, returning the full snapshot
+/// and the deltas from the oldest to the newest.
+pub async fn load_delta_chain(
+    storage: &Arc<dyn BackupStorage>,
+    delta: StateSnapshotDeltaBackup,
+) -> Result<(StateSnapshotBackup, Vec<StateSnapshotDeltaBackup>)> {
+    let mut deltas = vec![delta];
+    loop {
+        let delta = deltas.last().expect("Not empty.");
+        let base: StateSnapshotManifest = storage.load_json_file(&delta.base_manifest).await?;
+        ensure!(
+            base.version() == delta.base_version,
+            "State snapshot delta at version {} expects base at version {}, got {}.",
+            delta.version,
+            delta.base_version,
+            base.version(),
+        );
+        match base {
+            StateSnapshotManifest::Full(full) => {
+                deltas.reverse();
+                return Ok((full, deltas));
+            },
+            StateSnapshotManifest::Delta(delta) => deltas.push(delta),
+        }
+    }
+}
+
+/// Changed state keys by their hashes, with their latest values, `None` meaning deleted.
+type StateChanges = BTreeMap<HashValue, (StateKey, Option<StateValue>)>;
+
+struct ComposedChunk {
+    blobs: Vec<(StateKey, StateValue)>,
+    proof: SparseMerkleRangeProof,
+    last_idx: usize,
+}
+
+/// Applies the changes in the deltas to the chunks of the base snapshot as they come in key order,
+/// and cuts the resulting state into chunks ending at the keys of the range proofs.
+struct StateComposer {
+    changes: StateChanges,
+    range_proofs: Peekable<vec::IntoIter<(usize, HashValue, SparseMerkleRangeProof)>>,
+    resume_point: Option<HashValue>,
+    chunk: Vec<(StateKey, StateValue)>,
+    full_chunks: Vec<ComposedChunk>,
+}
+
+impl StateComposer {
+    fn new(
+        mut changes: StateChanges,
+        mut range_proofs: Vec<(usize, HashValue, SparseMerkleRangeProof)>,
+        resume_point: Option<HashValue>,
+    ) -> Self {
+        if let Some(resume_point) = resume_point {
+            changes = changes.split_off(&resume_point);
+            changes.remove(&resume_point);
+            range_proofs.retain(|(_, key, _)| *key > resume_point);
+        }
+        Self {
+            changes,
+            range_proofs: range_proofs.into_iter().peekable(),
+            resume_point,
+            chunk: Vec::new(),
+            full_chunks: Vec::new(),
+        }
+    }
+
+    fn add_base_chunk(&mut self, blobs: Vec<(StateKey, StateValue)>) -> Result<Vec<ComposedChunk>> {
+        for (key, value) in blobs {
+            let key_hash = key.hash();
+            while self
+                .changes
+                .first_key_value()
+                .map_or(false, |(changed, _)| *changed < key_hash)
+            {
+                let (changed, (key, value)) = self.changes.pop_first().expect("Not empty.");
+                if let Some(value) = value {
+                    self.push(changed, key, value)?;
+                }
+            }
+            match self.changes.remove(&key_hash) {
+                Some((key, Some(value))) => self.push(key_hash, key, value)?,
+                Some((_, None)) => (),
+                None => self.push(key_hash, key, value)?,
+            }
+        }
+        Ok(std::mem::take(&mut self.full_chunks))
+    }
+
+    fn finish(mut self) -> Result<Vec<ComposedChunk>> {
+        while let Some((changed, (key, value))) = self.changes.pop_first() {
+            if let Some(value) = value {
+                self.push(changed, key, value)?;
+            }
+        }
+        ensure!(
+            self.range_proofs.peek().is_none(),
+            "State composed of the snapshot and the deltas ended before the key of range proof at \
+            index {}.",
+            self.range_proofs.peek().map_or(0, |p| p.0),
+        );
+        Ok(self.full_chunks)
+    }
+
+    fn push(&mut self, key_hash: HashValue, key: StateKey, value: StateValue) -> Result<()> {
+        if self.resume_point.map_or(false, |p| key_hash <= p) {
+            return Ok(());
+        }
+        let (proof_idx, proof_key) = match self.range_proofs.peek() {
+            Some((idx, key, _)) => (*idx, *key),
+            None => bail!(
+                "State composed of the snapshot and the deltas has key {:x} after the key of the \
+                last range proof.",
+                key_hash,
+            ),
+        };
+        ensure!(
+            key_hash <= proof_key,
+            "State composed of the snapshot and the deltas is missing key {:x} of range proof at \
+            index {}.",
+            proof_key,
+            proof_idx,
+        );
+
+        self.chunk.push((key, value));
+        if key_hash == proof_key {
+            let (last_idx, _, proof) = self.range_proofs.next().expect("Peeked.");
+            self.full_chunks.push(ComposedChunk {
+                blobs: std::mem::take(&mut self.chunk),
+                proof,
+                last_idx,
+            });
+        }
+        Ok(())
+    }
+}
@@ -5,11 +5,16 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        delta_backup::{StateSnapshotDeltaBackupController, StateSnapshotDeltaBackupOpt},
+        manifest::StateSnapshotBackup,
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
+    coordinators::verify::VerifyCoordinator,
+    metadata::cache::MetadataCacheOpt,
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
         RocksdbOpt, TrustedWaypointOpt,
@@ -18,6 +23,7 @@ use crate::{
 use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
 use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
 use std::{convert::TryInto, sync::Arc};
 use tokio::time::Duration;
 
@@ -111,3 +117,201 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn end_to_end_delta() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let latest_epoch_ending_li = src_db
+        .get_epoch_ending_ledger_infos(latest_epoch, latest_epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap();
+    let version = latest_epoch_ending_li.ledger_info().version();
+    let state_root_hash = src_db
+        .get_transactions(version, 1, version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+        concurrent_data_requests: 2,
+    };
+
+    // A full snapshot at the end of epoch 0, and a chain of deltas at the end of every epoch
+    // after it.
+    let mut manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch: 0 },
+                global_opt.clone(),
+                client.clone(),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    for epoch in 1..=latest_epoch {
+        manifest_handle = rt
+            .block_on(
+                StateSnapshotDeltaBackupController::new(
+                    StateSnapshotDeltaBackupOpt {
+                        epoch,
+                        base_manifest: manifest_handle,
+                    },
+                    global_opt.clone(),
+                    client.clone(),
+                    Arc::clone(&store),
+                )
+                .with_range_proof_interval(2)
+                .run(),
+            )
+            .unwrap();
+    }
+
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle,
+                version,
+                validate_modules: false,
+                restore_mode: StateSnapshotRestoreMode::Default,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: None, // max
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                enable_state_indices: false,
+            }
+            .try_into()
+            .unwrap(),
+            store,
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, state_root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn verify_delta_checks_base_files() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let latest_epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+        concurrent_data_requests: 2,
+    };
+
+    let full_manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch: 0 },
+                global_opt.clone(),
+                client.clone(),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let mut manifest_handle = full_manifest_handle.clone();
+    for epoch in 1..=latest_epoch {
+        manifest_handle = rt
+            .block_on(
+                StateSnapshotDeltaBackupController::new(
+                    StateSnapshotDeltaBackupOpt {
+                        epoch,
+                        base_manifest: manifest_handle,
+                    },
+                    global_opt.clone(),
+                    client.clone(),
+                    Arc::clone(&store),
+                )
+                .with_range_proof_interval(2)
+                .run(),
+            )
+            .unwrap();
+    }
+
+    // Only the file hashes are checked, of the latest delta and all its bases
+    let verify = || {
+        let metadata_cache_dir = TempPath::new();
+        rt.block_on(
+            VerifyCoordinator::new(
+                Arc::clone(&store),
+                MetadataCacheOpt::new(Some(metadata_cache_dir.path())),
+                TrustedWaypointOpt::default(),
+                1, /* concurrent_downloads */
+                0, /* start_version */
+                0, /* end_version */
+                Version::MAX,
+                true,  /* skip_epoch_endings */
+                false, /* validate_modules */
+                None,  /* output_transaction_analysis */
+                true,  /* file_hashes_only */
+            )
+            .unwrap()
+            .run(),
+        )
+    };
+    verify().unwrap();
+
+    // Tampering with a file of the full snapshot at the bottom of the chain fails verification
+    let full: StateSnapshotBackup = rt
+        .block_on(store.load_json_file(&full_manifest_handle))
+        .unwrap();
+    std::fs::write(backup_dir.path().join(&full.chunks[0].blobs), b"tampered").unwrap();
+    verify().unwrap_err();
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
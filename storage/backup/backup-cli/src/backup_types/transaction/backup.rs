@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::transaction::manifest::{TransactionBackup, TransactionChunk},
+    backup_types::{
+        file_hashes::BackupFileWriter,
+        transaction::manifest::{TransactionBackup, TransactionChunk},
+    },
     metadata::Metadata,
-    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    storage::{BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
         should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
@@ -17,7 +20,7 @@ use aptos_types::transaction::Version;
 use clap::Parser;
 use once_cell::sync::Lazy;
 use std::{convert::TryInto, str::FromStr, sync::Arc};
-use tokio::io::AsyncWriteExt;
+use tokio::io::AsyncReadExt;
 
 #[derive(Parser)]
 pub struct TransactionBackupOpt {
@@ -72,6 +75,7 @@ impl TransactionBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let files = BackupFileWriter::new(self.storage.clone(), backup_handle);
 
         let mut chunks = Vec::new();
         let mut chunk_bytes = Vec::new();
@@ -86,12 +90,7 @@ impl TransactionBackupController {
         while let Some(record_bytes) = transactions_file.read_record_bytes().await? {
             if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
                 let chunk = self
-                    .write_chunk(
-                        &backup_handle,
-                        &chunk_bytes,
-                        chunk_first_ver,
-                        current_ver - 1,
-                    )
+                    .write_chunk(&files, &chunk_bytes, chunk_first_ver, current_ver - 1)
                     .await?;
                 chunks.push(chunk);
                 chunk_bytes = vec![];
@@ -112,16 +111,11 @@ impl TransactionBackupController {
             current_ver,
         );
         let chunk = self
-            .write_chunk(
-                &backup_handle,
-                &chunk_bytes,
-                chunk_first_ver,
-                current_ver - 1,
-            )
+            .write_chunk(&files, &chunk_bytes, chunk_first_ver, current_ver - 1)
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, self.start_version, current_ver - 1, chunks)
+        self.write_manifest(files, self.start_version, current_ver - 1, chunks)
             .await
     }
 
@@ -147,34 +141,27 @@ impl TransactionBackupController {
 
     async fn write_chunk(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: &BackupFileWriter,
         chunk_bytes: &[u8],
         first_version: u64,
         last_version: u64,
     ) -> Result<TransactionChunk> {
-        let (proof_handle, mut proof_file) = self
-            .storage
-            .create_for_write(
-                backup_handle,
+        let mut proof_bytes = Vec::new();
+        self.client
+            .get_transaction_range_proof(first_version, last_version)
+            .await?
+            .read_to_end(&mut proof_bytes)
+            .await?;
+        let proof_handle = files
+            .write(
                 &Self::chunk_proof_name(first_version, last_version),
+                &proof_bytes,
             )
             .await?;
-        tokio::io::copy(
-            &mut self
-                .client
-                .get_transaction_range_proof(first_version, last_version)
-                .await?,
-            &mut proof_file,
-        )
-        .await?;
-        proof_file.shutdown().await?;
-
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_version))
+
+        let chunk_handle = files
+            .write(&Self::chunk_name(first_version), chunk_bytes)
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
 
         Ok(TransactionChunk {
             first_version,
@@ -186,7 +173,7 @@ impl TransactionBackupController {
 
     async fn write_manifest(
         &self,
-        backup_handle: &BackupHandleRef,
+        files: BackupFileWriter,
         first_version: Version,
         last_version: Version,
         chunks: Vec<TransactionChunk>,
@@ -196,17 +183,17 @@ impl TransactionBackupController {
             last_version,
             chunks,
         };
-        let (manifest_handle, mut manifest_file) = self
-            .storage
-            .create_for_write(backup_handle, Self::manifest_name())
-            .await?;
-        manifest_file
-            .write_all(&serde_json::to_vec(&manifest)?)
+        let manifest_handle = files
+            .write(Self::manifest_name(), &serde_json::to_vec(&manifest)?)
             .await?;
-        manifest_file.shutdown().await?;
+        let file_hashes = files.finish().await?;
 
-        let metadata =
-            Metadata::new_transaction_backup(first_version, last_version, manifest_handle.clone());
+        let metadata = Metadata::new_transaction_backup(
+            first_version,
+            last_version,
+            manifest_handle.clone(),
+            Some(file_hashes),
+        );
         self.storage
             .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
             .await?;
@@ -5,7 +5,10 @@
 use crate::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
-        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            delta_backup::{StateSnapshotDeltaBackupController, StateSnapshotDeltaBackupOpt},
+        },
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
@@ -21,7 +24,7 @@ use crate::{
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_db::backup::backup_handler::DbState;
-use aptos_infallible::duration_since_epoch;
+use aptos_infallible::{duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use clap::Parser;
@@ -56,6 +59,16 @@ pub struct BackupCoordinatorOpt {
         is already at 19, then snapshot at 15 will be taken instead of at 10 (not at 18)."
     )]
     pub state_snapshot_interval_epochs: usize,
+    #[clap(
+        long,
+        help = "[Defaults to not taking state snapshot deltas] Frequency (in number of epochs) to \
+        take state snapshot deltas, which hold only the state changed since the previous snapshot, \
+        full or delta, in between the full state snapshots taken according to \
+        --state-snapshot-interval-epochs. A restore of a delta reads the full snapshot and all \
+        the deltas in between, so deltas can be much cheaper to take frequently, at the cost of \
+        restore time."
+    )]
+    pub state_snapshot_delta_interval_epochs: Option<usize>,
     // Defaulting to 1M, which converts to a 20 minutes delay of a transaction showing up in a backup,
     // from a 1K TPS chain, and a few minutes replay time.
     #[clap(
@@ -79,6 +92,13 @@ impl BackupCoordinatorOpt {
             self.state_snapshot_interval_epochs > 0 && self.transaction_batch_size > 0,
             "Backup interval and batch size must be greater than 0."
         );
+        if let Some(delta_interval) = self.state_snapshot_delta_interval_epochs {
+            ensure!(
+                delta_interval > 0 && delta_interval < self.state_snapshot_interval_epochs,
+                "State snapshot delta interval must be greater than 0 and less than the state \
+                snapshot interval."
+            );
+        }
         Ok(())
     }
 }
@@ -89,8 +109,11 @@ pub struct BackupCoordinator {
     global_opt: GlobalBackupOpt,
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval_epochs: usize,
+    state_snapshot_delta_interval_epochs: Option<usize>,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
+    /// Manifest of the latest state snapshot, full or delta, which the next delta is based on.
+    last_state_snapshot_manifest: Mutex<Option<FileHandle>>,
 }
 
 impl BackupCoordinator {
@@ -107,20 +130,25 @@ impl BackupCoordinator {
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
+            state_snapshot_delta_interval_epochs: opt.state_snapshot_delta_interval_epochs,
             transaction_batch_size: opt.transaction_batch_size,
             concurrent_downloads: opt.concurrent_downloads.get(),
+            last_state_snapshot_manifest: Mutex::new(None),
         }
     }
 
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state()?;
+        .await?;
+        let backup_state = metadata_view.get_storage_state()?;
+        *self.last_state_snapshot_manifest.lock() = metadata_view
+            .select_state_snapshot(Version::MAX)?
+            .map(|snapshot| snapshot.manifest);
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -246,7 +274,8 @@ impl BackupCoordinator {
         let epoch = get_next_snapshot(
             last_snapshot_epoch_in_backup,
             db_state,
-            self.state_snapshot_interval_epochs,
+            self.state_snapshot_delta_interval_epochs
+                .unwrap_or(self.state_snapshot_interval_epochs),
         );
 
         // <= because db_state.epoch is still open
@@ -255,14 +284,40 @@ impl BackupCoordinator {
             return Ok(last_snapshot_epoch_in_backup);
         }
 
-        StateSnapshotBackupController::new(
-            StateSnapshotBackupOpt { epoch },
-            self.global_opt.clone(),
-            Arc::clone(&self.client),
-            Arc::clone(&self.storage),
-        )
-        .run()
-        .await?;
+        let base_manifest = self.last_state_snapshot_manifest.lock().clone();
+        let manifest = match base_manifest {
+            Some(base_manifest)
+                if self.state_snapshot_delta_interval_epochs.is_some()
+                    && is_delta_snapshot(
+                        last_snapshot_epoch_in_backup,
+                        epoch,
+                        self.state_snapshot_interval_epochs,
+                    ) =>
+            {
+                StateSnapshotDeltaBackupController::new(
+                    StateSnapshotDeltaBackupOpt {
+                        epoch,
+                        base_manifest,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.client),
+                    Arc::clone(&self.storage),
+                )
+                .run()
+                .await?
+            },
+            _ => {
+                StateSnapshotBackupController::new(
+                    StateSnapshotBackupOpt { epoch },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.client),
+                    Arc::clone(&self.storage),
+                )
+                .run()
+                .await?
+            },
+        };
+        *self.last_state_snapshot_manifest.lock() = Some(manifest);
 
         Ok(Some(epoch))
     }
@@ -448,6 +503,17 @@ impl BackupCompactor {
                 .await?;
             new_files.insert(file_handle);
         }
+        for range in
+            metaview.compact_state_delta_backups(self.state_snapshot_file_compact_factor)?
+        {
+            let (state_range, file_name) =
+                Metadata::compact_state_snapshot_delta_backup_range(range.to_vec())?;
+            let file_handle = self
+                .storage
+                .save_metadata_lines(&file_name, state_range.as_slice())
+                .await?;
+            new_files.insert(file_handle);
+        }
 
         // Move expired files to the metadata backup folder
         let (to_move, compaction_meta) =
@@ -508,9 +574,18 @@ fn get_next_snapshot(last_in_backup: Option<u64>, db_state: DbState, interval: u
     std::cmp::max(next_for_storage, last_for_db)
 }
 
+fn is_delta_snapshot(last_in_backup: Option<u64>, next: u64, full_interval: usize) -> bool {
+    // A full snapshot is taken when the next snapshot falls into a new full snapshot interval,
+    // otherwise a delta on top of the last one, full or delta. With full interval 100, after
+    // snapshots at 100 and 150, the one at 190 is a delta and the one at 200 or 210 is full.
+    last_in_backup.map_or(false, |last| {
+        last / full_interval as u64 == next / full_interval as u64
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::coordinators::backup::{get_batch_range, get_next_snapshot};
+    use crate::coordinators::backup::{get_batch_range, get_next_snapshot, is_delta_snapshot};
     use aptos_db::backup::backup_handler::DbState;
 
     #[test]
@@ -540,4 +615,14 @@ mod tests {
         assert_eq!(get_next_snapshot(Some(0), _state(250), 100), 200);
         assert_eq!(get_next_snapshot(Some(200), _state(250), 100), 300);
     }
+
+    #[test]
+    fn test_is_delta_snapshot() {
+        assert!(!is_delta_snapshot(None, 0, 100));
+        assert!(is_delta_snapshot(Some(0), 10, 100));
+        assert!(is_delta_snapshot(Some(150), 190, 100));
+        assert!(!is_delta_snapshot(Some(190), 200, 100));
+        assert!(!is_delta_snapshot(Some(190), 210, 100));
+        assert!(!is_delta_snapshot(Some(90), 250, 100));
+    }
 }
@@ -0,0 +1,569 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::StateSnapshotManifest, transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{
+        cache::{LoadMetadataLines, MetadataCacheOpt},
+        FileHashesMeta, StateSnapshotBackupMeta, TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{error_notes::ErrorNotes, storage_ext::BackupStorageExt},
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, proof::TransactionInfoWithProof, transaction::Version,
+};
+use clap::Parser;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use itertools::Itertools;
+use std::{collections::HashSet, fmt, ops::RangeInclusive, sync::Arc};
+
+const USECS_PER_DAY: u64 = 86_400_000_000;
+
+#[derive(Clone, Parser)]
+pub struct RetentionPolicyOpt {
+    #[clap(
+        long,
+        help = "Keep the N latest state snapshots, full or delta. The latest one is always kept, \
+        since it's what a restore picks by default. If none of the state snapshot policies is \
+        specified, all state snapshots are kept."
+    )]
+    pub keep_latest_state_snapshots: Option<usize>,
+    #[clap(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Keep the first state snapshot of every N epochs, like 1 for one per epoch."
+    )]
+    pub keep_state_snapshot_every_epochs: Option<u64>,
+    #[clap(
+        long,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Keep the first state snapshot of every N days, like 7 for one per week, by the \
+        timestamps of the ledger infos proving the snapshots."
+    )]
+    pub keep_state_snapshot_every_days: Option<u64>,
+    #[clap(
+        long,
+        help = "Keep the transaction backups with transactions at or after this version. The \
+        transactions needed to replay from the kept state snapshots are always kept. If not \
+        specified, all transaction backups are kept. Epoch ending backups are always kept, since \
+        they are needed to verify everything else."
+    )]
+    pub keep_transactions_since_version: Option<Version>,
+}
+
+impl RetentionPolicyOpt {
+    /// Returns whether each of the state snapshots, sorted by version, is retained.
+    fn retain_state_snapshots(
+        &self,
+        snapshots: &[StateSnapshot],
+        timestamps_usecs: Option<&[u64]>,
+    ) -> Vec<bool> {
+        if self.keep_latest_state_snapshots.is_none()
+            && self.keep_state_snapshot_every_epochs.is_none()
+            && self.keep_state_snapshot_every_days.is_none()
+        {
+            return vec![true; snapshots.len()];
+        }
+
+        let mut retained = vec![false; snapshots.len()];
+        let num_latest = self.keep_latest_state_snapshots.unwrap_or(0).max(1);
+        retained
+            .iter_mut()
+            .rev()
+            .take(num_latest)
+            .for_each(|r| *r = true);
+        if let Some(epochs) = self.keep_state_snapshot_every_epochs {
+            retain_first_in_buckets(
+                &mut retained,
+                snapshots.iter().map(|s| s.meta.epoch / epochs),
+            );
+        }
+        if let (Some(days), Some(timestamps_usecs)) =
+            (self.keep_state_snapshot_every_days, timestamps_usecs)
+        {
+            let bucket_usecs = days * USECS_PER_DAY;
+            retain_first_in_buckets(
+                &mut retained,
+                timestamps_usecs.iter().map(|t| t / bucket_usecs),
+            );
+        }
+
+        // A delta can't be restored without the snapshots it's based on. Bases are older, so
+        // going from the newest, the bases of the bases are reached as well.
+        for idx in (0..snapshots.len()).rev() {
+            if let (true, Some(base_version)) = (retained[idx], snapshots[idx].base_version) {
+                for (base_idx, base) in snapshots.iter().enumerate() {
+                    if base.meta.version == base_version {
+                        retained[base_idx] = true;
+                    }
+                }
+            }
+        }
+        retained
+    }
+
+    /// Returns whether each of the transaction backups, sorted by version, is retained.
+    fn retain_transactions(
+        &self,
+        backups: &[TransactionBackupMeta],
+        oldest_state_snapshot_version: Option<Version>,
+    ) -> Vec<bool> {
+        let since = match self.keep_transactions_since_version {
+            Some(version) => version,
+            None => return vec![true; backups.len()],
+        };
+        // Restoring a state snapshot needs the transaction at its version as well.
+        let since = oldest_state_snapshot_version.map_or(since, |v| std::cmp::min(v, since));
+        backups.iter().map(|b| b.last_version >= since).collect()
+    }
+}
+
+/// Marks the first one in each bucket, expecting the buckets in order.
+fn retain_first_in_buckets(retained: &mut [bool], buckets: impl Iterator<Item = u64>) {
+    let mut last_bucket = None;
+    for (r, bucket) in retained.iter_mut().zip(buckets) {
+        if last_bucket != Some(bucket) {
+            *r = true;
+            last_bucket = Some(bucket);
+        }
+    }
+}
+
+/// A state snapshot, full or delta, with the version of the snapshot a delta is based on.
+struct StateSnapshot {
+    meta: StateSnapshotBackupMeta,
+    base_version: Option<Version>,
+}
+
+pub struct GarbageCollectionReport {
+    pub dry_run: bool,
+    pub state_snapshot_versions: Vec<Version>,
+    pub transaction_ranges: Vec<RangeInclusive<Version>>,
+    pub num_files: usize,
+    pub bytes: u64,
+}
+
+impl fmt::Display for GarbageCollectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {} files, {} bytes in total, of",
+            if self.dry_run {
+                "Would remove"
+            } else {
+                "Removed"
+            },
+            self.num_files,
+            self.bytes,
+        )?;
+        writeln!(
+            f,
+            "  {} state snapshots at versions {:?}",
+            self.state_snapshot_versions.len(),
+            self.state_snapshot_versions,
+        )?;
+        write!(
+            f,
+            "  {} transaction backups of versions {:?}",
+            self.transaction_ranges.len(),
+            self.transaction_ranges,
+        )
+    }
+}
+
+/// Removes the state snapshot and transaction backups not retained by the policies, along with
+/// the metadata lines referring to them.
+pub struct BackupGarbageCollector {
+    policy: RetentionPolicyOpt,
+    dry_run: bool,
+    metadata_cache_opt: MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+}
+
+impl BackupGarbageCollector {
+    pub fn new(
+        policy: RetentionPolicyOpt,
+        dry_run: bool,
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrent_downloads: usize,
+    ) -> Self {
+        Self {
+            policy,
+            dry_run,
+            metadata_cache_opt,
+            storage,
+            concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<GarbageCollectionReport> {
+        info!(dry_run = self.dry_run, "Backup garbage collection started.");
+        // Checked before anything changes, as the metadata is removed before the files.
+        ensure!(
+            self.dry_run || self.storage.supports_garbage_collection(),
+            "The backup storage can't delete files or back metadata files up. For the command \
+            adapter, configure the delete_file and backup_metadata_file commands."
+        );
+        let metaview = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let snapshots = metaview
+            .state_snapshot_backups()
+            .iter()
+            .map(|meta| StateSnapshot {
+                meta: meta.clone(),
+                base_version: None,
+            })
+            .chain(
+                metaview
+                    .state_snapshot_delta_backups()
+                    .iter()
+                    .map(|delta| StateSnapshot {
+                        meta: delta.clone().into(),
+                        base_version: Some(delta.base_version),
+                    }),
+            )
+            .sorted_by_key(|s| s.meta.version)
+            .collect::<Vec<_>>();
+        let timestamps_usecs = match self.policy.keep_state_snapshot_every_days {
+            Some(_) => Some(self.load_timestamps_usecs(&snapshots).await?),
+            None => None,
+        };
+        let retained_snapshots = self
+            .policy
+            .retain_state_snapshots(&snapshots, timestamps_usecs.as_deref());
+        let oldest_state_snapshot_version = snapshots
+            .iter()
+            .zip(&retained_snapshots)
+            .find(|(_, retained)| **retained)
+            .map(|(s, _)| s.meta.version);
+        let removed_snapshots = snapshots
+            .into_iter()
+            .zip(retained_snapshots)
+            .filter(|(_, retained)| !retained)
+            .map(|(s, _)| s.meta)
+            .collect::<Vec<_>>();
+
+        let transactions = metaview.transaction_backups();
+        let removed_transactions = transactions
+            .iter()
+            .zip(
+                self.policy
+                    .retain_transactions(transactions, oldest_state_snapshot_version),
+            )
+            .filter(|(_, retained)| !retained)
+            .map(|(t, _)| t.clone())
+            .collect::<Vec<_>>();
+
+        // Every backup is written to a dedicated folder, so the files of the removed backups are
+        // not referred to by the retained ones, except for the base manifests of the deltas,
+        // which are retained along with the deltas.
+        let mut files = Vec::new();
+        for meta in &removed_snapshots {
+            files.extend(self.state_snapshot_files(meta).await?);
+        }
+        for meta in &removed_transactions {
+            files.extend(self.transaction_files(meta).await?);
+        }
+        let bytes = stream::iter(&files)
+            .map(|file_handle| self.storage.file_size(file_handle))
+            .buffered(self.concurrent_downloads)
+            .try_fold(0, |total, size| future::ok(total + size))
+            .await?;
+
+        if !self.dry_run {
+            let removed_manifests = removed_snapshots
+                .iter()
+                .map(|s| &s.manifest)
+                .chain(removed_transactions.iter().map(|t| &t.manifest))
+                .cloned()
+                .collect();
+            // Metadata goes first, so that no restore picks a backup being deleted.
+            self.remove_metadata(&removed_manifests).await?;
+            stream::iter(&files)
+                .map(|file_handle| async move {
+                    self.storage
+                        .delete_file(file_handle)
+                        .await
+                        .err_notes(file_handle)
+                })
+                .buffer_unordered(self.concurrent_downloads)
+                .try_collect::<()>()
+                .await?;
+        }
+
+        let report = GarbageCollectionReport {
+            dry_run: self.dry_run,
+            state_snapshot_versions: removed_snapshots.iter().map(|s| s.version).collect(),
+            transaction_ranges: removed_transactions
+                .iter()
+                .map(|t| t.first_version..=t.last_version)
+                .collect(),
+            num_files: files.len(),
+            bytes,
+        };
+        info!(
+            num_files = report.num_files,
+            bytes = report.bytes,
+            "Backup garbage collection finished."
+        );
+        Ok(report)
+    }
+
+    async fn load_timestamps_usecs(&self, snapshots: &[StateSnapshot]) -> Result<Vec<u64>> {
+        stream::iter(snapshots)
+            .map(|s| async move {
+                let manifest: StateSnapshotManifest =
+                    self.storage.load_json_file(&s.meta.manifest).await?;
+                let (_, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+                    self.storage.load_bcs_file(manifest.proof()).await?;
+                Result::<u64>::Ok(li.ledger_info().timestamp_usecs())
+            })
+            .buffered(self.concurrent_downloads)
+            .try_collect()
+            .await
+    }
+
+    async fn state_snapshot_files(
+        &self,
+        meta: &StateSnapshotBackupMeta,
+    ) -> Result<Vec<FileHandle>> {
+        let manifest: StateSnapshotManifest = self.storage.load_json_file(&meta.manifest).await?;
+        Ok(with_manifest_files(
+            manifest.files(),
+            &meta.manifest,
+            &meta.file_hashes,
+        ))
+    }
+
+    async fn transaction_files(&self, meta: &TransactionBackupMeta) -> Result<Vec<FileHandle>> {
+        let manifest: TransactionBackup = self.storage.load_json_file(&meta.manifest).await?;
+        let files = manifest
+            .chunks
+            .into_iter()
+            .flat_map(|chunk| [chunk.transactions, chunk.proof])
+            .collect();
+        Ok(with_manifest_files(
+            files,
+            &meta.manifest,
+            &meta.file_hashes,
+        ))
+    }
+
+    /// Moves the metadata files referring to the removed backups to the metadata backup folder,
+    /// after saving the other lines in them, of compacted files, to files of their own.
+    async fn remove_metadata(&self, removed_manifests: &HashSet<FileHandle>) -> Result<()> {
+        let is_removed = |manifest: Option<&FileHandle>| {
+            manifest.map_or(false, |manifest| removed_manifests.contains(manifest))
+        };
+        for file_handle in self.storage.list_metadata_files().await? {
+            let lines = self
+                .storage
+                .open_for_read(&file_handle)
+                .await
+                .err_notes(&file_handle)?
+                .load_metadata_lines()
+                .await
+                .err_notes(&file_handle)?;
+            if !lines.iter().any(|meta| is_removed(meta.manifest())) {
+                continue;
+            }
+            for meta in lines.iter().filter(|meta| !is_removed(meta.manifest())) {
+                self.storage
+                    .save_metadata_line(&meta.name(), &meta.to_text_line()?)
+                    .await?;
+            }
+            info!(file = file_handle, "Backup metadata file.");
+            self.storage.backup_metadata_file(&file_handle).await?;
+        }
+        Ok(())
+    }
+}
+
+fn with_manifest_files(
+    mut files: Vec<FileHandle>,
+    manifest: &FileHandle,
+    file_hashes: &Option<FileHashesMeta>,
+) -> Vec<FileHandle> {
+    files.push(manifest.clone());
+    files.extend(file_hashes.iter().map(|meta| meta.file.clone()));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coordinators::gc::{
+            BackupGarbageCollector, RetentionPolicyOpt, StateSnapshot, USECS_PER_DAY,
+        },
+        metadata::{cache::MetadataCacheOpt, StateSnapshotBackupMeta, TransactionBackupMeta},
+        storage::{
+            command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+            BackupStorage,
+        },
+    };
+    use aptos_temppath::TempPath;
+    use aptos_types::transaction::Version;
+    use std::sync::Arc;
+    use tokio::runtime::Runtime;
+
+    fn policy() -> RetentionPolicyOpt {
+        RetentionPolicyOpt {
+            keep_latest_state_snapshots: None,
+            keep_state_snapshot_every_epochs: None,
+            keep_state_snapshot_every_days: None,
+            keep_transactions_since_version: None,
+        }
+    }
+
+    fn snapshot(epoch: u64, base_version: Option<Version>) -> StateSnapshot {
+        StateSnapshot {
+            meta: StateSnapshotBackupMeta {
+                epoch,
+                version: epoch * 10,
+                manifest: format!("state_{}", epoch),
+                file_hashes: None,
+            },
+            base_version,
+        }
+    }
+
+    fn transactions(first_version: Version, last_version: Version) -> TransactionBackupMeta {
+        TransactionBackupMeta {
+            first_version,
+            last_version,
+            manifest: format!("transaction_{}", first_version),
+            file_hashes: None,
+        }
+    }
+
+    #[test]
+    fn test_retain_state_snapshots() {
+        let snapshots: Vec<_> = (0..6).map(|epoch| snapshot(epoch, None)).collect();
+
+        assert_eq!(
+            policy().retain_state_snapshots(&snapshots, None),
+            vec![true; 6]
+        );
+
+        let mut p = policy();
+        p.keep_latest_state_snapshots = Some(0);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![false, false, false, false, false, true]
+        );
+        p.keep_latest_state_snapshots = Some(2);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![false, false, false, false, true, true]
+        );
+
+        let mut p = policy();
+        p.keep_state_snapshot_every_epochs = Some(4);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![true, false, false, false, true, true]
+        );
+
+        let mut p = policy();
+        p.keep_state_snapshot_every_days = Some(2);
+        let timestamps: Vec<_> = (0..6).map(|day| day * USECS_PER_DAY).collect();
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, Some(&timestamps)),
+            vec![true, false, true, false, true, true]
+        );
+    }
+
+    #[test]
+    fn test_retain_state_snapshot_deltas() {
+        let snapshots = vec![
+            snapshot(0, None),
+            snapshot(1, Some(0)),
+            snapshot(2, Some(10)),
+            snapshot(3, None),
+            snapshot(4, Some(30)),
+        ];
+
+        let mut p = policy();
+        p.keep_latest_state_snapshots = Some(1);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![false, false, false, true, true]
+        );
+        p.keep_state_snapshot_every_epochs = Some(3);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![true, false, false, true, true]
+        );
+        p.keep_latest_state_snapshots = Some(3);
+        assert_eq!(
+            p.retain_state_snapshots(&snapshots, None),
+            vec![true, true, true, true, true]
+        );
+    }
+
+    #[test]
+    fn test_retain_transactions() {
+        let backups = vec![
+            transactions(0, 99),
+            transactions(100, 199),
+            transactions(200, 299),
+        ];
+
+        assert_eq!(
+            policy().retain_transactions(&backups, Some(250)),
+            vec![true, true, true]
+        );
+
+        let mut p = policy();
+        p.keep_transactions_since_version = Some(250);
+        assert_eq!(
+            p.retain_transactions(&backups, None),
+            vec![false, false, true]
+        );
+        assert_eq!(
+            p.retain_transactions(&backups, Some(150)),
+            vec![false, true, true]
+        );
+        p.keep_transactions_since_version = Some(100);
+        assert_eq!(
+            p.retain_transactions(&backups, Some(250)),
+            vec![false, true, true]
+        );
+    }
+
+    #[test]
+    fn test_refuse_storage_without_delete() {
+        // No command is configured, so nothing can be deleted
+        let storage: Arc<dyn BackupStorage> =
+            Arc::new(CommandAdapter::new(CommandAdapterConfig::default()));
+        let metadata_cache_dir = TempPath::new();
+        let err = Runtime::new()
+            .unwrap()
+            .block_on(
+                BackupGarbageCollector::new(
+                    policy(),
+                    false, /* dry_run */
+                    MetadataCacheOpt::new(Some(metadata_cache_dir.path())),
+                    storage,
+                    1, /* concurrent_downloads */
+                )
+                .run(),
+            )
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("can't delete files"));
+    }
+}
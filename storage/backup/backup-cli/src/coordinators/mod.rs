@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod gc;
 pub mod replay_verify;
 pub mod restore;
 pub mod verify;
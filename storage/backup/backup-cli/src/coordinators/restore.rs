@@ -4,25 +4,34 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::{manifest::EpochEndingBackup, restore::EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
-        transaction::restore::TransactionRestoreBatchController,
+        transaction::{manifest::TransactionBackup, restore::TransactionRestoreBatchController},
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{cache::MetadataCacheOpt, view::MetadataView, TransactionBackupMeta},
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, unix_timestamp_sec,
+        GlobalRestoreOptions,
+    },
 };
 use anyhow::{anyhow, bail, ensure, Result};
 use aptos_db::state_restore::StateSnapshotRestoreMode;
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_logger::prelude::*;
-use aptos_types::transaction::Version;
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
 use clap::Parser;
 use std::sync::Arc;
+use tokio::io::BufReader;
 
 #[derive(Parser)]
 pub struct RestoreCoordinatorOpt {
@@ -42,6 +51,21 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Option<Version>,
     #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["target_version", "target_epoch_end"],
+        help = "Restore to the state as of this unix timestamp, in seconds, i.e. up to the last \
+        version before the first block newer than it, according to the block metadata in the \
+        transaction backups. Fails if the transaction backups end before the timestamp, as \
+        the blocks up to it may not be backed up yet."
+    )]
+    pub target_timestamp: Option<u64>,
+    #[clap(
+        long,
+        conflicts_with = "target_version",
+        help = "Restore to the last version of this epoch, according to the epoch ending backups."
+    )]
+    pub target_epoch_end: Option<u64>,
 }
 
 pub struct RestoreCoordinator {
@@ -51,6 +75,8 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Option<Version>,
     skip_epoch_endings: bool,
+    target_timestamp: Option<u64>,
+    target_epoch_end: Option<u64>,
 }
 
 impl RestoreCoordinator {
@@ -66,6 +92,8 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            target_timestamp: opt.target_timestamp,
+            target_epoch_end: opt.target_epoch_end,
         }
     }
 
@@ -104,7 +132,7 @@ impl RestoreCoordinator {
     /// b. replay the txn till the target version
     ///
     /// we are support the resume from any point when the restore is interrupted.
-    async fn run_impl(self) -> Result<()> {
+    async fn run_impl(mut self) -> Result<()> {
         // if replay_all is set, we will replay all transactions from the lhs to the target version
         let mut replay_all_mode = false;
         if self.replay_all {
@@ -126,6 +154,9 @@ impl RestoreCoordinator {
         let max_txn_ver = metadata_view
             .max_transaction_version()?
             .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        if let Some(version) = self.resolve_target_version(&metadata_view).await? {
+            self.global_opt.target_version = version;
+        }
         let target_version = std::cmp::min(self.global_opt.target_version, max_txn_ver);
         info!(
             "User specified target version: {}, max transaction version: {}, Target version is set to {}",
@@ -377,6 +408,147 @@ impl RestoreCoordinator {
 }
 
 impl RestoreCoordinator {
+    /// Resolves `--target-epoch-end` or `--target-timestamp`, if specified, to the version to
+    /// restore to.
+    async fn resolve_target_version(
+        &self,
+        metadata_view: &MetadataView,
+    ) -> Result<Option<Version>> {
+        if let Some(epoch) = self.target_epoch_end {
+            let version = self.epoch_ending_version(metadata_view, epoch).await?;
+            let max_txn_ver = metadata_view.max_transaction_version()?.unwrap_or(0);
+            ensure!(
+                version <= max_txn_ver,
+                "Epoch {} ends at version {}, newer than the latest version {} in the transaction \
+                backups.",
+                epoch,
+                version,
+                max_txn_ver,
+            );
+            info!(
+                epoch = epoch,
+                version = version,
+                "Resolved target epoch end."
+            );
+            Ok(Some(version))
+        } else if let Some(timestamp) = self.target_timestamp {
+            let version = self
+                .version_at_timestamp(metadata_view, timestamp.saturating_mul(1_000_000))
+                .await?;
+            info!(
+                timestamp = timestamp,
+                version = version,
+                "Resolved target timestamp."
+            );
+            Ok(Some(version))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn epoch_ending_version(
+        &self,
+        metadata_view: &MetadataView,
+        epoch: u64,
+    ) -> Result<Version> {
+        let backup = metadata_view.expect_epoch_ending_backup(epoch)?;
+        let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+        manifest.verify()?;
+        Ok(manifest.waypoints[(epoch - manifest.first_epoch) as usize].version())
+    }
+
+    /// Finds the last version no newer than `timestamp_usecs`, i.e. the one right before the first
+    /// block newer than it. The epoch ending ledger infos narrow down the range of transactions
+    /// to scan for the block. Nothing is verified here, but the restore verifies everything up to
+    /// the version found afterwards.
+    ///
+    /// Without a newer block in the backups, the state as of the timestamp is only known if the
+    /// last block backed up is at the timestamp, otherwise the blocks up to it may not be backed
+    /// up yet, so this fails.
+    async fn version_at_timestamp(
+        &self,
+        metadata_view: &MetadataView,
+        timestamp_usecs: u64,
+    ) -> Result<Version> {
+        let mut start_version = 0;
+        let mut end_version = Version::MAX;
+        let mut last_block_timestamp_usecs = None;
+        'outer: for backup in metadata_view
+            .select_epoch_ending_backups(Version::MAX)?
+            .iter()
+            .rev()
+        {
+            let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks.iter().rev() {
+                let mut file = self.storage.open_for_read(&chunk.ledger_infos).await?;
+                let mut lis = Vec::new();
+                while let Some(record_bytes) = file.read_record_bytes().await? {
+                    lis.push(bcs::from_bytes::<LedgerInfoWithSignatures>(&record_bytes)?);
+                }
+                for li in lis.iter().rev() {
+                    if li.ledger_info().timestamp_usecs() <= timestamp_usecs {
+                        start_version = li.ledger_info().version() + 1;
+                        last_block_timestamp_usecs = Some(li.ledger_info().timestamp_usecs());
+                        break 'outer;
+                    }
+                    end_version = li.ledger_info().version();
+                }
+            }
+        }
+
+        for backup in metadata_view.select_transaction_backups(start_version, end_version)? {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                if chunk.last_version < start_version {
+                    continue;
+                }
+                let mut file =
+                    BufReader::new(self.storage.open_for_read(&chunk.transactions).await?);
+                let mut version = chunk.first_version;
+                while let Some(record_bytes) = file.read_record_bytes().await? {
+                    let (txn, _, _, _): (
+                        Transaction,
+                        TransactionInfo,
+                        Vec<ContractEvent>,
+                        WriteSet,
+                    ) = bcs::from_bytes(&record_bytes)?;
+                    match block_timestamp_usecs(&txn) {
+                        Some(ts) if version >= start_version && ts > timestamp_usecs => {
+                            // The genesis is not a block, so the version can't be 0.
+                            return Ok(version - 1);
+                        },
+                        Some(ts) if version >= start_version => {
+                            last_block_timestamp_usecs = Some(ts);
+                        },
+                        _ => {},
+                    }
+                    version += 1;
+                }
+            }
+        }
+
+        match last_block_timestamp_usecs {
+            Some(last_block_timestamp_usecs) if last_block_timestamp_usecs >= timestamp_usecs => {
+                let version = metadata_view
+                    .max_transaction_version()?
+                    .ok_or_else(|| anyhow!("No transaction backup found."))?;
+                info!(
+                    timestamp_usecs = timestamp_usecs,
+                    version = version,
+                    "The last block in the backups is at the target timestamp, will restore \
+                    everything."
+                );
+                Ok(version)
+            },
+            _ => bail!(
+                "No block newer than the target timestamp {}us in the backups, whose last block is \
+                at {:?}us. The blocks up to the target timestamp may not be backed up yet.",
+                timestamp_usecs,
+                last_block_timestamp_usecs,
+            ),
+        }
+    }
+
     fn target_version(&self) -> Version {
         self.global_opt.target_version
     }
@@ -406,3 +578,11 @@ impl RestoreCoordinator {
         }
     }
 }
+
+fn block_timestamp_usecs(txn: &Transaction) -> Option<u64> {
+    match txn {
+        Transaction::BlockMetadata(b) => Some(b.timestamp_usecs()),
+        Transaction::BlockMetadataExt(b) => Some(b.timestamp_usecs()),
+        _ => None,
+    }
+}
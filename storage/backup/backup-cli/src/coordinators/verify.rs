@@ -5,16 +5,25 @@
 use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
-        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        file_hashes::FileHashes,
+        state_snapshot::{
+            manifest::StateSnapshotManifest,
+            restore::{load_delta_chain, StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
-    metadata::cache::MetadataCacheOpt,
+    metadata::{
+        cache::MetadataCacheOpt, view::MetadataView, FileHashesMeta, StateSnapshotBackupMeta,
+    },
     metrics::verify::{
         VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
     },
-    storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        storage_ext::BackupStorageExt, unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode,
+        TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_db::state_restore::StateSnapshotRestoreMode;
@@ -34,6 +43,7 @@ pub struct VerifyCoordinator {
     skip_epoch_endings: bool,
     validate_modules: bool,
     output_transaction_analysis: Option<PathBuf>,
+    file_hashes_only: bool,
 }
 
 impl VerifyCoordinator {
@@ -48,6 +58,7 @@ impl VerifyCoordinator {
         skip_epoch_endings: bool,
         validate_modules: bool,
         output_transaction_analysis: Option<PathBuf>,
+        file_hashes_only: bool,
     ) -> Result<Self> {
         Ok(Self {
             storage,
@@ -60,6 +71,7 @@ impl VerifyCoordinator {
             skip_epoch_endings,
             validate_modules,
             output_transaction_analysis,
+            file_hashes_only,
         })
     }
 
@@ -95,6 +107,28 @@ impl VerifyCoordinator {
         let transactions =
             metadata_view.select_transaction_backups(self.start_version, self.end_version)?;
         let epoch_endings = metadata_view.select_epoch_ending_backups(ver_max)?;
+        let state_snapshot_chain = match &state_snapshot {
+            Some(backup) => {
+                self.load_state_snapshot_chain(&metadata_view, backup)
+                    .await?
+            },
+            None => vec![],
+        };
+
+        let mut file_hashes = vec![];
+        if !self.skip_epoch_endings {
+            file_hashes.extend(epoch_endings.iter().map(|b| (&b.manifest, &b.file_hashes)));
+        }
+        file_hashes.extend(
+            state_snapshot_chain
+                .iter()
+                .map(|b| (&b.manifest, &b.file_hashes)),
+        );
+        file_hashes.extend(transactions.iter().map(|b| (&b.manifest, &b.file_hashes)));
+        self.verify_file_hashes(file_hashes).await?;
+        if self.file_hashes_only {
+            return Ok(());
+        }
 
         let global_opt = GlobalRestoreOptions {
             target_version: ver_max,
@@ -158,4 +192,49 @@ impl VerifyCoordinator {
 
         Ok(())
     }
+
+    /// Returns the state snapshot, followed by its bases down to a full snapshot if it's a delta,
+    /// since restoring a delta reads the files of all of them.
+    async fn load_state_snapshot_chain(
+        &self,
+        metadata_view: &MetadataView,
+        backup: &StateSnapshotBackupMeta,
+    ) -> Result<Vec<StateSnapshotBackupMeta>> {
+        let mut chain = vec![backup.clone()];
+        if let StateSnapshotManifest::Delta(delta) =
+            self.storage.load_json_file(&backup.manifest).await?
+        {
+            let (_, deltas) = load_delta_chain(&self.storage, delta).await?;
+            for delta in deltas.iter().rev() {
+                chain.push(metadata_view.expect_state_snapshot_by_manifest(&delta.base_manifest)?);
+            }
+        }
+        Ok(chain)
+    }
+
+    /// Checks the files of the backups against the hashes recorded in their metadata.
+    async fn verify_file_hashes(
+        &self,
+        backups: Vec<(&FileHandle, &Option<FileHashesMeta>)>,
+    ) -> Result<()> {
+        let mut num_verified = 0;
+        for (manifest, file_hashes) in backups {
+            match file_hashes {
+                Some(file_hashes) => {
+                    FileHashes::verify(&self.storage, file_hashes, self.concurrent_downloads)
+                        .await?;
+                    num_verified += 1;
+                },
+                None => warn!(
+                    manifest = manifest,
+                    "No file hashes recorded for the backup, skipping the check of its files."
+                ),
+            }
+        }
+        info!(
+            num_backups = num_verified,
+            "Files of backups match their recorded hashes."
+        );
+        Ok(())
+    }
 }
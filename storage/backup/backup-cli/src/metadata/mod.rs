@@ -20,6 +20,7 @@ pub(crate) enum Metadata {
     TransactionBackup(TransactionBackupMeta),
     Identity(IdentityMeta),
     CompactionTimestamps(CompactionTimestampsMeta),
+    StateSnapshotDeltaBackup(StateSnapshotDeltaBackupMeta),
 }
 
 impl Metadata {
@@ -29,6 +30,7 @@ impl Metadata {
         first_version: Version,
         last_version: Version,
         manifest: FileHandle,
+        file_hashes: Option<FileHashesMeta>,
     ) -> Self {
         Self::EpochEndingBackup(EpochEndingBackupMeta {
             first_epoch,
@@ -36,14 +38,37 @@ impl Metadata {
             first_version,
             last_version,
             manifest,
+            file_hashes,
         })
     }
 
-    pub fn new_state_snapshot_backup(epoch: u64, version: Version, manifest: FileHandle) -> Self {
+    pub fn new_state_snapshot_backup(
+        epoch: u64,
+        version: Version,
+        manifest: FileHandle,
+        file_hashes: Option<FileHashesMeta>,
+    ) -> Self {
         Self::StateSnapshotBackup(StateSnapshotBackupMeta {
             epoch,
             version,
             manifest,
+            file_hashes,
+        })
+    }
+
+    pub fn new_state_snapshot_delta_backup(
+        epoch: u64,
+        version: Version,
+        base_version: Version,
+        manifest: FileHandle,
+        file_hashes: Option<FileHashesMeta>,
+    ) -> Self {
+        Self::StateSnapshotDeltaBackup(StateSnapshotDeltaBackupMeta {
+            epoch,
+            version,
+            base_version,
+            manifest,
+            file_hashes,
         })
     }
 
@@ -51,11 +76,13 @@ impl Metadata {
         first_version: Version,
         last_version: Version,
         manifest: FileHandle,
+        file_hashes: Option<FileHashesMeta>,
     ) -> Self {
         Self::TransactionBackup(TransactionBackupMeta {
             first_version,
             last_version,
             manifest,
+            file_hashes,
         })
     }
 
@@ -112,6 +139,25 @@ impl Metadata {
         Ok((res, name.parse()?))
     }
 
+    pub fn compact_state_snapshot_delta_backup_range(
+        backup_metas: Vec<StateSnapshotDeltaBackupMeta>,
+    ) -> Result<(Vec<TextLine>, ShellSafeName)> {
+        ensure!(
+            !backup_metas.is_empty(),
+            "compacting an empty metadata vector"
+        );
+        let name = format!(
+            "state_snapshot_delta_compacted_epoch_{}_{}.meta",
+            backup_metas[0].epoch,
+            backup_metas[backup_metas.len() - 1].epoch
+        );
+        let res: Vec<TextLine> = backup_metas
+            .into_iter()
+            .map(|e| Metadata::StateSnapshotDeltaBackup(e).to_text_line())
+            .collect::<Result<_>>()?;
+        Ok((res, name.parse()?))
+    }
+
     pub fn compact_transaction_backup_range(
         backup_metas: Vec<TransactionBackupMeta>,
     ) -> Result<(Vec<TextLine>, ShellSafeName)> {
@@ -163,11 +209,25 @@ impl Metadata {
             Self::CompactionTimestamps(e) => {
                 format!("compaction_timestamps_{}.meta", e.file_compacted_at,)
             },
+            Self::StateSnapshotDeltaBackup(s) => {
+                format!("state_snapshot_delta_ver_{}.meta", s.version)
+            },
         }
         .try_into()
         .unwrap()
     }
 
+    /// The manifest of the backup the metadata is about, if it's about a backup.
+    pub fn manifest(&self) -> Option<&FileHandle> {
+        match self {
+            Self::EpochEndingBackup(e) => Some(&e.manifest),
+            Self::StateSnapshotBackup(s) => Some(&s.manifest),
+            Self::TransactionBackup(t) => Some(&t.manifest),
+            Self::Identity(_) | Self::CompactionTimestamps(_) => None,
+            Self::StateSnapshotDeltaBackup(s) => Some(&s.manifest),
+        }
+    }
+
     pub fn to_text_line(&self) -> Result<TextLine> {
         TextLine::new(&serde_json::to_string(self)?)
     }
@@ -180,6 +240,8 @@ pub struct EpochEndingBackupMeta {
     pub first_version: Version,
     pub last_version: Version,
     pub manifest: FileHandle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hashes: Option<FileHashesMeta>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -187,6 +249,31 @@ pub struct StateSnapshotBackupMeta {
     pub epoch: u64,
     pub version: Version,
     pub manifest: FileHandle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hashes: Option<FileHashesMeta>,
+}
+
+/// A state snapshot taken as the changes on top of the snapshot at `base_version`, which can be a
+/// full snapshot or another delta.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StateSnapshotDeltaBackupMeta {
+    pub epoch: u64,
+    pub version: Version,
+    pub base_version: Version,
+    pub manifest: FileHandle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hashes: Option<FileHashesMeta>,
+}
+
+impl From<StateSnapshotDeltaBackupMeta> for StateSnapshotBackupMeta {
+    fn from(delta: StateSnapshotDeltaBackupMeta) -> Self {
+        Self {
+            epoch: delta.epoch,
+            version: delta.version,
+            manifest: delta.manifest,
+            file_hashes: delta.file_hashes,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -194,6 +281,16 @@ pub struct TransactionBackupMeta {
     pub first_version: Version,
     pub last_version: Version,
     pub manifest: FileHandle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hashes: Option<FileHashesMeta>,
+}
+
+/// Refers to the `FileHashes` of a backup. Backups made by older versions have none.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct FileHashesMeta {
+    pub file: FileHandle,
+    /// Hash of the content of the file, so that the hashes can't be tampered with either.
+    pub hash: HashValue,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -5,10 +5,10 @@
 use crate::{
     metadata::{
         CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta, Metadata,
-        StateSnapshotBackupMeta, TransactionBackupMeta,
+        StateSnapshotBackupMeta, StateSnapshotDeltaBackupMeta, TransactionBackupMeta,
     },
     metrics::backup::COMPACTED_TXN_VERSION,
-    storage::FileHandle,
+    storage::{FileHandle, FileHandleRef},
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_infallible::duration_since_epoch;
@@ -20,6 +20,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    state_snapshot_delta_backups: Vec<StateSnapshotDeltaBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
     _identity: Option<IdentityMeta>,
     // The compaction timestamps of the file handles producing this view
@@ -30,6 +31,7 @@ impl MetadataView {
     pub(crate) fn new(metadata_vec: Vec<Metadata>, file_handles: Vec<FileHandle>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut state_snapshot_delta_backups = Vec::new();
         let mut transaction_backups = Vec::new();
         let mut identity = None;
         let mut compaction_timestamps = Vec::new();
@@ -41,12 +43,15 @@ impl MetadataView {
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
                 Metadata::Identity(i) => identity = Some(i),
                 Metadata::CompactionTimestamps(t) => compaction_timestamps.push(t),
+                Metadata::StateSnapshotDeltaBackup(s) => state_snapshot_delta_backups.push(s),
             }
         }
         epoch_ending_backups.sort_unstable();
         epoch_ending_backups.dedup();
         state_snapshot_backups.sort_unstable();
         state_snapshot_backups.dedup();
+        state_snapshot_delta_backups.sort_unstable();
+        state_snapshot_delta_backups.dedup();
         transaction_backups.sort_unstable();
         transaction_backups.dedup();
 
@@ -72,6 +77,7 @@ impl MetadataView {
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            state_snapshot_delta_backups,
             transaction_backups,
             _identity: identity,
             compaction_timestamps: compaction_meta_opt,
@@ -105,17 +111,33 @@ impl MetadataView {
         self.compaction_timestamps.clone()
     }
 
+    /// Selects the latest state snapshot no newer than the target version. A state snapshot delta
+    /// is selected in the same way as a full one, since its manifest leads the restore to its base
+    /// snapshots, but a full one is preferred at the same version.
     pub fn select_state_snapshot(
         &self,
         target_version: Version,
     ) -> Result<Option<StateSnapshotBackupMeta>> {
-        Ok(self
+        let full = self
             .state_snapshot_backups
             .iter()
             .sorted()
             .rev()
             .find(|m| m.version <= target_version)
-            .cloned())
+            .cloned();
+        let delta = self
+            .state_snapshot_delta_backups
+            .iter()
+            .sorted()
+            .rev()
+            .find(|m| m.version <= target_version);
+
+        Ok(match delta {
+            Some(delta) if full.as_ref().map_or(true, |f| f.version < delta.version) => {
+                Some(delta.clone().into())
+            },
+            _ => full,
+        })
     }
 
     pub fn expect_state_snapshot(&self, version: Version) -> Result<StateSnapshotBackupMeta> {
@@ -123,9 +145,35 @@ impl MetadataView {
             .iter()
             .find(|m| m.version == version)
             .cloned()
+            .or_else(|| {
+                self.state_snapshot_delta_backups
+                    .iter()
+                    .find(|m| m.version == version)
+                    .cloned()
+                    .map(Into::into)
+            })
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    /// Finds the full or delta state snapshot backup by its manifest, e.g. the base of a delta.
+    pub fn expect_state_snapshot_by_manifest(
+        &self,
+        manifest: &FileHandleRef,
+    ) -> Result<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .find(|m| m.manifest == manifest)
+            .cloned()
+            .or_else(|| {
+                self.state_snapshot_delta_backups
+                    .iter()
+                    .find(|m| m.manifest == manifest)
+                    .cloned()
+                    .map(Into::into)
+            })
+            .ok_or_else(|| anyhow!("State snapshot not found with manifest {}", manifest))
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -133,24 +181,34 @@ impl MetadataView {
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
         // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // The first ones can have been removed by the garbage collection, though.
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
-            ensure!(
-                backup.first_version == next_ver,
-                "Transaction backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
-                backup.first_version,
-            );
+            match next_ver {
+                Some(next_ver) => ensure!(
+                    backup.first_version == next_ver,
+                    "Transaction backup ranges not continuous, expecting version {}, got {}.",
+                    next_ver,
+                    backup.first_version,
+                ),
+                None => ensure!(
+                    backup.first_version <= start_version,
+                    "Transaction backups start at version {}, after version {}. Older ones were \
+                    removed by the garbage collection?",
+                    backup.first_version,
+                    start_version,
+                ),
+            }
 
             if backup.last_version >= start_version {
                 res.push(backup.clone());
             }
 
-            next_ver = backup.last_version + 1;
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
@@ -192,6 +250,14 @@ impl MetadataView {
         Ok(res)
     }
 
+    pub fn expect_epoch_ending_backup(&self, epoch: u64) -> Result<EpochEndingBackupMeta> {
+        self.epoch_ending_backups
+            .iter()
+            .find(|m| m.first_epoch <= epoch && epoch <= m.last_epoch)
+            .cloned()
+            .ok_or_else(|| anyhow!("Epoch ending backup not found for epoch {}", epoch))
+    }
+
     /// Compact the epoch ending metdata files and merge compaction_cnt files into 1 metadata file
     /// The generated chunks should be sorted based on version
     pub fn compact_backups<T>(backups: &[T], compaction_cnt: usize) -> Result<Vec<&[T]>> {
@@ -235,6 +301,25 @@ impl MetadataView {
         Self::compact_backups(&self.state_snapshot_backups, compaction_cnt)
     }
 
+    pub fn compact_state_delta_backups(
+        &mut self,
+        compaction_cnt: usize,
+    ) -> Result<Vec<&[StateSnapshotDeltaBackupMeta]>> {
+        Self::compact_backups(&self.state_snapshot_delta_backups, compaction_cnt)
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn state_snapshot_delta_backups(&self) -> &[StateSnapshotDeltaBackupMeta] {
+        &self.state_snapshot_delta_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     pub fn get_file_handles(&self) -> Vec<FileHandle> {
         self.select_latest_compaction_timestamps()
             .as_ref()
@@ -8,6 +8,7 @@ use once_cell::sync::Lazy;
 pub mod backup;
 pub mod metadata;
 pub mod restore;
+pub mod storage;
 pub mod verify;
 
 pub static OTHER_TIMERS_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
@@ -0,0 +1,34 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use aptos_push_metrics::{exponential_buckets, register_histogram_vec, HistogramVec};
+use once_cell::sync::Lazy;
+
+pub static OBJECT_STORAGE_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_backup_object_storage_bytes",
+        "Bytes uploaded to or downloaded from the object storage.",
+        &["op"]
+    )
+    .unwrap()
+});
+
+pub static OBJECT_STORAGE_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_backup_object_storage_errors",
+        "Failed object storage operations, after retries.",
+        &["op"]
+    )
+    .unwrap()
+});
+
+pub static OBJECT_STORAGE_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_backup_object_storage_latency_seconds",
+        "Latency of object storage requests, including retries.",
+        &["op"],
+        exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
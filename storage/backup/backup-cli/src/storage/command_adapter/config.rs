@@ -68,8 +68,20 @@ pub struct Commands {
     /// Command line to list all existing metadata file handles.
     /// expected stdout to stream out lines of file handles.
     pub list_metadata_files: String,
-    /// Command line to backup one metadata file to a metadata backup folder
+    /// Command line to backup one metadata file to a metadata backup folder, needed by the
+    /// garbage collection and the metadata compaction.
     pub backup_metadata_file: Option<String>,
+    /// Command line to get the size of a file as stored, needed to report the bytes reclaimed by
+    /// the garbage collection. If not specified, the file is read through to count its bytes.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    /// expected output on stdout:
+    ///     size in bytes, trailing newline is trimmed
+    pub get_file_size: Option<String>,
+    /// Command line to delete a file, needed by the garbage collection.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -124,6 +124,49 @@ impl BackupStorage for CommandAdapter {
         Ok(Box::new(child.into_data_source()))
     }
 
+    async fn file_size(&self, file_handle: &FileHandleRef) -> Result<u64> {
+        let cmd = match self.config.commands.get_file_size.as_ref() {
+            Some(cmd) => cmd,
+            None => {
+                let mut file = self.open_for_read(file_handle).await?;
+                return Ok(tokio::io::copy(&mut file, &mut tokio::io::sink())
+                    .await
+                    .err_notes(file_handle)?);
+            },
+        };
+        let child = self
+            .cmd(cmd, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?;
+        let mut buf = String::new();
+        child
+            .into_data_source()
+            .read_to_string(&mut buf)
+            .await
+            .err_notes(file_handle)?;
+        buf.trim()
+            .parse()
+            .map_err(|e| format_err!("Bad size of {}: {:?}, {}", file_handle, buf, e))
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let cmd = self
+            .config
+            .commands
+            .delete_file
+            .as_ref()
+            .ok_or_else(|| format_err!("delete_file command not defined."))?;
+        let child = self
+            .cmd(cmd, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?;
+        child.join().await?;
+        Ok(())
+    }
+
+    fn supports_garbage_collection(&self) -> bool {
+        self.config.commands.delete_file.is_some()
+            && self.config.commands.backup_metadata_file.is_some()
+    }
+
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
         let child = self
             .cmd(&self.config.commands.list_metadata_files, vec![])
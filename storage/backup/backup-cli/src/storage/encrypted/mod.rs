@@ -0,0 +1,509 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client-side envelope encryption of the files of a backup storage.
+//!
+//! Every file is encrypted with its own random data key, which is stored in the header of the file
+//! encrypted with the master key. The content follows in segments sealed with AES-256-GCM, whose
+//! nonces carry the index of the segment and whether it's the last one, so that reordered,
+//! truncated or extended files fail to decrypt:
+//!
+//! ```text
+//! magic | master key id | segment size | data key nonce | encrypted data key | nonce prefix |
+//! segment...
+//! ```
+//!
+//! Metadata files must be text, so their encrypted content is base64 encoded into a single line.
+//!
+//! Once a key is configured, every file read must be encrypted with it, metadata files included.
+//! As the metadata of all the backups of a storage is read to pick the ones to restore from,
+//! encrypted backups must go to a storage location holding no plaintext backup.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_config::config::SecureBackend;
+use aptos_crypto::HashValue;
+use aptos_secure_storage::{KVStorage, Storage};
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use futures::{stream, StreamExt};
+use std::{
+    fmt,
+    io::{self, Cursor},
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio_util::io::StreamReader;
+
+const MAGIC: &[u8; 8] = b"APTBKENC";
+const KEY_ID_LEN: usize = 8;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const ENCRYPTED_DATA_KEY_LEN: usize = 32 + TAG_LEN;
+const NONCE_PREFIX_LEN: usize = 7;
+const SEGMENT_SIZE_LEN: usize = 4;
+const HEADER_LEN: usize = MAGIC.len()
+    + KEY_ID_LEN
+    + SEGMENT_SIZE_LEN
+    + NONCE_LEN
+    + ENCRYPTED_DATA_KEY_LEN
+    + NONCE_PREFIX_LEN;
+const DEFAULT_SEGMENT_SIZE: usize = 64 * 1024;
+/// Bounds the memory used to read a segment, whatever the header of the file says.
+const MAX_SEGMENT_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Parser, Clone, Debug)]
+pub struct EncryptionOpt {
+    #[clap(
+        long,
+        value_parser,
+        help = "Encrypt the backup files written, and decrypt the ones read, with the hex encoded \
+        256 bit key in this file, which can be generated with `openssl rand -hex 32`. Every file \
+        is encrypted with AES-256-GCM under its own data key, itself encrypted with this key. \
+        Reading plaintext files fails once a key is set, so encrypted backups need a storage \
+        location of their own."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with = "encryption_key_file",
+        help = "Like --encryption-key-file, but reads the hex encoded key from the secure storage \
+        backend configured in this YAML file, like a Vault, under --encryption-key-name."
+    )]
+    pub encryption_key_secure_backend: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "backup_encryption_key",
+        help = "Name of the encryption key in the secure storage backend."
+    )]
+    pub encryption_key_name: String,
+}
+
+impl EncryptionOpt {
+    pub async fn load_key(&self) -> Result<Option<EncryptionKey>> {
+        let hex = if let Some(path) = &self.encryption_key_file {
+            tokio::fs::read_to_string(path).await.err_notes(path)?
+        } else if let Some(path) = &self.encryption_key_secure_backend {
+            let backend: SecureBackend =
+                serde_yaml::from_str(&tokio::fs::read_to_string(path).await.err_notes(path)?)?;
+            let name = self.encryption_key_name.clone();
+            tokio::task::spawn_blocking(move || {
+                Storage::from(&backend)
+                    .get::<String>(&name)
+                    .map(|response| response.value)
+            })
+            .await??
+        } else {
+            return Ok(None);
+        };
+        EncryptionKey::from_hex(&hex).map(Some)
+    }
+
+    /// Wraps the storage to encrypt and decrypt files if a key is configured.
+    pub async fn init_storage(
+        &self,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Result<Arc<dyn BackupStorage>> {
+        Ok(match self.load_key().await? {
+            Some(key) => Arc::new(EncryptedStorage::new(storage, key)),
+            None => storage,
+        })
+    }
+}
+
+/// The master key of an `EncryptedStorage`.
+pub struct EncryptionKey {
+    key: Key<Aes256Gcm>,
+    /// Identifies the key in the headers of files, to tell a wrong key from a corrupted file.
+    id: [u8; KEY_ID_LEN],
+}
+
+impl EncryptionKey {
+    pub fn new(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() == 32,
+            "Encryption key must be 32 bytes, got {}.",
+            bytes.len()
+        );
+        let mut id = [0u8; KEY_ID_LEN];
+        id.copy_from_slice(&HashValue::sha3_256_of(bytes)[..KEY_ID_LEN]);
+        Ok(Self {
+            key: Key::<Aes256Gcm>::clone_from_slice(bytes),
+            id,
+        })
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let bytes =
+            hex::decode(hex.trim()).map_err(|_| format_err!("Invalid hex encryption key."))?;
+        Self::new(&bytes)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey({})", hex::encode(self.id))
+    }
+}
+
+/// A storage encrypting the files written to, and decrypting the files read from, another storage.
+pub struct EncryptedStorage {
+    inner: Arc<dyn BackupStorage>,
+    key: EncryptionKey,
+    /// Size of the segments of the files written, the ones read have theirs in their header.
+    segment_size: usize,
+}
+
+impl EncryptedStorage {
+    pub fn new(inner: Arc<dyn BackupStorage>, key: EncryptionKey) -> Self {
+        Self {
+            inner,
+            key,
+            segment_size: DEFAULT_SEGMENT_SIZE,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_segment_size(mut self, segment_size: usize) -> Self {
+        self.segment_size = segment_size;
+        self
+    }
+
+    async fn decrypt(
+        &self,
+        mut file: Box<dyn AsyncRead + Send + Unpin>,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header)
+            .await
+            .map_err(|_| format_err!("{} is too short to be encrypted.", file_handle))?;
+        let cipher = FileCipher::open_header(&self.key, &header)
+            .map_err(|err| format_err!("Failed to decrypt {}: {}", file_handle, err))?;
+
+        let segments = stream::try_unfold(
+            (BufReader::new(file), cipher, false),
+            |(mut file, mut cipher, done)| async move {
+                if done {
+                    return Ok::<_, io::Error>(None);
+                }
+                let mut segment = vec![0u8; cipher.segment_size + TAG_LEN];
+                let mut len = 0;
+                while len < segment.len() {
+                    match file.read(&mut segment[len..]).await? {
+                        0 => break,
+                        read => len += read,
+                    }
+                }
+                segment.truncate(len);
+                let last = file.fill_buf().await?.is_empty();
+                let plaintext = cipher.open(&segment, last)?;
+                Ok(Some((Bytes::from(plaintext), (file, cipher, last))))
+            },
+        );
+        Ok(Box::new(StreamReader::new(segments.boxed())))
+    }
+}
+
+#[async_trait]
+impl BackupStorage for EncryptedStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, file) = self.inner.create_for_write(backup_handle, name).await?;
+        Ok((
+            file_handle,
+            Box::new(EncryptingWriter::new(file, &self.key, self.segment_size)?),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let mut file = self.inner.open_for_read(file_handle).await?;
+        let mut magic = [0u8; MAGIC.len()];
+        file.read_exact(&mut magic)
+            .await
+            .map_err(|_| format_err!("{} is too short to be encrypted.", file_handle))?;
+        if &magic == MAGIC {
+            return self
+                .decrypt(Box::new(Cursor::new(magic).chain(file)), file_handle)
+                .await;
+        }
+
+        // Not binary, expecting a base64 encoded metadata file.
+        let mut encoded = magic.to_vec();
+        file.read_to_end(&mut encoded)
+            .await
+            .err_notes(file_handle)?;
+        let encrypted = match std::str::from_utf8(&encoded)
+            .ok()
+            .and_then(|text| base64::decode(text.trim()).ok())
+        {
+            Some(encrypted) if encrypted.starts_with(MAGIC) => encrypted,
+            _ => bail!(
+                "{} is not encrypted, but an encryption key is configured. Encrypted backups \
+                can't share a storage location with plaintext ones.",
+                file_handle
+            ),
+        };
+        self.decrypt(Box::new(Cursor::new(encrypted)), file_handle)
+            .await
+    }
+
+    async fn file_size(&self, file_handle: &FileHandleRef) -> Result<u64> {
+        self.inner.file_size(file_handle).await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.delete_file(file_handle).await
+    }
+
+    fn supports_garbage_collection(&self) -> bool {
+        self.inner.supports_garbage_collection()
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.backup_metadata_file(file_handle).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let mut writer = EncryptingWriter::new(Vec::new(), &self.key, self.segment_size)?;
+        for line in lines {
+            writer.write_all(line.as_ref().as_bytes()).await?;
+        }
+        writer.shutdown().await?;
+        let line = TextLine::new(&base64::encode(writer.into_inner()))?;
+        self.inner.save_metadata_lines(name, &[line]).await
+    }
+}
+
+/// Seals and opens the segments of a file, with its data key.
+struct FileCipher {
+    cipher: Aes256Gcm,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    /// Size of the plaintext of every segment but the last one.
+    segment_size: usize,
+    /// Index of the next segment.
+    counter: u32,
+}
+
+impl FileCipher {
+    /// Generates a data key, returning the cipher and the header of the file.
+    fn new(key: &EncryptionKey, segment_size: usize) -> Result<(Self, Vec<u8>)> {
+        ensure!(
+            segment_size > 0 && segment_size <= MAX_SEGMENT_SIZE,
+            "Invalid segment size {}.",
+            segment_size
+        );
+        let data_key = Aes256Gcm::generate_key(&mut OsRng);
+        let data_key_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let encrypted_data_key = Aes256Gcm::new(&key.key)
+            .encrypt(&data_key_nonce, data_key.as_slice())
+            .map_err(|_| format_err!("Failed to encrypt the data key."))?;
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        OsRng.fill_bytes(&mut nonce_prefix);
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&key.id);
+        header.extend_from_slice(&(segment_size as u32).to_be_bytes());
+        header.extend_from_slice(&data_key_nonce);
+        header.extend_from_slice(&encrypted_data_key);
+        header.extend_from_slice(&nonce_prefix);
+        Ok((
+            Self {
+                cipher: Aes256Gcm::new(&data_key),
+                nonce_prefix,
+                segment_size,
+                counter: 0,
+            },
+            header,
+        ))
+    }
+
+    /// Decrypts the data key in the header of a file.
+    fn open_header(key: &EncryptionKey, header: &[u8; HEADER_LEN]) -> Result<Self> {
+        let (magic, rest) = header.split_at(MAGIC.len());
+        let (key_id, rest) = rest.split_at(KEY_ID_LEN);
+        let (segment_size, rest) = rest.split_at(SEGMENT_SIZE_LEN);
+        let (data_key_nonce, rest) = rest.split_at(NONCE_LEN);
+        let (encrypted_data_key, nonce_prefix) = rest.split_at(ENCRYPTED_DATA_KEY_LEN);
+        ensure!(magic == MAGIC, "not an encrypted file");
+        ensure!(
+            key_id == key.id,
+            "encrypted with key {}, not the configured key {}",
+            hex::encode(key_id),
+            hex::encode(key.id),
+        );
+        let segment_size = u32::from_be_bytes(segment_size.try_into()?) as usize;
+        ensure!(
+            segment_size > 0 && segment_size <= MAX_SEGMENT_SIZE,
+            "invalid segment size {}",
+            segment_size
+        );
+
+        let data_key = Aes256Gcm::new(&key.key)
+            .decrypt(Nonce::from_slice(data_key_nonce), encrypted_data_key)
+            .map_err(|_| format_err!("corrupted data key"))?;
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key)),
+            nonce_prefix: nonce_prefix.try_into()?,
+            segment_size,
+            counter: 0,
+        })
+    }
+
+    fn next_nonce(&mut self, last: bool) -> io::Result<[u8; NONCE_LEN]> {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[NONCE_LEN - 1] = u8::from(last);
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Too many segments."))?;
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, plaintext: &[u8], last: bool) -> io::Result<Vec<u8>> {
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Failed to encrypt."))
+    }
+
+    fn open(&mut self, ciphertext: &[u8], last: bool) -> io::Result<Vec<u8>> {
+        let nonce = self.next_nonce(last)?;
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Failed to decrypt, the file is corrupted or was tampered with.",
+                )
+            })
+    }
+}
+
+/// Encrypts what's written to it segment by segment, and writes it to the inner writer.
+struct EncryptingWriter<W> {
+    inner: W,
+    cipher: FileCipher,
+    /// Content of the segment being written.
+    plaintext: Vec<u8>,
+    /// Encrypted bytes not written to the inner writer yet, starting with the header.
+    ciphertext: Vec<u8>,
+    written: usize,
+    sealed_last: bool,
+}
+
+impl<W: AsyncWrite + Unpin> EncryptingWriter<W> {
+    fn new(inner: W, key: &EncryptionKey, segment_size: usize) -> Result<Self> {
+        let (cipher, header) = FileCipher::new(key, segment_size)?;
+        Ok(Self {
+            inner,
+            cipher,
+            plaintext: Vec::with_capacity(segment_size),
+            ciphertext: header,
+            written: 0,
+            sealed_last: false,
+        })
+    }
+
+    fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn seal_segment(&mut self, last: bool) -> io::Result<()> {
+        let sealed = self.cipher.seal(&self.plaintext, last)?;
+        self.ciphertext.extend_from_slice(&sealed);
+        self.plaintext.clear();
+        Ok(())
+    }
+
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.ciphertext.len() {
+            let written =
+                ready!(Pin::new(&mut self.inner).poll_write(cx, &self.ciphertext[self.written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.written += written;
+        }
+        self.ciphertext.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for EncryptingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        // A full segment is only sealed once more content comes, as the last one is sealed
+        // differently on shutdown.
+        let segment_size = this.cipher.segment_size;
+        if this.plaintext.len() == segment_size && !buf.is_empty() {
+            this.seal_segment(false)?;
+            ready!(this.poll_drain(cx))?;
+        }
+        let len = buf.len().min(segment_size - this.plaintext.len());
+        this.plaintext.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        if !this.sealed_last {
+            this.seal_segment(true)?;
+            this.sealed_last = true;
+            ready!(this.poll_drain(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
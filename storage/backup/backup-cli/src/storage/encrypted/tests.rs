@@ -0,0 +1,169 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+/// Small segments, so that files span several of them
+const SEGMENT_SIZE: usize = 64;
+
+fn new_store(dir: &TempPath, key: [u8; 32]) -> EncryptedStorage {
+    EncryptedStorage::new(
+        Arc::new(LocalFs::new(dir.path().to_path_buf())),
+        EncryptionKey::new(&key).unwrap(),
+    )
+    .with_segment_size(SEGMENT_SIZE)
+}
+
+async fn write_file(store: &dyn BackupStorage, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&ShellSafeName::from_str("backup").unwrap())
+        .await
+        .unwrap();
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &ShellSafeName::from_str("file").unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut content)
+        .await?;
+    Ok(content)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = new_store(&tmpdir, [1; 32]);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = new_store(&tmpdir, [1; 32]);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[test]
+fn test_segment_boundaries() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        for len in [
+            0,
+            1,
+            SEGMENT_SIZE - 1,
+            SEGMENT_SIZE,
+            SEGMENT_SIZE + 1,
+            3 * SEGMENT_SIZE,
+        ] {
+            let tmpdir = TempPath::new();
+            tmpdir.create_as_dir().unwrap();
+            let store = new_store(&tmpdir, [1; 32]);
+            let content: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+            let file_handle = write_file(&store, &content).await;
+            assert_eq!(read_file(&store, &file_handle).await.unwrap(), content);
+        }
+    });
+}
+
+#[test]
+fn test_segment_size_read_from_header() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = new_store(&tmpdir, [1; 32]);
+        let content: Vec<u8> = (0..3 * SEGMENT_SIZE + 1).map(|i| i as u8).collect();
+        let file_handle = write_file(&store, &content).await;
+
+        let default_store = EncryptedStorage::new(
+            Arc::new(LocalFs::new(tmpdir.path().to_path_buf())),
+            EncryptionKey::new(&[1; 32]).unwrap(),
+        );
+        assert_eq!(
+            read_file(&default_store, &file_handle).await.unwrap(),
+            content
+        );
+    });
+}
+
+#[test]
+fn test_tampering_detected() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = new_store(&tmpdir, [1; 32]);
+        let file_handle = write_file(&store, &[7; 3 * SEGMENT_SIZE]).await;
+        let path = tmpdir.path().join(&file_handle);
+        let encrypted = std::fs::read(&path).unwrap();
+
+        // Flipping a bit of the content.
+        let mut corrupted = encrypted.clone();
+        corrupted[HEADER_LEN + 1] ^= 1;
+        std::fs::write(&path, &corrupted).unwrap();
+        assert!(read_file(&store, &file_handle).await.is_err());
+
+        // Dropping the last segment.
+        std::fs::write(
+            &path,
+            &encrypted[..HEADER_LEN + 2 * (SEGMENT_SIZE + TAG_LEN)],
+        )
+        .unwrap();
+        assert!(read_file(&store, &file_handle).await.is_err());
+
+        // Reading with another key.
+        std::fs::write(&path, &encrypted).unwrap();
+        let other_store = new_store(&tmpdir, [2; 32]);
+        assert!(read_file(&other_store, &file_handle).await.is_err());
+        assert!(read_file(&store, &file_handle).await.is_ok());
+    });
+}
+
+#[test]
+fn test_plain_files_rejected() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let plain_store = LocalFs::new(tmpdir.path().to_path_buf());
+        let file_handle = write_file(&plain_store, b"not encrypted content").await;
+
+        let store = new_store(&tmpdir, [1; 32]);
+        assert!(read_file(&store, &file_handle).await.is_err());
+    });
+}
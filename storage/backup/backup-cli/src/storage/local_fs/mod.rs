@@ -22,7 +22,7 @@ use std::{
     str::FromStr,
 };
 use tokio::{
-    fs::{create_dir_all, read_dir, rename, OpenOptions},
+    fs::{create_dir_all, metadata, read_dir, remove_dir, remove_file, rename, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -109,6 +109,25 @@ impl BackupStorage for LocalFs {
         Ok(Box::new(file))
     }
 
+    async fn file_size(&self, file_handle: &FileHandleRef) -> Result<u64> {
+        let path = self.dir.join(file_handle);
+        Ok(metadata(&path).await.err_notes(&path)?.len())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+        // Remove the folder of the backup as well once it's empty, failing if it's not.
+        if let Some(backup_dir) = path.parent().filter(|dir| *dir != self.dir) {
+            remove_dir(backup_dir).await.ok();
+        }
+        Ok(())
+    }
+
+    fn supports_garbage_collection(&self) -> bool {
+        true
+    }
+
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
         let dir = self.metadata_dir();
         let rel_path = Path::new(Self::METADATA_DIR);
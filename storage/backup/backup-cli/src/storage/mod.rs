@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod command_adapter;
+pub mod encrypted;
 pub mod local_fs;
+pub mod object_storage;
 
 #[cfg(test)]
 mod test_util;
@@ -12,7 +14,9 @@ mod tests;
 
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
+    encrypted::EncryptionOpt,
     local_fs::{LocalFs, LocalFsOpt},
+    object_storage::{ObjectStorage, ObjectStorageConfig, ObjectStorageOpt},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -25,6 +29,7 @@ use regex::Regex;
 use std::convert::TryInto;
 use std::{convert::TryFrom, ops::Deref, str::FromStr, sync::Arc};
 use tokio::io::{AsyncRead, AsyncWrite};
+use url::Url;
 
 /// String returned by a specific storage implementation to identify a backup, probably a folder name
 /// which is exactly the same with the backup name we pass into `create_backup()`
@@ -153,6 +158,16 @@ pub trait BackupStorage: Send + Sync {
         &self,
         file_handle: &FileHandleRef,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+    /// Returns the size in bytes of a file as it is stored, which can be different from the size
+    /// of what is read from it, when the storage compresses it, for example.
+    async fn file_size(&self, file_handle: &FileHandleRef) -> Result<u64>;
+    /// Deletes a file. Only files of backups not referred to by the metadata are expected to be
+    /// deleted, see `BackupGarbageCollector`.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
+    /// Whether the storage can delete files and back metadata files up, which is what the
+    /// garbage collection needs. It's checked before the garbage collection changes anything, so
+    /// that it doesn't remove the metadata of backups whose files it then fails to delete.
+    fn supports_garbage_collection(&self) -> bool;
     /// Asks to save a metadata entry and return the File handle of the saved file.
     /// A metadata entry is one line of text.
     /// The backup system doesn't expect a metadata entry to exclusively map to a single file
@@ -198,6 +213,12 @@ pub enum StorageOpt {
     https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/command_adapter/sample_configs/"
     )]
     CommandAdapter(CommandAdapterOpt),
+    #[clap(
+        about = "Select the ObjectStorage backup storage type, which talks to S3 compatible \
+    storages, Azure Blob Storage or Google Cloud Storage through their APIs, with multipart \
+    uploads, parallel ranged reads, retries and checksums."
+    )]
+    ObjectStorage(ObjectStorageOpt),
 }
 
 impl StorageOpt {
@@ -205,6 +226,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::ObjectStorage(opt) => Arc::new(ObjectStorage::new_with_opt(opt)?),
         })
     }
 }
@@ -213,7 +235,7 @@ impl StorageOpt {
 #[clap(group(
     ArgGroup::new("storage")
     .required(true)
-    .args(&["local_fs_dir", "command_adapter_config"]),
+    .args(&["local_fs_dir", "command_adapter_config", "object_storage_url"]),
 ))]
 pub struct DBToolStorageOpt {
     #[clap(
@@ -229,14 +251,31 @@ pub struct DBToolStorageOpt {
     https://github.com/aptos-labs/aptos-networks/tree/main/testnet/backups "
     )]
     command_adapter_config: Option<CommandAdapterOpt>,
+    #[clap(
+        long,
+        help = "Select the ObjectStorage backup storage type, with the URL of the folder holding \
+    the backups, like s3://bucket/path, az://container/path or gs://bucket/path. Credentials are \
+    read from the environment variables of the cloud."
+    )]
+    object_storage_url: Option<Url>,
+    #[clap(flatten)]
+    object_storage_config: ObjectStorageConfig,
+    #[clap(flatten)]
+    encryption: EncryptionOpt,
 }
 
 impl DBToolStorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
-        Ok(if self.local_fs_dir.is_some() {
+        let storage: Arc<dyn BackupStorage> = if self.local_fs_dir.is_some() {
             Arc::new(LocalFs::new_with_opt(self.local_fs_dir.unwrap()))
+        } else if let Some(url) = &self.object_storage_url {
+            Arc::new(ObjectStorage::new_with_config(
+                url,
+                &self.object_storage_config,
+            )?)
         } else {
             Arc::new(CommandAdapter::new_with_opt(self.command_adapter_config.unwrap()).await?)
-        })
+        };
+        self.encryption.init_storage(storage).await
     }
 }
@@ -0,0 +1,486 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    metrics::storage::{
+        OBJECT_STORAGE_BYTES, OBJECT_STORAGE_ERRORS, OBJECT_STORAGE_LATENCY_SECONDS,
+    },
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_logger::warn;
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use futures::{
+    future::{self, BoxFuture},
+    stream::{self, BoxStream},
+    Future, FutureExt, Stream, StreamExt, TryStreamExt,
+};
+use object_store::{
+    aws::AmazonS3Builder, azure::MicrosoftAzureBuilder, buffered::BufWriter,
+    gcp::GoogleCloudStorageBuilder, path::Path, ClientOptions, ObjectStore, RetryConfig,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::min,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::io::StreamReader;
+use url::Url;
+
+#[derive(Parser, Clone, Debug)]
+pub struct ObjectStorageOpt {
+    #[clap(
+        long = "url",
+        help = "URL of the folder holding the backups, like s3://bucket/path, az://container/path \
+        or gs://bucket/path. Credentials are read from the environment variables of the cloud, \
+        like AWS_ACCESS_KEY_ID, AZURE_STORAGE_ACCOUNT_KEY or GOOGLE_SERVICE_ACCOUNT."
+    )]
+    pub url: Url,
+    #[clap(flatten)]
+    pub config: ObjectStorageConfig,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct ObjectStorageConfig {
+    #[clap(
+        long = "object-storage-endpoint",
+        help = "Endpoint of an S3 compatible storage other than AWS S3, like MinIO."
+    )]
+    pub endpoint: Option<String>,
+    #[clap(
+        long = "object-storage-allow-http",
+        help = "Allow plain HTTP connections to the storage, like a MinIO running locally."
+    )]
+    pub allow_http: bool,
+    #[clap(
+        long = "object-storage-part-size-mb",
+        default_value_t = 16,
+        value_parser = clap::value_parser!(u64).range(5..),
+        help = "Size of the ranged reads, in MiB. Files smaller than it are uploaded in a single \
+        request, larger ones in a multipart upload."
+    )]
+    pub part_size_mb: u64,
+    #[clap(
+        long = "object-storage-concurrency",
+        default_value_t = 8,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Number of ranged reads of a file in parallel."
+    )]
+    pub concurrency: u64,
+    #[clap(
+        long = "object-storage-max-retries",
+        default_value_t = 10,
+        help = "Number of times a failed request to the storage is retried, with exponential \
+        backoff."
+    )]
+    pub max_retries: usize,
+}
+
+/// A storage backend talking to S3 compatible storages, Azure Blob Storage and Google Cloud
+/// Storage through their APIs, as opposed to the `CommandAdapter` running their command line
+/// tools.
+///
+/// Large files are uploaded in parts and read with parallel ranged requests. A SHA-256 checksum
+/// of each file is saved next to it, in a file with the `.sha256` suffix, and verified when the
+/// file is read to the end.
+pub struct ObjectStorage {
+    store: Arc<dyn ObjectStore>,
+    /// The folder holding the backups, file handles are relative to it.
+    prefix: Path,
+    part_size: usize,
+    concurrency: usize,
+}
+
+impl ObjectStorage {
+    const CHECKSUM_SUFFIX: &'static str = ".sha256";
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(
+        store: Arc<dyn ObjectStore>,
+        prefix: Path,
+        part_size: usize,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            store,
+            prefix,
+            part_size: part_size.max(1),
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    pub fn new_with_opt(opt: ObjectStorageOpt) -> Result<Self> {
+        Self::new_with_config(&opt.url, &opt.config)
+    }
+
+    pub fn new_with_config(url: &Url, config: &ObjectStorageConfig) -> Result<Self> {
+        ensure!(
+            config.endpoint.is_none() || url.scheme() == "s3",
+            "--object-storage-endpoint is only supported for s3:// URLs, got {}",
+            url,
+        );
+        let client_options = ClientOptions::new().with_allow_http(config.allow_http);
+        let retry_config = RetryConfig {
+            max_retries: config.max_retries,
+            ..Default::default()
+        };
+
+        let store: Arc<dyn ObjectStore> = match url.scheme() {
+            "s3" => {
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_url(url.as_str())
+                    .with_client_options(client_options)
+                    .with_retry(retry_config);
+                if let Some(endpoint) = &config.endpoint {
+                    builder = builder.with_endpoint(endpoint);
+                }
+                Arc::new(builder.build()?)
+            },
+            "az" | "azure" => Arc::new(
+                MicrosoftAzureBuilder::from_env()
+                    .with_url(url.as_str())
+                    .with_client_options(client_options)
+                    .with_retry(retry_config)
+                    .build()?,
+            ),
+            "gs" => Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(url.as_str())
+                    .with_client_options(client_options)
+                    .with_retry(retry_config)
+                    .build()?,
+            ),
+            scheme => bail!(
+                "Unsupported object storage URL scheme {}, expecting s3, az or gs.",
+                scheme,
+            ),
+        };
+        let prefix = Path::from_url_path(url.path())?;
+
+        Ok(Self::new(
+            store,
+            prefix,
+            (config.part_size_mb << 20) as usize,
+            config.concurrency as usize,
+        ))
+    }
+
+    fn path(&self, file_handle: &FileHandleRef) -> Result<Path> {
+        let relative = Path::parse(file_handle)?;
+        Ok(self.prefix.parts().chain(relative.parts()).collect())
+    }
+
+    fn file_handle(&self, path: &Path) -> Result<FileHandle> {
+        let relative: Path = path
+            .prefix_match(&self.prefix)
+            .ok_or_else(|| format_err!("{} is not under {}", path, self.prefix))?
+            .collect();
+        Ok(relative.to_string())
+    }
+
+    fn checksum_path(path: &Path) -> Path {
+        Path::from(format!("{}{}", path, Self::CHECKSUM_SUFFIX))
+    }
+
+    /// Returns the saved checksum of the file, if any.
+    async fn read_checksum(&self, path: &Path) -> Result<Option<String>> {
+        match self.store.get(&Self::checksum_path(path)).await {
+            Ok(result) => Ok(Some(String::from_utf8(result.bytes().await?.to_vec())?)),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(err) => {
+                OBJECT_STORAGE_ERRORS.with_label_values(&["read"]).inc();
+                Err(err.into())
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for ObjectStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create in object storages.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let path = self.path(&file_handle).err_notes(&file_handle)?;
+        let writer = BufWriter::with_capacity(self.store.clone(), path.clone(), self.part_size);
+        Ok((
+            file_handle,
+            Box::new(ChecksumWriter::new(self.store.clone(), path, writer)),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let path = self.path(file_handle).err_notes(file_handle)?;
+        let expected_checksum = self.read_checksum(&path).await.err_notes(file_handle)?;
+        let size = observe("head", self.store.head(&path))
+            .await
+            .err_notes(file_handle)?
+            .size;
+
+        let (store, part_size) = (self.store.clone(), self.part_size);
+        let chunks = stream::iter((0..size).step_by(part_size))
+            .map(move |start| {
+                let (store, path) = (store.clone(), path.clone());
+                async move {
+                    observe(
+                        "read",
+                        store.get_range(&path, start..min(start + part_size, size)),
+                    )
+                    .await
+                }
+            })
+            .buffered(self.concurrency)
+            .map_ok(|bytes| {
+                OBJECT_STORAGE_BYTES
+                    .with_label_values(&["read"])
+                    .inc_by(bytes.len() as u64);
+                bytes
+            })
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+            .boxed();
+
+        let chunks = match expected_checksum {
+            Some(expected) => verify_checksum(chunks, expected, file_handle.to_string()).boxed(),
+            None => {
+                warn!("No checksum saved for {}, not verifying it.", file_handle);
+                chunks
+            },
+        };
+        Ok(Box::new(StreamReader::new(chunks)))
+    }
+
+    async fn file_size(&self, file_handle: &FileHandleRef) -> Result<u64> {
+        let path = self.path(file_handle).err_notes(file_handle)?;
+        let meta = observe("head", self.store.head(&path))
+            .await
+            .err_notes(file_handle)?;
+        Ok(meta.size as u64)
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.path(file_handle).err_notes(file_handle)?;
+        observe("delete", self.store.delete(&path))
+            .await
+            .err_notes(file_handle)?;
+        match self.store.delete(&Self::checksum_path(&path)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(err) => {
+                OBJECT_STORAGE_ERRORS.with_label_values(&["delete"]).inc();
+                Err(err.into())
+            },
+        }
+    }
+
+    fn supports_garbage_collection(&self) -> bool {
+        true
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let dir = self.path(Self::METADATA_DIR)?;
+        let objects: Vec<_> = observe("list", self.store.list(Some(&dir)).try_collect())
+            .await
+            .err_notes(&dir)?;
+
+        objects
+            .iter()
+            .filter(|object| !object.location.as_ref().ends_with(Self::CHECKSUM_SUFFIX))
+            .map(|object| self.file_handle(&object.location))
+            .collect()
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    /// file_handle is a path with `metadata` in the path, Ex: metadata/epoch_ending_1.meta
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.path(file_handle)?;
+        let name = path
+            .filename()
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        let backup_path = self.path(Self::METADATA_BACKUP_DIR)?.child(name);
+
+        observe("rename", self.store.rename(&path, &backup_path))
+            .await
+            .err_notes(file_handle)?;
+        match self
+            .store
+            .rename(
+                &Self::checksum_path(&path),
+                &Self::checksum_path(&backup_path),
+            )
+            .await
+        {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(err) => {
+                OBJECT_STORAGE_ERRORS.with_label_values(&["rename"]).inc();
+                Err(err.into())
+            },
+        }
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        let path = self.path(&file_handle)?;
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let checksum = hex::encode(Sha256::digest(content.as_bytes()));
+        let size = content.len() as u64;
+
+        observe("write", self.store.put(&path, content.into()))
+            .await
+            .err_notes(name)?;
+        observe(
+            "write",
+            self.store.put(&Self::checksum_path(&path), checksum.into()),
+        )
+        .await
+        .err_notes(name)?;
+        OBJECT_STORAGE_BYTES
+            .with_label_values(&["write"])
+            .inc_by(size);
+        Ok(file_handle)
+    }
+}
+
+/// Times a request to the storage, counting it in the errors if it fails after retries.
+async fn observe<T>(
+    op: &'static str,
+    request: impl Future<Output = object_store::Result<T>>,
+) -> Result<T> {
+    let _timer = OBJECT_STORAGE_LATENCY_SECONDS
+        .with_label_values(&[op])
+        .start_timer();
+    request.await.map_err(|err| {
+        OBJECT_STORAGE_ERRORS.with_label_values(&[op]).inc();
+        err.into()
+    })
+}
+
+/// Fails the read at the end of the file if its content doesn't match the expected checksum.
+fn verify_checksum(
+    chunks: BoxStream<'static, io::Result<Bytes>>,
+    expected: String,
+    file_handle: FileHandle,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    stream::try_unfold(
+        (chunks, Sha256::new(), expected, file_handle),
+        |(mut chunks, mut hasher, expected, file_handle)| async move {
+            match chunks.try_next().await? {
+                Some(bytes) => {
+                    hasher.update(&bytes);
+                    Ok(Some((bytes, (chunks, hasher, expected, file_handle))))
+                },
+                None => {
+                    let actual = hex::encode(hasher.finalize());
+                    if actual == expected.trim() {
+                        Ok(None)
+                    } else {
+                        OBJECT_STORAGE_ERRORS.with_label_values(&["checksum"]).inc();
+                        Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "Checksum mismatch for {}, expected {}, got {}.",
+                                file_handle, expected, actual
+                            ),
+                        ))
+                    }
+                },
+            }
+        },
+    )
+}
+
+/// Uploads a file through a `BufWriter`, then the checksum of its content once it's complete.
+struct ChecksumWriter {
+    store: Arc<dyn ObjectStore>,
+    path: Path,
+    writer: BufWriter,
+    hasher: Sha256,
+    put_checksum: Option<BoxFuture<'static, Result<()>>>,
+}
+
+impl ChecksumWriter {
+    fn new(store: Arc<dyn ObjectStore>, path: Path, writer: BufWriter) -> Self {
+        Self {
+            store,
+            path,
+            writer,
+            hasher: Sha256::new(),
+            put_checksum: None,
+        }
+    }
+}
+
+impl AsyncWrite for ChecksumWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let written = ready!(Pin::new(&mut self.writer).poll_write(cx, buf))?;
+        self.hasher.update(&buf[..written]);
+        OBJECT_STORAGE_BYTES
+            .with_label_values(&["write"])
+            .inc_by(written as u64);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.put_checksum.is_none() {
+            ready!(Pin::new(&mut self.writer).poll_shutdown(cx)).map_err(|err| {
+                OBJECT_STORAGE_ERRORS.with_label_values(&["write"]).inc();
+                err
+            })?;
+            let checksum = hex::encode(self.hasher.finalize_reset());
+            let (store, path) = (self.store.clone(), ObjectStorage::checksum_path(&self.path));
+            self.put_checksum = Some(
+                async move {
+                    observe("write", store.put(&path, checksum.into()))
+                        .await
+                        .map(|_| ())
+                }
+                .boxed(),
+            );
+        }
+
+        let put_checksum = self.put_checksum.as_mut().expect("Set above.");
+        ready!(put_checksum.poll_unpin(cx))
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        // Shutting down again is a no-op.
+        self.put_checksum = Some(future::ok(()).boxed());
+        Poll::Ready(Ok(()))
+    }
+}
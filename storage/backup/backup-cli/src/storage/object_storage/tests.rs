@@ -0,0 +1,125 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_size_and_delete_impl, test_write_and_read_impl,
+};
+use object_store::memory::InMemory;
+use proptest::prelude::*;
+use std::str::FromStr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+/// Small parts, so that most files are uploaded and read in several parts.
+const PART_SIZE: usize = 100;
+
+fn new_store() -> ObjectStorage {
+    ObjectStorage::new(
+        Arc::new(InMemory::new()),
+        Path::from("backups/e1"),
+        PART_SIZE,
+        4,
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(new_store()), backups));
+    }
+
+    #[test]
+    fn test_size_and_delete(
+        backups in arb_backups()
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_size_and_delete_impl(Box::new(new_store()), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(new_store()), input));
+    }
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let storage = new_store();
+        let backup_handle = storage
+            .create_backup(&ShellSafeName::from_str("backup").unwrap())
+            .await
+            .unwrap();
+        let (file_handle, mut file) = storage
+            .create_for_write(&backup_handle, &ShellSafeName::from_str("file").unwrap())
+            .await
+            .unwrap();
+        file.write_all(&[1; 3 * PART_SIZE]).await.unwrap();
+        file.shutdown().await.unwrap();
+
+        // Corrupt the content of the file behind the back of the storage.
+        storage
+            .store
+            .put(
+                &storage.path(&file_handle).unwrap(),
+                vec![2; 3 * PART_SIZE].into(),
+            )
+            .await
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let err = storage
+            .open_for_read(&file_handle)
+            .await
+            .unwrap()
+            .read_to_end(&mut buf)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    });
+}
+
+#[test]
+fn test_backup_metadata_file() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let storage = new_store();
+        let file_handle = storage
+            .save_metadata_line(
+                &ShellSafeName::from_str("epoch_ending_1.meta").unwrap(),
+                &TextLine::new("line").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.list_metadata_files().await.unwrap(),
+            vec![file_handle.clone()]
+        );
+
+        storage.backup_metadata_file(&file_handle).await.unwrap();
+        assert!(storage.list_metadata_files().await.unwrap().is_empty());
+
+        let mut content = String::new();
+        storage
+            .open_for_read("metadata_backup/epoch_ending_1.meta")
+            .await
+            .unwrap()
+            .read_to_string(&mut content)
+            .await
+            .unwrap();
+        assert_eq!(content, "line\n");
+    });
+}
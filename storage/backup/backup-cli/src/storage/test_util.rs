@@ -48,6 +48,28 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+pub async fn test_size_and_delete_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+) {
+    let mut handles = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            handles.push((handle, content.len() as u64));
+        }
+    }
+
+    for (handle, size) in &handles {
+        assert_eq!(store.file_size(handle).await.unwrap(), *size);
+        store.delete_file(handle).await.unwrap();
+        assert!(store.file_size(handle).await.is_err());
+    }
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
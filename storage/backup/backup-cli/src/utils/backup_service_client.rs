@@ -168,4 +168,16 @@ impl BackupServiceClient {
         )
         .await
     }
+
+    pub async fn get_write_sets(
+        &self,
+        start_version: Version,
+        num_transactions: usize,
+    ) -> Result<impl AsyncRead> {
+        self.get(
+            "write_sets",
+            &format!("{}/{}", start_version, num_transactions),
+        )
+        .await
+    }
 }
@@ -23,6 +23,7 @@ static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
+static WRITE_SETS: &str = "write_sets";
 
 pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
     // GET db_state
@@ -110,7 +111,7 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .recover(handle_rejection);
 
     // GET transaction_range_proof/<first_version>/<last_version>
-    let bh = backup_handler;
+    let bh = backup_handler.clone();
     let transaction_range_proof = warp::path!(Version / Version)
         .map(move |first_version, last_version| {
             reply_with_bcs_bytes(
@@ -121,6 +122,17 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // GET write_sets/<start_version>/<num_transactions>
+    let bh = backup_handler;
+    let write_sets = warp::path!(Version / usize)
+        .map(move |start_version, num_transactions| {
+            reply_with_bytes_sender(&bh, WRITE_SETS, move |bh, sender| {
+                bh.get_write_set_iter(start_version, num_transactions)?
+                    .try_for_each(|record_res| sender.send_size_prefixed_bcs_bytes(record_res?))
+            })
+        })
+        .recover(handle_rejection);
+
     // Route by endpoint name.
     let routes = warp::any()
         .and(warp::path(DB_STATE).and(db_state))
@@ -131,7 +143,8 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
         .or(warp::path(EPOCH_ENDING_LEDGER_INFOS).and(epoch_ending_ledger_infos))
         .or(warp::path(TRANSACTIONS).and(transactions))
-        .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
+        .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof))
+        .or(warp::path(WRITE_SETS).and(write_sets));
 
     // Serve all routes for GET only.
     warp::get()
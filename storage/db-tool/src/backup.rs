@@ -6,7 +6,10 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
-        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            delta_backup::{StateSnapshotDeltaBackupController, StateSnapshotDeltaBackupOpt},
+        },
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     coordinators::{
@@ -98,6 +101,12 @@ enum BackupType {
         #[clap[flatten]]
         storage: DBToolStorageOpt,
     },
+    StateSnapshotDelta {
+        #[clap(flatten)]
+        opt: StateSnapshotDeltaBackupOpt,
+        #[clap[flatten]]
+        storage: DBToolStorageOpt,
+    },
     Transaction {
         #[clap(flatten)]
         opt: TransactionBackupOpt,
@@ -163,6 +172,12 @@ pub struct VerifyOpt {
         help = "Optionally, while verifying transactions, output analysis files to specified dir."
     )]
     output_transaction_analysis: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Only check the files of the backups against the content hashes recorded in the \
+        metadata, without verifying the proofs and signatures in them."
+    )]
+    file_hashes_only: bool,
 }
 
 impl Command {
@@ -193,6 +208,16 @@ impl Command {
                         .run()
                         .await?;
                     },
+                    BackupType::StateSnapshotDelta { opt, storage } => {
+                        StateSnapshotDeltaBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    },
                     BackupType::Transaction { opt, storage } => {
                         TransactionBackupController::new(
                             opt,
@@ -246,6 +271,7 @@ impl Command {
                     opt.skip_epoch_endings,
                     opt.validate_modules,
                     opt.output_transaction_analysis,
+                    opt.file_hashes_only,
                 )?
                 .run()
                 .await?
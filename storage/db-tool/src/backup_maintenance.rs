@@ -2,8 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::{
+        backup::BackupCompactor,
+        gc::{BackupGarbageCollector, RetentionPolicyOpt},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::ConcurrentDownloadsOpt,
 };
 use clap::{Parser, Subcommand};
 
@@ -14,6 +19,8 @@ pub enum Command {
     Compact(CompactionOpt),
     #[clap(about = "Cleanup the backup metadata files")]
     Cleanup(CleanupOpt),
+    #[clap(about = "Remove the backups not retained by the retention policies")]
+    Gc(GcOpt),
 }
 
 #[derive(Parser)]
@@ -48,6 +55,21 @@ pub struct CleanupOpt {
     pub storage: DBToolStorageOpt,
 }
 
+#[derive(Parser)]
+pub struct GcOpt {
+    #[clap(flatten)]
+    pub policy: RetentionPolicyOpt,
+    /// Only report what would be removed, without removing anything
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+}
+
 impl Command {
     pub async fn run(self) -> Result<()> {
         match self {
@@ -66,6 +88,16 @@ impl Command {
             Command::Cleanup(_) => {
                 // TODO: add cleanup logic for removing obsolete metadata files
             },
+            Command::Gc(opt) => {
+                let gc = BackupGarbageCollector::new(
+                    opt.policy,
+                    opt.dry_run,
+                    opt.metadata_cache_opt,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                );
+                println!("{}", gc.run().await?);
+            },
         }
         Ok(())
     }
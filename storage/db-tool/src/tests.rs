@@ -47,6 +47,56 @@ fn test_various_cmd_parsing() {
     ]);
 
     run_cmd(&["aptos-db-tool", "backup", "verify", "--local-fs-dir", "."]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "backup-maintenance",
+        "gc",
+        "--keep-latest-state-snapshots",
+        "2",
+        "--keep-state-snapshot-every-days",
+        "7",
+        "--keep-transactions-since-version",
+        "100",
+        "--dry-run",
+        "--local-fs-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "restore",
+        "bootstrap-db",
+        "--target-timestamp",
+        "1700000000",
+        "--target-db-dir",
+        ".",
+        "--local-fs-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "restore",
+        "bootstrap-db",
+        "--target-epoch-end",
+        "10",
+        "--target-db-dir",
+        ".",
+        "--local-fs-dir",
+        ".",
+    ]);
+    assert!(DBTool::try_parse_from([
+        "aptos-db-tool",
+        "restore",
+        "bootstrap-db",
+        "--target-epoch-end",
+        "10",
+        "--target-version",
+        "100",
+        "--target-db-dir",
+        ".",
+        "--local-fs-dir",
+        ".",
+    ])
+    .is_err());
     run_cmd(&[
         "aptos-db-tool",
         "replay-verify",
@@ -89,7 +139,7 @@ mod dbtool_tests {
     use aptos_temppath::TempPath;
     use aptos_types::{
         state_store::state_key::{inner::StateKeyTag::AccessPath, prefix::StateKeyPrefix},
-        transaction::Version,
+        transaction::{Transaction, Version},
     };
     use clap::Parser;
     use std::{
@@ -276,6 +326,45 @@ mod dbtool_tests {
             ))
             .unwrap();
         assert_metadata_view_eq(&old_metaview, &new_metaview);
+
+        // garbage collect the older state snapshot, listed in a compacted metadata file
+        let gc = |dry_run: bool| {
+            let mut args = vec![
+                "aptos-db-tool",
+                "backup-maintenance",
+                "gc",
+                "--keep-latest-state-snapshots",
+                "1",
+                "--metadata-cache-dir",
+                metadata_cache_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.path().to_str().unwrap(),
+            ];
+            if dry_run {
+                args.push("--dry-run");
+            }
+            rt.block_on(DBTool::try_parse_from(args).unwrap().run())
+                .unwrap();
+            rt.block_on(metadata::cache::sync_and_load(
+                &metadata_opt,
+                Arc::clone(&store),
+                1,
+            ))
+            .unwrap()
+        };
+        let snapshots = new_metaview.state_snapshot_backups().to_vec();
+        assert_eq!(snapshots.len(), 2);
+        let gc_metaview = gc(true);
+        assert_eq!(gc_metaview.state_snapshot_backups(), snapshots.as_slice());
+        assert!(backup_dir.path().join(&snapshots[0].manifest).exists());
+        let gc_metaview = gc(false);
+        assert_eq!(gc_metaview.state_snapshot_backups(), &snapshots[1..]);
+        assert!(!backup_dir.path().join(&snapshots[0].manifest).exists());
+        assert!(backup_dir.path().join(&snapshots[1].manifest).exists());
+        assert_eq!(
+            gc_metaview.transaction_backups(),
+            new_metaview.transaction_backups()
+        );
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
@@ -588,6 +677,117 @@ mod dbtool_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
+    #[test]
+    fn test_restore_db_to_epoch_end() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let old_db_dir = TempPath::new();
+        let (rt, _) = db_restore_test_setup(
+            16,
+            16,
+            PathBuf::from(backup_dir.path()),
+            PathBuf::from(old_db_dir.path()),
+            PathBuf::from(new_db_dir.path()),
+            false,
+        );
+
+        let epoch_db_dir = TempPath::new();
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "restore",
+                "bootstrap-db",
+                "--target-epoch-end",
+                "0",
+                "--target-db-dir",
+                epoch_db_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.path().to_str().unwrap(),
+            ])
+            .unwrap()
+            .run(),
+        )
+        .unwrap();
+
+        let db = AptosDB::new_for_test(epoch_db_dir.path());
+        let epoch_ending_version = db
+            .get_epoch_ending_ledger_infos(0, 1)
+            .unwrap()
+            .ledger_info_with_sigs[0]
+            .ledger_info()
+            .version();
+        assert_eq!(db.get_synced_version().unwrap(), Some(epoch_ending_version));
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_restore_db_to_timestamp() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let old_db_dir = TempPath::new();
+        let (rt, _) = db_restore_test_setup(
+            1,
+            16,
+            PathBuf::from(backup_dir.path()),
+            PathBuf::from(old_db_dir.path()),
+            PathBuf::from(new_db_dir.path()),
+            false,
+        );
+
+        // The blocks of the test DB are all within the first second, so any block is newer than
+        // the timestamp 0
+        let target_timestamp_usecs = 0;
+        let first_newer_block_version = {
+            let db = AptosDB::new_readonly_for_test(&new_db_dir);
+            db.get_transactions(1, 16, 16, false)
+                .unwrap()
+                .transactions
+                .iter()
+                .zip(1..)
+                .find(|(txn, _)| {
+                    matches!(txn, Transaction::BlockMetadata(block)
+                        if block.timestamp_usecs() > target_timestamp_usecs)
+                })
+                .map(|(_, version)| version)
+                .unwrap()
+        };
+
+        let restore_to_timestamp = |timestamp: &str, db_dir: &TempPath| {
+            rt.block_on(
+                DBTool::try_parse_from([
+                    "aptos-db-tool",
+                    "restore",
+                    "bootstrap-db",
+                    "--target-timestamp",
+                    timestamp,
+                    "--target-db-dir",
+                    db_dir.path().to_str().unwrap(),
+                    "--local-fs-dir",
+                    backup_dir.path().to_str().unwrap(),
+                ])
+                .unwrap()
+                .run(),
+            )
+        };
+
+        let timestamp_db_dir = TempPath::new();
+        restore_to_timestamp("0", &timestamp_db_dir).unwrap();
+        let db = AptosDB::new_for_test(timestamp_db_dir.path());
+        assert_eq!(
+            db.get_synced_version().unwrap(),
+            Some(first_newer_block_version - 1)
+        );
+
+        // No block is newer than a timestamp after all the backed up ones, whose blocks may not be
+        // backed up yet
+        let late_db_dir = TempPath::new();
+        restore_to_timestamp("1700000000", &late_db_dir).unwrap_err();
+
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
     #[test]
     #[ignore]
     // TODO(grao): Re-enable this test.
@@ -6,9 +6,9 @@ use aptos_config::config::internal_indexer_db_config::InternalIndexerDBConfig;
 use aptos_db_indexer_schemas::{
     metadata::{MetadataKey, MetadataValue, StateSnapshotProgress},
     schema::{
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
-        indexer_metadata::InternalIndexerMetadataSchema, state_keys::StateKeysSchema,
-        transaction_by_account::TransactionByAccountSchema,
+        event_by_key::EventByKeySchema, event_by_type::EventByTypeSchema,
+        event_by_version::EventByVersionSchema, indexer_metadata::InternalIndexerMetadataSchema,
+        state_keys::StateKeysSchema, transaction_by_account::TransactionByAccountSchema,
     },
     utils::{
         error_if_too_many_requested, get_first_seq_num_and_limit, AccountTransactionVersionIter,
@@ -31,8 +31,9 @@ use aptos_types::{
     transaction::{AccountTransactionsWithProof, Transaction, Version},
     write_set::{TransactionWrite, WriteSet},
 };
+use move_core_types::language_storage::TypeTag;
 use std::{
-    cmp::min,
+    cmp::{max, min},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc,
@@ -114,6 +115,39 @@ impl InternalIndexerDB {
         self.get_version(&MetadataKey::TransactionVersion)
     }
 
+    /// Returns the first version covered by the events by type index. Transactions committed
+    /// before the index was introduced aren't indexed by type.
+    pub fn get_event_by_type_start_version(&self) -> Result<Option<Version>> {
+        self.get_version(&MetadataKey::EventByTypeStartVersion)
+    }
+
+    pub fn get_event_pruner_progress(&self) -> Result<Option<Version>> {
+        self.get_version(&MetadataKey::EventPrunerProgress)
+    }
+
+    /// Records the first version covered by the events by type index, if not recorded yet. On an
+    /// existing DB that's the version after the last one indexed for events, since the events by
+    /// type index is only built for new transactions; on a new DB it's 0.
+    pub fn init_event_by_type_start_version(&self) -> Result<()> {
+        if self.get_event_by_type_start_version()?.is_none() {
+            let start_version = self.get_event_version()?.map_or(0, |v| v + 1);
+            self.db.put::<InternalIndexerMetadataSchema>(
+                &MetadataKey::EventByTypeStartVersion,
+                &MetadataValue::Version(start_version),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the oldest version whose events can be looked up by type, i.e. the later of the
+    /// start of the events by type index and the event pruner progress.
+    pub fn get_events_by_type_min_version(&self) -> Result<Version> {
+        Ok(max(
+            self.get_event_by_type_start_version()?.unwrap_or(0),
+            self.get_event_pruner_progress()?.unwrap_or(0),
+        ))
+    }
+
     pub fn event_enabled(&self) -> bool {
         self.config.enable_event
     }
@@ -227,6 +261,43 @@ impl InternalIndexerDB {
         Ok(result)
     }
 
+    /// Given `type_tag` and `start_version`, returns events of that type identified by
+    /// transaction version and index among all events emitted by the same transaction.
+    /// Result won't contain records with a transaction version > `end_version` and is in
+    /// ascending order. Once `limit` is reached, the remaining events of the last
+    /// transaction are still returned, so that a page never splits a transaction.
+    pub fn lookup_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+    ) -> Result<
+        Vec<(
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        let mut iter = self.db.iter::<EventByTypeSchema>()?;
+        iter.seek(&(type_tag.clone(), start_version, 0))?;
+
+        let mut result: Vec<(Version, u64)> = Vec::new();
+        for res in iter {
+            let ((tag, ver, idx), ()) = res?;
+            if &tag != type_tag || ver > end_version {
+                break;
+            }
+            if result.len() as u64 >= limit
+                && result.last().map_or(true, |(last_ver, _)| *last_ver != ver)
+            {
+                break;
+            }
+            result.push((ver, idx));
+        }
+
+        Ok(result)
+    }
+
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn get_restore_version_and_progress(
         &self,
@@ -299,6 +370,12 @@ impl DBIndexer {
     pub fn new(indexer_db: InternalIndexerDB, db_reader: Arc<dyn DbReader>) -> Self {
         let (sender, reciver) = mpsc::channel();
 
+        if indexer_db.event_enabled() {
+            indexer_db
+                .init_event_by_type_start_version()
+                .expect("Failed to initialize the events by type index start version");
+        }
+
         let db = indexer_db.get_inner_db_ref().to_owned();
         let committer_handle = thread::spawn(move || {
             let committer = DBCommitter::new(db, reciver);
@@ -378,6 +455,12 @@ impl DBIndexer {
 
             if self.indexer_db.event_enabled() {
                 events.iter().enumerate().for_each(|(idx, event)| {
+                    batch
+                        .put::<EventByTypeSchema>(
+                            &(event.type_tag().clone(), version, idx as u64),
+                            &(),
+                        )
+                        .expect("Failed to put events by type to a batch");
                     if let ContractEvent::V1(v1) = event {
                         batch
                             .put::<EventByKeySchema>(
@@ -567,4 +650,49 @@ impl DBIndexer {
 
         Ok(events_with_version)
     }
+
+    /// Returns events of the given type, including module events which aren't attached to an
+    /// event handle, emitted by transactions between `start_version` and `end_version`
+    /// inclusive, in ascending order.
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        end_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.indexer_db
+            .ensure_cover_ledger_version(ledger_version)?;
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        let min_version = self.indexer_db.get_events_by_type_min_version()?;
+        ensure!(
+            start_version >= min_version,
+            "Events by type before version {} are pruned or were never indexed, requested: {}",
+            min_version,
+            start_version
+        );
+
+        self.indexer_db
+            .lookup_events_by_type(
+                type_tag,
+                start_version,
+                min(end_version, ledger_version),
+                limit,
+            )?
+            .into_iter()
+            .map(|(ver, idx)| {
+                let event = self
+                    .main_db_reader
+                    .get_event_by_version_and_index(ver, idx)?;
+                ensure!(
+                    event.type_tag() == type_tag,
+                    "Index broken, expected type:{}, actual:{}",
+                    type_tag,
+                    event.type_tag()
+                );
+                Ok(EventWithVersion::new(ver, event))
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,21 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use move_core_types::language_storage::StructTag;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        struct_tag in any::<StructTag>(),
+        version in any::<Version>(),
+        index in any::<u64>(),
+    ) {
+        let type_tag = TypeTag::Struct(Box::new(struct_tag));
+        assert_encode_decode::<EventByTypeSchema>(&(type_tag, version, index), &());
+    }
+}
+
+test_no_panic_decoding!(EventByTypeSchema);
@@ -7,6 +7,7 @@
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
 pub mod event_by_key;
+pub mod event_by_type;
 pub mod event_by_version;
 pub mod indexer_metadata;
 pub mod state_keys;
@@ -20,6 +21,7 @@ pub const INTERNAL_INDEXER_METADATA_CF_NAME: ColumnFamilyName = "internal_indexe
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
 pub const EVENT_BY_VERSION_CF_NAME: ColumnFamilyName = "event_by_version";
+pub const EVENT_BY_TYPE_CF_NAME: ColumnFamilyName = "event_by_type";
 pub const TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transaction_by_account";
 pub const STATE_KEYS_CF_NAME: ColumnFamilyName = "state_keys";
 
@@ -37,6 +39,7 @@ pub fn internal_indexer_column_families() -> Vec<ColumnFamilyName> {
         INTERNAL_INDEXER_METADATA_CF_NAME,
         EVENT_BY_KEY_CF_NAME,
         EVENT_BY_VERSION_CF_NAME,
+        EVENT_BY_TYPE_CF_NAME,
         TRANSACTION_BY_ACCOUNT_CF_NAME,
         STATE_KEYS_CF_NAME,
     ]
@@ -2,9 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::smoke_test_environment::SwarmBuilder;
-use aptos::{account::create::DEFAULT_FUNDED_COINS, common::types::GasOptions};
+use aptos::{
+    account::create::DEFAULT_FUNDED_COINS, common::types::GasOptions, move_tool::MemberId,
+    test::CliTestFramework,
+};
 use aptos_crypto::{PrivateKey, ValidCryptoMaterialStringExt};
 use aptos_keygen::KeyGen;
+use aptos_temppath::TempPath;
+use std::{collections::BTreeMap, str::FromStr};
 
 #[tokio::test]
 async fn test_account_flow() {
@@ -136,3 +141,243 @@ async fn test_account_key_rotation() {
         .await
         .expect("New key should be able to transfer");
 }
+
+#[tokio::test]
+async fn test_offline_signing_flow() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(2)
+        .await;
+    let transfer = MemberId::from_str("0x1::aptos_account::transfer").unwrap();
+    let receiver = format!("address:{}", cli.account_id(1));
+
+    // The unsigned transaction can be written both as JSON and as BCS
+    let mut expected_receiver_amount = DEFAULT_FUNDED_COINS;
+    for (extension, transfer_amount) in [("json", 100), ("bcs", 200)] {
+        let unsigned_file = TempPath::new().as_ref().with_extension(extension);
+        let signed_file = TempPath::new();
+        cli.build_transaction(
+            0,
+            transfer.clone(),
+            vec![&receiver, &format!("u64:{}", transfer_amount)],
+            None,
+            unsigned_file.clone(),
+        )
+        .await
+        .unwrap();
+        let signed = cli
+            .sign_transaction(0, unsigned_file, signed_file.path().to_path_buf())
+            .await
+            .unwrap();
+        let submitted = cli
+            .submit_signed_transaction(signed_file.path().to_path_buf())
+            .await
+            .unwrap();
+        assert_eq!(signed.transaction_hash, submitted.transaction_hash);
+        assert_eq!(submitted.success, Some(true));
+
+        expected_receiver_amount += transfer_amount;
+        cli.assert_account_balance_now(1, expected_receiver_amount)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn test_output_unsigned_transfer() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(2)
+        .await;
+    let transfer_amount = 100;
+
+    // A transfer written with --output-unsigned isn't submitted until it's signed
+    let unsigned_file = TempPath::new();
+    let signed_file = TempPath::new();
+    let unsigned = cli
+        .transfer_coins_unsigned(0, 1, transfer_amount, unsigned_file.path().to_path_buf())
+        .await
+        .unwrap();
+    assert_eq!(unsigned.sender, cli.account_id(0));
+    assert_eq!(unsigned.success, None);
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS)
+        .await;
+
+    let signed = cli
+        .sign_transaction(
+            0,
+            unsigned_file.path().to_path_buf(),
+            signed_file.path().to_path_buf(),
+        )
+        .await
+        .unwrap();
+    let submitted = cli
+        .submit_signed_transaction(signed_file.path().to_path_buf())
+        .await
+        .unwrap();
+    assert_eq!(signed.transaction_hash, submitted.transaction_hash);
+    assert_eq!(submitted.success, Some(true));
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS + transfer_amount)
+        .await;
+}
+
+#[tokio::test]
+async fn test_offline_sponsored_transaction_flow() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(3)
+        .await;
+    let transfer = MemberId::from_str("0x1::aptos_account::transfer").unwrap();
+    let receiver = format!("address:{}", cli.account_id(2));
+    let transfer_amount = 100;
+
+    // Account 0 sends the coins, and account 1 pays for the gas
+    let unsigned_file = TempPath::new();
+    let partially_signed_file = TempPath::new();
+    let signed_file = TempPath::new();
+    cli.build_transaction(
+        0,
+        transfer,
+        vec![&receiver, &format!("u64:{}", transfer_amount)],
+        Some(1),
+        unsigned_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+    cli.sign_transaction(
+        0,
+        unsigned_file.path().to_path_buf(),
+        partially_signed_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+
+    // The transaction can't be submitted before the fee payer signs it
+    assert!(cli
+        .submit_signed_transaction(partially_signed_file.path().to_path_buf())
+        .await
+        .is_err());
+
+    cli.sign_transaction(
+        1,
+        partially_signed_file.path().to_path_buf(),
+        signed_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+    let response = cli
+        .submit_signed_transaction(signed_file.path().to_path_buf())
+        .await
+        .unwrap();
+    assert_eq!(response.success, Some(true));
+    let octa_spent = response.gas_used.unwrap() * response.gas_unit_price.unwrap();
+
+    cli.assert_account_balance_now(0, DEFAULT_FUNDED_COINS - transfer_amount)
+        .await;
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS - octa_spent)
+        .await;
+    cli.assert_account_balance_now(2, DEFAULT_FUNDED_COINS + transfer_amount)
+        .await;
+}
+
+#[tokio::test]
+async fn test_offline_multi_agent_transaction_flow() {
+    let (_swarm, mut cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(3)
+        .await;
+    let transfer_amount = 100;
+
+    // Publish a function which both signers pay from
+    cli.init_move_dir();
+    cli.init_package(
+        "MultiAgent".to_string(),
+        BTreeMap::from([("multi_agent", "_")]),
+        Some(CliTestFramework::aptos_framework_dir()),
+    )
+    .await
+    .unwrap();
+    cli.add_file_in_package(
+        "sources/multi_agent.move",
+        r#"
+        module multi_agent::pay {
+            use aptos_framework::aptos_account;
+
+            public entry fun pay_together(
+                sender: &signer,
+                co_payer: &signer,
+                receiver: address,
+                amount: u64,
+            ) {
+                aptos_account::transfer(sender, receiver, amount);
+                aptos_account::transfer(co_payer, receiver, amount);
+            }
+        }
+        "#
+        .to_string(),
+    );
+    let publisher = cli.account_id(2).to_hex_literal();
+    let publish_summary = cli
+        .publish_package(
+            2,
+            None,
+            BTreeMap::from([("multi_agent", publisher.as_str())]),
+            None,
+        )
+        .await
+        .unwrap();
+    let publish_octa_spent =
+        publish_summary.gas_used.unwrap() * publish_summary.gas_unit_price.unwrap();
+
+    // Account 0 sends the transaction, and account 1 co-signs it as the secondary signer
+    let pay_together = MemberId::from_str(&format!("{}::pay::pay_together", publisher)).unwrap();
+    let receiver = format!("address:{}", cli.account_id(2));
+    let unsigned_file = TempPath::new();
+    let partially_signed_file = TempPath::new();
+    let signed_file = TempPath::new();
+    cli.build_multi_agent_transaction(
+        0,
+        pay_together,
+        vec![&receiver, &format!("u64:{}", transfer_amount)],
+        vec![1],
+        unsigned_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+
+    // The secondary signer can sign before the sender
+    cli.sign_transaction(
+        1,
+        unsigned_file.path().to_path_buf(),
+        partially_signed_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+    assert!(cli
+        .submit_signed_transaction(partially_signed_file.path().to_path_buf())
+        .await
+        .is_err());
+
+    cli.sign_transaction(
+        0,
+        partially_signed_file.path().to_path_buf(),
+        signed_file.path().to_path_buf(),
+    )
+    .await
+    .unwrap();
+    let response = cli
+        .submit_signed_transaction(signed_file.path().to_path_buf())
+        .await
+        .unwrap();
+    assert_eq!(response.success, Some(true));
+    let octa_spent = response.gas_used.unwrap() * response.gas_unit_price.unwrap();
+
+    cli.assert_account_balance_now(0, DEFAULT_FUNDED_COINS - transfer_amount - octa_spent)
+        .await;
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS - transfer_amount)
+        .await;
+    cli.assert_account_balance_now(
+        2,
+        DEFAULT_FUNDED_COINS - publish_octa_spent + 2 * transfer_amount,
+    )
+    .await;
+}
@@ -356,6 +356,26 @@ impl RawTransaction {
         self.sender
     }
 
+    /// Return the sequence number of this transaction.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Return the gas unit price of this transaction.
+    pub fn gas_unit_price(&self) -> u64 {
+        self.gas_unit_price
+    }
+
+    /// Return the payload of this transaction.
+    pub fn payload(&self) -> &TransactionPayload {
+        &self.payload
+    }
+
+    /// Return the expiration timestamp of this transaction, in seconds.
+    pub fn expiration_timestamp_secs(&self) -> u64 {
+        self.expiration_timestamp_secs
+    }
+
     /// Return the signing message for creating transaction signature.
     pub fn signing_message(&self) -> Result<Vec<u8>, CryptoMaterialError> {
         signing_message(self)